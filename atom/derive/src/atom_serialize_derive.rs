@@ -0,0 +1,729 @@
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Attribute, Data, DeriveInput, Error, Fields, Generics, Ident, Index, Result,
+    Type,
+};
+
+/// Combine an optional error with another one.
+fn push_error(errors: &mut Option<Error>, error: Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// Parse the class of an object, as declared by the `lv2` attributes of a struct or variant.
+fn parse_class(attrs: &[Attribute]) -> Result<Option<Type>> {
+    let mut class = None;
+    for attr in attrs.iter().filter(|attr| attr.path().is_ident("lv2")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("class") {
+                class = Some(meta.value()?.parse()?);
+                Ok(())
+            } else {
+                Err(meta.error("unsupported object attribute"))
+            }
+        })?;
+    }
+    Ok(class)
+}
+
+/// Build the type of a collection from nested pairs, ending with `()`.
+fn nested_type(types: impl DoubleEndedIterator<Item = TokenStream2>) -> TokenStream2 {
+    types
+        .rev()
+        .fold(quote!(()), |rest, type_| quote!((#type_, #rest)))
+}
+
+/// Build the expression that accesses the element with the given index of a nested collection.
+fn nested_access(collection: TokenStream2, index: usize) -> TokenStream2 {
+    let second = Index::from(1);
+    let first = Index::from(0);
+    let mut access = collection;
+    for _ in 0..index {
+        access = quote!(#access.#second);
+    }
+    quote!(#access.#first)
+}
+
+/// A field of a struct or variant, which is stored as a property.
+struct PropertyField {
+    ident: Ident,
+    ty: Type,
+    /// The key of the property, or `None` if the field is skipped.
+    key: Option<Type>,
+}
+
+impl PropertyField {
+    /// Parse a field and it's `lv2` attributes.
+    fn from_field(field: &syn::Field) -> Result<Self> {
+        let mut key: Option<Type> = None;
+        let mut skip = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("lv2"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error("unsupported field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let ident = field.ident.clone().unwrap();
+        match (key, skip) {
+            (Some(key), true) => Err(Error::new_spanned(key, "a skipped field can't have a key")),
+            (None, false) => Err(Error::new(
+                ident.span(),
+                format!("the field `{}` requires a key or has to be skipped", ident),
+            )),
+            (key, _) => Ok(Self {
+                ident,
+                ty: field.ty.clone(),
+                key,
+            }),
+        }
+    }
+}
+
+/// The properties of an object, as declared by the fields of a struct or variant.
+struct ObjectFields {
+    /// The class of the object.
+    class: Type,
+    fields: Vec<PropertyField>,
+}
+
+impl ObjectFields {
+    /// Parse the class and the fields of a struct or variant.
+    ///
+    /// Unit structs are objects without properties. Unit variants are handled by the enum itself.
+    fn from_input(
+        ident: &Ident,
+        attrs: &[Attribute],
+        fields: &Fields,
+        errors: &mut Option<Error>,
+    ) -> Option<Self> {
+        let class = match parse_class(attrs) {
+            Ok(Some(class)) => Some(class),
+            Ok(None) => {
+                push_error(
+                    errors,
+                    Error::new(
+                        ident.span(),
+                        format!(
+                            "`{}` requires a class, declared with `#[lv2(class = ...)]`",
+                            ident
+                        ),
+                    ),
+                );
+                None
+            }
+            Err(error) => {
+                push_error(errors, error);
+                None
+            }
+        };
+
+        let fields = match fields {
+            Fields::Named(fields) => fields.named.iter().collect(),
+            Fields::Unit => Vec::new(),
+            Fields::Unnamed(fields) => {
+                push_error(
+                    errors,
+                    Error::new(fields.span(), "only fields with names can be properties"),
+                );
+                Vec::new()
+            }
+        };
+        let mut properties: Vec<PropertyField> = Vec::new();
+        for field in fields {
+            match PropertyField::from_field(field) {
+                Ok(property) => properties.push(property),
+                Err(error) => push_error(errors, error),
+            }
+        }
+
+        // Compare the keys by their tokens, which catches the obvious duplicates.
+        let mut keys: Vec<String> = Vec::new();
+        for key in properties.iter().filter_map(|field| field.key.as_ref()) {
+            let name = quote!(#key).to_string();
+            if keys.contains(&name) {
+                push_error(
+                    errors,
+                    Error::new_spanned(key, format!("the key `{}` is used more than once", name)),
+                );
+            } else {
+                keys.push(name);
+            }
+        }
+
+        class.map(|class| Self {
+            class,
+            fields: properties,
+        })
+    }
+
+    /// The fields that are stored as properties.
+    fn properties(&self) -> impl DoubleEndedIterator<Item = (&PropertyField, &Type)> {
+        self.fields
+            .iter()
+            .filter_map(|field| field.key.as_ref().map(|key| (field, key)))
+    }
+
+    /// The type of the URID collection, which contains the class and the keys and URIDs of the properties.
+    fn urids_type(&self, trait_: &TokenStream2) -> TokenStream2 {
+        let class = &self.class;
+        let properties = nested_type(self.properties().map(|(field, key)| {
+            let ty = &field.ty;
+            quote!((URID<#key>, <#ty as #trait_>::URIDs))
+        }));
+        quote!((URID<#class>, #properties))
+    }
+
+    /// Write the object, with the values of the fields bound to the given identifiers.
+    fn serialize(&self, values: &[Ident]) -> TokenStream2 {
+        let properties = self
+            .properties()
+            .zip(values)
+            .enumerate()
+            .map(|(index, (_, value))| {
+                let property = nested_access(quote!(urids.1), index);
+                quote! {
+                    writer.serialize_property(#property.0, #value, &#property.1, atom_urids)?;
+                }
+            });
+        let header = quote! {
+            ObjectHeader {
+                id: None,
+                otype: urids.0.into_general(),
+            }
+        };
+        if values.is_empty() {
+            return quote! {
+                space
+                    .init(atom_urids.object, #header)
+                    .map(|_| ())
+                    .ok_or(SerializeError::NoSpace)
+            };
+        }
+        quote! {
+            let mut writer = space
+                .init(atom_urids.object, #header)
+                .ok_or(SerializeError::NoSpace)?;
+            #(#properties)*
+            Ok(())
+        }
+    }
+
+    /// Read the fields from the properties in the `reader` and construct the value with the given path.
+    fn deserialize(&self, path: TokenStream2) -> TokenStream2 {
+        let mut index = 0;
+        let fields = self.fields.iter().map(|field| {
+            let ident = &field.ident;
+            if field.key.is_none() {
+                return quote!(#ident: Default::default(),);
+            }
+            let name = ident.to_string();
+            let property = nested_access(quote!(urids.1), index);
+            index += 1;
+            quote! {
+                #ident: reader.deserialize_property(#name, #property.0, &#property.1, atom_urids)?,
+            }
+        });
+        quote!(#path { #(#fields)* })
+    }
+
+    /// The pattern for the property reader, which is only used if there are any properties.
+    fn reader_pattern(&self) -> TokenStream2 {
+        if self.properties().next().is_some() {
+            quote!(reader)
+        } else {
+            quote!(_)
+        }
+    }
+
+    /// The identifiers that the values of the stored fields are bound to.
+    fn bindings(&self) -> Vec<Ident> {
+        (0..self.properties().count())
+            .map(|index| format_ident!("field_{}", index))
+            .collect()
+    }
+}
+
+/// A variant of an enum.
+struct AtomVariant {
+    ident: Ident,
+    /// The object of a variant with fields, or `None` if it's a unit variant.
+    object: Option<ObjectFields>,
+    /// The class of a unit variant, which is written as a URID.
+    class: Option<Type>,
+}
+
+/// The contents of a type we implement the atom traits for.
+enum AtomData {
+    Struct(Box<ObjectFields>),
+    Enum(Vec<AtomVariant>),
+}
+
+/// Representation of a type we implement `AtomSerialize` and `AtomDeserialize` for.
+struct AtomDataType<'a> {
+    ident: &'a Ident,
+    generics: &'a Generics,
+    data: AtomData,
+}
+
+impl<'a> AtomDataType<'a> {
+    /// Construct a `Self` instance from a `DeriveInput` and it's `lv2` attributes.
+    fn from_derive_input(input: &'a DeriveInput) -> Result<Self> {
+        let mut errors: Option<Error> = None;
+        let data = match &input.data {
+            Data::Struct(data) => {
+                ObjectFields::from_input(&input.ident, &input.attrs, &data.fields, &mut errors)
+                    .map(|object| AtomData::Struct(Box::new(object)))
+            }
+            Data::Enum(data) => {
+                if data.variants.is_empty() {
+                    push_error(
+                        &mut errors,
+                        Error::new(
+                            input.ident.span(),
+                            "an enum without variants can't be an atom",
+                        ),
+                    );
+                }
+                let mut variants: Vec<AtomVariant> = Vec::new();
+                for variant in data.variants.iter() {
+                    if let Fields::Unit = variant.fields {
+                        match parse_class(&variant.attrs) {
+                            Ok(Some(class)) => variants.push(AtomVariant {
+                                ident: variant.ident.clone(),
+                                object: None,
+                                class: Some(class),
+                            }),
+                            Ok(None) => push_error(
+                                &mut errors,
+                                Error::new(
+                                    variant.ident.span(),
+                                    format!(
+                                        "`{}` requires a class, declared with `#[lv2(class = ...)]`",
+                                        variant.ident
+                                    ),
+                                ),
+                            ),
+                            Err(error) => push_error(&mut errors, error),
+                        }
+                    } else if let Some(object) = ObjectFields::from_input(
+                        &variant.ident,
+                        &variant.attrs,
+                        &variant.fields,
+                        &mut errors,
+                    ) {
+                        variants.push(AtomVariant {
+                            ident: variant.ident.clone(),
+                            object: Some(object),
+                            class: None,
+                        });
+                    }
+                }
+
+                let mut classes: Vec<String> = Vec::new();
+                for class in variants.iter().map(|variant| variant.class()) {
+                    let name = quote!(#class).to_string();
+                    if classes.contains(&name) {
+                        push_error(
+                            &mut errors,
+                            Error::new_spanned(
+                                class,
+                                format!("the class `{}` is used more than once", name),
+                            ),
+                        );
+                    } else {
+                        classes.push(name);
+                    }
+                }
+                Some(AtomData::Enum(variants))
+            }
+            Data::Union(data) => {
+                push_error(
+                    &mut errors,
+                    Error::new(data.union_token.span(), "unions can't be atoms"),
+                );
+                None
+            }
+        };
+
+        match (errors, data) {
+            (None, Some(data)) => Ok(Self {
+                ident: &input.ident,
+                generics: &input.generics,
+                data,
+            }),
+            (Some(errors), _) => Err(errors),
+            (None, None) => unreachable!(),
+        }
+    }
+
+    /// The type of the URID collection.
+    ///
+    /// The collection of a struct contains the class and the property keys. The collection of an enum contains the class and property keys for every variant.
+    fn urids_type(&self, trait_: &TokenStream2) -> TokenStream2 {
+        match &self.data {
+            AtomData::Struct(object) => object.urids_type(trait_),
+            AtomData::Enum(variants) => {
+                nested_type(variants.iter().map(|variant| match &variant.object {
+                    Some(object) => object.urids_type(trait_),
+                    None => {
+                        let class = variant.class();
+                        quote!((URID<#class>, ()))
+                    }
+                }))
+            }
+        }
+    }
+
+    /// Implement `AtomSerialize`.
+    fn make_serialize(&self) -> TokenStream {
+        let ident = self.ident;
+        let (impl_generics, type_generics, where_clause) = self.generics.split_for_impl();
+        let urids_type = self.urids_type(&quote!(AtomSerialize));
+
+        let body = match &self.data {
+            AtomData::Struct(object) => {
+                let bindings = object.bindings();
+                let values =
+                    object
+                        .properties()
+                        .zip(bindings.iter())
+                        .map(|((field, _), binding)| {
+                            let field = &field.ident;
+                            quote!(let #binding = &self.#field;)
+                        });
+                let serialize = object.serialize(&bindings);
+                quote! {
+                    #(#values)*
+                    #serialize
+                }
+            }
+            AtomData::Enum(variants) => {
+                let arms = variants.iter().enumerate().map(|(index, variant)| {
+                    let variant_ident = &variant.ident;
+                    let variant_urids = nested_access(quote!(urids), index);
+                    match &variant.object {
+                        Some(object) => {
+                            let bindings = object.bindings();
+                            let fields = object.properties().map(|(field, _)| &field.ident);
+                            let serialize = object.serialize(&bindings);
+                            quote! {
+                                Self::#variant_ident { #(#fields: #bindings,)* .. } => {
+                                    let urids = &#variant_urids;
+                                    #serialize
+                                }
+                            }
+                        }
+                        None => quote! {
+                            Self::#variant_ident => space
+                                .init(atom_urids.urid, #variant_urids.0.into_general())
+                                .map(|_| ())
+                                .ok_or(SerializeError::NoSpace),
+                        },
+                    }
+                });
+                quote! {
+                    match self {
+                        #(#arms)*
+                    }
+                }
+            }
+        };
+
+        (quote! {
+            impl #impl_generics AtomSerialize for #ident #type_generics #where_clause {
+                type URIDs = #urids_type;
+
+                fn serialize<'a>(
+                    &self,
+                    space: &mut dyn MutSpace<'a>,
+                    urids: &Self::URIDs,
+                    atom_urids: &AtomURIDCollection,
+                ) -> Result<(), SerializeError> {
+                    #body
+                }
+            }
+        })
+        .into()
+    }
+
+    /// Implement `AtomDeserialize`.
+    fn make_deserialize(&self) -> TokenStream {
+        let ident = self.ident;
+        let (impl_generics, type_generics, where_clause) = self.generics.split_for_impl();
+        let urids_type = self.urids_type(&quote!(AtomDeserialize));
+
+        let body = match &self.data {
+            AtomData::Struct(object) => {
+                let construct = object.deserialize(quote!(Self));
+                let reader = object.reader_pattern();
+                quote! {
+                    let (header, #reader) = atom.read_object(atom_urids)?;
+                    if header.otype != urids.0 {
+                        return Err(DeserializeError::WrongClass {
+                            expected: urids.0.into_general(),
+                            found: header.otype,
+                        });
+                    }
+                    Ok(#construct)
+                }
+            }
+            AtomData::Enum(variants) => {
+                let units = variants
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, variant)| variant.object.is_none())
+                    .map(|(index, variant)| {
+                        let variant_ident = &variant.ident;
+                        let variant_urids = nested_access(quote!(urids), index);
+                        quote! {
+                            if class == #variant_urids.0 {
+                                return Ok(Self::#variant_ident);
+                            }
+                        }
+                    })
+                    .collect::<Vec<_>>();
+                let objects = variants
+                    .iter()
+                    .enumerate()
+                    .filter_map(|(index, variant)| {
+                        let object = variant.object.as_ref()?;
+                        let variant_ident = &variant.ident;
+                        let variant_urids = nested_access(quote!(urids), index);
+                        let construct = object.deserialize(quote!(Self::#variant_ident));
+                        Some(quote! {
+                            if header.otype == #variant_urids.0 {
+                                let urids = &#variant_urids;
+                                return Ok(#construct);
+                            }
+                        })
+                    })
+                    .collect::<Vec<_>>();
+
+                let read_units = if units.is_empty() {
+                    quote!()
+                } else if objects.is_empty() {
+                    quote! {
+                        let class = atom.read_checked(atom_urids.urid, ())?;
+                        #(#units)*
+                        return Err(DeserializeError::UnknownVariant { found: class });
+                    }
+                } else {
+                    quote! {
+                        if atom.type_urid().is_some_and(|type_| type_ == atom_urids.urid) {
+                            let class = atom.read_checked(atom_urids.urid, ())?;
+                            #(#units)*
+                            return Err(DeserializeError::UnknownVariant { found: class });
+                        }
+                    }
+                };
+                let read_objects = if objects.is_empty() {
+                    quote!()
+                } else {
+                    let reader = if variants
+                        .iter()
+                        .filter_map(|variant| variant.object.as_ref())
+                        .any(|object| object.properties().next().is_some())
+                    {
+                        quote!(reader)
+                    } else {
+                        quote!(_)
+                    };
+                    quote! {
+                        let (header, #reader) = atom.read_object(atom_urids)?;
+                        #(#objects)*
+                        Err(DeserializeError::UnknownVariant { found: header.otype })
+                    }
+                };
+                quote! {
+                    #read_units
+                    #read_objects
+                }
+            }
+        };
+
+        (quote! {
+            impl #impl_generics AtomDeserialize for #ident #type_generics #where_clause {
+                type URIDs = #urids_type;
+
+                fn deserialize(
+                    atom: UnidentifiedAtom,
+                    urids: &Self::URIDs,
+                    atom_urids: &AtomURIDCollection,
+                ) -> Result<Self, DeserializeError> {
+                    #body
+                }
+            }
+        })
+        .into()
+    }
+}
+
+impl AtomVariant {
+    /// The class of the variant.
+    fn class(&self) -> &Type {
+        match &self.object {
+            Some(object) => &object.class,
+            None => self.class.as_ref().unwrap(),
+        }
+    }
+}
+
+/// Implement `AtomSerialize` for a struct or an enum.
+#[inline]
+pub fn atom_serialize_derive_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    match AtomDataType::from_derive_input(&input) {
+        Ok(data) => data.make_serialize(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+/// Implement `AtomDeserialize` for a struct or an enum.
+#[inline]
+pub fn atom_deserialize_derive_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    match AtomDataType::from_derive_input(&input) {
+        Ok(data) => data.make_deserialize(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn validate(input: DeriveInput) -> std::result::Result<(), String> {
+        AtomDataType::from_derive_input(&input)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_valid_types() {
+        let input: DeriveInput = parse_quote! {
+            #[lv2(class = SettingsClass)]
+            struct Settings {
+                #[lv2(key = Gain)]
+                gain: f32,
+                #[lv2(key = Sample)]
+                sample: Option<String>,
+                #[lv2(skip)]
+                buffer: Vec<f32>,
+            }
+        };
+        assert_eq!(Ok(()), validate(input));
+
+        let input: DeriveInput = parse_quote! {
+            enum Shape {
+                #[lv2(class = Sine)]
+                Sine,
+                #[lv2(class = Pulse)]
+                Pulse {
+                    #[lv2(key = Width)]
+                    width: f32,
+                },
+            }
+        };
+        assert_eq!(Ok(()), validate(input));
+    }
+
+    #[test]
+    fn test_invalid_types() {
+        let input: DeriveInput = parse_quote! {
+            struct Settings {
+                #[lv2(key = Gain)]
+                gain: f32,
+            }
+        };
+        assert_eq!(
+            Err("`Settings` requires a class, declared with `#[lv2(class = ...)]`".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(class = SettingsClass)]
+            struct Settings {
+                gain: f32,
+            }
+        };
+        assert_eq!(
+            Err("the field `gain` requires a key or has to be skipped".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(class = SettingsClass)]
+            struct Settings {
+                #[lv2(key = Gain, skip)]
+                gain: f32,
+            }
+        };
+        assert_eq!(
+            Err("a skipped field can't have a key".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(class = SettingsClass)]
+            struct Settings {
+                #[lv2(key = Gain)]
+                gain: f32,
+                #[lv2(key = Gain)]
+                level: f32,
+            }
+        };
+        assert_eq!(
+            Err("the key `Gain` is used more than once".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(class = SettingsClass)]
+            struct Settings(f32);
+        };
+        assert_eq!(
+            Err("only fields with names can be properties".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            enum Shape {
+                #[lv2(class = Sine)]
+                Sine,
+                #[lv2(class = Sine)]
+                Saw,
+            }
+        };
+        assert_eq!(
+            Err("the class `Sine` is used more than once".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            enum Shape {
+                Sine,
+            }
+        };
+        assert_eq!(
+            Err("`Sine` requires a class, declared with `#[lv2(class = ...)]`".to_string()),
+            validate(input)
+        );
+    }
+}
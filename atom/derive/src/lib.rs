@@ -0,0 +1,18 @@
+//! Procedural macros for `lv2-atom`.
+extern crate proc_macro;
+
+mod atom_serialize_derive;
+
+use proc_macro::TokenStream;
+
+/// Implement the `AtomSerialize` trait for a struct or an enum.
+#[proc_macro_derive(AtomSerialize, attributes(lv2))]
+pub fn atom_serialize_derive(input: TokenStream) -> TokenStream {
+    atom_serialize_derive::atom_serialize_derive_impl(input)
+}
+
+/// Implement the `AtomDeserialize` trait for a struct or an enum.
+#[proc_macro_derive(AtomDeserialize, attributes(lv2))]
+pub fn atom_deserialize_derive(input: TokenStream) -> TokenStream {
+    atom_serialize_derive::atom_deserialize_derive_impl(input)
+}
@@ -68,25 +68,35 @@ pub mod chunk;
 pub mod object;
 pub mod scalar;
 pub mod sequence;
+pub mod serialize;
+pub mod sound;
 pub mod space;
 pub mod string;
+pub mod text;
 pub mod tuple;
 pub mod vector;
 
 #[cfg(feature = "lv2-core")]
 pub mod port;
 
+#[cfg(feature = "lv2-atom-derive")]
+pub use lv2_atom_derive::*;
+
 /// Prelude of `lv2_atom` for wildcard usage.
 pub mod prelude {
     use crate::*;
 
     pub use crate::{Atom, AtomURIDCollection, UnidentifiedAtom};
     pub use chunk::Chunk;
+    #[cfg(feature = "lv2-atom-derive")]
+    pub use lv2_atom_derive::{AtomDeserialize, AtomSerialize};
     pub use object::{Object, ObjectHeader, PropertyHeader};
     pub use port::AtomPort;
-    pub use scalar::{AtomURID, Bool, Double, Float, Int, Long};
-    pub use sequence::{Sequence, TimeStamp, TimeStampURID};
-    pub use space::{FramedMutSpace, MutSpace, Space};
+    pub use scalar::{AtomURID, Bool, Double, Float, Int, Long, Number};
+    pub use sequence::{Event, Sequence, TempoMap, TimeStamp, TimeStampURID};
+    pub use serialize::{AtomDeserialize, AtomSerialize, DeserializeError, SerializeError};
+    pub use sound::Sound;
+    pub use space::{AtomBuf, AtomBuffer, AtomBufferSpace, FramedMutSpace, MutSpace, Space};
     pub use string::{Literal, LiteralInfo, String};
     pub use tuple::Tuple;
     pub use vector::Vector;
@@ -115,6 +125,7 @@ pub struct AtomURIDCollection {
     pub uri: URID<string::URI>,
     pub tuple: URID<tuple::Tuple>,
     pub sequence: URID<sequence::Sequence>,
+    pub sound: URID<sound::Sound>,
 }
 
 impl AtomURIDCollection {
@@ -211,4 +222,11 @@ impl<'a> UnidentifiedAtom<'a> {
             .split_type::<sys::LV2_Atom>()
             .and_then(|(header, _)| URID::new(header.type_))
     }
+
+    /// Retrieve the space of the atom, including the header.
+    ///
+    /// This can be used to copy or send the atom without knowing it's type.
+    pub fn space(self) -> Space<'a> {
+        self.space
+    }
 }
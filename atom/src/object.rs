@@ -84,6 +84,7 @@ unsafe impl UriBound for Object {
 }
 
 /// Information about an object atom.
+#[derive(Clone, Copy)]
 pub struct ObjectHeader {
     /// The id of the object to distinguish different objects of the same type.
     ///
@@ -168,6 +169,7 @@ where
 /// An iterator over all properties in an object.
 ///
 /// Each iteration item is the header of the property, as well as the space occupied by the value atom. You can use normal `read` methods on the returned space.
+#[derive(Clone, Copy)]
 pub struct ObjectReader<'a> {
     space: Space<'a>,
 }
@@ -218,6 +220,15 @@ impl<'a, 'b> ObjectWriter<'a, 'b> {
         Property::write_header::<K, ()>(&mut self.frame, key, None)?;
         (&mut self.frame as &mut dyn MutSpace).init(child_urid, parameter)
     }
+
+    /// Write the header of a new property and return the space for it's value.
+    pub(crate) fn property<K: ?Sized>(
+        &mut self,
+        key: URID<K>,
+    ) -> Option<&mut FramedMutSpace<'a, 'b>> {
+        Property::write_header::<K, ()>(&mut self.frame, key, None)?;
+        Some(&mut self.frame)
+    }
 }
 
 /// An atom containing a key-value pair.
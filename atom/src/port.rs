@@ -27,7 +27,7 @@ use crate::space::*;
 use lv2_core::port::PortType;
 use std::ffi::c_void;
 use std::ptr::NonNull;
-use urid::URID;
+use urid::{UriBound, URID};
 
 /// A handle to read atoms from a port.
 ///
@@ -101,10 +101,16 @@ impl<'a> PortWriter<'a> {
 /// [See also the module documentation.](index.html)
 pub struct AtomPort;
 
+unsafe impl UriBound for AtomPort {
+    const URI: &'static [u8] = sys::LV2_ATOM__AtomPort;
+}
+
 impl PortType for AtomPort {
     type InputPortType = PortReader<'static>;
     type OutputPortType = PortWriter<'static>;
 
+    const PORT_CLASS: Option<&'static [u8]> = Some(Self::URI);
+
     #[inline]
     unsafe fn input_from_raw(pointer: NonNull<c_void>, _sample_count: u32) -> PortReader<'static> {
         let space = Space::from_atom(pointer.cast().as_ref());
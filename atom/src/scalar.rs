@@ -160,6 +160,128 @@ make_scalar_atom!(
     |urids: &AtomURIDCollection| urids.urid
 );
 
+/// A value of any of the number atoms.
+///
+/// The specification defines `Int`, `Long`, `Float` and `Double` as subclasses of `atom:Number`. Hosts and other plugins don't always agree on the type they use for a value, for example a host might send a tempo as a `Double` while a plugin expects a `Float`. This type can read any of these atoms and convert the value to the type you need.
+///
+/// ```
+/// use lv2_atom::prelude::*;
+///
+/// fn read_gain(atom: UnidentifiedAtom, urids: &AtomURIDCollection) -> Option<f32> {
+///     Number::read(atom, urids).map(Number::as_f32)
+/// }
+/// ```
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum Number {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+}
+
+impl Number {
+    /// Try to read any number atom.
+    ///
+    /// Returns `None` if the atom isn't a number atom.
+    pub fn read(atom: UnidentifiedAtom, urids: &AtomURIDCollection) -> Option<Self> {
+        atom.read(urids.int, ())
+            .map(Number::Int)
+            .or_else(|| atom.read(urids.long, ()).map(Number::Long))
+            .or_else(|| atom.read(urids.float, ()).map(Number::Float))
+            .or_else(|| atom.read(urids.double, ()).map(Number::Double))
+    }
+
+    /// Write the number as the atom of it's variant.
+    ///
+    /// Returns `None` if the space is insufficient.
+    pub fn write<'a>(self, space: &mut dyn MutSpace<'a>, urids: &AtomURIDCollection) -> Option<()> {
+        match self {
+            Number::Int(value) => space.init(urids.int, value).map(|_| ()),
+            Number::Long(value) => space.init(urids.long, value).map(|_| ()),
+            Number::Float(value) => space.init(urids.float, value).map(|_| ()),
+            Number::Double(value) => space.init(urids.double, value).map(|_| ()),
+        }
+    }
+
+    /// The URID of the atom type of this number.
+    pub fn type_urid(self, urids: &AtomURIDCollection) -> URID {
+        match self {
+            Number::Int(_) => urids.int.into_general(),
+            Number::Long(_) => urids.long.into_general(),
+            Number::Float(_) => urids.float.into_general(),
+            Number::Double(_) => urids.double.into_general(),
+        }
+    }
+
+    /// Convert the number to a 32-bit integer.
+    ///
+    /// Floating-point numbers are truncated and all values are saturated to the range of `i32`.
+    pub fn as_i32(self) -> i32 {
+        match self {
+            Number::Int(value) => value,
+            Number::Long(value) => value.clamp(i32::MIN as i64, i32::MAX as i64) as i32,
+            Number::Float(value) => value as i32,
+            Number::Double(value) => value as i32,
+        }
+    }
+
+    /// Convert the number to a 64-bit integer.
+    ///
+    /// Floating-point numbers are truncated and saturated to the range of `i64`.
+    pub fn as_i64(self) -> i64 {
+        match self {
+            Number::Int(value) => value as i64,
+            Number::Long(value) => value,
+            Number::Float(value) => value as i64,
+            Number::Double(value) => value as i64,
+        }
+    }
+
+    /// Convert the number to a 32-bit float.
+    pub fn as_f32(self) -> f32 {
+        match self {
+            Number::Int(value) => value as f32,
+            Number::Long(value) => value as f32,
+            Number::Float(value) => value,
+            Number::Double(value) => value as f32,
+        }
+    }
+
+    /// Convert the number to a 64-bit float.
+    pub fn as_f64(self) -> f64 {
+        match self {
+            Number::Int(value) => value as f64,
+            Number::Long(value) => value as f64,
+            Number::Float(value) => value as f64,
+            Number::Double(value) => value,
+        }
+    }
+}
+
+impl From<i32> for Number {
+    fn from(value: i32) -> Self {
+        Number::Int(value)
+    }
+}
+
+impl From<i64> for Number {
+    fn from(value: i64) -> Self {
+        Number::Long(value)
+    }
+}
+
+impl From<f32> for Number {
+    fn from(value: f32) -> Self {
+        Number::Float(value)
+    }
+}
+
+impl From<f64> for Number {
+    fn from(value: f64) -> Self {
+        Number::Double(value)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::prelude::*;
@@ -219,4 +341,45 @@ mod tests {
         test_scalar::<Bool>(1);
         test_scalar::<AtomURID>(URID::try_from(1).unwrap());
     }
+
+    #[test]
+    fn test_number() {
+        let map = HashURIDMapper::new();
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let numbers = [
+            Number::Int(-3),
+            Number::Long(1 << 40),
+            Number::Float(2.5),
+            Number::Double(-0.75),
+        ];
+        for number in numbers.iter() {
+            let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+            {
+                let mut space = RootMutSpace::new(raw_space.as_mut());
+                number
+                    .write(&mut space as &mut dyn MutSpace, &urids)
+                    .unwrap();
+            }
+            let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+            assert_eq!(Some(number.type_urid(&urids)), atom.type_urid());
+            assert_eq!(Some(*number), Number::read(atom, &urids));
+        }
+
+        assert_eq!(i32::MAX, Number::Long(1 << 40).as_i32());
+        assert_eq!(2, Number::Float(2.5).as_i64());
+        assert_eq!(-3.0, Number::Int(-3).as_f32());
+        assert_eq!(-0.75, Number::Double(-0.75).as_f64());
+
+        // Other atoms are no numbers.
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            (&mut space as &mut dyn MutSpace)
+                .init(urids.bool, 1)
+                .unwrap();
+        }
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        assert_eq!(None, Number::read(atom, &urids));
+    }
 }
@@ -62,11 +62,16 @@
 //! }
 //! ```
 //!
+//! Hosts may measure time stamps in frames or in beats. If you need frame offsets in any case, you can convert the time stamps with a [`TempoMap`](struct.TempoMap.html) by calling [`frames`](struct.SequenceIterator.html#method.frames) on the sequence iterator.
+//!
+//! Most plugins process their audio in chunks between events. The [`sub_blocks`](struct.SequenceIterator.html#method.sub_blocks) adaptor splits a block of frames at the events of a sequence and yields these chunks together with the event that follows them.
+//!
 //! # Specification
 //!
 //! [http://lv2plug.in/ns/ext/atom/atom.html#Sequence](http://lv2plug.in/ns/ext/atom/atom.html#Sequence)
 use crate::space::*;
 use crate::*;
+use std::ops::Range;
 use sys::LV2_Atom_Event__bindgen_ty_1 as RawTimeStamp;
 use units::prelude::*;
 use urid::*;
@@ -152,6 +157,12 @@ impl From<TimeStampURID> for TimeStampUnit {
     }
 }
 
+impl From<i64> for TimeStamp {
+    fn from(frames: i64) -> TimeStamp {
+        TimeStamp::Frames(frames)
+    }
+}
+
 impl TimeStamp {
     pub fn as_frames(self) -> Option<i64> {
         match self {
@@ -166,6 +177,171 @@ impl TimeStamp {
             _ => None,
         }
     }
+
+    /// The unit this time stamp is measured in.
+    pub fn unit(self) -> TimeStampUnit {
+        match self {
+            Self::Frames(_) => TimeStampUnit::Frames,
+            Self::BeatsPerMinute(_) => TimeStampUnit::BeatsPerMinute,
+        }
+    }
+}
+
+/// A single time-stamped event of a sequence.
+///
+/// Sequence iterators yield their events as pairs of a time stamp and an atom, which can be converted to events. An event can be read as a certain atom type while keeping it's time stamp and can be forwarded to another sequence.
+///
+/// ```
+/// use lv2_atom::prelude::*;
+/// use lv2_atom::sequence::*;
+///
+/// fn sum(events: SequenceIterator, urids: &AtomURIDCollection) -> i32 {
+///     events
+///         .map(Event::from)
+///         .filter_map(|event| event.read(urids.int, ()))
+///         .map(|(_, value)| value)
+///         .sum()
+/// }
+/// ```
+#[derive(Clone, Copy)]
+pub struct Event<'a> {
+    pub time_stamp: TimeStamp,
+    pub body: UnidentifiedAtom<'a>,
+}
+
+impl<'a> Event<'a> {
+    pub fn new(time_stamp: TimeStamp, body: UnidentifiedAtom<'a>) -> Self {
+        Self { time_stamp, body }
+    }
+
+    /// Try to read the body of the event.
+    ///
+    /// If the body is an atom of the given type, the time stamp and the reading handle are returned.
+    pub fn read<'b, A: Atom<'a, 'b>>(
+        self,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Option<(TimeStamp, A::ReadHandle)> {
+        self.body
+            .read(urid, parameter)
+            .map(|handle| (self.time_stamp, handle))
+    }
+
+    /// Retrieve the type URID of the body.
+    pub fn type_urid(self) -> Option<URID> {
+        self.body.type_urid()
+    }
+}
+
+impl<'a> From<(TimeStamp, UnidentifiedAtom<'a>)> for Event<'a> {
+    fn from((time_stamp, body): (TimeStamp, UnidentifiedAtom<'a>)) -> Self {
+        Self { time_stamp, body }
+    }
+}
+
+/// The relation between frames and beats, used to convert time stamps.
+///
+/// A tempo map is usually created from the sample rate of the plugin and updated with the `time:Position` objects sent by the host. The `lv2-time` crate contains a function to do that.
+///
+/// Time stamps of a sequence are relative to the start of the current block, which is why they are converted only with the tempo, the speed and the sample rate. The position of the transport is used to calculate the musical position of an event.
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub struct TempoMap {
+    /// The sample rate in frames per second.
+    pub sample_rate: f64,
+    /// The tempo in beats per minute.
+    pub beats_per_minute: f64,
+    /// The speed of the transport, where `1.0` is normal speed, `0.0` is stopped and negative values play backwards.
+    pub speed: f64,
+    /// The transport frame at the start of the block.
+    pub frame: i64,
+    /// The beat within the bar at the start of the block.
+    pub bar_beat: f64,
+    /// The number of beats in a bar, if known.
+    pub beats_per_bar: Option<f64>,
+}
+
+impl TempoMap {
+    /// Create a new tempo map for a running transport at the start of the timeline.
+    pub fn new(sample_rate: f64, beats_per_minute: f64) -> Self {
+        Self {
+            sample_rate,
+            beats_per_minute,
+            speed: 1.0,
+            frame: 0,
+            bar_beat: 0.0,
+            beats_per_bar: None,
+        }
+    }
+
+    /// The number of frames that pass during one beat.
+    ///
+    /// Returns `None` if the transport is stopped or the tempo is invalid.
+    pub fn frames_per_beat(&self) -> Option<f64> {
+        let frames = self.sample_rate * 60.0 / (self.beats_per_minute * self.speed);
+        if frames.is_finite() && frames != 0.0 {
+            Some(frames)
+        } else {
+            None
+        }
+    }
+
+    /// Convert a number of beats to frames, rounded to the nearest frame.
+    pub fn beats_to_frames(&self, beats: f64) -> Option<i64> {
+        Some((beats * self.frames_per_beat()?).round() as i64)
+    }
+
+    /// Convert a number of frames to beats.
+    pub fn frames_to_beats(&self, frames: i64) -> f64 {
+        let beats = frames as f64 * self.beats_per_minute * self.speed / (self.sample_rate * 60.0);
+        if beats.is_finite() {
+            beats
+        } else {
+            0.0
+        }
+    }
+
+    /// Get the time stamp as a number of frames.
+    pub fn to_frames(&self, stamp: TimeStamp) -> Option<i64> {
+        match stamp {
+            TimeStamp::Frames(frames) => Some(frames),
+            TimeStamp::BeatsPerMinute(beats) => self.beats_to_frames(beats),
+        }
+    }
+
+    /// Get the time stamp as a number of beats.
+    pub fn to_beats(&self, stamp: TimeStamp) -> f64 {
+        match stamp {
+            TimeStamp::Frames(frames) => self.frames_to_beats(frames),
+            TimeStamp::BeatsPerMinute(beats) => beats,
+        }
+    }
+
+    /// Convert the time stamp to the given unit.
+    ///
+    /// Beats can not be converted to frames while the transport is stopped; In this case, `None` is returned.
+    pub fn convert(&self, stamp: TimeStamp, unit: TimeStampUnit) -> Option<TimeStamp> {
+        match unit {
+            TimeStampUnit::Frames => self.to_frames(stamp).map(TimeStamp::Frames),
+            TimeStampUnit::BeatsPerMinute => Some(TimeStamp::BeatsPerMinute(self.to_beats(stamp))),
+        }
+    }
+
+    /// The transport frame of an event with the given time stamp.
+    pub fn transport_frame(&self, stamp: TimeStamp) -> Option<i64> {
+        let offset = self.to_frames(stamp)? as f64 * self.speed;
+        Some(self.frame + offset.round() as i64)
+    }
+
+    /// The beat within the bar of an event with the given time stamp.
+    ///
+    /// If the number of beats per bar is unknown, the beat isn't wrapped to the bar.
+    pub fn bar_beat(&self, stamp: TimeStamp) -> f64 {
+        let beat = self.bar_beat + self.to_beats(stamp);
+        match self.beats_per_bar {
+            Some(beats_per_bar) if beats_per_bar > 0.0 => beat.rem_euclid(beats_per_bar),
+            _ => beat,
+        }
+    }
 }
 
 /// An iterator over all events in a sequence.
@@ -178,6 +354,25 @@ impl<'a> SequenceIterator<'a> {
     pub fn unit(&self) -> TimeStampUnit {
         self.unit
     }
+
+    /// Iterate over the events with their time stamps converted to frame offsets.
+    ///
+    /// This works regardless of the unit the host has used for the sequence.
+    pub fn frames(self, tempo: TempoMap) -> FrameIterator<'a> {
+        FrameIterator {
+            events: self,
+            tempo,
+            anchor: (0, 0.0),
+            last: (0, 0.0),
+        }
+    }
+
+    /// Split a block of `sample_count` frames at the events of this sequence.
+    ///
+    /// Only time stamps in frames can be placed; Events with time stamps in beats are handled at the current position. Use [`frames`](#method.frames) first if the sequence may be measured in beats.
+    pub fn sub_blocks(self, sample_count: u32) -> SubBlockIterator<Self> {
+        SubBlockIterator::new(self, sample_count)
+    }
 }
 
 impl<'a> Iterator for SequenceIterator<'a> {
@@ -195,6 +390,149 @@ impl<'a> Iterator for SequenceIterator<'a> {
     }
 }
 
+/// An iterator over all events in a sequence, yielding their offsets in frames.
+///
+/// Time stamps in beats are converted with the tempo map. If the tempo changes within the sequence, for example because the host has sent a new `time:Position` object, you can update the tempo map with [`set_tempo`](#method.set_tempo). The events after that are converted relative to the last event.
+///
+/// If beats can't be converted to frames, because the transport is stopped, the event is placed at the last converted offset.
+pub struct FrameIterator<'a> {
+    events: SequenceIterator<'a>,
+    tempo: TempoMap,
+    anchor: (i64, f64),
+    last: (i64, f64),
+}
+
+impl<'a> FrameIterator<'a> {
+    /// The unit of the underlying sequence.
+    pub fn unit(&self) -> TimeStampUnit {
+        self.events.unit()
+    }
+
+    /// The tempo map that is used to convert time stamps.
+    pub fn tempo(&self) -> &TempoMap {
+        &self.tempo
+    }
+
+    /// Change the tempo, starting at the last event.
+    pub fn set_tempo(&mut self, tempo: TempoMap) {
+        self.anchor = self.last;
+        self.tempo = tempo;
+    }
+
+    /// Split a block of `sample_count` frames at the events of this sequence.
+    pub fn sub_blocks(self, sample_count: u32) -> SubBlockIterator<Self> {
+        SubBlockIterator::new(self, sample_count)
+    }
+}
+
+impl<'a> Iterator for FrameIterator<'a> {
+    type Item = (i64, UnidentifiedAtom<'a>);
+
+    fn next(&mut self) -> Option<(i64, UnidentifiedAtom<'a>)> {
+        let (stamp, atom) = self.events.next()?;
+        let (anchor_frames, anchor_beats) = self.anchor;
+        self.last = match stamp {
+            TimeStamp::Frames(frames) => (
+                frames,
+                anchor_beats + self.tempo.frames_to_beats(frames - anchor_frames),
+            ),
+            TimeStamp::BeatsPerMinute(beats) => (
+                self.tempo
+                    .beats_to_frames(beats - anchor_beats)
+                    .map(|offset| anchor_frames + offset)
+                    .unwrap_or(self.last.0),
+                beats,
+            ),
+        };
+        Some((self.last.0, atom))
+    }
+}
+
+/// An iterator that splits a block of frames at the events of a sequence.
+///
+/// Every item contains a range of frames and the event that follows it. Plugins process their audio and control ports in this range and handle the event afterwards, which gives sample-accurate timing. After all events, the remaining frames of the block are yielded without an event, even if this range is empty.
+///
+/// The specification doesn't require time stamps to be sorted or in range. Time stamps before the end of the last range, as well as negative ones, are handled at the end of the last range and time stamps after the block are handled at the end of the block. Therefore, the ranges never overlap and cover exactly the whole block.
+///
+/// # Example
+///
+/// ```
+/// use lv2_atom::prelude::*;
+/// use lv2_atom::sequence::*;
+///
+/// fn run(events: SequenceIterator, input: &[f32], output: &mut [f32], gain: &mut f32, urids: &AtomURIDCollection) {
+///     for (range, event) in events.sub_blocks(input.len() as u32) {
+///         for (input, output) in input[range.clone()].iter().zip(output[range].iter_mut()) {
+///             *output = *input * *gain;
+///         }
+///         if let Some(new_gain) = event.and_then(|atom| atom.read(urids.float, ())) {
+///             *gain = new_gain;
+///         }
+///     }
+/// }
+/// ```
+pub struct SubBlockIterator<I> {
+    events: I,
+    sample_count: usize,
+    position: usize,
+    done: bool,
+}
+
+impl<I> SubBlockIterator<I> {
+    /// Create a new iterator from an event iterator and the number of frames in the block.
+    ///
+    /// The event iterator may yield [`TimeStamp`s](enum.TimeStamp.html) or frame offsets.
+    pub fn new(events: I, sample_count: u32) -> Self {
+        Self {
+            events,
+            sample_count: sample_count as usize,
+            position: 0,
+            done: false,
+        }
+    }
+
+    /// The underlying event iterator.
+    pub fn events(&self) -> &I {
+        &self.events
+    }
+
+    /// The underlying event iterator, for example to change the tempo of a [`FrameIterator`](struct.FrameIterator.html).
+    pub fn events_mut(&mut self) -> &mut I {
+        &mut self.events
+    }
+}
+
+impl<'a, I, S> Iterator for SubBlockIterator<I>
+where
+    I: Iterator<Item = (S, UnidentifiedAtom<'a>)>,
+    S: Into<TimeStamp>,
+{
+    type Item = (Range<usize>, Option<UnidentifiedAtom<'a>>);
+
+    fn next(&mut self) -> Option<(Range<usize>, Option<UnidentifiedAtom<'a>>)> {
+        if self.done {
+            return None;
+        }
+        let start = self.position;
+        match self.events.next() {
+            Some((stamp, atom)) => {
+                let end = match stamp.into().as_frames() {
+                    Some(frames) if frames > start as i64 => {
+                        (frames.min(self.sample_count as i64)) as usize
+                    }
+                    _ => start,
+                };
+                self.position = end;
+                Some((start..end, Some(atom)))
+            }
+            None => {
+                self.done = true;
+                Some((start..self.sample_count, None))
+            }
+        }
+    }
+}
+
 /// The writing handle for sequences.
 pub struct SequenceWriter<'a, 'b> {
     frame: FramedMutSpace<'a, 'b>,
@@ -259,6 +597,13 @@ impl<'a, 'b> SequenceWriter<'a, 'b> {
         self.write_time_stamp(stamp)?;
         self.frame.write_raw(data, true).map(|_| ())
     }
+
+    /// Forward an event to the sequence.
+    ///
+    /// This works just like [`forward`](#method.forward), with the time stamp of the event.
+    pub fn forward_event(&mut self, event: Event) -> Option<()> {
+        self.forward(event.time_stamp, event.body)
+    }
 }
 
 #[cfg(test)]
@@ -361,4 +706,189 @@ mod tests {
             assert!(reader.next().is_none());
         }
     }
+
+    #[test]
+    fn test_tempo_map() {
+        let mut tempo = TempoMap::new(48000.0, 120.0);
+        assert_eq!(Some(24000.0), tempo.frames_per_beat());
+        assert_eq!(Some(12000), tempo.beats_to_frames(0.5));
+        assert_eq!(1.5, tempo.frames_to_beats(36000));
+
+        let stamp = tempo
+            .convert(TimeStamp::Frames(6000), TimeStampUnit::BeatsPerMinute)
+            .unwrap();
+        assert_eq!(Some(0.25), stamp.as_bpm());
+        let stamp = tempo.convert(stamp, TimeStampUnit::Frames).unwrap();
+        assert_eq!(Some(6000), stamp.as_frames());
+
+        tempo.frame = 96000;
+        tempo.bar_beat = 3.5;
+        tempo.beats_per_bar = Some(4.0);
+        assert_eq!(
+            Some(108000),
+            tempo.transport_frame(TimeStamp::Frames(12000))
+        );
+        assert_eq!(0.0, tempo.bar_beat(TimeStamp::Frames(12000)));
+
+        // Half speed
+        tempo.speed = 0.5;
+        assert_eq!(Some(48000), tempo.beats_to_frames(1.0));
+        assert_eq!(
+            Some(102000),
+            tempo.transport_frame(TimeStamp::Frames(12000))
+        );
+
+        // Stopped
+        tempo.speed = 0.0;
+        assert_eq!(None, tempo.frames_per_beat());
+        assert!(tempo
+            .convert(TimeStamp::BeatsPerMinute(1.0), TimeStampUnit::Frames)
+            .is_none());
+        assert_eq!(0.0, tempo.frames_to_beats(100));
+    }
+
+    #[test]
+    fn test_frame_iterator() {
+        let map = HashURIDMapper::new();
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::BeatsPerMinute(urids.units.beat),
+                )
+                .unwrap();
+            for (beats, value) in [(0.0, 0), (0.5, 1), (1.0, 2), (1.5, 3)].iter() {
+                writer
+                    .init::<Int>(TimeStamp::BeatsPerMinute(*beats), urids.atom.int, *value)
+                    .unwrap();
+            }
+        }
+
+        let space = Space::from_slice(raw_space.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+        let mut events = Sequence::read(body, urids.units.beat)
+            .unwrap()
+            .frames(TempoMap::new(48000.0, 120.0));
+        assert_eq!(TimeStampUnit::BeatsPerMinute, events.unit());
+
+        let mut offsets = Vec::new();
+        while let Some((offset, atom)) = events.next() {
+            offsets.push(offset);
+            // Double the tempo after the second event.
+            if atom.read(urids.atom.int, ()) == Some(1) {
+                events.set_tempo(TempoMap::new(48000.0, 240.0));
+            }
+        }
+        assert_eq!(vec![0, 12000, 18000, 24000], offsets);
+    }
+
+    #[test]
+    fn test_sub_blocks() {
+        let map = HashURIDMapper::new();
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            for (frames, value) in [(-4, 0), (0, 1), (16, 2), (16, 3), (80, 4)].iter() {
+                writer
+                    .init::<Int>(TimeStamp::Frames(*frames), urids.atom.int, *value)
+                    .unwrap();
+            }
+        }
+        let space = Space::from_slice(raw_space.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+
+        let blocks: Vec<(Range<usize>, Option<i32>)> = Sequence::read(body, urids.units.beat)
+            .unwrap()
+            .sub_blocks(64)
+            .map(|(range, atom)| (range, atom.and_then(|atom| atom.read(urids.atom.int, ()))))
+            .collect();
+        assert_eq!(
+            vec![
+                (0..0, Some(0)),
+                (0..0, Some(1)),
+                (0..16, Some(2)),
+                (16..16, Some(3)),
+                (16..64, Some(4)),
+                (64..64, None),
+            ],
+            blocks
+        );
+
+        // Unsorted time stamps
+        let atom = UnidentifiedAtom::new(space);
+        let events = vec![(8, atom), (4, atom), (12, atom)];
+        let ranges: Vec<Range<usize>> = SubBlockIterator::new(events.into_iter(), 16)
+            .map(|(range, _)| range)
+            .collect();
+        assert_eq!(vec![0..8, 8..8, 8..12, 12..16], ranges);
+    }
+
+    #[test]
+    fn test_events() {
+        let map = HashURIDMapper::new();
+        let urids = TestURIDCollection::from_map(&map).unwrap();
+
+        let mut input: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(input.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            writer
+                .init(TimeStamp::Frames(3), urids.atom.int, 42)
+                .unwrap();
+            writer
+                .init(TimeStamp::Frames(5), urids.atom.float, 1.0)
+                .unwrap();
+        }
+
+        // Forward all integer events.
+        let mut output: Box<[u8]> = Box::new([0; 256]);
+        {
+            let space = Space::from_slice(input.as_ref());
+            let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+            let mut space = RootMutSpace::new(output.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.atom.sequence,
+                    TimeStampURID::Frames(urids.units.frame),
+                )
+                .unwrap();
+            for event in Sequence::read(body, urids.units.beat)
+                .unwrap()
+                .map(Event::from)
+            {
+                if event.type_urid() == Some(urids.atom.int.into_general()) {
+                    writer.forward_event(event).unwrap();
+                }
+            }
+        }
+
+        let space = Space::from_slice(output.as_ref());
+        let (body, _) = space.split_atom_body(urids.atom.sequence).unwrap();
+        let events: Vec<Event> = Sequence::read(body, urids.units.beat)
+            .unwrap()
+            .map(Event::from)
+            .collect();
+        assert_eq!(1, events.len());
+        let (stamp, value) = events[0].read(urids.atom.int, ()).unwrap();
+        assert_eq!(Some(3), stamp.as_frames());
+        assert_eq!(42, value);
+        assert!(events[0].read(urids.atom.float, ()).is_none());
+    }
 }
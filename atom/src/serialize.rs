@@ -0,0 +1,735 @@
+//! Conversion between Rust values and atoms.
+//!
+//! Writing nested objects property by property and reading them back by looping over the properties is tedious. The [`AtomSerialize`](trait.AtomSerialize.html) and [`AtomDeserialize`](trait.AtomDeserialize.html) traits instead describe how a value is written to and read from an atom.
+//!
+//! Numbers, booleans, strings, paths, URIDs and vectors of numbers map to their respective atom types. Structs become objects and their fields become properties, keyed by the URIDs of `UriBound` types. The traits can be derived for these structs:
+//!
+//! * The struct needs the class of the object, given with `#[lv2(class = MyClass)]`.
+//! * Every field needs the key of it's property, given with `#[lv2(key = MyProperty)]`, or has to be skipped with `#[lv2(skip)]`. Skipped fields are not written and are set to their default value when reading.
+//! * Fields of type `Option<T>` are optional properties. All other properties are required.
+//!
+//! Enums can be derived too: Every variant needs a class. Unit variants are written as the URID of their class and variants with named fields are written as objects of their class.
+//!
+//! Besides the [`AtomURIDCollection`](../struct.AtomURIDCollection.html), every type has it's own [`URIDs`](trait.AtomSerialize.html#associatedtype.URIDs), which contain the URIDs of it's classes and keys. These have to be retrieved from the host beforehand, for example as a part of the plugin's URID collection.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use std::path::PathBuf;
+//! use urid::*;
+//!
+//! #[uri("urn:synth#Settings")]
+//! struct SettingsClass;
+//!
+//! #[uri("urn:synth#gain")]
+//! struct Gain;
+//!
+//! #[uri("urn:synth#sample")]
+//! struct Sample;
+//!
+//! #[derive(AtomSerialize, AtomDeserialize, Debug, PartialEq)]
+//! #[lv2(class = SettingsClass)]
+//! struct Settings {
+//!     #[lv2(key = Gain)]
+//!     gain: f32,
+//!     #[lv2(key = Sample)]
+//!     sample: Option<PathBuf>,
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! struct URIDs {
+//!     atom: AtomURIDCollection,
+//!     settings: <Settings as AtomSerialize>::URIDs,
+//! }
+//!
+//! # let map = HashURIDMapper::new();
+//! // URID cache creation is omitted.
+//! let urids: URIDs = map.populate_collection().unwrap();
+//! let settings = Settings {
+//!     gain: 0.5,
+//!     sample: Some(PathBuf::from("kick.wav")),
+//! };
+//!
+//! // Writing the settings as an object.
+//! let mut buffer = AtomBuffer::<256>::new();
+//! let mut space = buffer.mut_space();
+//! settings
+//!     .serialize(&mut space as &mut dyn MutSpace, &urids.settings, &urids.atom)
+//!     .unwrap();
+//!
+//! // Reading them again.
+//! let atom = buffer.atom().unwrap();
+//! assert_eq!(Ok(settings), atom.deserialize(&urids.settings, &urids.atom));
+//!
+//! // Errors tell exactly what's wrong.
+//! let error = atom.deserialize::<i32>(&(), &urids.atom).unwrap_err();
+//! assert_eq!(
+//!     DeserializeError::WrongType {
+//!         expected: urids.atom.int.into_general(),
+//!         found: urids.atom.object.into_general(),
+//!     },
+//!     error
+//! );
+//! ```
+use crate::object::*;
+use crate::scalar::*;
+use crate::space::*;
+use crate::string::{Path, String as StringAtom};
+use crate::vector::Vector;
+use crate::*;
+use std::fmt;
+use std::path::PathBuf;
+
+/// A value that can be written as an atom.
+///
+/// [See also the module documentation.](index.html)
+pub trait AtomSerialize {
+    /// The URIDs needed to write the value, besides the ones of the `AtomURIDCollection`.
+    type URIDs: URIDCollection;
+
+    /// Write the value as an atom to the space.
+    ///
+    /// If the value can't be written, an error is returned and the written data is malformed.
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        urids: &Self::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError>;
+
+    /// Check whether the value is absent and therefore shouldn't be written as a property.
+    ///
+    /// This is only `true` for the `None` value of an `Option`.
+    fn is_absent(&self) -> bool {
+        false
+    }
+}
+
+/// A value that can be read from an atom.
+///
+/// [See also the module documentation.](index.html)
+pub trait AtomDeserialize: Sized {
+    /// The URIDs needed to read the value, besides the ones of the `AtomURIDCollection`.
+    type URIDs: URIDCollection;
+
+    /// Read the value from an atom.
+    fn deserialize(
+        atom: UnidentifiedAtom,
+        urids: &Self::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Self, DeserializeError>;
+
+    /// The value of a property that is missing.
+    ///
+    /// If the value is required, `None` is returned. This is only `Some` for `Option`s, which are `None` if the property is missing.
+    fn absent() -> Option<Self> {
+        None
+    }
+}
+
+/// Errors that may occur while writing a value as an atom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SerializeError {
+    /// The space isn't big enough for the atom.
+    NoSpace,
+    /// The value is absent, like the `None` value of an `Option`, and has no atom representation.
+    Absent,
+    /// The value contains text that isn't valid UTF-8, like a non-UTF-8 path.
+    NotUtf8,
+}
+
+impl fmt::Display for SerializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SerializeError::NoSpace => write!(f, "not enough space for the atom"),
+            SerializeError::Absent => write!(f, "an absent value can't be written"),
+            SerializeError::NotUtf8 => write!(f, "the value isn't valid UTF-8"),
+        }
+    }
+}
+
+impl std::error::Error for SerializeError {}
+
+/// Errors that may occur while reading a value from an atom.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DeserializeError {
+    /// The space doesn't contain an atom.
+    NoAtom,
+    /// The atom isn't of the expected type.
+    WrongType { expected: URID, found: URID },
+    /// The atom has the expected type, but it's body is malformed.
+    MalformedBody { type_: URID },
+    /// The object isn't an instance of the expected class.
+    WrongClass { expected: URID, found: URID },
+    /// None of the variants of an enum has the class of the object or URID.
+    UnknownVariant { found: URID },
+    /// A required property of an object is missing.
+    MissingProperty { field: &'static str, key: URID },
+    /// A property of an object isn't of the expected type.
+    WrongPropertyType {
+        field: &'static str,
+        key: URID,
+        expected: URID,
+        found: URID,
+    },
+}
+
+impl fmt::Display for DeserializeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DeserializeError::NoAtom => write!(f, "no atom found"),
+            DeserializeError::WrongType { expected, found } => write!(
+                f,
+                "expected an atom of type {}, found type {}",
+                expected.get(),
+                found.get()
+            ),
+            DeserializeError::MalformedBody { type_ } => {
+                write!(f, "malformed body of an atom of type {}", type_.get())
+            }
+            DeserializeError::WrongClass { expected, found } => write!(
+                f,
+                "expected an object of class {}, found class {}",
+                expected.get(),
+                found.get()
+            ),
+            DeserializeError::UnknownVariant { found } => {
+                write!(f, "no variant for class {}", found.get())
+            }
+            DeserializeError::MissingProperty { field, key } => {
+                write!(f, "missing property {} for field `{}`", key.get(), field)
+            }
+            DeserializeError::WrongPropertyType {
+                field,
+                key,
+                expected,
+                found,
+            } => write!(
+                f,
+                "expected property {} for field `{}` to be of type {}, found type {}",
+                key.get(),
+                field,
+                expected.get(),
+                found.get()
+            ),
+        }
+    }
+}
+
+impl std::error::Error for DeserializeError {}
+
+impl<'a> UnidentifiedAtom<'a> {
+    /// Read a value from the atom.
+    ///
+    /// This is the same as [`AtomDeserialize::deserialize`](serialize/trait.AtomDeserialize.html#tymethod.deserialize), but can be called on the atom.
+    pub fn deserialize<T: AtomDeserialize>(
+        self,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<T, DeserializeError> {
+        T::deserialize(self, urids, atom_urids)
+    }
+
+    /// Read an atom of the given type and return precise errors if that's not possible.
+    pub fn read_checked<'b, A: Atom<'a, 'b>>(
+        self,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Result<A::ReadHandle, DeserializeError> {
+        let found = self.type_urid().ok_or(DeserializeError::NoAtom)?;
+        if found != urid {
+            return Err(DeserializeError::WrongType {
+                expected: urid.into_general(),
+                found,
+            });
+        }
+        self.read(urid, parameter)
+            .ok_or(DeserializeError::MalformedBody { type_: found })
+    }
+
+    /// Read an object or a blank object and return precise errors if that's not possible.
+    ///
+    /// The class of the object isn't checked.
+    pub fn read_object(
+        self,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(ObjectHeader, ObjectReader<'a>), DeserializeError> {
+        match self.type_urid() {
+            Some(found) if found == atom_urids.blank => self.read_checked(atom_urids.blank, ()),
+            _ => self.read_checked(atom_urids.object, ()),
+        }
+    }
+}
+
+impl<'a> ObjectReader<'a> {
+    /// Find the first property with the given key.
+    pub fn get<K: ?Sized>(self, key: URID<K>) -> Option<UnidentifiedAtom<'a>> {
+        self.into_iter()
+            .find(|(header, _)| header.key == key)
+            .map(|(_, atom)| atom)
+    }
+
+    /// Read the value of a property.
+    ///
+    /// The name of the field is used to create precise errors. If the property is missing, the [`absent`](trait.AtomDeserialize.html#method.absent) value is returned, if there is any.
+    pub fn deserialize_property<K: ?Sized, T: AtomDeserialize>(
+        self,
+        field: &'static str,
+        key: URID<K>,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<T, DeserializeError> {
+        let key = key.into_general();
+        let atom = match self.get(key) {
+            Some(atom) => atom,
+            None => return T::absent().ok_or(DeserializeError::MissingProperty { field, key }),
+        };
+        T::deserialize(atom, urids, atom_urids).map_err(|error| match error {
+            DeserializeError::WrongType { expected, found } => {
+                DeserializeError::WrongPropertyType {
+                    field,
+                    key,
+                    expected,
+                    found,
+                }
+            }
+            error => error,
+        })
+    }
+}
+
+impl<'a, 'b> ObjectWriter<'a, 'b> {
+    /// Write a value as a property.
+    ///
+    /// Absent values, like the `None` value of an `Option`, aren't written.
+    pub fn serialize_property<K: ?Sized, T: AtomSerialize + ?Sized>(
+        &mut self,
+        key: URID<K>,
+        value: &T,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        if value.is_absent() {
+            return Ok(());
+        }
+        let space = self.property(key).ok_or(SerializeError::NoSpace)?;
+        value.serialize(space, urids, atom_urids)
+    }
+}
+
+/// Implement the traits for types that are written as scalar atoms.
+macro_rules! impl_scalar {
+    ($type:ty, $atom:ident, $urid:ident) => {
+        impl AtomSerialize for $type {
+            type URIDs = ();
+
+            fn serialize<'a>(
+                &self,
+                space: &mut dyn MutSpace<'a>,
+                _: &(),
+                atom_urids: &AtomURIDCollection,
+            ) -> Result<(), SerializeError> {
+                space
+                    .init(atom_urids.$urid, *self)
+                    .map(|_| ())
+                    .ok_or(SerializeError::NoSpace)
+            }
+        }
+
+        impl AtomDeserialize for $type {
+            type URIDs = ();
+
+            fn deserialize(
+                atom: UnidentifiedAtom,
+                _: &(),
+                atom_urids: &AtomURIDCollection,
+            ) -> Result<Self, DeserializeError> {
+                atom.read_checked(atom_urids.$urid, ())
+            }
+        }
+
+        impl AtomSerialize for Vec<$type> {
+            type URIDs = ();
+
+            fn serialize<'a>(
+                &self,
+                space: &mut dyn MutSpace<'a>,
+                _: &(),
+                atom_urids: &AtomURIDCollection,
+            ) -> Result<(), SerializeError> {
+                let mut writer = space
+                    .init(atom_urids.vector::<$atom>(), atom_urids.$urid)
+                    .ok_or(SerializeError::NoSpace)?;
+                writer
+                    .append(self.as_slice())
+                    .map(|_| ())
+                    .ok_or(SerializeError::NoSpace)
+            }
+        }
+
+        impl AtomDeserialize for Vec<$type> {
+            type URIDs = ();
+
+            fn deserialize(
+                atom: UnidentifiedAtom,
+                _: &(),
+                atom_urids: &AtomURIDCollection,
+            ) -> Result<Self, DeserializeError> {
+                atom.read_checked::<Vector<$atom>>(atom_urids.vector(), atom_urids.$urid)
+                    .map(|slice| slice.to_vec())
+            }
+        }
+    };
+}
+
+impl_scalar!(i32, Int, int);
+impl_scalar!(i64, Long, long);
+impl_scalar!(f32, Float, float);
+impl_scalar!(f64, Double, double);
+
+impl AtomSerialize for bool {
+    type URIDs = ();
+
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        space
+            .init(atom_urids.bool, *self as i32)
+            .map(|_| ())
+            .ok_or(SerializeError::NoSpace)
+    }
+}
+
+impl AtomDeserialize for bool {
+    type URIDs = ();
+
+    fn deserialize(
+        atom: UnidentifiedAtom,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Self, DeserializeError> {
+        atom.read_checked(atom_urids.bool, ())
+            .map(|value| value != 0)
+    }
+}
+
+impl AtomSerialize for URID {
+    type URIDs = ();
+
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        space
+            .init(atom_urids.urid, *self)
+            .map(|_| ())
+            .ok_or(SerializeError::NoSpace)
+    }
+}
+
+impl AtomDeserialize for URID {
+    type URIDs = ();
+
+    fn deserialize(
+        atom: UnidentifiedAtom,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Self, DeserializeError> {
+        atom.read_checked(atom_urids.urid, ())
+    }
+}
+
+impl AtomSerialize for str {
+    type URIDs = ();
+
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        space
+            .init(atom_urids.string, ())
+            .and_then(|mut writer| writer.append(self).map(|_| ()))
+            .ok_or(SerializeError::NoSpace)
+    }
+}
+
+impl AtomSerialize for std::string::String {
+    type URIDs = ();
+
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        urids: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        self.as_str().serialize(space, urids, atom_urids)
+    }
+}
+
+/// Reading a string allocates memory.
+impl AtomDeserialize for std::string::String {
+    type URIDs = ();
+
+    fn deserialize(
+        atom: UnidentifiedAtom,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Self, DeserializeError> {
+        atom.read_checked::<StringAtom>(atom_urids.string, ())
+            .map(str::to_owned)
+    }
+}
+
+/// Paths are written as `atom:Path`, which requires them to be valid UTF-8.
+impl AtomSerialize for PathBuf {
+    type URIDs = ();
+
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        let path = self.to_str().ok_or(SerializeError::NotUtf8)?;
+        space
+            .init(atom_urids.path, ())
+            .and_then(|mut writer| writer.append(path).map(|_| ()))
+            .ok_or(SerializeError::NoSpace)
+    }
+}
+
+/// Reading a path allocates memory.
+impl AtomDeserialize for PathBuf {
+    type URIDs = ();
+
+    fn deserialize(
+        atom: UnidentifiedAtom,
+        _: &(),
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Self, DeserializeError> {
+        atom.read_checked::<Path>(atom_urids.path, ())
+            .map(PathBuf::from)
+    }
+}
+
+impl<T: AtomSerialize> AtomSerialize for Option<T> {
+    type URIDs = T::URIDs;
+
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        self.as_ref()
+            .ok_or(SerializeError::Absent)?
+            .serialize(space, urids, atom_urids)
+    }
+
+    fn is_absent(&self) -> bool {
+        self.is_none()
+    }
+}
+
+impl<T: AtomDeserialize> AtomDeserialize for Option<T> {
+    type URIDs = T::URIDs;
+
+    fn deserialize(
+        atom: UnidentifiedAtom,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Self, DeserializeError> {
+        T::deserialize(atom, urids, atom_urids).map(Some)
+    }
+
+    fn absent() -> Option<Self> {
+        Some(None)
+    }
+}
+
+impl<T: AtomSerialize + ?Sized> AtomSerialize for Box<T> {
+    type URIDs = T::URIDs;
+
+    fn serialize<'a>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), SerializeError> {
+        self.as_ref().serialize(space, urids, atom_urids)
+    }
+
+    fn is_absent(&self) -> bool {
+        self.as_ref().is_absent()
+    }
+}
+
+impl<T: AtomDeserialize> AtomDeserialize for Box<T> {
+    type URIDs = T::URIDs;
+
+    fn deserialize(
+        atom: UnidentifiedAtom,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Self, DeserializeError> {
+        T::deserialize(atom, urids, atom_urids).map(Box::new)
+    }
+
+    fn absent() -> Option<Self> {
+        T::absent().map(Box::new)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::space::*;
+    use std::path::PathBuf;
+    use urid::*;
+
+    fn round_trip<T: AtomSerialize<URIDs = ()> + AtomDeserialize<URIDs = ()>>(
+        value: &T,
+        urids: &AtomURIDCollection,
+    ) -> Result<T, DeserializeError> {
+        let mut buffer = AtomBuffer::<256>::new();
+        let mut space = buffer.mut_space();
+        value
+            .serialize(&mut space as &mut dyn MutSpace, &(), urids)
+            .unwrap();
+        buffer.atom().unwrap().deserialize(&(), urids)
+    }
+
+    #[test]
+    fn test_values() {
+        let map = HashURIDMapper::new();
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        assert_eq!(Ok(-17), round_trip(&-17i32, &urids));
+        assert_eq!(Ok(1 << 40), round_trip(&(1i64 << 40), &urids));
+        assert_eq!(Ok(0.5), round_trip(&0.5f32, &urids));
+        assert_eq!(Ok(0.25), round_trip(&0.25f64, &urids));
+        assert_eq!(Ok(true), round_trip(&true, &urids));
+        assert_eq!(
+            Ok(urids.int.into_general()),
+            round_trip(&urids.int.into_general(), &urids)
+        );
+        assert_eq!(
+            Ok("Hello".to_owned()),
+            round_trip(&"Hello".to_owned(), &urids)
+        );
+        assert_eq!(
+            Ok(PathBuf::from("/tmp/kick.wav")),
+            round_trip(&PathBuf::from("/tmp/kick.wav"), &urids)
+        );
+        assert_eq!(
+            Ok(vec![1.0f32, 2.0, 3.0]),
+            round_trip(&vec![1.0f32, 2.0, 3.0], &urids)
+        );
+        assert_eq!(Ok(Some(3)), round_trip(&Some(3i32), &urids));
+    }
+
+    #[test]
+    fn test_errors() {
+        let map = HashURIDMapper::new();
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut buffer = AtomBuffer::<256>::new();
+        let mut space = buffer.mut_space();
+        1.0f32
+            .serialize(&mut space as &mut dyn MutSpace, &(), &urids)
+            .unwrap();
+        let atom = buffer.atom().unwrap();
+
+        assert_eq!(
+            Err(DeserializeError::WrongType {
+                expected: urids.int.into_general(),
+                found: urids.float.into_general(),
+            }),
+            atom.deserialize::<i32>(&(), &urids)
+        );
+        assert_eq!(
+            Err(DeserializeError::WrongType {
+                expected: urids.vector::<Int>().into_general(),
+                found: urids.float.into_general(),
+            }),
+            atom.deserialize::<Vec<i32>>(&(), &urids)
+        );
+        assert_eq!(
+            Err(DeserializeError::NoAtom),
+            UnidentifiedAtom::new(Space::from_slice(&[])).deserialize::<i32>(&(), &urids)
+        );
+
+        // The vector has the right type, but the wrong children.
+        let mut buffer = AtomBuffer::<256>::new();
+        let mut space = buffer.mut_space();
+        vec![1.0f32]
+            .serialize(&mut space as &mut dyn MutSpace, &(), &urids)
+            .unwrap();
+        assert_eq!(
+            Err(DeserializeError::MalformedBody {
+                type_: urids.vector::<Int>().into_general()
+            }),
+            buffer.atom().unwrap().deserialize::<Vec<i32>>(&(), &urids)
+        );
+
+        // The body of the vector isn't a multiple of the child size.
+        let mut buffer = AtomBuffer::<256>::new();
+        {
+            let mut space = buffer.mut_space();
+            let mut frame =
+                FramedMutSpace::new(&mut space as &mut dyn MutSpace, urids.vector::<Int>())
+                    .unwrap();
+            let header = sys::LV2_Atom_Vector_Body {
+                child_type: urids.int.get(),
+                child_size: 4,
+            };
+            (&mut frame as &mut dyn MutSpace)
+                .write(&header, false)
+                .unwrap();
+            frame.write_raw(&[1, 2, 3, 4, 5, 6], false).unwrap();
+        }
+        assert_eq!(
+            Err(DeserializeError::MalformedBody {
+                type_: urids.vector::<Int>().into_general()
+            }),
+            buffer.atom().unwrap().deserialize::<Vec<i32>>(&(), &urids)
+        );
+
+        let mut buffer = AtomBuffer::<8>::new();
+        let mut space = buffer.mut_space();
+        assert_eq!(
+            Err(SerializeError::NoSpace),
+            "Hello".serialize(&mut space as &mut dyn MutSpace, &(), &urids)
+        );
+        assert_eq!(
+            Err(SerializeError::Absent),
+            None::<i32>.serialize(&mut space as &mut dyn MutSpace, &(), &urids)
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_non_utf8_path() {
+        use std::ffi::OsStr;
+        use std::os::unix::ffi::OsStrExt;
+
+        let map = HashURIDMapper::new();
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut buffer = AtomBuffer::<256>::new();
+        let mut space = buffer.mut_space();
+        let path = PathBuf::from(OsStr::from_bytes(b"/tmp/\xff.wav"));
+        assert_eq!(
+            Err(SerializeError::NotUtf8),
+            path.serialize(&mut space as &mut dyn MutSpace, &(), &urids)
+        );
+    }
+}
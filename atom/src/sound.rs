@@ -0,0 +1,113 @@
+//! An atom containing a snippet of audio.
+//!
+//! A sound is a vector of floats in the format of an audio port buffer, but with an arbitrary length. It inherently depends on the sample rate, which is assumed to be known from context. Sounds are commonly used to send audio to a UI, for example to display a waveform or a scope.
+//!
+//! Reading and writing works just like with vectors of floats: The reading handle is a slice of samples and the writing handle appends samples to the atom.
+//!
+//! # Example
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::vector::VectorWriter;
+//!
+//! #[derive(PortCollection)]
+//! struct MyPorts {
+//!     audio: InputPort<Audio>,
+//!     notify: OutputPort<AtomPort>,
+//! }
+//!
+//! fn run(ports: &mut MyPorts, urids: &AtomURIDCollection) {
+//!     let mut output: VectorWriter<Float> = ports.notify.init(urids.sound, urids.float).unwrap();
+//!     output.append(&ports.audio).unwrap();
+//! }
+//! ```
+//!
+//! # Specification
+//!
+//! [http://lv2plug.in/ns/ext/atom/atom.html#Sound](http://lv2plug.in/ns/ext/atom/atom.html#Sound)
+use crate::scalar::Float;
+use crate::space::*;
+use crate::vector::*;
+use crate::*;
+use urid::*;
+
+/// An atom containing a snippet of audio.
+///
+/// [See also the module documentation.](index.html)
+pub struct Sound;
+
+unsafe impl UriBound for Sound {
+    const URI: &'static [u8] = sys::LV2_ATOM__Sound;
+}
+
+impl<'a, 'b> Atom<'a, 'b> for Sound
+where
+    'a: 'b,
+{
+    type ReadParameter = URID<Float>;
+    type ReadHandle = &'a [f32];
+    type WriteParameter = URID<Float>;
+    type WriteHandle = VectorWriter<'a, 'b, Float>;
+
+    fn read(body: Space<'a>, float_urid: URID<Float>) -> Option<&'a [f32]> {
+        read_children(body, float_urid)
+    }
+
+    fn init(
+        frame: FramedMutSpace<'a, 'b>,
+        float_urid: URID<Float>,
+    ) -> Option<VectorWriter<'a, 'b, Float>> {
+        init_children(frame, float_urid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::space::*;
+    use std::mem::size_of;
+    use urid::*;
+
+    #[test]
+    fn test_sound() {
+        let map = HashURIDMapper::new();
+        let urids = crate::AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        let samples: Vec<f32> = (0..16).map(|i| i as f32 / 16.0).collect();
+
+        // writing
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(urids.sound, urids.float)
+                .unwrap();
+            writer.append(&samples[..15]).unwrap();
+            writer.push(samples[15]).unwrap();
+        }
+
+        // verifying
+        {
+            let (vector, _) = raw_space.split_at(size_of::<sys::LV2_Atom_Vector>());
+            let vector = unsafe { &*(vector.as_ptr() as *const sys::LV2_Atom_Vector) };
+            assert_eq!(vector.atom.type_, urids.sound);
+            assert_eq!(
+                vector.atom.size as usize,
+                size_of::<sys::LV2_Atom_Vector_Body>() + size_of::<f32>() * samples.len()
+            );
+            assert_eq!(vector.body.child_size as usize, size_of::<f32>());
+            assert_eq!(vector.body.child_type, urids.float);
+        }
+
+        // reading
+        {
+            let space = Space::from_slice(raw_space.as_ref());
+            let atom = UnidentifiedAtom::new(space);
+            assert_eq!(
+                samples.as_slice(),
+                atom.read(urids.sound, urids.float).unwrap()
+            );
+            assert!(atom.read(urids.vector::<Float>(), urids.float).is_none());
+        }
+    }
+}
@@ -7,7 +7,7 @@
 //! In the first case, we have to trust that the space behind the atom header is accessible since we have no way to check whether it is or not. Therefore, we have to assume that it is sound.
 //!
 //! The second case is sound since a) the data is contained in a slice and therefore is accessible, b) generic type parameter bounds assure that the type is plain-old-data and c) 64-bit padding is assured.
-use crate::Atom;
+use crate::{Atom, UnidentifiedAtom};
 use std::cell::Cell;
 use std::marker::Unpin;
 use std::mem::{size_of, size_of_val};
@@ -355,6 +355,309 @@ impl<'a> MutSpace<'a> for SpaceHead<'a> {
     }
 }
 
+/// A fixed-capacity buffer of `N` bytes to write atoms to, without any allocations.
+///
+/// The buffer is 64-bit-aligned and can be kept on the stack or in the plugin struct. Its [`mut_space`](#method.mut_space) is a [`MutSpace`](trait.MutSpace.html) which allocates the bytes of the buffer one after another. Once the atom is written, it can be read again with [`atom`](#method.atom), for example to forward it to a sequence. Calling [`reset`](#method.reset) makes the whole buffer available again, so the same buffer can be used in every cycle.
+///
+/// # Usage example
+///
+/// ```
+/// # use lv2_atom::prelude::*;
+/// # use lv2_atom::space::*;
+/// # use urid::*;
+/// # let map = HashURIDMapper::new();
+/// // URID cache creation is omitted.
+/// let urids: AtomURIDCollection = map.populate_collection().unwrap();
+///
+/// let mut buffer = AtomBuffer::<64>::new();
+/// for value in 0..4 {
+///     // Clearing the buffer from the previous cycle.
+///     buffer.reset();
+///
+///     // Writing an integer.
+///     let mut space = buffer.mut_space();
+///     (&mut space as &mut dyn MutSpace).init(urids.int, value).unwrap();
+///
+///     // Reading the written atom.
+///     let atom = buffer.atom().unwrap();
+///     assert_eq!(value, atom.read(urids.int, ()).unwrap());
+/// }
+/// ```
+#[repr(C, align(8))]
+pub struct AtomBuffer<const N: usize> {
+    data: [u8; N],
+    allocated_bytes: usize,
+}
+
+impl<const N: usize> AtomBuffer<N> {
+    /// Create a new, empty buffer.
+    pub const fn new() -> Self {
+        Self {
+            data: [0; N],
+            allocated_bytes: 0,
+        }
+    }
+
+    /// Return the number of bytes the buffer can hold.
+    pub const fn capacity(&self) -> usize {
+        N
+    }
+
+    /// Return the number of bytes that have been allocated since the last reset.
+    pub fn len(&self) -> usize {
+        self.allocated_bytes
+    }
+
+    /// Check whether nothing has been allocated since the last reset.
+    pub fn is_empty(&self) -> bool {
+        self.allocated_bytes == 0
+    }
+
+    /// Make the whole buffer available for allocation again.
+    ///
+    /// The old data is not cleared, but it will be overwritten by the next atom.
+    pub fn reset(&mut self) {
+        self.allocated_bytes = 0;
+    }
+
+    /// Return a space that allocates the free bytes of the buffer.
+    ///
+    /// The bytes are allocated after the ones that have already been allocated, so multiple spaces can be created one after another without a reset.
+    pub fn mut_space(&mut self) -> AtomBufferSpace<'_> {
+        AtomBufferSpace {
+            space: &mut self.data[self.allocated_bytes..],
+            allocated_bytes: &mut self.allocated_bytes,
+        }
+    }
+
+    /// Return the bytes that have been allocated since the last reset.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.data[..self.allocated_bytes]
+    }
+
+    /// Return the space that has been allocated since the last reset.
+    pub fn space(&self) -> Space<'_> {
+        Space::from_slice(self.as_bytes())
+    }
+
+    /// Return the first atom that has been written to the buffer.
+    ///
+    /// If nothing has been written since the last reset, `None` is returned.
+    pub fn atom(&self) -> Option<UnidentifiedAtom<'_>> {
+        let (atom, _) = self.space().split_atom()?;
+        Some(UnidentifiedAtom::new(atom))
+    }
+}
+
+impl<const N: usize> Default for AtomBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/// A [`MutSpace`](trait.MutSpace.html) that allocates the free bytes of an [`AtomBuffer`](struct.AtomBuffer.html).
+///
+/// It is created with [`AtomBuffer::mut_space`](struct.AtomBuffer.html#method.mut_space) and holds the free part of the buffer as a slice, which is split with every allocation. Therefore, allocated slices never overlap and stay valid while more bytes are allocated.
+pub struct AtomBufferSpace<'a> {
+    space: &'a mut [u8],
+    allocated_bytes: &'a mut usize,
+}
+
+impl<'a> MutSpace<'a> for AtomBufferSpace<'a> {
+    fn allocate(&mut self, size: usize, apply_padding: bool) -> Option<(usize, &'a mut [u8])> {
+        // The buffer is 64-bit-aligned, so the alignment of the free space follows from the number of allocated bytes.
+        let padding = if apply_padding {
+            (8 - *self.allocated_bytes % 8) % 8
+        } else {
+            0
+        };
+        let end = padding.checked_add(size)?;
+        if end > self.space.len() {
+            return None;
+        }
+        let (data, rest) = std::mem::take(&mut self.space).split_at_mut(end);
+        self.space = rest;
+        *self.allocated_bytes += end;
+        Some((padding, &mut data[padding..]))
+    }
+}
+
+/// An owned copy of an atom.
+///
+/// An [`UnidentifiedAtom`](../struct.UnidentifiedAtom.html) borrows the buffer it was read from, for example the buffer of a port, and therefore can not be kept after `run` returns. An `AtomBuf` owns a 64-bit-aligned copy of the atom instead. It can be sent to other threads, stored in queues, and read or forwarded like any other atom.
+///
+/// Creating or cloning an `AtomBuf` allocates memory. If you need to copy atoms in the audio threading class, create the buffer with [`with_capacity`](#method.with_capacity) beforehand and use [`copy_from`](#method.copy_from), which never allocates.
+///
+/// ```
+/// use lv2_atom::prelude::*;
+/// use lv2_atom::space::*;
+/// use lv2_units::prelude::*;
+/// use urid::*;
+///
+/// let map = HashURIDMapper::new();
+/// let urids: AtomURIDCollection = map.populate_collection().unwrap();
+/// let frame: URID<Frame> = map.map_type().unwrap();
+///
+/// let mut input = AtomBuffer::<64>::new();
+/// (&mut input.mut_space() as &mut dyn MutSpace).init(urids.int, 42).unwrap();
+///
+/// // Copying the atom, for example to keep it beyond the current cycle.
+/// let buf = AtomBuf::new(input.atom().unwrap()).unwrap();
+/// let buf = std::thread::spawn(move || buf).join().unwrap();
+/// assert_eq!(42, buf.read(urids.int, ()).unwrap());
+///
+/// // Forwarding the copy to a sequence.
+/// let mut output = AtomBuffer::<128>::new();
+/// let mut space = output.mut_space();
+/// let mut writer = (&mut space as &mut dyn MutSpace)
+///     .init(urids.sequence, TimeStampURID::Frames(frame))
+///     .unwrap();
+/// writer.forward(TimeStamp::Frames(0), buf.atom()).unwrap();
+/// ```
+pub struct AtomBuf {
+    data: Box<[u64]>,
+    len: usize,
+}
+
+impl AtomBuf {
+    /// Create a buffer that can hold atoms with up to `capacity` bytes, including the header.
+    ///
+    /// The buffer is empty until an atom is copied into it.
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            data: vec![0; capacity.div_ceil(8)].into_boxed_slice(),
+            len: 0,
+        }
+    }
+
+    /// Create a copy of an atom.
+    ///
+    /// If the space of the atom doesn't contain a complete atom, `None` is returned.
+    pub fn new(atom: UnidentifiedAtom) -> Option<Self> {
+        let data = Self::atom_data(atom)?;
+        let mut buf = Self::with_capacity(data.len());
+        buf.copy_from(atom)?;
+        Some(buf)
+    }
+
+    /// Create an atom from it's type and body.
+    ///
+    /// This is useful if the header and the body of an atom are stored separately, for example by a host that stores state properties. If the body is too big to be described by an atom header, `None` is returned.
+    pub fn from_body<T: ?Sized>(type_: URID<T>, body: &[u8]) -> Option<Self> {
+        if body.len() > u32::MAX as usize {
+            return None;
+        }
+        let header = sys::LV2_Atom {
+            size: body.len() as u32,
+            type_: type_.get(),
+        };
+        let header_size = size_of::<sys::LV2_Atom>();
+        let mut buf = Self::with_capacity(header_size + body.len());
+        let bytes = buf.bytes_mut();
+        bytes[..header_size].copy_from_slice(unsafe {
+            std::slice::from_raw_parts(&header as *const _ as *const u8, header_size)
+        });
+        bytes[header_size..header_size + body.len()].copy_from_slice(body);
+        buf.len = header_size + body.len();
+        Some(buf)
+    }
+
+    /// Replace the contents of the buffer with a copy of an atom.
+    ///
+    /// This method never allocates memory. If the atom is bigger than the capacity of the buffer, `None` is returned and the buffer is left unchanged.
+    pub fn copy_from(&mut self, atom: UnidentifiedAtom) -> Option<()> {
+        let data = Self::atom_data(atom)?;
+        if data.len() > self.capacity() {
+            return None;
+        }
+        self.bytes_mut()[..data.len()].copy_from_slice(data);
+        self.len = data.len();
+        Some(())
+    }
+
+    /// Return the header and the body of the atom, without any space behind it.
+    fn atom_data(atom: UnidentifiedAtom<'_>) -> Option<&[u8]> {
+        let (space, _) = atom.space().split_atom()?;
+        space.data()
+    }
+
+    /// Return the number of bytes the buffer can hold.
+    pub fn capacity(&self) -> usize {
+        self.data.len() * 8
+    }
+
+    /// Return the number of bytes of the contained atom, including the header.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check whether the buffer contains an atom.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Remove the atom from the buffer, but keep the allocated memory.
+    pub fn clear(&mut self) {
+        self.len = 0;
+    }
+
+    /// Return the bytes of the contained atom, including the header.
+    pub fn as_bytes(&self) -> &[u8] {
+        unsafe { std::slice::from_raw_parts(self.data.as_ptr() as *const u8, self.len) }
+    }
+
+    fn bytes_mut(&mut self) -> &mut [u8] {
+        unsafe {
+            std::slice::from_raw_parts_mut(self.data.as_mut_ptr() as *mut u8, self.capacity())
+        }
+    }
+
+    /// Return the contained atom.
+    ///
+    /// The atom can be read and forwarded like any other atom. If the buffer is empty, the returned atom doesn't contain anything and can not be read.
+    pub fn atom(&self) -> UnidentifiedAtom<'_> {
+        UnidentifiedAtom::new(Space::from_slice(self.as_bytes()))
+    }
+
+    /// Try to read the contained atom.
+    ///
+    /// This is a shortcut for [`UnidentifiedAtom::read`](../struct.UnidentifiedAtom.html#method.read).
+    pub fn read<'a, A: Atom<'a, 'a>>(
+        &'a self,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Option<A::ReadHandle> {
+        self.atom().read(urid, parameter)
+    }
+}
+
+/// Cloning a buffer allocates only as much memory as the contained atom needs.
+impl Clone for AtomBuf {
+    fn clone(&self) -> Self {
+        let mut buf = Self::with_capacity(self.len);
+        buf.bytes_mut()[..self.len].copy_from_slice(self.as_bytes());
+        buf.len = self.len;
+        buf
+    }
+}
+
+impl PartialEq for AtomBuf {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for AtomBuf {}
+
+impl std::fmt::Debug for AtomBuf {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("AtomBuf")
+            .field("type_urid", &self.atom().type_urid())
+            .field("len", &self.len)
+            .finish()
+    }
+}
+
 /// A `MutSpace` that notes the amount of allocated space in an atom header.
 pub struct FramedMutSpace<'a, 'b> {
     atom: &'a mut sys::LV2_Atom,
@@ -563,6 +866,79 @@ mod tests {
         test_mut_space(head);
     }
 
+    #[test]
+    fn test_atom_buffer() {
+        let mut buffer = AtomBuffer::<256>::new();
+        assert_eq!(0, &buffer as *const AtomBuffer<256> as usize % 8);
+        test_mut_space(buffer.mut_space());
+        assert!(!buffer.is_empty());
+
+        buffer.reset();
+        assert!(buffer.is_empty());
+        assert!(buffer.atom().is_none());
+
+        let urid = URID::<()>::new(1).unwrap();
+        {
+            let mut space = buffer.mut_space();
+            let mut frame = FramedMutSpace::new(&mut space as &mut dyn MutSpace, urid).unwrap();
+            (&mut frame as &mut dyn MutSpace)
+                .write::<u32>(&42, true)
+                .unwrap();
+        }
+        assert_eq!(size_of::<sys::LV2_Atom>() + 4, buffer.len());
+        let atom = buffer.atom().unwrap();
+        assert_eq!(Some(urid), atom.type_urid());
+
+        // The rest of the buffer is allocated after 4 bytes of padding.
+        let mut space = buffer.mut_space();
+        let space = &mut space as &mut dyn MutSpace;
+        assert_eq!(
+            Some(4),
+            space.allocate(240, true).map(|(padding, _)| padding)
+        );
+        assert!(space.allocate(1, false).is_none());
+    }
+
+    #[test]
+    fn test_atom_buf() {
+        let urid = URID::<()>::new(1).unwrap();
+        let mut buffer = AtomBuffer::<64>::new();
+        {
+            let mut space = buffer.mut_space();
+            let mut frame = FramedMutSpace::new(&mut space as &mut dyn MutSpace, urid).unwrap();
+            (&mut frame as &mut dyn MutSpace)
+                .write::<u64>(&42, true)
+                .unwrap();
+        }
+        let atom = buffer.atom().unwrap();
+
+        let buf = AtomBuf::new(atom).unwrap();
+        assert_eq!(0, buf.as_bytes().as_ptr() as usize % 8);
+        assert_eq!(buffer.as_bytes(), buf.as_bytes());
+        assert_eq!(Some(urid), buf.atom().type_urid());
+        assert_eq!(buf, buf.clone());
+
+        // Copying without allocations.
+        let mut copy = AtomBuf::with_capacity(8);
+        assert!(copy.is_empty());
+        assert!(copy.atom().type_urid().is_none());
+        assert!(copy.copy_from(atom).is_none());
+        assert!(copy.is_empty());
+
+        let mut copy = AtomBuf::with_capacity(32);
+        copy.copy_from(atom).unwrap();
+        assert_eq!(buf, copy);
+        assert_eq!(32, copy.capacity());
+        copy.clear();
+        assert!(copy.is_empty());
+
+        // Reassembling the atom from it's header and body.
+        let body = &buffer.as_bytes()[size_of::<sys::LV2_Atom>()..];
+        assert_eq!(buf, AtomBuf::from_body(urid, body).unwrap());
+
+        assert!(AtomBuf::new(UnidentifiedAtom::new(Space::from_slice(&[]))).is_none());
+    }
+
     #[test]
     fn test_padding_inside_frame() {
         const MEMORY_SIZE: usize = 256;
@@ -0,0 +1,1474 @@
+//! Textual representations of atoms.
+//!
+//! Atoms are binary data, which is hard to read when something goes wrong. This module renders any atom as JSON or as Turtle-like text, with all URIDs resolved to their URIs by an [`Unmap`](../../urid/trait.Unmap.html) implementation. It can also parse the JSON representation and write it to a [`MutSpace`](../space/trait.MutSpace.html), which is useful to write test fixtures.
+//!
+//! The JSON representation of an atom is an object with it's type and it's value:
+//!
+//! ```json
+//! {"type": "atom:Int", "value": 42}
+//! ```
+//!
+//! URIs in the atom namespace are abbreviated with the `atom:` prefix. URIDs that can not be unmapped are represented by their numbers. Objects contain their class, maybe an id, and their properties, and sequences contain their events with either a `frames` or a `beats` time stamp:
+//!
+//! ```json
+//! {
+//!     "type": "atom:Sequence",
+//!     "unit": "http://lv2plug.in/ns/extensions/units#frame",
+//!     "events": [
+//!         {"frames": 0, "value": {
+//!             "type": "atom:Object",
+//!             "otype": "http://lv2plug.in/ns/ext/patch#Set",
+//!             "properties": [
+//!                 {"key": "http://lv2plug.in/ns/ext/patch#value", "value": {"type": "atom:Float", "value": 0.5}}
+//!             ]
+//!         }}
+//!     ]
+//! }
+//! ```
+//!
+//! Tuples and vectors contain a list of values, vectors also contain the `childType` of their items. Literals have either a `lang` or a `datatype`. The bodies of chunks and atoms of unknown types are written as hexadecimal strings. If a known atom type has a malformed body, it's rendered like an unknown type, so the raw bytes can still be inspected.
+//!
+//! The Turtle-like representation resembles the way the atoms would be described in Turtle. It's only meant to be read by humans and therefore, there is no parser for it.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_atom::text;
+//! use urid::*;
+//!
+//! let map = HashURIDMapper::new();
+//! let urids = AtomURIDCollection::from_map(&map).unwrap();
+//!
+//! // Write a fixture.
+//! let mut buffer = AtomBuffer::<256>::new();
+//! let mut space = buffer.mut_space();
+//! text::from_json(
+//!     r#"{"type": "atom:Tuple", "value": [
+//!         {"type": "atom:Int", "value": 42},
+//!         {"type": "atom:String", "value": "Hello"}
+//!     ]}"#,
+//!     &mut space as &mut dyn MutSpace,
+//!     &map,
+//! )
+//! .unwrap();
+//!
+//! let atom = buffer.atom().unwrap();
+//! let mut items = atom.read(urids.tuple, ()).unwrap();
+//! assert_eq!(42, items.next().unwrap().read(urids.int, ()).unwrap());
+//!
+//! // Render it again.
+//! assert_eq!(
+//!     "(\n    \"42\"^^atom:Int\n    \"Hello\"\n)",
+//!     text::to_turtle(atom, &map)
+//! );
+//! ```
+use crate::chunk::Chunk;
+use crate::object::{Blank, Object};
+use crate::scalar::*;
+use crate::sequence::Sequence;
+use crate::space::*;
+use crate::string::{Literal, LiteralInfo, Path, String as StringAtom, URI};
+use crate::tuple::Tuple;
+use crate::vector::Vector;
+use crate::*;
+use std::fmt;
+use sys::LV2_Atom_Event__bindgen_ty_1 as RawTimeStamp;
+use units::units::Beat;
+use urid::*;
+
+/// The namespace of the atom specification, which is abbreviated with `atom:`.
+const ATOM_PREFIX: &str = "http://lv2plug.in/ns/ext/atom#";
+
+/// The URI prefix of language tags used by literals.
+const LANGUAGE_PREFIX: &str = "http://lexvo.org/id/iso639-1/";
+
+/// Render an atom as JSON.
+///
+/// If the space of the atom doesn't even contain an atom header, `null` is returned.
+pub fn to_json<U: Unmap + ?Sized>(atom: UnidentifiedAtom, unmap: &U) -> std::string::String {
+    let mut out = std::string::String::new();
+    match Node::read(atom, unmap) {
+        Some(node) => node.write_json(&mut out, 0),
+        None => out.push_str("null"),
+    }
+    out
+}
+
+/// Render an atom as Turtle-like text.
+///
+/// If the space of the atom doesn't even contain an atom header, `[]` is returned.
+pub fn to_turtle<U: Unmap + ?Sized>(atom: UnidentifiedAtom, unmap: &U) -> std::string::String {
+    let mut out = std::string::String::new();
+    match Node::read(atom, unmap) {
+        Some(node) => node.write_turtle(&mut out, 0),
+        None => out.push_str("[]"),
+    }
+    out
+}
+
+/// Parse the JSON representation of an atom and write the atom to the space.
+///
+/// All URIs are mapped with the given map. If the text can't be parsed or the space is too small, an error is returned and the space may contain a partially written atom.
+pub fn from_json<'a, M: Map + ?Sized>(
+    text: &str,
+    space: &mut dyn MutSpace<'a>,
+    map: &M,
+) -> Result<(), ParseError> {
+    let value = JsonParser::new(text).parse_document()?;
+    let node = Node::from_json(&value)?;
+    let urids = AtomURIDCollection::from_map(map)
+        .ok_or_else(|| ParseError::Unmapped(ATOM_PREFIX.to_owned()))?;
+    node.write(space, map, &urids)
+}
+
+/// Errors that may occur while parsing the textual representation of an atom.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseError {
+    /// The text isn't valid JSON. The byte offset of the error is included.
+    Syntax(usize),
+    /// The JSON value doesn't describe a valid atom.
+    InvalidAtom(&'static str),
+    /// A URI couldn't be mapped.
+    Unmapped(std::string::String),
+    /// The space is too small for the atom.
+    NoSpace,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ParseError::Syntax(offset) => write!(f, "invalid JSON at byte {}", offset),
+            ParseError::InvalidAtom(message) => write!(f, "invalid atom: {}", message),
+            ParseError::Unmapped(uri) => write!(f, "could not map the URI {}", uri),
+            ParseError::NoSpace => write!(f, "not enough space for the atom"),
+        }
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+/// A URI, or the number of a URID that couldn't be unmapped.
+#[derive(Clone, Debug, PartialEq)]
+enum Name {
+    Uri(std::string::String),
+    Urid(u32),
+}
+
+impl Name {
+    fn unmap<U: Unmap + ?Sized>(urid: URID, unmap: &U) -> Self {
+        unmap
+            .unmap(urid)
+            .and_then(|uri| uri.to_str().ok())
+            .map(|uri| Name::Uri(uri.to_owned()))
+            .unwrap_or_else(|| Name::Urid(urid.get()))
+    }
+
+    /// Unmap a raw URID, which is `0` if it's absent.
+    fn unmap_raw<U: Unmap + ?Sized>(urid: u32, unmap: &U) -> Option<Self> {
+        URID::new(urid).map(|urid| Self::unmap(urid, unmap))
+    }
+
+    /// Check whether this is the URI of the given type.
+    fn is<T: UriBound + ?Sized>(&self) -> bool {
+        match self {
+            Name::Uri(uri) => uri.as_bytes() == T::uri().to_bytes(),
+            Name::Urid(_) => false,
+        }
+    }
+
+    fn map<M: Map + ?Sized>(&self, map: &M) -> Result<URID, ParseError> {
+        match self {
+            Name::Uri(uri) => map
+                .map_str(uri)
+                .ok_or_else(|| ParseError::Unmapped(uri.clone())),
+            Name::Urid(urid) => URID::new(*urid).ok_or(ParseError::InvalidAtom("a URID is zero")),
+        }
+    }
+
+    fn map_raw<M: Map + ?Sized>(name: &Option<Self>, map: &M) -> Result<u32, ParseError> {
+        match name {
+            Some(name) => name.map(map).map(|urid| urid.get()),
+            None => Ok(0),
+        }
+    }
+
+    fn from_json(value: &JsonValue) -> Result<Self, ParseError> {
+        match value {
+            JsonValue::String(uri) => Ok(match uri.strip_prefix("atom:") {
+                Some(suffix) => Name::Uri(format!("{}{}", ATOM_PREFIX, suffix)),
+                None => Name::Uri(uri.clone()),
+            }),
+            JsonValue::Number(number) => number
+                .parse()
+                .ok()
+                .filter(|urid| *urid != 0)
+                .map(Name::Urid)
+                .ok_or(ParseError::InvalidAtom("a URID is not a positive integer")),
+            _ => Err(ParseError::InvalidAtom("a URI is not a string")),
+        }
+    }
+
+    fn write_json(&self, out: &mut std::string::String) {
+        match self {
+            Name::Uri(uri) => match uri.strip_prefix(ATOM_PREFIX) {
+                Some(suffix) => write_string(out, &format!("atom:{}", suffix)),
+                None => write_string(out, uri),
+            },
+            Name::Urid(urid) => out.push_str(&urid.to_string()),
+        }
+    }
+
+    fn write_turtle(&self, out: &mut std::string::String) {
+        match self {
+            Name::Uri(uri) => match uri.strip_prefix(ATOM_PREFIX) {
+                Some(suffix) => out.push_str(&format!("atom:{}", suffix)),
+                None => out.push_str(&format!("<{}>", uri)),
+            },
+            Name::Urid(urid) => out.push_str(&format!("\"{}\"^^atom:URID", urid)),
+        }
+    }
+}
+
+/// The time stamp of an event.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Time {
+    Frames(i64),
+    Beats(f64),
+}
+
+/// The contents of an atom, with resolved URIs.
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Int(i32),
+    Long(i64),
+    Float(f32),
+    Double(f64),
+    Bool(bool),
+    Urid(Name),
+    String(std::string::String),
+    Path(std::string::String),
+    Uri(std::string::String),
+    Literal {
+        text: std::string::String,
+        lang: Option<Name>,
+        datatype: Option<Name>,
+    },
+    Chunk(Vec<u8>),
+    Tuple(Vec<Node>),
+    Vector {
+        child_type: Name,
+        items: Vec<Node>,
+    },
+    Object {
+        blank: bool,
+        id: Option<Name>,
+        otype: Name,
+        properties: Vec<(Name, Option<Name>, Node)>,
+    },
+    Sequence {
+        unit: Option<Name>,
+        events: Vec<(Time, Node)>,
+    },
+    Unknown {
+        type_: Name,
+        body: Vec<u8>,
+    },
+}
+
+impl Node {
+    /// Read an atom and resolve all of it's URIDs.
+    fn read<U: Unmap + ?Sized>(atom: UnidentifiedAtom, unmap: &U) -> Option<Self> {
+        let type_urid = atom.type_urid()?;
+        let (body, _) = atom.space().split_atom_body(type_urid)?;
+        let type_ = Name::unmap(type_urid, unmap);
+        Some(
+            Self::read_body(&type_, body, unmap).unwrap_or_else(|| Node::Unknown {
+                type_,
+                body: body.data().unwrap_or(&[]).to_vec(),
+            }),
+        )
+    }
+
+    /// Read the body of an atom with a known type.
+    fn read_body<U: Unmap + ?Sized>(type_: &Name, body: Space, unmap: &U) -> Option<Self> {
+        let node = if type_.is::<Int>() {
+            Node::Int(Int::read(body, ())?)
+        } else if type_.is::<Long>() {
+            Node::Long(Long::read(body, ())?)
+        } else if type_.is::<Float>() {
+            Node::Float(Float::read(body, ())?)
+        } else if type_.is::<Double>() {
+            Node::Double(Double::read(body, ())?)
+        } else if type_.is::<Bool>() {
+            Node::Bool(Bool::read(body, ())? != 0)
+        } else if type_.is::<AtomURID>() {
+            Node::Urid(Name::unmap(AtomURID::read(body, ())?, unmap))
+        } else if type_.is::<StringAtom>() {
+            Node::String(StringAtom::read(body, ())?.to_owned())
+        } else if type_.is::<Path>() {
+            Node::Path(Path::read(body, ())?.to_owned())
+        } else if type_.is::<URI>() {
+            Node::Uri(URI::read(body, ())?.to_owned())
+        } else if type_.is::<Literal>() {
+            let (info, text) = Literal::read(body, ())?;
+            let (lang, datatype) = match info {
+                LiteralInfo::Language(lang) => (Some(Name::unmap(lang, unmap)), None),
+                LiteralInfo::Datatype(datatype) => (None, Some(Name::unmap(datatype, unmap))),
+            };
+            Node::Literal {
+                text: text.to_owned(),
+                lang,
+                datatype,
+            }
+        } else if type_.is::<Chunk>() {
+            Node::Chunk(Chunk::read(body, ())?.to_vec())
+        } else if type_.is::<Tuple>() {
+            Node::Tuple(
+                Tuple::read(body, ())?
+                    .map(|atom| Node::read(atom, unmap))
+                    .collect::<Option<_>>()?,
+            )
+        } else if type_.is::<Vector<Int>>() {
+            Self::read_vector(body, unmap)?
+        } else if type_.is::<Object>() || type_.is::<Blank>() {
+            let (header, reader) = Object::read(body, ())?;
+            Node::Object {
+                blank: type_.is::<Blank>(),
+                id: header.id.map(|id| Name::unmap(id, unmap)),
+                otype: Name::unmap(header.otype, unmap),
+                properties: reader
+                    .map(|(header, atom)| {
+                        Some((
+                            Name::unmap(header.key, unmap),
+                            header.context.map(|context| Name::unmap(context, unmap)),
+                            Node::read(atom, unmap)?,
+                        ))
+                    })
+                    .collect::<Option<_>>()?,
+            }
+        } else if type_.is::<Sequence>() {
+            Self::read_sequence(body, unmap)?
+        } else {
+            return None;
+        };
+        Some(node)
+    }
+
+    /// Read the body of a vector.
+    ///
+    /// Only vectors of numbers, booleans and URIDs are supported.
+    fn read_vector<U: Unmap + ?Sized>(body: Space, unmap: &U) -> Option<Self> {
+        let (header, _) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
+        let child_type = Name::unmap(URID::new(header.child_type)?, unmap);
+
+        // The child type is known, so the URID can be typed.
+        fn items<C: ScalarAtom, T>(
+            body: Space,
+            child_type: u32,
+            node: impl Fn(C::InternalType) -> T,
+        ) -> Option<Vec<T>> {
+            let urid = unsafe { URID::<C>::new_unchecked(child_type) };
+            Some(
+                Vector::<C>::read(body, urid)?
+                    .iter()
+                    .copied()
+                    .map(node)
+                    .collect(),
+            )
+        }
+
+        let items = if child_type.is::<Int>() {
+            items::<Int, _>(body, header.child_type, Node::Int)?
+        } else if child_type.is::<Long>() {
+            items::<Long, _>(body, header.child_type, Node::Long)?
+        } else if child_type.is::<Float>() {
+            items::<Float, _>(body, header.child_type, Node::Float)?
+        } else if child_type.is::<Double>() {
+            items::<Double, _>(body, header.child_type, Node::Double)?
+        } else if child_type.is::<Bool>() {
+            items::<Bool, _>(body, header.child_type, |value| Node::Bool(value != 0))?
+        } else if child_type.is::<AtomURID>() {
+            items::<AtomURID, _>(body, header.child_type, |urid| {
+                Node::Urid(Name::unmap(urid, unmap))
+            })?
+        } else {
+            return None;
+        };
+        Some(Node::Vector { child_type, items })
+    }
+
+    /// Read the body of a sequence.
+    fn read_sequence<U: Unmap + ?Sized>(body: Space, unmap: &U) -> Option<Self> {
+        let (header, mut space) = body.split_type::<sys::LV2_Atom_Sequence_Body>()?;
+        let unit = Name::unmap_raw(header.unit, unmap);
+        let beats = unit.as_ref().is_some_and(Name::is::<Beat>);
+
+        let mut events = Vec::new();
+        while let Some((raw_stamp, rest)) = space.split_type::<RawTimeStamp>() {
+            let time = if beats {
+                Time::Beats(unsafe { raw_stamp.beats })
+            } else {
+                Time::Frames(unsafe { raw_stamp.frames })
+            };
+            let (atom, rest) = rest.split_atom()?;
+            events.push((time, Node::read(UnidentifiedAtom::new(atom), unmap)?));
+            space = rest;
+        }
+        Some(Node::Sequence { unit, events })
+    }
+
+    /// Write the atom to the space.
+    fn write<'a, M: Map + ?Sized>(
+        &self,
+        space: &mut dyn MutSpace<'a>,
+        map: &M,
+        urids: &AtomURIDCollection,
+    ) -> Result<(), ParseError> {
+        let written = match self {
+            Node::Int(value) => space.init(urids.int, *value).map(|_| ()),
+            Node::Long(value) => space.init(urids.long, *value).map(|_| ()),
+            Node::Float(value) => space.init(urids.float, *value).map(|_| ()),
+            Node::Double(value) => space.init(urids.double, *value).map(|_| ()),
+            Node::Bool(value) => space.init(urids.bool, *value as i32).map(|_| ()),
+            Node::Urid(name) => space.init(urids.urid, name.map(map)?).map(|_| ()),
+            Node::String(text) => space
+                .init(urids.string, ())
+                .and_then(|mut writer| writer.append(text).map(|_| ())),
+            Node::Path(text) => space
+                .init(urids.path, ())
+                .and_then(|mut writer| writer.append(text).map(|_| ())),
+            Node::Uri(text) => space
+                .init(urids.uri, ())
+                .and_then(|mut writer| writer.append(text).map(|_| ())),
+            Node::Literal {
+                text,
+                lang,
+                datatype,
+            } => {
+                let info = match (lang, datatype) {
+                    (Some(lang), None) => LiteralInfo::Language(lang.map(map)?),
+                    (None, Some(datatype)) => LiteralInfo::Datatype(datatype.map(map)?),
+                    _ => {
+                        return Err(ParseError::InvalidAtom(
+                            "a literal needs either a language or a datatype",
+                        ))
+                    }
+                };
+                space
+                    .init(urids.literal, info)
+                    .and_then(|mut writer| writer.append(text).map(|_| ()))
+            }
+            Node::Chunk(data) => space
+                .init(urids.chunk, ())
+                .and_then(|mut frame| write_bytes(&mut frame, data)),
+            Node::Tuple(items) => {
+                let mut frame =
+                    FramedMutSpace::new(space, urids.tuple).ok_or(ParseError::NoSpace)?;
+                for item in items {
+                    item.write(&mut frame, map, urids)?;
+                }
+                Some(())
+            }
+            Node::Vector { child_type, items } => {
+                Self::write_vector(space, child_type, items, map, urids)?
+            }
+            Node::Object {
+                blank,
+                id,
+                otype,
+                properties,
+            } => {
+                let mut frame = if *blank {
+                    FramedMutSpace::new(space, urids.blank)
+                } else {
+                    FramedMutSpace::new(space, urids.object)
+                }
+                .ok_or(ParseError::NoSpace)?;
+                let header = sys::LV2_Atom_Object_Body {
+                    id: Name::map_raw(id, map)?,
+                    otype: otype.map(map)?.get(),
+                };
+                (&mut frame as &mut dyn MutSpace)
+                    .write(&header, true)
+                    .ok_or(ParseError::NoSpace)?;
+                for (key, context, value) in properties {
+                    let key = key.map(map)?.get();
+                    let context = Name::map_raw(context, map)?;
+                    let frame = &mut frame as &mut dyn MutSpace;
+                    frame.write(&key, true).ok_or(ParseError::NoSpace)?;
+                    frame.write(&context, false).ok_or(ParseError::NoSpace)?;
+                    value.write(frame, map, urids)?;
+                }
+                Some(())
+            }
+            Node::Sequence { unit, events } => {
+                let mut frame =
+                    FramedMutSpace::new(space, urids.sequence).ok_or(ParseError::NoSpace)?;
+                let header = sys::LV2_Atom_Sequence_Body {
+                    unit: Name::map_raw(unit, map)?,
+                    pad: 0,
+                };
+                (&mut frame as &mut dyn MutSpace)
+                    .write(&header, true)
+                    .ok_or(ParseError::NoSpace)?;
+                for (time, value) in events {
+                    let raw_stamp = match *time {
+                        Time::Frames(frames) => RawTimeStamp { frames },
+                        Time::Beats(beats) => RawTimeStamp { beats },
+                    };
+                    (&mut frame as &mut dyn MutSpace)
+                        .write(&raw_stamp, true)
+                        .ok_or(ParseError::NoSpace)?;
+                    value.write(&mut frame, map, urids)?;
+                }
+                Some(())
+            }
+            Node::Unknown { type_, body } => FramedMutSpace::new(space, type_.map(map)?)
+                .and_then(|mut frame| write_bytes(&mut frame, body)),
+        };
+        written.ok_or(ParseError::NoSpace)
+    }
+
+    /// Write a vector, whose items have already been checked to be of the child type.
+    fn write_vector<'a, M: Map + ?Sized>(
+        space: &mut dyn MutSpace<'a>,
+        child_type: &Name,
+        items: &[Node],
+        map: &M,
+        urids: &AtomURIDCollection,
+    ) -> Result<Option<()>, ParseError> {
+        macro_rules! write_items {
+            ($atom:ident, $urid:ident, $pattern:pat => $value:expr) => {{
+                let values: Vec<_> = items
+                    .iter()
+                    .filter_map(|item| match item {
+                        $pattern => Some($value),
+                        _ => None,
+                    })
+                    .collect();
+                space
+                    .init(urids.vector::<$atom>(), urids.$urid)
+                    .and_then(|mut writer| writer.append(&values).map(|_| ()))
+            }};
+        }
+
+        Ok(if child_type.is::<Int>() {
+            write_items!(Int, int, Node::Int(value) => *value)
+        } else if child_type.is::<Long>() {
+            write_items!(Long, long, Node::Long(value) => *value)
+        } else if child_type.is::<Float>() {
+            write_items!(Float, float, Node::Float(value) => *value)
+        } else if child_type.is::<Double>() {
+            write_items!(Double, double, Node::Double(value) => *value)
+        } else if child_type.is::<Bool>() {
+            write_items!(Bool, bool, Node::Bool(value) => *value as i32)
+        } else if child_type.is::<AtomURID>() {
+            let values = items
+                .iter()
+                .filter_map(|item| match item {
+                    Node::Urid(name) => Some(name.map(map)),
+                    _ => None,
+                })
+                .collect::<Result<Vec<URID>, ParseError>>()?;
+            space
+                .init(urids.vector::<AtomURID>(), urids.urid)
+                .and_then(|mut writer| writer.append(&values).map(|_| ()))
+        } else {
+            return Err(ParseError::InvalidAtom(
+                "vectors may only contain numbers, booleans or URIDs",
+            ));
+        })
+    }
+
+    /// Interpret a JSON value as an atom.
+    fn from_json(value: &JsonValue) -> Result<Self, ParseError> {
+        let type_ = Name::from_json(
+            value
+                .get("type")
+                .ok_or(ParseError::InvalidAtom("an atom has no type"))?,
+        )?;
+        let body = value
+            .get("value")
+            .ok_or(ParseError::InvalidAtom("an atom has no value"));
+
+        if let Ok(body) = body {
+            if let Some(node) = Self::scalar_from_json(&type_, body)? {
+                return Ok(node);
+            }
+        }
+
+        let node = if type_.is::<AtomURID>() {
+            Node::Urid(Name::from_json(body?)?)
+        } else if type_.is::<StringAtom>() {
+            Node::String(body?.as_str()?.to_owned())
+        } else if type_.is::<Path>() {
+            Node::Path(body?.as_str()?.to_owned())
+        } else if type_.is::<URI>() {
+            Node::Uri(body?.as_str()?.to_owned())
+        } else if type_.is::<Literal>() {
+            Node::Literal {
+                text: body?.as_str()?.to_owned(),
+                lang: value.get("lang").map(Name::from_json).transpose()?,
+                datatype: value.get("datatype").map(Name::from_json).transpose()?,
+            }
+        } else if type_.is::<Chunk>() {
+            Node::Chunk(parse_hex(body?.as_str()?)?)
+        } else if type_.is::<Tuple>() {
+            Node::Tuple(
+                body?
+                    .as_array()?
+                    .iter()
+                    .map(Node::from_json)
+                    .collect::<Result<_, _>>()?,
+            )
+        } else if type_.is::<Vector<Int>>() {
+            let child_type = Name::from_json(
+                value
+                    .get("childType")
+                    .ok_or(ParseError::InvalidAtom("a vector has no child type"))?,
+            )?;
+            let items = body?
+                .as_array()?
+                .iter()
+                .map(|item| {
+                    if child_type.is::<AtomURID>() {
+                        return Ok(Node::Urid(Name::from_json(item)?));
+                    }
+                    Self::scalar_from_json(&child_type, item)?.ok_or(ParseError::InvalidAtom(
+                        "vectors may only contain numbers, booleans or URIDs",
+                    ))
+                })
+                .collect::<Result<_, _>>()?;
+            Node::Vector { child_type, items }
+        } else if type_.is::<Object>() || type_.is::<Blank>() {
+            let properties = value
+                .get("properties")
+                .ok_or(ParseError::InvalidAtom("an object has no properties"))?
+                .as_array()?
+                .iter()
+                .map(|property| {
+                    let key = property
+                        .get("key")
+                        .ok_or(ParseError::InvalidAtom("a property has no key"))?;
+                    let value = property
+                        .get("value")
+                        .ok_or(ParseError::InvalidAtom("a property has no value"))?;
+                    Ok((
+                        Name::from_json(key)?,
+                        property.get("context").map(Name::from_json).transpose()?,
+                        Node::from_json(value)?,
+                    ))
+                })
+                .collect::<Result<_, ParseError>>()?;
+            Node::Object {
+                blank: type_.is::<Blank>(),
+                id: value.get("id").map(Name::from_json).transpose()?,
+                otype: Name::from_json(
+                    value
+                        .get("otype")
+                        .ok_or(ParseError::InvalidAtom("an object has no class"))?,
+                )?,
+                properties,
+            }
+        } else if type_.is::<Sequence>() {
+            let events = value
+                .get("events")
+                .ok_or(ParseError::InvalidAtom("a sequence has no events"))?
+                .as_array()?
+                .iter()
+                .map(|event| {
+                    let time = match (event.get("frames"), event.get("beats")) {
+                        (Some(frames), None) => Time::Frames(frames.parse_number()?),
+                        (None, Some(beats)) => Time::Beats(beats.parse_number()?),
+                        _ => {
+                            return Err(ParseError::InvalidAtom(
+                                "an event needs either frames or beats",
+                            ))
+                        }
+                    };
+                    let value = event
+                        .get("value")
+                        .ok_or(ParseError::InvalidAtom("an event has no value"))?;
+                    Ok((time, Node::from_json(value)?))
+                })
+                .collect::<Result<_, ParseError>>()?;
+            Node::Sequence {
+                unit: value.get("unit").map(Name::from_json).transpose()?,
+                events,
+            }
+        } else {
+            Node::Unknown {
+                type_,
+                body: parse_hex(body?.as_str()?)?,
+            }
+        };
+        Ok(node)
+    }
+
+    /// Interpret a JSON value as the body of a scalar atom, if the type is a scalar type.
+    fn scalar_from_json(type_: &Name, value: &JsonValue) -> Result<Option<Self>, ParseError> {
+        let node = if type_.is::<Int>() {
+            Node::Int(value.parse_number()?)
+        } else if type_.is::<Long>() {
+            Node::Long(value.parse_number()?)
+        } else if type_.is::<Float>() {
+            Node::Float(value.parse_number()?)
+        } else if type_.is::<Double>() {
+            Node::Double(value.parse_number()?)
+        } else if type_.is::<Bool>() {
+            match value {
+                JsonValue::Bool(value) => Node::Bool(*value),
+                _ => {
+                    return Err(ParseError::InvalidAtom(
+                        "a boolean is not `true` or `false`",
+                    ))
+                }
+            }
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(node))
+    }
+
+    /// The type of the atom, as it's rendered.
+    fn type_name(&self) -> &'static str {
+        match self {
+            Node::Int(_) => "atom:Int",
+            Node::Long(_) => "atom:Long",
+            Node::Float(_) => "atom:Float",
+            Node::Double(_) => "atom:Double",
+            Node::Bool(_) => "atom:Bool",
+            Node::Urid(_) => "atom:URID",
+            Node::String(_) => "atom:String",
+            Node::Path(_) => "atom:Path",
+            Node::Uri(_) => "atom:URI",
+            Node::Literal { .. } => "atom:Literal",
+            Node::Chunk(_) => "atom:Chunk",
+            Node::Tuple(_) => "atom:Tuple",
+            Node::Vector { .. } => "atom:Vector",
+            Node::Object { blank: false, .. } => "atom:Object",
+            Node::Object { blank: true, .. } => "atom:Blank",
+            Node::Sequence { .. } => "atom:Sequence",
+            Node::Unknown { .. } => "",
+        }
+    }
+
+    /// Render the value of a scalar atom or a vector item, without it's type.
+    fn scalar_text(&self) -> Option<std::string::String> {
+        Some(match self {
+            Node::Int(value) => value.to_string(),
+            Node::Long(value) => value.to_string(),
+            Node::Float(value) => value.to_string(),
+            Node::Double(value) => value.to_string(),
+            Node::Bool(value) => value.to_string(),
+            _ => return None,
+        })
+    }
+
+    fn write_json(&self, out: &mut std::string::String, indent: usize) {
+        out.push_str("{\"type\": ");
+        match self {
+            Node::Unknown { type_, .. } => type_.write_json(out),
+            _ => write_string(out, self.type_name()),
+        }
+
+        match self {
+            Node::Float(value) if !value.is_finite() => {
+                out.push_str(", \"value\": ");
+                write_string(out, &value.to_string());
+            }
+            Node::Double(value) if !value.is_finite() => {
+                out.push_str(", \"value\": ");
+                write_string(out, &value.to_string());
+            }
+            Node::Int(_) | Node::Long(_) | Node::Float(_) | Node::Double(_) | Node::Bool(_) => {
+                out.push_str(", \"value\": ");
+                out.push_str(&self.scalar_text().unwrap());
+            }
+            Node::Urid(name) => {
+                out.push_str(", \"value\": ");
+                name.write_json(out);
+            }
+            Node::String(text) | Node::Path(text) | Node::Uri(text) => {
+                out.push_str(", \"value\": ");
+                write_string(out, text);
+            }
+            Node::Literal {
+                text,
+                lang,
+                datatype,
+            } => {
+                out.push_str(", \"value\": ");
+                write_string(out, text);
+                if let Some(lang) = lang {
+                    out.push_str(", \"lang\": ");
+                    lang.write_json(out);
+                }
+                if let Some(datatype) = datatype {
+                    out.push_str(", \"datatype\": ");
+                    datatype.write_json(out);
+                }
+            }
+            Node::Chunk(data) | Node::Unknown { body: data, .. } => {
+                out.push_str(", \"value\": ");
+                write_string(out, &to_hex(data));
+            }
+            Node::Vector { child_type, items } => {
+                out.push_str(", \"childType\": ");
+                child_type.write_json(out);
+                out.push_str(", \"value\": [");
+                for (index, item) in items.iter().enumerate() {
+                    if index > 0 {
+                        out.push_str(", ");
+                    }
+                    match item {
+                        Node::Urid(name) => name.write_json(out),
+                        Node::Float(value) if !value.is_finite() => {
+                            write_string(out, &value.to_string())
+                        }
+                        Node::Double(value) if !value.is_finite() => {
+                            write_string(out, &value.to_string())
+                        }
+                        item => out.push_str(&item.scalar_text().unwrap_or_default()),
+                    }
+                }
+                out.push(']');
+            }
+            Node::Tuple(items) => {
+                out.push_str(", \"value\": [");
+                for (index, item) in items.iter().enumerate() {
+                    out.push_str(if index > 0 { ",\n" } else { "\n" });
+                    write_indent(out, indent + 1);
+                    item.write_json(out, indent + 1);
+                }
+                close_list(out, indent, !items.is_empty(), ']');
+            }
+            Node::Object {
+                id,
+                otype,
+                properties,
+                ..
+            } => {
+                if let Some(id) = id {
+                    out.push_str(", \"id\": ");
+                    id.write_json(out);
+                }
+                out.push_str(", \"otype\": ");
+                otype.write_json(out);
+                out.push_str(", \"properties\": [");
+                for (index, (key, context, value)) in properties.iter().enumerate() {
+                    out.push_str(if index > 0 { ",\n" } else { "\n" });
+                    write_indent(out, indent + 1);
+                    out.push_str("{\"key\": ");
+                    key.write_json(out);
+                    if let Some(context) = context {
+                        out.push_str(", \"context\": ");
+                        context.write_json(out);
+                    }
+                    out.push_str(", \"value\": ");
+                    value.write_json(out, indent + 1);
+                    out.push('}');
+                }
+                close_list(out, indent, !properties.is_empty(), ']');
+            }
+            Node::Sequence { unit, events } => {
+                if let Some(unit) = unit {
+                    out.push_str(", \"unit\": ");
+                    unit.write_json(out);
+                }
+                out.push_str(", \"events\": [");
+                for (index, (time, value)) in events.iter().enumerate() {
+                    out.push_str(if index > 0 { ",\n" } else { "\n" });
+                    write_indent(out, indent + 1);
+                    match time {
+                        Time::Frames(frames) => out.push_str(&format!("{{\"frames\": {}", frames)),
+                        Time::Beats(beats) => out.push_str(&format!("{{\"beats\": {}", beats)),
+                    }
+                    out.push_str(", \"value\": ");
+                    value.write_json(out, indent + 1);
+                    out.push('}');
+                }
+                close_list(out, indent, !events.is_empty(), ']');
+            }
+        }
+        out.push('}');
+    }
+
+    fn write_turtle(&self, out: &mut std::string::String, indent: usize) {
+        match self {
+            Node::Int(_) | Node::Long(_) | Node::Float(_) | Node::Double(_) | Node::Bool(_) => {
+                write_string(out, &self.scalar_text().unwrap());
+                out.push_str("^^");
+                out.push_str(self.type_name());
+            }
+            Node::Urid(name) => name.write_turtle(out),
+            Node::String(text) => write_string(out, text),
+            Node::Path(text) | Node::Uri(text) => {
+                write_string(out, text);
+                out.push_str("^^");
+                out.push_str(self.type_name());
+            }
+            Node::Literal {
+                text,
+                lang,
+                datatype,
+            } => {
+                write_string(out, text);
+                match (lang, datatype) {
+                    (Some(Name::Uri(lang)), _) if lang.starts_with(LANGUAGE_PREFIX) => {
+                        out.push('@');
+                        out.push_str(&lang[LANGUAGE_PREFIX.len()..]);
+                    }
+                    (Some(lang), _) => {
+                        out.push('@');
+                        lang.write_turtle(out);
+                    }
+                    (None, Some(datatype)) => {
+                        out.push_str("^^");
+                        datatype.write_turtle(out);
+                    }
+                    (None, None) => (),
+                }
+            }
+            Node::Chunk(data) => {
+                write_string(out, &to_hex(data));
+                out.push_str("^^atom:Chunk");
+            }
+            Node::Unknown { type_, body } => {
+                write_string(out, &to_hex(body));
+                out.push_str("^^");
+                type_.write_turtle(out);
+            }
+            Node::Tuple(items) => {
+                out.push('(');
+                for item in items {
+                    out.push('\n');
+                    write_indent(out, indent + 1);
+                    item.write_turtle(out, indent + 1);
+                }
+                close_list(out, indent, !items.is_empty(), ')');
+            }
+            Node::Vector { child_type, items } => {
+                out.push_str("[\n");
+                write_indent(out, indent + 1);
+                out.push_str("a atom:Vector ;\n");
+                write_indent(out, indent + 1);
+                out.push_str("atom:childType ");
+                child_type.write_turtle(out);
+                out.push_str(" ;\n");
+                write_indent(out, indent + 1);
+                out.push_str("rdf:value (");
+                for item in items {
+                    out.push(' ');
+                    item.write_turtle(out, indent + 1);
+                }
+                out.push_str(" ) ;\n");
+                write_indent(out, indent);
+                out.push(']');
+            }
+            Node::Object {
+                id,
+                otype,
+                properties,
+                ..
+            } => {
+                out.push_str("[\n");
+                write_indent(out, indent + 1);
+                out.push_str("a ");
+                otype.write_turtle(out);
+                out.push_str(" ;\n");
+                if let Some(id) = id {
+                    write_indent(out, indent + 1);
+                    out.push_str("atom:id ");
+                    id.write_turtle(out);
+                    out.push_str(" ;\n");
+                }
+                for (key, _, value) in properties {
+                    write_indent(out, indent + 1);
+                    key.write_turtle(out);
+                    out.push(' ');
+                    value.write_turtle(out, indent + 1);
+                    out.push_str(" ;\n");
+                }
+                write_indent(out, indent);
+                out.push(']');
+            }
+            Node::Sequence { unit, events } => {
+                out.push_str("[\n");
+                write_indent(out, indent + 1);
+                out.push_str("a atom:Sequence ;\n");
+                if let Some(unit) = unit {
+                    write_indent(out, indent + 1);
+                    out.push_str("atom:unit ");
+                    unit.write_turtle(out);
+                    out.push_str(" ;\n");
+                }
+                write_indent(out, indent + 1);
+                out.push_str("rdf:value (");
+                for (time, value) in events {
+                    out.push('\n');
+                    write_indent(out, indent + 2);
+                    out.push_str("[\n");
+                    write_indent(out, indent + 3);
+                    match time {
+                        Time::Frames(frames) => {
+                            out.push_str(&format!("atom:frameTime {} ;\n", frames))
+                        }
+                        Time::Beats(beats) => {
+                            out.push_str(&format!("atom:beatTime {:?} ;\n", beats))
+                        }
+                    }
+                    write_indent(out, indent + 3);
+                    out.push_str("rdf:value ");
+                    value.write_turtle(out, indent + 3);
+                    out.push_str(" ;\n");
+                    write_indent(out, indent + 2);
+                    out.push(']');
+                }
+                close_list(out, indent + 1, !events.is_empty(), ')');
+                out.push_str(" ;\n");
+                write_indent(out, indent);
+                out.push(']');
+            }
+        }
+    }
+}
+
+/// Write the bytes to the space, if there are any.
+fn write_bytes<'a>(space: &mut dyn MutSpace<'a>, data: &[u8]) -> Option<()> {
+    if data.is_empty() {
+        Some(())
+    } else {
+        space.write_raw(data, false).map(|_| ())
+    }
+}
+
+fn write_indent(out: &mut std::string::String, indent: usize) {
+    for _ in 0..indent {
+        out.push_str("    ");
+    }
+}
+
+/// Close a list, on a new line if it has any items.
+fn close_list(out: &mut std::string::String, indent: usize, multiline: bool, bracket: char) {
+    if multiline {
+        out.push('\n');
+        write_indent(out, indent);
+    }
+    out.push(bracket);
+}
+
+/// Write a quoted and escaped string, which is valid in JSON as well as in Turtle.
+fn write_string(out: &mut std::string::String, text: &str) {
+    out.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+fn to_hex(data: &[u8]) -> std::string::String {
+    data.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+fn parse_hex(text: &str) -> Result<Vec<u8>, ParseError> {
+    text.as_bytes()
+        .chunks(2)
+        .map(|digits| {
+            std::str::from_utf8(digits)
+                .ok()
+                .filter(|digits| digits.len() == 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+                .ok_or(ParseError::InvalidAtom("a hexadecimal string is malformed"))
+        })
+        .collect()
+}
+
+/// A parsed JSON value.
+///
+/// Numbers are kept as text, so they can be parsed as the type that the atom requires.
+#[derive(Clone, Debug, PartialEq)]
+enum JsonValue {
+    Null,
+    Bool(bool),
+    Number(std::string::String),
+    String(std::string::String),
+    Array(Vec<JsonValue>),
+    Object(Vec<(std::string::String, JsonValue)>),
+}
+
+impl JsonValue {
+    /// Get a member of an object.
+    fn get(&self, key: &str) -> Option<&JsonValue> {
+        match self {
+            JsonValue::Object(members) => members
+                .iter()
+                .find(|(name, _)| name == key)
+                .map(|(_, value)| value),
+            _ => None,
+        }
+    }
+
+    fn as_str(&self) -> Result<&str, ParseError> {
+        match self {
+            JsonValue::String(text) => Ok(text),
+            _ => Err(ParseError::InvalidAtom("a value is not a string")),
+        }
+    }
+
+    fn as_array(&self) -> Result<&[JsonValue], ParseError> {
+        match self {
+            JsonValue::Array(items) => Ok(items),
+            _ => Err(ParseError::InvalidAtom("a value is not a list")),
+        }
+    }
+
+    /// Parse a number, which may also be given as a string, like `"NaN"`.
+    fn parse_number<T: std::str::FromStr>(&self) -> Result<T, ParseError> {
+        match self {
+            JsonValue::Number(text) | JsonValue::String(text) => text
+                .parse()
+                .map_err(|_| ParseError::InvalidAtom("a number has the wrong format")),
+            _ => Err(ParseError::InvalidAtom("a value is not a number")),
+        }
+    }
+}
+
+/// A minimal JSON parser.
+struct JsonParser<'a> {
+    text: &'a str,
+    offset: usize,
+}
+
+impl<'a> JsonParser<'a> {
+    fn new(text: &'a str) -> Self {
+        Self { text, offset: 0 }
+    }
+
+    fn error(&self) -> ParseError {
+        ParseError::Syntax(self.offset)
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.text.as_bytes().get(self.offset).copied()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.offset += 1;
+        }
+    }
+
+    fn expect(&mut self, byte: u8) -> Result<(), ParseError> {
+        self.skip_whitespace();
+        if self.peek() == Some(byte) {
+            self.offset += 1;
+            Ok(())
+        } else {
+            Err(self.error())
+        }
+    }
+
+    /// Parse a value that has to take up the whole text.
+    fn parse_document(&mut self) -> Result<JsonValue, ParseError> {
+        let value = self.parse_value()?;
+        self.skip_whitespace();
+        if self.offset == self.text.len() {
+            Ok(value)
+        } else {
+            Err(self.error())
+        }
+    }
+
+    fn parse_value(&mut self) -> Result<JsonValue, ParseError> {
+        self.skip_whitespace();
+        match self.peek().ok_or_else(|| self.error())? {
+            b'{' => {
+                self.offset += 1;
+                let mut members = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b'}') {
+                    self.offset += 1;
+                    return Ok(JsonValue::Object(members));
+                }
+                loop {
+                    self.skip_whitespace();
+                    let key = self.parse_string()?;
+                    self.expect(b':')?;
+                    members.push((key, self.parse_value()?));
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.offset += 1,
+                        Some(b'}') => {
+                            self.offset += 1;
+                            return Ok(JsonValue::Object(members));
+                        }
+                        _ => return Err(self.error()),
+                    }
+                }
+            }
+            b'[' => {
+                self.offset += 1;
+                let mut items = Vec::new();
+                self.skip_whitespace();
+                if self.peek() == Some(b']') {
+                    self.offset += 1;
+                    return Ok(JsonValue::Array(items));
+                }
+                loop {
+                    items.push(self.parse_value()?);
+                    self.skip_whitespace();
+                    match self.peek() {
+                        Some(b',') => self.offset += 1,
+                        Some(b']') => {
+                            self.offset += 1;
+                            return Ok(JsonValue::Array(items));
+                        }
+                        _ => return Err(self.error()),
+                    }
+                }
+            }
+            b'"' => self.parse_string().map(JsonValue::String),
+            b'-' | b'0'..=b'9' => {
+                let start = self.offset;
+                while let Some(b'-' | b'+' | b'.' | b'e' | b'E' | b'0'..=b'9') = self.peek() {
+                    self.offset += 1;
+                }
+                Ok(JsonValue::Number(self.text[start..self.offset].to_owned()))
+            }
+            _ => {
+                for (literal, value) in [
+                    ("true", JsonValue::Bool(true)),
+                    ("false", JsonValue::Bool(false)),
+                    ("null", JsonValue::Null),
+                ] {
+                    if self.text[self.offset..].starts_with(literal) {
+                        self.offset += literal.len();
+                        return Ok(value);
+                    }
+                }
+                Err(self.error())
+            }
+        }
+    }
+
+    fn parse_string(&mut self) -> Result<std::string::String, ParseError> {
+        if self.peek() != Some(b'"') {
+            return Err(self.error());
+        }
+        self.offset += 1;
+        let mut string = std::string::String::new();
+        let mut chars = self.text[self.offset..].char_indices();
+        while let Some((index, c)) = chars.next() {
+            match c {
+                '"' => {
+                    self.offset += index + 1;
+                    return Ok(string);
+                }
+                '\\' => {
+                    let escaped = match chars.next().map(|(_, c)| c) {
+                        Some('"') => '"',
+                        Some('\\') => '\\',
+                        Some('/') => '/',
+                        Some('b') => '\u{8}',
+                        Some('f') => '\u{c}',
+                        Some('n') => '\n',
+                        Some('r') => '\r',
+                        Some('t') => '\t',
+                        Some('u') => {
+                            let mut code = 0;
+                            for _ in 0..4 {
+                                let digit = chars.next().and_then(|(_, c)| c.to_digit(16));
+                                code = code * 16
+                                    + digit.ok_or(ParseError::Syntax(self.offset + index))?;
+                            }
+                            char::from_u32(code).unwrap_or(char::REPLACEMENT_CHARACTER)
+                        }
+                        _ => return Err(ParseError::Syntax(self.offset + index)),
+                    };
+                    string.push(escaped);
+                }
+                c => string.push(c),
+            }
+        }
+        Err(ParseError::Syntax(self.text.len()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use crate::text::*;
+
+    const FIXTURE: &str = r#"{"type": "atom:Sequence", "unit": "http://lv2plug.in/ns/extensions/units#beat", "events": [
+    {"beats": 0.5, "value": {"type": "atom:Object", "id": "urn:voice", "otype": "urn:Voice", "properties": [
+        {"key": "urn:gain", "value": {"type": "atom:Float", "value": 0.25}},
+        {"key": "urn:level", "context": "urn:context", "value": {"type": "atom:Double", "value": "NaN"}},
+        {"key": "urn:shape", "value": {"type": "atom:URID", "value": "urn:sine"}},
+        {"key": "urn:detune", "value": {"type": "atom:Vector", "childType": "atom:Long", "value": [-1, 2]}}
+    ]}},
+    {"beats": 1, "value": {"type": "atom:Tuple", "value": [
+        {"type": "atom:Int", "value": -7},
+        {"type": "atom:Bool", "value": true},
+        {"type": "atom:String", "value": "say \"hi\"\n"},
+        {"type": "atom:Path", "value": "/tmp/kick.wav"},
+        {"type": "atom:URI", "value": "urn:kick"},
+        {"type": "atom:Literal", "value": "Hallo", "lang": "http://lexvo.org/id/iso639-1/de"},
+        {"type": "atom:Chunk", "value": "00ff10"},
+        {"type": "urn:custom", "value": "0102"},
+        {"type": "atom:Blank", "otype": "urn:Empty", "properties": []}
+    ]}}
+]}"#;
+
+    #[test]
+    fn test_json_round_trip() {
+        let map = HashURIDMapper::new();
+
+        let mut buffer = AtomBuffer::<1024>::new();
+        let mut space = buffer.mut_space();
+        from_json(FIXTURE, &mut space as &mut dyn MutSpace, &map).unwrap();
+        let json = to_json(buffer.atom().unwrap(), &map);
+
+        // The rendered JSON describes the same atom.
+        let mut other_buffer = AtomBuffer::<1024>::new();
+        let mut space = other_buffer.mut_space();
+        from_json(&json, &mut space as &mut dyn MutSpace, &map).unwrap();
+        assert_eq!(buffer.as_bytes(), other_buffer.as_bytes());
+        assert_eq!(json, to_json(other_buffer.atom().unwrap(), &map));
+
+        assert!(
+            json.contains(r#"{"key": "urn:gain", "value": {"type": "atom:Float", "value": 0.25}}"#)
+        );
+        assert!(json.contains(r#"{"type": "atom:String", "value": "say \"hi\"\n"}"#));
+        assert!(json.contains(r#"{"type": "urn:custom", "value": "0102"}"#));
+    }
+
+    #[test]
+    fn test_read_atoms() {
+        let map = HashURIDMapper::new();
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+
+        let mut buffer = AtomBuffer::<256>::new();
+        {
+            let mut space = buffer.mut_space();
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: map.map_str("urn:Voice").unwrap(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(map.map_str("urn:gain").unwrap(), urids.float, 0.5)
+                .unwrap();
+            writer.init(URID::new(1000).unwrap(), urids.int, 3).unwrap();
+        }
+        let atom = buffer.atom().unwrap();
+
+        assert_eq!(
+            "[\n    a <urn:Voice> ;\n    <urn:gain> \"0.5\"^^atom:Float ;\n    \"1000\"^^atom:URID \"3\"^^atom:Int ;\n]",
+            to_turtle(atom, &map)
+        );
+        assert_eq!(
+            "{\"type\": \"atom:Object\", \"otype\": \"urn:Voice\", \"properties\": [\n    {\"key\": \"urn:gain\", \"value\": {\"type\": \"atom:Float\", \"value\": 0.5}},\n    {\"key\": 1000, \"value\": {\"type\": \"atom:Int\", \"value\": 3}}\n]}",
+            to_json(atom, &map)
+        );
+
+        // A malformed body is rendered as bytes.
+        let mut buffer = AtomBuffer::<64>::new();
+        {
+            let mut space = buffer.mut_space();
+            let mut frame =
+                FramedMutSpace::new(&mut space as &mut dyn MutSpace, urids.int).unwrap();
+            frame.write_raw(&[1, 2], false).unwrap();
+        }
+        assert_eq!(
+            "{\"type\": \"atom:Int\", \"value\": \"0102\"}",
+            to_json(buffer.atom().unwrap(), &map)
+        );
+        assert_eq!(
+            "null",
+            to_json(UnidentifiedAtom::new(Space::from_slice(&[])), &map)
+        );
+
+        // A vector whose body isn't a multiple of the child size is rendered as bytes too.
+        buffer.reset();
+        {
+            let mut space = buffer.mut_space();
+            let mut frame =
+                FramedMutSpace::new(&mut space as &mut dyn MutSpace, urids.vector::<Int>())
+                    .unwrap();
+            let header = sys::LV2_Atom_Vector_Body {
+                child_type: urids.int.get(),
+                child_size: 4,
+            };
+            (&mut frame as &mut dyn MutSpace)
+                .write(&header, false)
+                .unwrap();
+            frame.write_raw(&[1, 2, 3, 4, 5, 6], false).unwrap();
+        }
+        let json = to_json(buffer.atom().unwrap(), &map);
+        assert!(json.starts_with("{\"type\": \"atom:Vector\", \"value\": \""));
+        assert!(json.ends_with("010203040506\"}"));
+        assert!(to_turtle(buffer.atom().unwrap(), &map).ends_with("010203040506\"^^atom:Vector"));
+
+        // A tuple with an unreadable item is rendered as bytes instead of dropping the item.
+        buffer.reset();
+        {
+            let mut space = buffer.mut_space();
+            let mut frame =
+                FramedMutSpace::new(&mut space as &mut dyn MutSpace, urids.tuple).unwrap();
+            (&mut frame as &mut dyn MutSpace)
+                .write(&sys::LV2_Atom { size: 0, type_: 0 }, true)
+                .unwrap();
+        }
+        assert_eq!(
+            "{\"type\": \"atom:Tuple\", \"value\": \"0000000000000000\"}",
+            to_json(buffer.atom().unwrap(), &map)
+        );
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let map = HashURIDMapper::new();
+        let mut buffer = AtomBuffer::<64>::new();
+        let mut parse = |text: &str| {
+            buffer.reset();
+            let mut space = buffer.mut_space();
+            from_json(text, &mut space as &mut dyn MutSpace, &map)
+        };
+
+        assert_eq!(Err(ParseError::Syntax(14)), parse(r#"{"type": "atom"#));
+        assert_eq!(
+            Err(ParseError::InvalidAtom("an atom has no type")),
+            parse(r#"{"value": 1}"#)
+        );
+        assert_eq!(
+            Err(ParseError::InvalidAtom("a number has the wrong format")),
+            parse(r#"{"type": "atom:Int", "value": 1.5}"#)
+        );
+        assert_eq!(
+            Err(ParseError::InvalidAtom(
+                "a literal needs either a language or a datatype"
+            )),
+            parse(r#"{"type": "atom:Literal", "value": "text"}"#)
+        );
+        assert_eq!(
+            Err(ParseError::NoSpace),
+            parse(
+                r#"{"type": "atom:String", "value": "This string is too long for the buffer, since it has more than sixty-four bytes."}"#
+            )
+        );
+    }
+}
@@ -59,40 +59,56 @@ where
     type WriteHandle = VectorWriter<'a, 'b, C>;
 
     fn read(body: Space<'a>, child_urid: URID<C>) -> Option<&'a [C::InternalType]> {
-        let (header, body) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
+        read_children(body, child_urid)
+    }
 
-        if header.child_type != child_urid
-            || header.child_size as usize != size_of::<C::InternalType>()
-        {
-            return None;
-        }
+    fn init(frame: FramedMutSpace<'a, 'b>, child_urid: URID<C>) -> Option<VectorWriter<'a, 'b, C>> {
+        init_children(frame, child_urid)
+    }
+}
 
-        let data = body.data()?;
+/// Read the children of a vector body.
+///
+/// This is shared with other atoms that have the layout of a vector, like `Sound`.
+pub(crate) fn read_children<'a, C: ScalarAtom>(
+    body: Space<'a>,
+    child_urid: URID<C>,
+) -> Option<&'a [C::InternalType]> {
+    let (header, body) = body.split_type::<sys::LV2_Atom_Vector_Body>()?;
+
+    if header.child_type != child_urid || header.child_size as usize != size_of::<C::InternalType>()
+    {
+        return None;
+    }
 
-        assert_eq!(data.len() % size_of::<C::InternalType>(), 0);
-        let children_count = data.len() / size_of::<C::InternalType>();
+    let data = body.data()?;
 
-        let children = unsafe {
-            std::slice::from_raw_parts(data.as_ptr() as *const C::InternalType, children_count)
-        };
-        Some(children)
+    if data.len() % size_of::<C::InternalType>() != 0 {
+        return None;
     }
+    let children_count = data.len() / size_of::<C::InternalType>();
 
-    fn init(
-        mut frame: FramedMutSpace<'a, 'b>,
-        child_urid: URID<C>,
-    ) -> Option<VectorWriter<'a, 'b, C>> {
-        let body = sys::LV2_Atom_Vector_Body {
-            child_type: child_urid.get(),
-            child_size: size_of::<C::InternalType>() as u32,
-        };
-        (&mut frame as &mut dyn MutSpace).write(&body, false)?;
+    let children = unsafe {
+        std::slice::from_raw_parts(data.as_ptr() as *const C::InternalType, children_count)
+    };
+    Some(children)
+}
 
-        Some(VectorWriter {
-            frame,
-            type_: PhantomData,
-        })
-    }
+/// Write the header of a vector body and create a writer for the children.
+pub(crate) fn init_children<'a, 'b, C: ScalarAtom>(
+    mut frame: FramedMutSpace<'a, 'b>,
+    child_urid: URID<C>,
+) -> Option<VectorWriter<'a, 'b, C>> {
+    let body = sys::LV2_Atom_Vector_Body {
+        child_type: child_urid.get(),
+        child_size: size_of::<C::InternalType>() as u32,
+    };
+    (&mut frame as &mut dyn MutSpace).write(&body, false)?;
+
+    Some(VectorWriter {
+        frame,
+        type_: PhantomData,
+    })
 }
 
 /// Handle to append elements to a vector.
@@ -0,0 +1,237 @@
+use lv2_atom::prelude::*;
+use std::path::PathBuf;
+use urid::*;
+
+#[uri("urn:rust-lv2:serialize#Voice")]
+struct VoiceClass;
+
+#[uri("urn:rust-lv2:serialize#Sine")]
+struct SineClass;
+
+#[uri("urn:rust-lv2:serialize#Pulse")]
+struct PulseClass;
+
+#[uri("urn:rust-lv2:serialize#Sampler")]
+struct SamplerClass;
+
+#[uri("urn:rust-lv2:serialize#gain")]
+struct Gain;
+
+#[uri("urn:rust-lv2:serialize#shape")]
+struct ShapeKey;
+
+#[uri("urn:rust-lv2:serialize#width")]
+struct Width;
+
+#[uri("urn:rust-lv2:serialize#sample")]
+struct SampleKey;
+
+#[uri("urn:rust-lv2:serialize#detune")]
+struct Detune;
+
+#[derive(AtomSerialize, AtomDeserialize, Debug, PartialEq)]
+enum Shape {
+    #[lv2(class = SineClass)]
+    Sine,
+    #[lv2(class = PulseClass)]
+    Pulse {
+        #[lv2(key = Width)]
+        width: f32,
+    },
+    #[lv2(class = SamplerClass)]
+    Sampler {
+        #[lv2(key = SampleKey)]
+        sample: PathBuf,
+        #[lv2(skip)]
+        frames: Vec<f32>,
+    },
+}
+
+#[derive(AtomSerialize, AtomDeserialize, Debug, PartialEq)]
+#[lv2(class = VoiceClass)]
+struct Voice {
+    #[lv2(key = Gain)]
+    gain: f32,
+    #[lv2(key = ShapeKey)]
+    shape: Shape,
+    #[lv2(key = Detune)]
+    detune: Option<Vec<f64>>,
+    #[lv2(skip)]
+    phase: f32,
+}
+
+#[derive(URIDCollection)]
+struct URIDs {
+    atom: AtomURIDCollection,
+    voice: <Voice as AtomSerialize>::URIDs,
+    gain: URID<Gain>,
+    shape: URID<ShapeKey>,
+}
+
+fn write<T: AtomSerialize>(
+    value: &T,
+    urids: &T::URIDs,
+    atom_urids: &AtomURIDCollection,
+) -> AtomBuffer<512> {
+    let mut buffer = AtomBuffer::<512>::new();
+    let mut space = buffer.mut_space();
+    value
+        .serialize(&mut space as &mut dyn MutSpace, urids, atom_urids)
+        .unwrap();
+    buffer
+}
+
+#[test]
+fn test_round_trip() {
+    let map = HashURIDMapper::new();
+    let urids: URIDs = map.populate_collection().unwrap();
+
+    let voices = [
+        Voice {
+            gain: 0.5,
+            shape: Shape::Sine,
+            detune: Some(vec![-0.1, 0.1]),
+            phase: 0.0,
+        },
+        Voice {
+            gain: 1.0,
+            shape: Shape::Pulse { width: 0.25 },
+            detune: None,
+            phase: 0.0,
+        },
+        Voice {
+            gain: 0.0,
+            shape: Shape::Sampler {
+                sample: PathBuf::from("/samples/kick.wav"),
+                frames: Vec::new(),
+            },
+            detune: None,
+            phase: 0.0,
+        },
+    ];
+
+    for voice in voices.iter() {
+        let buffer = write(voice, &urids.voice, &urids.atom);
+        let read: Voice = buffer
+            .atom()
+            .unwrap()
+            .deserialize(&urids.voice, &urids.atom)
+            .unwrap();
+        assert_eq!(voice, &read);
+    }
+
+    // Skipped fields are not written and reset to their default.
+    let voice = Voice {
+        gain: 0.5,
+        shape: Shape::Sampler {
+            sample: PathBuf::from("/samples/kick.wav"),
+            frames: vec![1.0; 4],
+        },
+        detune: None,
+        phase: 0.75,
+    };
+    let buffer = write(&voice, &urids.voice, &urids.atom);
+    let read: Voice = buffer
+        .atom()
+        .unwrap()
+        .deserialize(&urids.voice, &urids.atom)
+        .unwrap();
+    assert_eq!(0.0, read.phase);
+    assert_eq!(
+        Shape::Sampler {
+            sample: PathBuf::from("/samples/kick.wav"),
+            frames: Vec::new(),
+        },
+        read.shape
+    );
+
+    // Absent optional properties are not written.
+    let (_, reader) = buffer.atom().unwrap().read_object(&urids.atom).unwrap();
+    assert_eq!(2, reader.count());
+}
+
+#[test]
+fn test_errors() {
+    let map = HashURIDMapper::new();
+    let urids: URIDs = map.populate_collection().unwrap();
+
+    // An object of the wrong class.
+    let shape = Shape::Pulse { width: 0.5 };
+    let buffer = write(&shape, &urids.voice.1 .1 .0 .1, &urids.atom);
+    assert_eq!(
+        Err(DeserializeError::WrongClass {
+            expected: urids.voice.0.into_general(),
+            found: map.map_type::<PulseClass>().unwrap().into_general(),
+        }),
+        buffer
+            .atom()
+            .unwrap()
+            .deserialize::<Voice>(&urids.voice, &urids.atom)
+    );
+
+    // A missing property.
+    let mut buffer = AtomBuffer::<512>::new();
+    {
+        let mut space = buffer.mut_space();
+        let mut writer = (&mut space as &mut dyn MutSpace)
+            .init(
+                urids.atom.object,
+                ObjectHeader {
+                    id: None,
+                    otype: urids.voice.0.into_general(),
+                },
+            )
+            .unwrap();
+        writer.init(urids.gain, urids.atom.float, 0.5).unwrap();
+    }
+    assert_eq!(
+        Err(DeserializeError::MissingProperty {
+            field: "shape",
+            key: urids.shape.into_general(),
+        }),
+        buffer
+            .atom()
+            .unwrap()
+            .deserialize::<Voice>(&urids.voice, &urids.atom)
+    );
+
+    // A property of the wrong type.
+    let mut buffer = AtomBuffer::<512>::new();
+    {
+        let mut space = buffer.mut_space();
+        let mut writer = (&mut space as &mut dyn MutSpace)
+            .init(
+                urids.atom.object,
+                ObjectHeader {
+                    id: None,
+                    otype: urids.voice.0.into_general(),
+                },
+            )
+            .unwrap();
+        writer.init(urids.gain, urids.atom.int, 1).unwrap();
+    }
+    assert_eq!(
+        Err(DeserializeError::WrongPropertyType {
+            field: "gain",
+            key: urids.gain.into_general(),
+            expected: urids.atom.float.into_general(),
+            found: urids.atom.int.into_general(),
+        }),
+        buffer
+            .atom()
+            .unwrap()
+            .deserialize::<Voice>(&urids.voice, &urids.atom)
+    );
+
+    // A URID that isn't the class of a variant.
+    let buffer = write(&urids.gain.into_general(), &(), &urids.atom);
+    assert_eq!(
+        Err(DeserializeError::UnknownVariant {
+            found: urids.gain.into_general(),
+        }),
+        buffer
+            .atom()
+            .unwrap()
+            .deserialize::<Shape>(&urids.voice.1 .1 .0 .1, &urids.atom)
+    );
+}
@@ -0,0 +1,256 @@
+//! LV2 specification for buffer sizes, describing the block lengths a plugin can be run with.
+//!
+//! The host announces guarantees about the `sample_count` passed to `run` with the marker features [`BoundedBlockLength`](buf_size/struct.BoundedBlockLength.html), [`PowerOf2BlockLength`](buf_size/struct.PowerOf2BlockLength.html) and [`FixedBlockLength`](buf_size/struct.FixedBlockLength.html). The actual lengths are passed as options, which can be collected in a [`BufferSizes`](struct.BufferSizes.html) struct.
+//!
+//! See the original [specification](https://lv2plug.in/ns/ext/buf-size) for more information.
+//!
+//! # Example
+//!
+//! This example plugin allocates a buffer that is big enough for the largest block.
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_buf_size::prelude::*;
+//! use lv2_core::prelude::*;
+//! use lv2_options::prelude::*;
+//! use lv2_urid::*;
+//! use urid::*;
+//!
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     map: LV2Map<'a>,
+//!     options: Options<'a>,
+//!     _bounded: BoundedBlockLength,
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! pub struct URIDs {
+//!     atom: AtomURIDCollection,
+//!     buf_size: BufSizeURIDCollection,
+//! }
+//!
+//! #[uri("urn:my-plugin")]
+//! pub struct MyPlugin {
+//!     buffer: Vec<f32>,
+//! }
+//!
+//! impl Plugin for MyPlugin {
+//!     type Ports = ();
+//!     type InitFeatures = Features<'static>;
+//!     type AudioFeatures = ();
+//!
+//!     fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+//!         let urids: URIDs = features.map.populate_collection()?;
+//!         let sizes = BufferSizes::from_options(&features.options, &urids.buf_size, &urids.atom);
+//!         Some(Self {
+//!             buffer: vec![0.0; sizes.max_block_length? as usize],
+//!         })
+//!     }
+//!
+//!     fn run(&mut self, _: &mut (), _: &mut (), sample_count: u32) {
+//!         let _block = &mut self.buffer[..sample_count as usize];
+//!     }
+//! }
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_options as options;
+extern crate lv2_sys as sys;
+
+use atom::prelude::*;
+use options::Options;
+use urid::*;
+
+/// All buffer size URI bounds.
+pub mod buf_size {
+    use core::feature::Feature;
+    use core::prelude::*;
+    use std::ffi::c_void;
+    use urid::UriBound;
+
+    /// Marker feature to signal that the block length is bounded by the minimum and maximum block length options.
+    pub struct BoundedBlockLength;
+
+    unsafe impl UriBound for BoundedBlockLength {
+        const URI: &'static [u8] = sys::LV2_BUF_SIZE__boundedBlockLength;
+    }
+
+    unsafe impl Feature for BoundedBlockLength {
+        unsafe fn from_feature_ptr(_feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+            Some(Self)
+        }
+    }
+
+    /// Marker feature to signal that the block length is always a power of two.
+    pub struct PowerOf2BlockLength;
+
+    unsafe impl UriBound for PowerOf2BlockLength {
+        const URI: &'static [u8] = sys::LV2_BUF_SIZE__powerOf2BlockLength;
+    }
+
+    unsafe impl Feature for PowerOf2BlockLength {
+        unsafe fn from_feature_ptr(_feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+            Some(Self)
+        }
+    }
+
+    /// Marker feature to signal that the block length never changes.
+    pub struct FixedBlockLength;
+
+    unsafe impl UriBound for FixedBlockLength {
+        const URI: &'static [u8] = sys::LV2_BUF_SIZE__fixedBlockLength;
+    }
+
+    unsafe impl Feature for FixedBlockLength {
+        unsafe fn from_feature_ptr(_feature: *const c_void, _: ThreadingClass) -> Option<Self> {
+            Some(Self)
+        }
+    }
+
+    /// Option key of the maximal block length.
+    pub struct MaxBlockLength;
+    unsafe impl UriBound for MaxBlockLength {
+        const URI: &'static [u8] = sys::LV2_BUF_SIZE__maxBlockLength;
+    }
+
+    /// Option key of the minimal block length.
+    pub struct MinBlockLength;
+    unsafe impl UriBound for MinBlockLength {
+        const URI: &'static [u8] = sys::LV2_BUF_SIZE__minBlockLength;
+    }
+
+    /// Option key of the usual block length.
+    pub struct NominalBlockLength;
+    unsafe impl UriBound for NominalBlockLength {
+        const URI: &'static [u8] = sys::LV2_BUF_SIZE__nominalBlockLength;
+    }
+
+    /// Option key of the size of sequence buffers, in bytes.
+    pub struct SequenceSize;
+    unsafe impl UriBound for SequenceSize {
+        const URI: &'static [u8] = sys::LV2_BUF_SIZE__sequenceSize;
+    }
+}
+
+use buf_size::*;
+
+/// A URID cache containing all buffer size properties.
+#[derive(URIDCollection)]
+pub struct BufSizeURIDCollection {
+    pub bounded_block_length: URID<BoundedBlockLength>,
+    pub power_of_2_block_length: URID<PowerOf2BlockLength>,
+    pub fixed_block_length: URID<FixedBlockLength>,
+    pub max_block_length: URID<MaxBlockLength>,
+    pub min_block_length: URID<MinBlockLength>,
+    pub nominal_block_length: URID<NominalBlockLength>,
+    pub sequence_size: URID<SequenceSize>,
+}
+
+/// The buffer sizes passed by the host.
+///
+/// Every field is `None` if the host didn't pass the corresponding option or if the option didn't have the `Int` type.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BufferSizes {
+    /// The minimal `sample_count` the plugin is run with.
+    pub min_block_length: Option<u32>,
+    /// The maximal `sample_count` the plugin is run with.
+    pub max_block_length: Option<u32>,
+    /// The `sample_count` the plugin is usually run with.
+    pub nominal_block_length: Option<u32>,
+    /// The size of sequence port buffers, in bytes.
+    pub sequence_size: Option<u32>,
+}
+
+impl BufferSizes {
+    /// Collect the buffer sizes from the options feature.
+    ///
+    /// This should be done in the plugin's `new` method since the options are only valid during instantiation.
+    pub fn from_options(
+        options: &Options,
+        urids: &BufSizeURIDCollection,
+        atom_urids: &AtomURIDCollection,
+    ) -> Self {
+        let read = |key: URID| -> Option<u32> {
+            options
+                .read(key, atom_urids.int, ())
+                .and_then(|value| u32::try_from(value).ok())
+        };
+
+        Self {
+            min_block_length: read(urids.min_block_length.into_general()),
+            max_block_length: read(urids.max_block_length.into_general()),
+            nominal_block_length: read(urids.nominal_block_length.into_general()),
+            sequence_size: read(urids.sequence_size.into_general()),
+        }
+    }
+}
+
+/// Prelude of `lv2_buf_size` for wildcard usage.
+pub mod prelude {
+    pub use crate::buf_size::*;
+    pub use crate::{BufSizeURIDCollection, BufferSizes};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use std::ffi::c_void;
+    use std::ptr::null;
+
+    fn int_option(key: u32, type_: u32, value: &i32) -> sys::LV2_Options_Option {
+        sys::LV2_Options_Option {
+            context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+            subject: 0,
+            key,
+            size: std::mem::size_of::<i32>() as u32,
+            type_,
+            value: value as *const i32 as *const c_void,
+        }
+    }
+
+    #[test]
+    fn test_buffer_sizes() {
+        let map = HashURIDMapper::new();
+        let urids: BufSizeURIDCollection = map.populate_collection().unwrap();
+        let atom_urids: AtomURIDCollection = map.populate_collection().unwrap();
+
+        let min: i32 = 16;
+        let max: i32 = 4096;
+        let nominal: i32 = -1;
+        let sequence_size: i32 = 8192;
+
+        let raw_options = [
+            int_option(urids.min_block_length.get(), atom_urids.int.get(), &min),
+            int_option(urids.max_block_length.get(), atom_urids.int.get(), &max),
+            int_option(
+                urids.nominal_block_length.get(),
+                atom_urids.int.get(),
+                &nominal,
+            ),
+            int_option(
+                urids.sequence_size.get(),
+                atom_urids.float.get(),
+                &sequence_size,
+            ),
+            sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                subject: 0,
+                key: 0,
+                size: 0,
+                type_: 0,
+                value: null(),
+            },
+        ];
+        let options = unsafe { Options::new(&raw_options[0]) };
+
+        assert_eq!(
+            BufferSizes {
+                min_block_length: Some(16),
+                max_block_length: Some(4096),
+                nominal_block_length: None,
+                sequence_size: None,
+            },
+            BufferSizes::from_options(&options, &urids, &atom_urids)
+        );
+    }
+}
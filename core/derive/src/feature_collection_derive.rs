@@ -1,20 +1,27 @@
 use proc_macro::TokenStream;
 use proc_macro2::Span;
 use syn::Field;
-use syn::{parse_macro_input, Data, DataStruct, Ident};
+use syn::{parse_macro_input, Data, DataStruct, Ident, Type};
 use syn::{DeriveInput, Generics, Lifetime};
 
 struct FeatureCollectionField<'a> {
     identifier: &'a Ident,
+    feature_type: &'a Type,
 }
 
 impl<'a> FeatureCollectionField<'a> {
     fn from_input_field(input: &'a Field) -> Self {
         FeatureCollectionField {
             identifier: input.ident.as_ref().unwrap(),
+            feature_type: &input.ty,
         }
     }
 
+    fn make_requirement(&self) -> impl ::quote::ToTokens {
+        let feature_type = self.feature_type;
+        quote! {FeatureRequirement::of::<_, #feature_type>(),}
+    }
+
     fn make_retrieval(&self) -> impl ::quote::ToTokens {
         let identifier = self.identifier;
         quote! {#identifier: cache.retrieve_feature(class)?,}
@@ -47,6 +54,7 @@ impl<'a> FeatureCollectionStruct<'a> {
         let struct_name = self.struct_name;
         let generics = self.generics;
         let retrievals = self.fields.iter().map(|field| field.make_retrieval());
+        let requirements = self.fields.iter().map(|field| field.make_requirement());
         // retrieve the first lifetime of the struct, or set it to `'static` if there is none.
         let lifetime = self
             .generics
@@ -57,6 +65,10 @@ impl<'a> FeatureCollectionStruct<'a> {
 
         (quote! {
             impl#generics FeatureCollection<#lifetime> for #struct_name#generics {
+                const FEATURES: &'static [FeatureRequirement] = &[
+                    #(#requirements)*
+                ];
+
                 fn from_cache(
                     cache: &mut FeatureCache<#lifetime>,
                     class: ThreadingClass,
@@ -9,6 +9,7 @@ extern crate quote;
 
 mod feature_collection_derive;
 mod lv2_descriptors;
+mod plugin_metadata_derive;
 mod port_collection_derive;
 
 use proc_macro::TokenStream;
@@ -20,11 +21,17 @@ pub fn lv2_descriptors(input: TokenStream) -> TokenStream {
 }
 
 /// Implement the `PortCollection` trait for a port struct.
-#[proc_macro_derive(PortCollection)]
+#[proc_macro_derive(PortCollection, attributes(lv2))]
 pub fn port_collection_derive(input: TokenStream) -> TokenStream {
     port_collection_derive::port_collection_derive_impl(input)
 }
 
+/// Implement the `PluginMetadata` trait for a plugin struct.
+#[proc_macro_derive(PluginMetadata, attributes(lv2))]
+pub fn plugin_metadata_derive(input: TokenStream) -> TokenStream {
+    plugin_metadata_derive::plugin_metadata_derive_impl(input)
+}
+
 #[proc_macro_derive(FeatureCollection)]
 pub fn feature_collection_derive(input: TokenStream) -> TokenStream {
     feature_collection_derive::feature_collection_derive_impl(input)
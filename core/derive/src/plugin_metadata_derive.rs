@@ -0,0 +1,135 @@
+use proc_macro::TokenStream;
+use syn::parenthesized;
+use syn::parse::ParseStream;
+use syn::punctuated::Punctuated;
+use syn::{
+    parse_macro_input, DeriveInput, Generics, Ident, LitByteStr, LitStr, Result, Token, Type,
+};
+
+/// Parse a parenthesized, comma-separated list.
+fn parse_list<T: syn::parse::Parse>(input: ParseStream) -> Result<Vec<T>> {
+    let content;
+    parenthesized!(content in input);
+    Ok(Punctuated::<T, Token![,]>::parse_terminated(&content)?
+        .into_iter()
+        .collect())
+}
+
+/// Create a null-terminated byte string literal from a URI.
+fn uri_literal(uri: &str, span: proc_macro2::Span) -> LitByteStr {
+    LitByteStr::new(format!("{}\0", uri).as_bytes(), span)
+}
+
+/// Representation of a plugin we implement `PluginMetadata` for.
+struct PluginMetadataStruct<'a> {
+    struct_name: &'a Ident,
+    generics: &'a Generics,
+    name: Option<LitStr>,
+    classes: Vec<Ident>,
+    project: Option<LitStr>,
+    license: Option<LitStr>,
+    required_features: Vec<Type>,
+    optional_features: Vec<Type>,
+    extensions: Vec<Type>,
+}
+
+impl<'a> PluginMetadataStruct<'a> {
+    /// Construct a `Self` instance from a `DeriveInput` and it's `lv2` attributes.
+    fn from_derive_input(input: &'a DeriveInput) -> Result<Self> {
+        let mut metadata = Self {
+            struct_name: &input.ident,
+            generics: &input.generics,
+            name: None,
+            classes: Vec::new(),
+            project: None,
+            license: None,
+            required_features: Vec::new(),
+            optional_features: Vec::new(),
+            extensions: Vec::new(),
+        };
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("lv2"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("name") {
+                    metadata.name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("class") {
+                    metadata.classes.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("classes") {
+                    metadata.classes.extend(parse_list(meta.input)?);
+                } else if meta.path.is_ident("project") {
+                    metadata.project = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("license") {
+                    metadata.license = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("required_features") {
+                    metadata.required_features.extend(parse_list(meta.input)?);
+                } else if meta.path.is_ident("optional_features") {
+                    metadata.optional_features.extend(parse_list(meta.input)?);
+                } else if meta.path.is_ident("extensions") {
+                    metadata.extensions.extend(parse_list(meta.input)?);
+                } else {
+                    return Err(meta.error("unsupported plugin attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(metadata)
+    }
+
+    /// Implement `PluginMetadata` for the plugin.
+    fn make_implementation(&self) -> Result<TokenStream> {
+        let struct_name = self.struct_name;
+        let generics = self.generics;
+        let name = self.name.as_ref().ok_or_else(|| {
+            syn::Error::new(
+                struct_name.span(),
+                "`PluginMetadata` requires a name: #[lv2(name = \"...\")]",
+            )
+        })?;
+        let classes = self.classes.iter().map(|class| {
+            uri_literal(
+                &format!("http://lv2plug.in/ns/lv2core#{}", class),
+                class.span(),
+            )
+        });
+        let optional_uri = |uri: &Option<LitStr>| match uri {
+            Some(uri) => {
+                let uri = uri_literal(&uri.value(), uri.span());
+                quote! { Some(#uri) }
+            }
+            None => quote! { None },
+        };
+        let project = optional_uri(&self.project);
+        let license = optional_uri(&self.license);
+        let required_features = self.required_features.iter();
+        let optional_features = self.optional_features.iter();
+        let extensions = self.extensions.iter();
+
+        Ok((quote! {
+            impl#generics PluginMetadata for #struct_name#generics {
+                const NAME: &'static str = #name;
+                const CLASSES: &'static [&'static [u8]] = &[#(#classes),*];
+                const PROJECT: Option<&'static [u8]> = #project;
+                const LICENSE: Option<&'static [u8]> = #license;
+                const FEATURES: &'static [FeatureRequirement] = &[
+                    #(FeatureRequirement::required::<#required_features>(),)*
+                    #(FeatureRequirement::optional::<#optional_features>(),)*
+                ];
+                const EXTENSIONS: &'static [&'static [u8]] = &[#(<#extensions as UriBound>::URI),*];
+            }
+        })
+        .into())
+    }
+}
+
+/// Implement `PluginMetadata` for a plugin struct.
+#[inline]
+pub fn plugin_metadata_derive_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    match PluginMetadataStruct::from_derive_input(&input).and_then(|s| s.make_implementation()) {
+        Ok(tokens) => tokens,
+        Err(error) => error.to_compile_error().into(),
+    }
+}
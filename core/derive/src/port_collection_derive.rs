@@ -1,21 +1,301 @@
 use proc_macro::TokenStream;
 use proc_macro2::Span;
+use syn::parenthesized;
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
 use syn::DeriveInput;
 use syn::Field;
-use syn::{parse_macro_input, Data, DataStruct, Ident, Type};
+use syn::{parse_macro_input, Data, DataStruct, Expr, Ident, Lit, LitStr, Result, Token, Type};
+use syn::{Error, ExprLit, ExprUnary, UnOp};
+
+/// Evaluate a numeric literal, which may be negated.
+///
+/// Returns `None` if the expression is not a literal, for example a constant, in which case it can't be checked at compile time.
+fn literal_value(expr: &Expr) -> Option<f64> {
+    match expr {
+        Expr::Lit(ExprLit {
+            lit: Lit::Float(lit),
+            ..
+        }) => lit.base10_parse().ok(),
+        Expr::Lit(ExprLit {
+            lit: Lit::Int(lit), ..
+        }) => lit.base10_parse().ok(),
+        Expr::Unary(ExprUnary {
+            op: UnOp::Neg(_),
+            expr,
+            ..
+        }) => literal_value(expr).map(|value| -value),
+        Expr::Paren(paren) => literal_value(&paren.expr),
+        _ => None,
+    }
+}
+
+/// Check whether a symbol is a valid C identifier, as required by the LV2 specification.
+fn is_valid_symbol(symbol: &str) -> bool {
+    let mut chars = symbol.chars();
+    match chars.next() {
+        Some(c) if c.is_ascii_alphabetic() || c == '_' => (),
+        _ => return false,
+    }
+    chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Combine an optional error with another one.
+fn push_error(errors: &mut Option<Error>, error: Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// The metadata of a port, as declared by the `lv2` attribute of a field.
+#[derive(Default)]
+struct PortAttributes {
+    symbol: Option<LitStr>,
+    name: Option<LitStr>,
+    default: Option<Expr>,
+    minimum: Option<Expr>,
+    maximum: Option<Expr>,
+    unit: Option<Type>,
+    buffer_type: Option<Type>,
+    supports: Vec<Type>,
+    properties: Vec<Ident>,
+    group: Option<LitStr>,
+    designation: Option<Type>,
+}
+
+impl PortAttributes {
+    /// Parse all `lv2` attributes of a field.
+    fn from_input_field(input: &Field) -> Result<Self> {
+        let mut attributes = Self::default();
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("lv2"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("symbol") {
+                    attributes.symbol = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("name") {
+                    attributes.name = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("default") {
+                    attributes.default = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("min") {
+                    attributes.minimum = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("max") {
+                    attributes.maximum = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("unit") {
+                    attributes.unit = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("buffer_type") {
+                    attributes.buffer_type = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("supports") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let types = Punctuated::<Type, Token![,]>::parse_terminated(&content)?;
+                    attributes.supports.extend(types);
+                } else if meta.path.is_ident("properties") {
+                    let content;
+                    parenthesized!(content in meta.input);
+                    let properties = Punctuated::<Ident, Token![,]>::parse_terminated(&content)?;
+                    attributes.properties.extend(properties);
+                } else if meta.path.is_ident("group") {
+                    attributes.group = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("designation") {
+                    attributes.designation = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported port attribute"));
+                }
+                Ok(())
+            })?;
+        }
+        Ok(attributes)
+    }
+
+    /// Check that the declared range of the port is consistent.
+    fn validate(&self, errors: &mut Option<Error>) {
+        let minimum = self.minimum.as_ref().and_then(literal_value);
+        let maximum = self.maximum.as_ref().and_then(literal_value);
+        if let (Some(minimum), Some(maximum)) = (minimum, maximum) {
+            if minimum > maximum {
+                push_error(
+                    errors,
+                    Error::new_spanned(
+                        self.minimum.as_ref().unwrap(),
+                        format!(
+                            "the minimum {} is greater than the maximum {}",
+                            minimum, maximum
+                        ),
+                    ),
+                );
+            }
+        }
+        if let Some(default) = self.default.as_ref() {
+            let value = match literal_value(default) {
+                Some(value) => value,
+                None => return,
+            };
+            if minimum.is_some_and(|minimum| value < minimum)
+                || maximum.is_some_and(|maximum| value > maximum)
+            {
+                push_error(
+                    errors,
+                    Error::new_spanned(
+                        default,
+                        format!("the default {} is outside of the port's range", value),
+                    ),
+                );
+            }
+        }
+    }
+}
+
+/// A port group, as declared by a `group` entry in the `lv2` attribute of the struct.
+struct GroupAttributes {
+    span: Span,
+    symbol: Option<LitStr>,
+    name: Option<LitStr>,
+    classes: Vec<Type>,
+    main: bool,
+    side_chain_of: Option<LitStr>,
+}
+
+impl GroupAttributes {
+    /// Parse all groups declared by the `lv2` attributes of the struct.
+    fn from_derive_input(input: &DeriveInput) -> Result<Vec<Self>> {
+        let mut groups = Vec::new();
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("lv2"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if !meta.path.is_ident("group") {
+                    return Err(meta.error("unsupported port collection attribute"));
+                }
+                let mut group = GroupAttributes {
+                    span: meta.path.span(),
+                    symbol: None,
+                    name: None,
+                    classes: Vec::new(),
+                    main: false,
+                    side_chain_of: None,
+                };
+                meta.parse_nested_meta(|meta| {
+                    if meta.path.is_ident("symbol") {
+                        group.symbol = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("name") {
+                        group.name = Some(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("class") {
+                        group.classes.push(meta.value()?.parse()?);
+                    } else if meta.path.is_ident("main") {
+                        group.main = true;
+                    } else if meta.path.is_ident("side_chain_of") {
+                        group.side_chain_of = Some(meta.value()?.parse()?);
+                    } else {
+                        return Err(meta.error("unsupported port group attribute"));
+                    }
+                    Ok(())
+                })?;
+                groups.push(group);
+                Ok(())
+            })?;
+        }
+        Ok(groups)
+    }
+
+    /// Create the metadata table entry of the group.
+    ///
+    /// Groups without a symbol are rejected by the validation, so the symbol is always present here.
+    fn make_metadata(&self) -> impl ::quote::ToTokens {
+        let symbol = self.symbol.as_ref().unwrap();
+        let name = self.name.as_ref().unwrap_or(symbol);
+        let classes = self.classes.iter();
+        let main = self.main;
+        let side_chain_of = match &self.side_chain_of {
+            Some(group) => quote! { Some(#group) },
+            None => quote! { None },
+        };
+        quote! {
+            GroupMetadata {
+                symbol: #symbol,
+                name: #name,
+                classes: &[#(<#classes as UriBound>::URI),*],
+                main: #main,
+                side_chain_of: #side_chain_of,
+            },
+        }
+    }
+}
 
 /// A field in the struct we implement `PortCollection` for.
 struct PortCollectionField<'a> {
     identifier: &'a Ident,
     port_type: &'a Type,
+    attributes: PortAttributes,
 }
 
 impl<'a> PortCollectionField<'a> {
     /// Create a `Self` instance from a field object.
-    fn from_input_field(input: &'a Field) -> Self {
-        PortCollectionField {
+    fn from_input_field(input: &'a Field) -> Result<Self> {
+        Ok(PortCollectionField {
             identifier: input.ident.as_ref().unwrap(),
             port_type: &input.ty,
+            attributes: PortAttributes::from_input_field(input)?,
+        })
+    }
+
+    /// Return the symbol of the port, which defaults to the name of the field.
+    fn symbol(&self) -> LitStr {
+        self.attributes
+            .symbol
+            .clone()
+            .unwrap_or_else(|| LitStr::new(&self.identifier.to_string(), self.identifier.span()))
+    }
+
+    /// Create the metadata table entry of the port.
+    fn make_metadata(&self, index: u32) -> impl ::quote::ToTokens {
+        let port_type = self.port_type;
+        let attributes = &self.attributes;
+        let symbol = self.symbol();
+        let name = attributes.name.clone().unwrap_or_else(|| symbol.clone());
+        let optional_value = |value: &Option<Expr>| match value {
+            Some(value) => quote! { Some((#value) as f32) },
+            None => quote! { None },
+        };
+        let default = optional_value(&attributes.default);
+        let minimum = optional_value(&attributes.minimum);
+        let maximum = optional_value(&attributes.maximum);
+        let optional_uri = |bound: &Option<Type>| match bound {
+            Some(bound) => quote! { Some(<#bound as UriBound>::URI) },
+            None => quote! { None },
+        };
+        let unit = optional_uri(&attributes.unit);
+        let buffer_type = optional_uri(&attributes.buffer_type);
+        let designation = optional_uri(&attributes.designation);
+        let supports = attributes.supports.iter();
+        let properties = attributes.properties.iter();
+        let group = match &attributes.group {
+            Some(group) => quote! { Some(#group) },
+            None => quote! { None },
+        };
+        quote! {
+            PortMetadata {
+                index: #index,
+                symbol: #symbol,
+                name: #name,
+                direction: <#port_type as PortHandle>::DIRECTION,
+                class: <#port_type as PortHandle>::PORT_CLASS,
+                optional: <#port_type as PortHandle>::OPTIONAL,
+                default: #default,
+                minimum: #minimum,
+                maximum: #maximum,
+                unit: #unit,
+                buffer_type: #buffer_type,
+                supports: &[#(<#supports as UriBound>::URI),*],
+                properties: &[#(PortProperty::#properties),*],
+                group: #group,
+                designation: #designation,
+            },
         }
     }
 
@@ -59,6 +339,7 @@ impl<'a> PortCollectionField<'a> {
 /// the raw pointers for the port. Then, the ports object is created from the raw version.
 struct PortCollectionStruct<'a> {
     struct_name: &'a Ident,
+    groups: Vec<GroupAttributes>,
     fields: Vec<PortCollectionField<'a>>,
 }
 
@@ -75,18 +356,108 @@ impl<'a> PortCollectionStruct<'a> {
     }
 
     /// Construct a `Self` instance from a `DeriveInput`.
-    fn from_derive_input(input: &'a DeriveInput) -> Self {
+    fn from_derive_input(input: &'a DeriveInput) -> Result<Self> {
         let struct_name = &input.ident;
         let fields = match &input.data {
             Data::Enum(_) | Data::Union(_) => panic!("Only structs can implement PortCollection"),
             Data::Struct(DataStruct { fields, .. }) => fields
                 .iter()
                 .map(PortCollectionField::from_input_field)
-                .collect(),
+                .collect::<Result<_>>()?,
         };
-        PortCollectionStruct {
+        let collection = PortCollectionStruct {
             struct_name,
+            groups: GroupAttributes::from_derive_input(input)?,
             fields,
+        };
+        collection.validate()?;
+        Ok(collection)
+    }
+
+    /// Check the metadata of all ports and groups.
+    ///
+    /// The symbols of the ports and of the groups have to be unique, valid C identifiers and the defaults have to be within the ranges of the ports. Ports and side chains may only refer to declared groups. All errors are reported at once.
+    fn validate(&self) -> Result<()> {
+        let mut errors = None;
+        let mut groups: Vec<String> = Vec::with_capacity(self.groups.len());
+        for group in self.groups.iter() {
+            let symbol = match &group.symbol {
+                Some(symbol) => symbol,
+                None => {
+                    push_error(
+                        &mut errors,
+                        Error::new(group.span, "the port group requires a symbol"),
+                    );
+                    continue;
+                }
+            };
+            let value = symbol.value();
+            if !is_valid_symbol(&value) {
+                push_error(
+                    &mut errors,
+                    Error::new(
+                        symbol.span(),
+                        format!("the group symbol {:?} is not a valid C identifier", value),
+                    ),
+                );
+            }
+            if groups.contains(&value) {
+                push_error(
+                    &mut errors,
+                    Error::new(
+                        symbol.span(),
+                        format!("the group symbol {:?} is used more than once", value),
+                    ),
+                );
+            }
+            groups.push(value);
+        }
+        let check_group = |group: &Option<LitStr>, errors: &mut Option<Error>| {
+            if let Some(group) = group {
+                if !groups.contains(&group.value()) {
+                    push_error(
+                        errors,
+                        Error::new(
+                            group.span(),
+                            format!("the port group {:?} is not declared", group.value()),
+                        ),
+                    );
+                }
+            }
+        };
+        for group in self.groups.iter() {
+            check_group(&group.side_chain_of, &mut errors);
+        }
+
+        let mut symbols: Vec<String> = Vec::with_capacity(self.fields.len());
+        for field in self.fields.iter() {
+            let symbol = field.symbol();
+            let value = symbol.value();
+            if !is_valid_symbol(&value) {
+                push_error(
+                    &mut errors,
+                    Error::new(
+                        symbol.span(),
+                        format!("the port symbol {:?} is not a valid C identifier", value),
+                    ),
+                );
+            }
+            if symbols.contains(&value) {
+                push_error(
+                    &mut errors,
+                    Error::new(
+                        symbol.span(),
+                        format!("the port symbol {:?} is used more than once", value),
+                    ),
+                );
+            }
+            symbols.push(value);
+            check_group(&field.attributes.group, &mut errors);
+            field.attributes.validate(&mut errors);
+        }
+        match errors {
+            Some(errors) => Err(errors),
+            None => Ok(()),
         }
     }
 
@@ -113,11 +484,33 @@ impl<'a> PortCollectionStruct<'a> {
             .iter()
             .enumerate()
             .map(|(i, f)| f.make_connect_matcher(i as u32));
+        let metadata = self
+            .fields
+            .iter()
+            .enumerate()
+            .map(|(i, f)| f.make_metadata(i as u32));
+        // The table of groups is only created if there are groups, so that collections without groups don't need `GroupMetadata` in scope.
+        let groups = if self.groups.is_empty() {
+            None
+        } else {
+            let groups = self.groups.iter().map(GroupAttributes::make_metadata);
+            Some(quote! {
+                const GROUPS: &'static [GroupMetadata] = &[
+                    #(#groups)*
+                ];
+            })
+        };
 
         (quote! {
             impl PortCollection for #struct_name {
                 type Cache = #internal_cache_name;
 
+                const PORTS: &'static [PortMetadata] = &[
+                    #(#metadata)*
+                ];
+
+                #groups
+
                 #[inline]
                 unsafe fn from_connections(connections: &<Self as PortCollection>::Cache, sample_count: u32) -> Option<Self> {
                     Some(
@@ -159,6 +552,151 @@ impl<'a> PortCollectionStruct<'a> {
 #[inline]
 pub fn port_collection_derive_impl(input: TokenStream) -> TokenStream {
     let input: DeriveInput = parse_macro_input!(input);
-    let list = PortCollectionStruct::from_derive_input(&input);
-    list.make_derived_contents()
+    match PortCollectionStruct::from_derive_input(&input) {
+        Ok(list) => list.make_derived_contents(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn validate(input: DeriveInput) -> std::result::Result<(), String> {
+        PortCollectionStruct::from_derive_input(&input)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_literal_value() {
+        assert_eq!(Some(-90.0), literal_value(&parse_quote!(-90.0)));
+        assert_eq!(Some(24.0), literal_value(&parse_quote!(24)));
+        assert_eq!(Some(0.5), literal_value(&parse_quote!((0.5))));
+        assert_eq!(None, literal_value(&parse_quote!(MAX_GAIN)));
+    }
+
+    #[test]
+    fn test_valid_ports() {
+        let input: DeriveInput = parse_quote! {
+            struct Ports {
+                #[lv2(symbol = "gain", default = 0.0, min = -90.0, max = 24.0)]
+                gain: InputPort<Control>,
+                #[lv2(default = DEFAULT, min = 0.0)]
+                mix: InputPort<Control>,
+                input: InputPort<Audio>,
+            }
+        };
+        assert_eq!(Ok(()), validate(input));
+    }
+
+    #[test]
+    fn test_invalid_ports() {
+        let input: DeriveInput = parse_quote! {
+            struct Ports {
+                #[lv2(symbol = "in")]
+                gain: InputPort<Control>,
+                #[lv2(symbol = "in")]
+                input: InputPort<Audio>,
+            }
+        };
+        assert_eq!(
+            Err("the port symbol \"in\" is used more than once".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            struct Ports {
+                #[lv2(symbol = "2nd gain")]
+                gain: InputPort<Control>,
+            }
+        };
+        assert_eq!(
+            Err("the port symbol \"2nd gain\" is not a valid C identifier".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            struct Ports {
+                #[lv2(default = 30.0, min = -90.0, max = 24.0)]
+                gain: InputPort<Control>,
+            }
+        };
+        assert_eq!(
+            Err("the default 30 is outside of the port's range".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            struct Ports {
+                #[lv2(min = 1.0, max = -1.0)]
+                gain: InputPort<Control>,
+            }
+        };
+        assert_eq!(
+            Err("the minimum 1 is greater than the maximum -1".to_string()),
+            validate(input)
+        );
+    }
+
+    #[test]
+    fn test_valid_groups() {
+        let input: DeriveInput = parse_quote! {
+            #[lv2(group(symbol = "main_in", name = "Input", class = StereoGroup, main))]
+            #[lv2(group(symbol = "sidechain", class = MonoGroup, side_chain_of = "main_in"))]
+            struct Ports {
+                #[lv2(group = "main_in", designation = Left)]
+                left: InputPort<Audio>,
+                #[lv2(group = "main_in", designation = Right)]
+                right: InputPort<Audio>,
+                #[lv2(group = "sidechain", designation = Center)]
+                sidechain: InputPort<Audio>,
+            }
+        };
+        assert_eq!(Ok(()), validate(input));
+    }
+
+    #[test]
+    fn test_invalid_groups() {
+        let input: DeriveInput = parse_quote! {
+            #[lv2(group(symbol = "main_in"))]
+            struct Ports {
+                #[lv2(group = "main_out")]
+                output: OutputPort<Audio>,
+            }
+        };
+        assert_eq!(
+            Err("the port group \"main_out\" is not declared".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(group(symbol = "main_in"), group(symbol = "main_in"))]
+            #[lv2(group(symbol = "sidechain", side_chain_of = "main"))]
+            struct Ports {}
+        };
+        assert_eq!(
+            Err("the group symbol \"main_in\" is used more than once".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(group(name = "Input"))]
+            struct Ports {}
+        };
+        assert_eq!(
+            Err("the port group requires a symbol".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(unknown)]
+            struct Ports {}
+        };
+        assert_eq!(
+            Err("unsupported port collection attribute".to_string()),
+            validate(input)
+        );
+    }
 }
@@ -97,16 +97,23 @@ impl<'a> FeatureCollection<'a> for FeatureCache<'a> {
 /// For now this only covers `&T` and `Option<&T>` (where T is a `Feature`), but this may be
 /// extended in the future.
 pub trait FromResolvedFeature<F: Feature>: Sized {
+    /// Whether the plugin can't be instantiated without the feature.
+    const REQUIRED: bool = true;
+
     fn from_resolved_feature(feature: Option<F>) -> Result<Self, MissingFeatureError>;
 }
 
 impl<F: Feature> FromResolvedFeature<F> for F {
+    const REQUIRED: bool = true;
+
     fn from_resolved_feature(feature: Option<F>) -> Result<Self, MissingFeatureError> {
         feature.ok_or_else(|| MissingFeatureError { uri: F::uri() })
     }
 }
 
 impl<F: Feature> FromResolvedFeature<F> for Option<F> {
+    const REQUIRED: bool = false;
+
     #[inline]
     fn from_resolved_feature(feature: Option<F>) -> Result<Self, MissingFeatureError> {
         Ok(feature)
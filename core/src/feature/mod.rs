@@ -5,7 +5,7 @@ mod cache;
 mod core_features;
 mod descriptor;
 
-pub use cache::FeatureCache;
+pub use cache::{FeatureCache, FromResolvedFeature};
 pub use core_features::*;
 pub use descriptor::FeatureDescriptor;
 
@@ -60,6 +60,15 @@ pub struct MissingFeatureError {
     pub(crate) uri: &'static Uri,
 }
 
+impl MissingFeatureError {
+    /// Create a new error for the feature with the given URI.
+    ///
+    /// This is used by implementations of [`FromResolvedFeature`](trait.FromResolvedFeature.html) that can't be created without the feature.
+    pub fn new(uri: &'static Uri) -> Self {
+        Self { uri }
+    }
+}
+
 impl std::fmt::Display for MissingFeatureError {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
         let uri = self.uri.to_str().unwrap_or("[error while reading URI]");
@@ -71,6 +80,45 @@ impl std::fmt::Display for MissingFeatureError {
     }
 }
 
+/// Static description of a feature used by a plugin.
+///
+/// A table of these is created for every derived [`FeatureCollection`](trait.FeatureCollection.html) and is used to declare the required and optional features of the plugin in it's Turtle description.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FeatureRequirement {
+    /// The null-terminated URI of the feature.
+    pub uri: &'static [u8],
+    /// Whether the plugin can't be instantiated without the feature.
+    pub required: bool,
+}
+
+impl FeatureRequirement {
+    /// Describe a feature that is required by the plugin.
+    pub const fn required<F: Feature>() -> Self {
+        Self {
+            uri: F::URI,
+            required: true,
+        }
+    }
+
+    /// Describe a feature that is optionally used by the plugin.
+    pub const fn optional<F: Feature>() -> Self {
+        Self {
+            uri: F::URI,
+            required: false,
+        }
+    }
+
+    /// Describe the feature stored in a field of a feature collection.
+    ///
+    /// Fields of type `F` are required, while fields of type `Option<F>` are optional.
+    pub const fn of<F: Feature, T: cache::FromResolvedFeature<F>>() -> Self {
+        Self {
+            uri: F::URI,
+            required: T::REQUIRED,
+        }
+    }
+}
+
 /// Convenience trait for feature collections.
 ///
 /// The feature cache is only for temporary use; Once a feature is retrieved, it is removed from the cache. Therefore you need a way to properly store features.
@@ -93,6 +141,9 @@ pub trait FeatureCollection<'a>: Sized + 'a {
         cache: &mut FeatureCache<'a>,
         class: ThreadingClass,
     ) -> Result<Self, MissingFeatureError>;
+
+    /// The features stored in this collection.
+    const FEATURES: &'static [FeatureRequirement] = &[];
 }
 
 impl<'a> FeatureCollection<'a> for () {
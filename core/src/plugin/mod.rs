@@ -110,6 +110,77 @@ pub trait Plugin: UriBound + Sized + Send + Sync + 'static {
     }
 }
 
+/// Static description of a plugin.
+///
+/// Together with the port metadata of the plugin's
+/// [`PortCollection`](../port/trait.PortCollection.html) and the features of
+/// it's [`FeatureCollection`](../feature/trait.FeatureCollection.html)s, this
+/// is everything needed to generate the Turtle description of the plugin with
+/// the [`turtle`](../turtle/index.html) module.
+///
+/// This trait can be derived with the `lv2` attribute:
+///
+///     use lv2_core::feature::HardRTCapable;
+///     use lv2_core::prelude::*;
+///     use urid::*;
+///
+///     #[derive(PortCollection)]
+///     struct Ports {
+///         #[lv2(name = "Gain", default = 0.0, min = -90.0, max = 24.0)]
+///         gain: InputPort<Control>,
+///     }
+///
+///     #[uri("urn:my-plugin")]
+///     #[derive(PluginMetadata)]
+///     #[lv2(
+///         name = "My Plugin",
+///         class = AmplifierPlugin,
+///         license = "http://opensource.org/licenses/isc",
+///         optional_features(HardRTCapable),
+///     )]
+///     struct MyPlugin;
+///
+///     impl Plugin for MyPlugin {
+///         type Ports = Ports;
+///         type InitFeatures = ();
+///         type AudioFeatures = ();
+///
+///         fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+///             Some(Self)
+///         }
+///
+///         fn run(&mut self, _: &mut Ports, _: &mut (), _: u32) {}
+///     }
+///
+///     assert_eq!(MyPlugin::NAME, "My Plugin");
+///
+/// Besides `name`, the attribute accepts the classes of the plugin as
+/// identifiers of the `lv2` namespace (`class = A` or `classes(A, B)`), the
+/// URI of the `project` and the `license`, additional `required_features` and
+/// `optional_features` and the `extensions` that are returned by
+/// [`extension_data`](trait.Plugin.html#method.extension_data). The features
+/// and extensions are given as types that implement `UriBound`.
+pub trait PluginMetadata: Plugin {
+    /// The human-readable name of the plugin.
+    const NAME: &'static str;
+
+    /// The URIs of the plugin's classes, in addition to `lv2:Plugin`.
+    const CLASSES: &'static [&'static [u8]] = &[];
+
+    /// The URI of the project the plugin belongs to.
+    const PROJECT: Option<&'static [u8]> = None;
+
+    /// The URI of the plugin's license.
+    const LICENSE: Option<&'static [u8]> = None;
+
+    /// Features used by the plugin that aren't stored in it's feature
+    /// collections, like `lv2:inPlaceBroken`.
+    const FEATURES: &'static [FeatureRequirement] = &[];
+
+    /// The URIs of the extensions that are provided by the plugin.
+    const EXTENSIONS: &'static [&'static [u8]] = &[];
+}
+
 /// Plugin wrapper which translated between the host and the plugin.
 ///
 /// The host interacts with the plugin via a C API, but the plugin is
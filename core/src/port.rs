@@ -24,6 +24,11 @@ pub trait PortType {
     /// The type of output reference created by the port.
     type OutputPortType: Sized;
 
+    /// The null-terminated URI of the port class, for example `lv2:AudioPort`.
+    ///
+    /// It's used to describe the port in the [port metadata](struct.PortMetadata.html). Port types without a class are described without one.
+    const PORT_CLASS: Option<&'static [u8]> = None;
+
     /// Read data from the pointer or create a reference to the input.
     ///
     /// If the resulting data is a slice, `sample_count` is the length of the slice.
@@ -56,6 +61,15 @@ pub trait PortHandle: Sized {
 
     /// Get the index of the port.
     fn get_index(&self) -> u32;
+
+    /// The direction of the port, if it's known.
+    const DIRECTION: Option<PortDirection> = None;
+
+    /// The null-terminated URI of the port class, for example `lv2:AudioPort`, if it's known.
+    const PORT_CLASS: Option<&'static [u8]> = None;
+
+    /// Whether the port may be disconnected by the host.
+    const OPTIONAL: bool = false;
 }
 
 /// Handle for input ports.
@@ -76,6 +90,9 @@ impl<T: PortType> Deref for InputPort<T> {
 }
 
 impl<T: PortType> PortHandle for InputPort<T> {
+    const DIRECTION: Option<PortDirection> = Some(PortDirection::Input);
+    const PORT_CLASS: Option<&'static [u8]> = T::PORT_CLASS;
+
     #[inline]
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32, index: u32) -> Option<Self> {
         Some(Self {
@@ -114,6 +131,9 @@ impl<T: PortType> DerefMut for OutputPort<T> {
 }
 
 impl<T: PortType> PortHandle for OutputPort<T> {
+    const DIRECTION: Option<PortDirection> = Some(PortDirection::Output);
+    const PORT_CLASS: Option<&'static [u8]> = T::PORT_CLASS;
+
     #[inline]
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32, index: u32) -> Option<Self> {
         Some(Self {
@@ -128,6 +148,10 @@ impl<T: PortType> PortHandle for OutputPort<T> {
 }
 
 impl<T: PortHandle> PortHandle for Option<T> {
+    const DIRECTION: Option<PortDirection> = T::DIRECTION;
+    const PORT_CLASS: Option<&'static [u8]> = T::PORT_CLASS;
+    const OPTIONAL: bool = true;
+
     unsafe fn from_raw(pointer: *mut c_void, sample_count: u32, index: u32) -> Option<Self> {
         Some(T::from_raw(pointer, sample_count, index))
     }
@@ -160,6 +184,80 @@ impl<T: PortHandle> PortHandle for Option<T> {
 ///     }
 ///
 /// Please note that port indices are mapped in the order of occurrence; In our example, the implementation will treat `audio_input` as port `0`, `audio_output` as port `1` and so on. Therefore, your plugin definition and your port collection have to match. Otherwise, undefined behaviour will occur.
+///
+/// # Port metadata
+///
+/// The derive macro also creates a [`PortMetadata`](struct.PortMetadata.html) table for the fields, which can be used to generate the Turtle description of the plugin. It's symbol defaults to the name of the field, and more information can be given with the `lv2` attribute:
+///
+///     use lv2_core::port::*;
+///     # use urid::*;
+///     # pub struct Decibel;
+///     # unsafe impl UriBound for Decibel {
+///     #     const URI: &'static [u8] = b"http://lv2plug.in/ns/extensions/units#db\0";
+///     # }
+///
+///     #[derive(PortCollection)]
+///     struct MyPortCollection {
+///         #[lv2(name = "Gain", default = 0.0, min = -90.0, max = 24.0, unit = Decibel)]
+///         gain: InputPort<Control>,
+///         #[lv2(symbol = "in", name = "In")]
+///         input: InputPort<Audio>,
+///         #[lv2(symbol = "out", name = "Out")]
+///         output: OutputPort<Audio>,
+///         #[lv2(name = "Bypass", default = 0.0, properties(Toggled))]
+///         bypass: InputPort<Control>,
+///     }
+///
+///     assert_eq!(MyPortCollection::PORTS[2].symbol, "out");
+///     assert_eq!(MyPortCollection::PORTS[3].properties, &[PortProperty::Toggled]);
+///
+///     // Clamping a control input to it's declared range.
+///     fn gain(ports: &MyPortCollection) -> f32 {
+///         MyPortCollection::PORTS[ports.gain.get_index() as usize].clamp(*ports.gain)
+///     }
+///
+/// The unit may be any type that implements `UriBound`, like the units of the `lv2-units` crate.
+///
+/// The metadata is checked at compile time: Symbols have to be unique, valid C identifiers, the minimum may not be greater than the maximum and the default has to be within the range. Values that aren't literals, like constants, can't be checked.
+///
+/// # Port groups
+///
+/// Ports can be grouped, for example to declare the channels of a stereo input. Groups are declared with an `lv2` attribute of the struct and ports join them with the `group` attribute. The classes of a group and the designations of it's ports may be any type that implements `UriBound`, like the types of the `lv2-port-groups` crate:
+///
+///     use lv2_core::port::*;
+///     # use urid::*;
+///     # pub struct StereoGroup;
+///     # unsafe impl UriBound for StereoGroup {
+///     #     const URI: &'static [u8] = b"http://lv2plug.in/ns/ext/port-groups#StereoGroup\0";
+///     # }
+///     # pub struct Left;
+///     # unsafe impl UriBound for Left {
+///     #     const URI: &'static [u8] = b"http://lv2plug.in/ns/ext/port-groups#left\0";
+///     # }
+///     # pub struct Right;
+///     # unsafe impl UriBound for Right {
+///     #     const URI: &'static [u8] = b"http://lv2plug.in/ns/ext/port-groups#right\0";
+///     # }
+///
+///     #[derive(PortCollection)]
+///     #[lv2(group(symbol = "main_in", name = "Input", class = StereoGroup, main))]
+///     #[lv2(group(symbol = "sidechain", name = "Sidechain", class = StereoGroup, side_chain_of = "main_in"))]
+///     struct MyPortCollection {
+///         #[lv2(group = "main_in", designation = Left)]
+///         in_left: InputPort<Audio>,
+///         #[lv2(group = "main_in", designation = Right)]
+///         in_right: InputPort<Audio>,
+///         #[lv2(group = "sidechain", designation = Left)]
+///         sidechain_left: InputPort<Audio>,
+///         #[lv2(group = "sidechain", designation = Right)]
+///         sidechain_right: InputPort<Audio>,
+///     }
+///
+///     let group = GroupMetadata::find(MyPortCollection::GROUPS, "sidechain").unwrap();
+///     assert_eq!(Some("main_in"), group.side_chain_of);
+///     assert_eq!(2, group.ports(MyPortCollection::PORTS).count());
+///
+/// Every group needs a symbol and the groups referenced by ports and side chains have to be declared.
 pub trait PortCollection: Sized {
     /// The type of the port pointer cache.
     ///
@@ -174,6 +272,12 @@ pub trait PortCollection: Sized {
     ///
     /// Since the pointer cache is only storing the pointers, implementing this method requires the de-referencation of raw pointers and therefore, this method is unsafe.
     unsafe fn from_connections(cache: &Self::Cache, sample_count: u32) -> Option<Self>;
+
+    /// The metadata of all ports, ordered by their index.
+    const PORTS: &'static [PortMetadata] = &[];
+
+    /// The metadata of all port groups, in the order of their declaration.
+    const GROUPS: &'static [GroupMetadata] = &[];
 }
 
 impl PortCollection for () {
@@ -184,6 +288,179 @@ impl PortCollection for () {
     }
 }
 
+/// The direction of a port.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortDirection {
+    Input,
+    Output,
+}
+
+impl PortDirection {
+    /// Return the URI of the direction's port class, `lv2:InputPort` or `lv2:OutputPort`.
+    pub const fn uri(self) -> &'static [u8] {
+        match self {
+            PortDirection::Input => sys::LV2_CORE__InputPort,
+            PortDirection::Output => sys::LV2_CORE__OutputPort,
+        }
+    }
+}
+
+/// Properties of a port that may be declared in the plugin description.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortProperty {
+    /// The port only accepts integer values.
+    Integer,
+    /// The port is a switch; Values below or equal to zero are off, others are on.
+    Toggled,
+    /// The port only accepts the values of it's scale points.
+    Enumeration,
+    /// The bounds of the port are multiplied by the sample rate.
+    SampleRate,
+    /// The port reports the latency of the plugin.
+    ReportsLatency,
+    /// The port should be displayed with a logarithmic scale.
+    Logarithmic,
+    /// The port should not be displayed in a generic UI.
+    NotOnGui,
+    /// The port should not be automated by the host.
+    NotAutomatic,
+    /// The port is a trigger and is reset to it's default after it's value was used.
+    Trigger,
+    /// Changing the value of the port may cause audible artifacts.
+    CausesArtifacts,
+    /// Changing the value of the port is expensive.
+    Expensive,
+    /// The plugin requires the value of the port to be within it's bounds.
+    HasStrictBounds,
+}
+
+impl PortProperty {
+    /// Return the URI of the property.
+    pub const fn uri(self) -> &'static [u8] {
+        match self {
+            PortProperty::Integer => sys::LV2_CORE__integer,
+            PortProperty::Toggled => sys::LV2_CORE__toggled,
+            PortProperty::Enumeration => sys::LV2_CORE__enumeration,
+            PortProperty::SampleRate => sys::LV2_CORE__sampleRate,
+            PortProperty::ReportsLatency => sys::LV2_CORE__reportsLatency,
+            PortProperty::Logarithmic => sys::LV2_PORT_PROPS__logarithmic,
+            PortProperty::NotOnGui => sys::LV2_PORT_PROPS__notOnGUI,
+            PortProperty::NotAutomatic => sys::LV2_PORT_PROPS__notAutomatic,
+            PortProperty::Trigger => sys::LV2_PORT_PROPS__trigger,
+            PortProperty::CausesArtifacts => sys::LV2_PORT_PROPS__causesArtifacts,
+            PortProperty::Expensive => sys::LV2_PORT_PROPS__expensive,
+            PortProperty::HasStrictBounds => sys::LV2_PORT_PROPS__hasStrictBounds,
+        }
+    }
+}
+
+/// Static description of a port.
+///
+/// A table of these is created for every derived [`PortCollection`](trait.PortCollection.html) and describes the ports in the same way as the Turtle description of the plugin. All URIs are null-terminated, like the URIs of `UriBound`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PortMetadata {
+    /// The index of the port.
+    pub index: u32,
+    /// The symbol of the port, which has to be a valid C identifier.
+    pub symbol: &'static str,
+    /// The human-readable name of the port.
+    pub name: &'static str,
+    /// The direction of the port, if it's known.
+    pub direction: Option<PortDirection>,
+    /// The URI of the port class, for example `lv2:AudioPort`, if it's known.
+    pub class: Option<&'static [u8]>,
+    /// Whether the port may be disconnected by the host.
+    pub optional: bool,
+    /// The default value of a control port.
+    pub default: Option<f32>,
+    /// The minimum value of a control port.
+    pub minimum: Option<f32>,
+    /// The maximum value of a control port.
+    pub maximum: Option<f32>,
+    /// The URI of the unit of the port's values.
+    pub unit: Option<&'static [u8]>,
+    /// The URI of the buffer type of an atom port, for example `atom:Sequence`.
+    pub buffer_type: Option<&'static [u8]>,
+    /// The URIs of the atom types an atom port supports.
+    pub supports: &'static [&'static [u8]],
+    /// The properties of the port.
+    pub properties: &'static [PortProperty],
+    /// The symbol of the group the port belongs to.
+    pub group: Option<&'static str>,
+    /// The URI of the port's designation, for example the channel `pg:left` of a stereo group.
+    pub designation: Option<&'static [u8]>,
+}
+
+impl PortMetadata {
+    /// Clamp a value to the declared range of the port.
+    ///
+    /// Missing bounds are not applied, and neither are the bounds of ports with the `SampleRate` property, since they have to be multiplied with the sample rate first.
+    pub fn clamp(&self, value: f32) -> f32 {
+        if self.properties.contains(&PortProperty::SampleRate) {
+            return value;
+        }
+        let value = match self.minimum {
+            Some(minimum) if value < minimum => minimum,
+            _ => value,
+        };
+        match self.maximum {
+            Some(maximum) if value > maximum => maximum,
+            _ => value,
+        }
+    }
+
+    /// Check whether a value is within the declared range of the port.
+    pub fn contains(&self, value: f32) -> bool {
+        self.clamp(value) == value
+    }
+
+    /// Find the metadata of a port by it's symbol.
+    pub fn find<'a>(ports: &'a [PortMetadata], symbol: &str) -> Option<&'a PortMetadata> {
+        ports.iter().find(|port| port.symbol == symbol)
+    }
+}
+
+/// Static description of a port group.
+///
+/// A group bundles several ports that belong together, like the channels of a stereo input. The ports of a group refer to it by it's symbol, and the role of every port in the group is given by it's designation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct GroupMetadata {
+    /// The symbol of the group, which has to be a valid C identifier.
+    pub symbol: &'static str,
+    /// The human-readable name of the group.
+    pub name: &'static str,
+    /// The URIs of the group's classes, for example `pg:StereoGroup`.
+    pub classes: &'static [&'static [u8]],
+    /// Whether the group is the main input or output of the plugin.
+    pub main: bool,
+    /// The symbol of the group this group is a side chain of.
+    pub side_chain_of: Option<&'static str>,
+}
+
+impl GroupMetadata {
+    /// Find the metadata of a group by it's symbol.
+    pub fn find<'a>(groups: &'a [GroupMetadata], symbol: &str) -> Option<&'a GroupMetadata> {
+        groups.iter().find(|group| group.symbol == symbol)
+    }
+
+    /// Iterate over the ports of the group, ordered by their index.
+    pub fn ports<'a>(
+        &'a self,
+        ports: &'a [PortMetadata],
+    ) -> impl Iterator<Item = &'a PortMetadata> + 'a {
+        ports
+            .iter()
+            .filter(move |port| port.group == Some(self.symbol))
+    }
+
+    /// Return the direction of the group, which is the direction of it's ports.
+    ///
+    /// Returns `None` if the group has no ports.
+    pub fn direction(&self, ports: &[PortMetadata]) -> Option<PortDirection> {
+        self.ports(ports).next().and_then(|port| port.direction)
+    }
+}
+
 /// Cache for port connection pointers.
 ///
 /// The host will pass the port connection pointers one by one and in an undefined order. Therefore, the `PortCollection` struct can not be created instantly. Instead, the pointers will be stored in a cache, which is then used to create a proper port collection for the plugin.
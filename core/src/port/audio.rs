@@ -69,6 +69,8 @@ impl PortType for Audio {
     type InputPortType = &'static [f32];
     type OutputPortType = &'static mut [f32];
 
+    const PORT_CLASS: Option<&'static [u8]> = Some(Self::URI);
+
     #[inline]
     unsafe fn input_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::InputPortType {
         std::slice::from_raw_parts(pointer.as_ptr() as *const f32, sample_count as usize)
@@ -135,6 +137,8 @@ impl PortType for InPlaceAudio {
     type InputPortType = &'static [Cell<f32>];
     type OutputPortType = &'static [Cell<f32>];
 
+    const PORT_CLASS: Option<&'static [u8]> = Some(Self::URI);
+
     #[inline]
     unsafe fn input_from_raw(pointer: NonNull<c_void>, sample_count: u32) -> Self::InputPortType {
         Cell::from_mut(std::slice::from_raw_parts_mut(
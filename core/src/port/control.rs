@@ -68,6 +68,8 @@ impl PortType for Control {
     type InputPortType = f32;
     type OutputPortType = &'static mut f32;
 
+    const PORT_CLASS: Option<&'static [u8]> = Some(Self::URI);
+
     #[inline]
     unsafe fn input_from_raw(pointer: NonNull<c_void>, _sample_count: u32) -> f32 {
         *(pointer.cast().as_ref())
@@ -133,6 +135,8 @@ impl PortType for InPlaceControl {
     type InputPortType = &'static Cell<f32>;
     type OutputPortType = &'static Cell<f32>;
 
+    const PORT_CLASS: Option<&'static [u8]> = Some(Self::URI);
+
     #[inline]
     unsafe fn input_from_raw(pointer: NonNull<c_void>, _sample_count: u32) -> Self::InputPortType {
         Cell::from_mut(&mut *(pointer.as_ptr() as *mut f32))
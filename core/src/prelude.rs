@@ -1,9 +1,12 @@
 //! Prelude for wildcard use, containing many important types.
 pub use crate::extension::ExtensionDescriptor;
-pub use crate::feature::{FeatureCache, FeatureCollection, MissingFeatureError, ThreadingClass};
+pub use crate::feature::{
+    FeatureCache, FeatureCollection, FeatureRequirement, MissingFeatureError, ThreadingClass,
+};
 pub use crate::match_extensions;
 pub use crate::plugin::{
-    lv2_descriptors, Plugin, PluginInfo, PluginInstance, PluginInstanceDescriptor, PortCollection,
+    lv2_descriptors, Plugin, PluginInfo, PluginInstance, PluginInstanceDescriptor, PluginMetadata,
+    PortCollection,
 };
 pub use crate::port::*;
 pub use crate::sys::LV2_Descriptor;
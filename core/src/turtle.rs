@@ -0,0 +1,398 @@
+//! Generation of Turtle plugin descriptions.
+//!
+//! Hosts discover plugins by reading the Turtle files of a bundle: The `manifest.ttl` lists the plugins and their binaries and the plugin description lists the ports, features and extensions of every plugin. Writing these files by hand is error-prone, since the port indices have to match the order of the fields in the plugin's [`PortCollection`](../port/trait.PortCollection.html).
+//!
+//! Instead, these files can be generated from the [`PluginMetadata`](../plugin/trait.PluginMetadata.html) of the plugin, for example in a test or a small binary:
+//!
+//!     use lv2_core::prelude::*;
+//!     use lv2_core::turtle::*;
+//!     use urid::*;
+//!
+//!     #[derive(PortCollection)]
+//!     struct Ports {
+//!         #[lv2(name = "Gain", default = 0.0, min = -90.0, max = 24.0)]
+//!         gain: InputPort<Control>,
+//!         #[lv2(symbol = "in", name = "In")]
+//!         input: InputPort<Audio>,
+//!         #[lv2(symbol = "out", name = "Out")]
+//!         output: OutputPort<Audio>,
+//!     }
+//!
+//!     #[uri("urn:amp")]
+//!     #[derive(PluginMetadata)]
+//!     #[lv2(name = "Amp", class = AmplifierPlugin)]
+//!     struct Amp;
+//!
+//!     impl Plugin for Amp {
+//!         type Ports = Ports;
+//!         type InitFeatures = ();
+//!         type AudioFeatures = ();
+//!
+//!         fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+//!             Some(Self)
+//!         }
+//!
+//!         fn run(&mut self, _: &mut Ports, _: &mut (), _: u32) {}
+//!     }
+//!
+//!     let mut manifest = Manifest::new();
+//!     manifest.add::<Amp>(&binary_name("amp"), "amp.ttl");
+//!     assert!(manifest.to_string().contains("rdfs:seeAlso <amp.ttl>"));
+//!
+//!     let description = plugin_description::<Amp>();
+//!     assert!(description.contains("lv2:symbol \"gain\""));
+//!
+//! [`write_bundle`](fn.write_bundle.html) writes both files into the bundle directory.
+use crate::feature::{FeatureCollection, FeatureRequirement};
+use crate::plugin::PluginMetadata;
+use crate::port::{GroupMetadata, PortCollection, PortDirection, PortMetadata};
+use std::fmt::{Display, Formatter, Result as FmtResult, Write};
+use std::path::Path;
+use urid::UriBound;
+
+/// The prefixes used by the generated files.
+const PREFIXES: &str = "@prefix doap: <http://usefulinc.com/ns/doap#> .
+@prefix lv2:  <http://lv2plug.in/ns/lv2core#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+";
+
+/// Convert a null-terminated URI to a Turtle IRI reference.
+///
+/// Spaces, control characters and other characters that aren't allowed in IRI references are percent-encoded.
+pub fn iri(uri: &[u8]) -> String {
+    let uri = uri.strip_suffix(b"\0").unwrap_or(uri);
+    let mut iri = String::with_capacity(uri.len() + 2);
+    iri.push('<');
+    for c in String::from_utf8_lossy(uri).chars() {
+        if c <= ' ' || c == '\u{7f}' || "<>\"{}|^`\\".contains(c) {
+            let _ = write!(iri, "%{:02X}", c as u32);
+        } else {
+            iri.push(c);
+        }
+    }
+    iri.push('>');
+    iri
+}
+
+/// Convert a number to a Turtle numeric literal.
+///
+/// Finite numbers are written as decimal or double literals. Turtle has no numeric literals for infinities and NaN, so they are written as typed `xsd:float` literals.
+pub fn number(value: f32) -> String {
+    if value.is_finite() {
+        format!("{:?}", value)
+    } else {
+        let value = if value.is_nan() {
+            "NaN"
+        } else if value > 0.0 {
+            "INF"
+        } else {
+            "-INF"
+        };
+        format!("\"{}\"^^<http://www.w3.org/2001/XMLSchema#float>", value)
+    }
+}
+
+/// Convert a string to a Turtle string literal, escaping quotes, backslashes and line breaks.
+pub fn literal(value: &str) -> String {
+    let mut literal = String::with_capacity(value.len() + 2);
+    literal.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => literal.push_str("\\\""),
+            '\\' => literal.push_str("\\\\"),
+            '\n' => literal.push_str("\\n"),
+            '\r' => literal.push_str("\\r"),
+            c => literal.push(c),
+        }
+    }
+    literal.push('"');
+    literal
+}
+
+/// Return the file name of a plugin library with the given crate name on the current platform.
+///
+/// For example, the library of the crate `amp` is called `libamp.so` on Linux and `amp.dll` on Windows.
+pub fn binary_name(crate_name: &str) -> String {
+    format!(
+        "{}{}{}",
+        std::env::consts::DLL_PREFIX,
+        crate_name.replace('-', "_"),
+        std::env::consts::DLL_SUFFIX
+    )
+}
+
+/// The content of a `manifest.ttl`.
+///
+/// The manifest lists all plugins of a bundle together with their binary and the file with their description.
+#[derive(Clone, Debug, Default)]
+pub struct Manifest {
+    entries: String,
+}
+
+impl Manifest {
+    /// Create an empty manifest.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a plugin to the manifest.
+    ///
+    /// The paths of the binary and the description are relative to the bundle directory. Characters that aren't allowed in IRIs, like spaces, are percent-encoded.
+    pub fn add<P: UriBound>(&mut self, binary: &str, description: &str) -> &mut Self {
+        let _ = write!(
+            self.entries,
+            "\n{}\n    a lv2:Plugin ;\n    lv2:binary {} ;\n    rdfs:seeAlso {} .\n",
+            iri(P::URI),
+            iri(binary.as_bytes()),
+            iri(description.as_bytes())
+        );
+        self
+    }
+
+    /// Add a preset of a plugin to the manifest.
+    ///
+    /// The path of the description is relative to the bundle directory. Like the paths, the preset URI is percent-encoded where necessary.
+    pub fn add_preset<P: UriBound>(&mut self, preset: &str, description: &str) -> &mut Self {
+        let _ = write!(
+            self.entries,
+            "\n{}\n    a {} ;\n    lv2:appliesTo {} ;\n    rdfs:seeAlso {} .\n",
+            iri(preset.as_bytes()),
+            iri(sys::LV2_PRESETS__Preset),
+            iri(P::URI),
+            iri(description.as_bytes())
+        );
+        self
+    }
+}
+
+impl Display for Manifest {
+    fn fmt(&self, f: &mut Formatter) -> FmtResult {
+        write!(f, "{}{}", PREFIXES, self.entries)
+    }
+}
+
+/// Collect the features of a plugin.
+///
+/// Features are returned in the order of their first appearance and a feature is only optional if no collection requires it.
+pub fn features<P: PluginMetadata>() -> Vec<FeatureRequirement> {
+    let mut features: Vec<FeatureRequirement> = Vec::new();
+    let all_features = P::FEATURES
+        .iter()
+        .chain(<P::InitFeatures as FeatureCollection<'static>>::FEATURES)
+        .chain(<P::AudioFeatures as FeatureCollection<'static>>::FEATURES);
+    for feature in all_features {
+        match features.iter_mut().find(|known| known.uri == feature.uri) {
+            Some(known) => known.required |= feature.required,
+            None => features.push(*feature),
+        }
+    }
+    features
+}
+
+/// Write a list of objects of a predicate.
+fn write_objects<'a>(
+    out: &mut String,
+    predicate: &str,
+    objects: impl IntoIterator<Item = &'a [u8]>,
+) {
+    let objects: Vec<String> = objects.into_iter().map(iri).collect();
+    if !objects.is_empty() {
+        let _ = write!(out, " ;\n    {} {}", predicate, objects.join(" , "));
+    }
+}
+
+/// Return the IRI of a port group of a plugin.
+///
+/// Groups are identified by the plugin URI with the group symbol as fragment, or appended to an existing fragment.
+fn group_iri(plugin: &[u8], symbol: &str) -> String {
+    let plugin = plugin.strip_suffix(b"\0").unwrap_or(plugin);
+    let separator = if plugin.contains(&b'#') { '_' } else { '#' };
+    format!(
+        "<{}{}{}>",
+        String::from_utf8_lossy(plugin),
+        separator,
+        symbol
+    )
+}
+
+/// Write the description of a port.
+fn write_port(out: &mut String, port: &PortMetadata, plugin: &[u8]) {
+    let classes: Vec<String> = port
+        .direction
+        .map(PortDirection::uri)
+        .into_iter()
+        .chain(port.class)
+        .map(iri)
+        .collect();
+    // Ports with an unknown direction and class are only described as ports.
+    let classes = if classes.is_empty() {
+        String::from("lv2:Port")
+    } else {
+        classes.join(" , ")
+    };
+    let _ = write!(
+        out,
+        "[\n        a {} ;\n        lv2:index {} ;\n        lv2:symbol {} ;\n        lv2:name {}",
+        classes,
+        port.index,
+        literal(port.symbol),
+        literal(port.name),
+    );
+    let values = [
+        ("lv2:default", port.default),
+        ("lv2:minimum", port.minimum),
+        ("lv2:maximum", port.maximum),
+    ];
+    for (predicate, value) in values.iter() {
+        if let Some(value) = value {
+            let _ = write!(out, " ;\n        {} {}", predicate, number(*value));
+        }
+    }
+    if let Some(unit) = port.unit {
+        let _ = write!(
+            out,
+            " ;\n        <http://lv2plug.in/ns/extensions/units#unit> {}",
+            iri(unit)
+        );
+    }
+    if let Some(buffer_type) = port.buffer_type {
+        let _ = write!(
+            out,
+            " ;\n        <http://lv2plug.in/ns/ext/atom#bufferType> {}",
+            iri(buffer_type)
+        );
+    }
+    if let Some(group) = port.group {
+        let _ = write!(
+            out,
+            " ;\n        {} {}",
+            iri(sys::LV2_PORT_GROUPS__group),
+            group_iri(plugin, group)
+        );
+    }
+    if let Some(designation) = port.designation {
+        let _ = write!(out, " ;\n        lv2:designation {}", iri(designation));
+    }
+    for supported in port.supports {
+        let _ = write!(
+            out,
+            " ;\n        <http://lv2plug.in/ns/ext/atom#supports> {}",
+            iri(supported)
+        );
+    }
+    let optional = port
+        .optional
+        .then_some(sys::LV2_CORE__connectionOptional as &[u8]);
+    let properties = port
+        .properties
+        .iter()
+        .map(|property| property.uri())
+        .chain(optional);
+    for property in properties {
+        let _ = write!(out, " ;\n        lv2:portProperty {}", iri(property));
+    }
+    out.push_str("\n    ]");
+}
+
+/// Write the description of a port group.
+///
+/// Groups are input or output groups, depending on the direction of their ports.
+fn write_group(out: &mut String, group: &GroupMetadata, ports: &[PortMetadata], plugin: &[u8]) {
+    let _ = write!(
+        out,
+        "\n{}\n    a {}",
+        group_iri(plugin, group.symbol),
+        iri(sys::LV2_PORT_GROUPS__Group)
+    );
+    let direction = match group.direction(ports) {
+        Some(PortDirection::Input) => Some(sys::LV2_PORT_GROUPS__InputGroup as &[u8]),
+        Some(PortDirection::Output) => Some(sys::LV2_PORT_GROUPS__OutputGroup as &[u8]),
+        None => None,
+    };
+    for class in group.classes.iter().copied().chain(direction) {
+        let _ = write!(out, " , {}", iri(class));
+    }
+    let _ = write!(
+        out,
+        " ;\n    lv2:symbol {} ;\n    lv2:name {}",
+        literal(group.symbol),
+        literal(group.name)
+    );
+    if let Some(main_group) = group.side_chain_of {
+        let _ = write!(
+            out,
+            " ;\n    {} {}",
+            iri(sys::LV2_PORT_GROUPS__sideChainOf),
+            group_iri(plugin, main_group)
+        );
+    }
+    out.push_str(" .\n");
+}
+
+/// Generate the description of a plugin.
+///
+/// The description contains the classes, the name, the features, the extensions, the ports and the port groups of the plugin.
+pub fn plugin_description<P: PluginMetadata>() -> String {
+    let mut out = String::from(PREFIXES);
+
+    let _ = write!(out, "\n{}\n    a lv2:Plugin", iri(P::URI));
+    for class in P::CLASSES {
+        let _ = write!(out, " , {}", iri(class));
+    }
+    let _ = write!(out, " ;\n    doap:name {}", literal(P::NAME));
+    write_objects(&mut out, "lv2:project", P::PROJECT);
+    write_objects(&mut out, "doap:license", P::LICENSE);
+
+    let features = features::<P>();
+    write_objects(
+        &mut out,
+        "lv2:requiredFeature",
+        features.iter().filter(|f| f.required).map(|f| f.uri),
+    );
+    write_objects(
+        &mut out,
+        "lv2:optionalFeature",
+        features.iter().filter(|f| !f.required).map(|f| f.uri),
+    );
+    write_objects(&mut out, "lv2:extensionData", P::EXTENSIONS.iter().copied());
+
+    let ports = <P::Ports as PortCollection>::PORTS;
+    let groups = <P::Ports as PortCollection>::GROUPS;
+    for group in groups.iter().filter(|group| group.main) {
+        let predicate = match group.direction(ports) {
+            Some(PortDirection::Input) => sys::LV2_PORT_GROUPS__mainInput as &[u8],
+            Some(PortDirection::Output) => sys::LV2_PORT_GROUPS__mainOutput,
+            None => continue,
+        };
+        let _ = write!(
+            out,
+            " ;\n    {} {}",
+            iri(predicate),
+            group_iri(P::URI, group.symbol)
+        );
+    }
+    for (i, port) in ports.iter().enumerate() {
+        out.push_str(if i == 0 { " ;\n    lv2:port " } else { " , " });
+        write_port(&mut out, port, P::URI);
+    }
+    out.push_str(" .\n");
+    for group in groups {
+        write_group(&mut out, group, ports, P::URI);
+    }
+    out
+}
+
+/// Write the `manifest.ttl` and the description of a plugin into a bundle directory.
+///
+/// The binary is the file name of the plugin library, which can be created with [`binary_name`](fn.binary_name.html), and the description is written to the file with the given name.
+pub fn write_bundle<P: PluginMetadata>(
+    bundle: &Path,
+    binary: &str,
+    description: &str,
+) -> std::io::Result<()> {
+    let mut manifest = Manifest::new();
+    manifest.add::<P>(binary, description);
+    std::fs::create_dir_all(bundle)?;
+    std::fs::write(bundle.join("manifest.ttl"), manifest.to_string())?;
+    std::fs::write(bundle.join(description), plugin_description::<P>())
+}
@@ -0,0 +1,406 @@
+use lv2_core::feature::{HardRTCapable, InPlaceBroken, IsLive};
+use lv2_core::prelude::*;
+use lv2_core::turtle::*;
+use urid::*;
+
+#[uri("http://lv2plug.in/plugins.rs/example_amp")]
+#[derive(PluginMetadata)]
+#[lv2(
+    name = "Simple \"Amplifier\"",
+    classes(AmplifierPlugin, UtilityPlugin),
+    project = "https://github.com/RustAudio/rust-lv2",
+    license = "http://opensource.org/licenses/isc",
+    required_features(InPlaceBroken),
+    optional_features(IsLive),
+    extensions(Extension)
+)]
+struct Amp;
+
+struct Decibel;
+
+unsafe impl UriBound for Decibel {
+    const URI: &'static [u8] = b"http://lv2plug.in/ns/extensions/units#db\0";
+}
+
+struct Extension;
+
+unsafe impl UriBound for Extension {
+    const URI: &'static [u8] = b"urn:rust-lv2:extension\0";
+}
+
+#[derive(PortCollection)]
+#[allow(dead_code)]
+struct AmpPorts {
+    #[lv2(name = "Gain", default = 0.0, min = -90.0, max = 24.0, unit = Decibel)]
+    gain: InputPort<Control>,
+    #[lv2(symbol = "in", name = "In")]
+    input: InputPort<Audio>,
+    #[lv2(symbol = "out", name = "Out")]
+    output: OutputPort<Audio>,
+    #[lv2(default = 1, properties(Toggled, NotAutomatic))]
+    enabled: Option<InputPort<Control>>,
+}
+
+#[derive(FeatureCollection)]
+struct InitFeatures {
+    _rt_capable: Option<HardRTCapable>,
+    _is_live: IsLive,
+}
+
+impl Plugin for Amp {
+    type Ports = AmpPorts;
+    type InitFeatures = InitFeatures;
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut InitFeatures) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, _: &mut AmpPorts, _: &mut (), _: u32) {}
+}
+
+#[test]
+fn test_port_metadata() {
+    let ports = AmpPorts::PORTS;
+    assert_eq!(4, ports.len());
+
+    assert_eq!(0, ports[0].index);
+    assert_eq!("gain", ports[0].symbol);
+    assert_eq!("Gain", ports[0].name);
+    assert_eq!(Some(PortDirection::Input), ports[0].direction);
+    assert_eq!(Some(Control::URI), ports[0].class);
+    assert_eq!(Some(-90.0), ports[0].minimum);
+    assert_eq!(Some(Decibel::URI), ports[0].unit);
+
+    assert_eq!(Some(PortDirection::Output), ports[2].direction);
+    assert_eq!(Some(Audio::URI), ports[2].class);
+    assert_eq!(None, ports[2].default);
+
+    assert_eq!("enabled", ports[3].name);
+    assert_eq!(Some(1.0), ports[3].default);
+    assert!(ports[3].optional);
+    assert_eq!(
+        &[PortProperty::Toggled, PortProperty::NotAutomatic],
+        ports[3].properties
+    );
+}
+
+/// A port type of another crate, which doesn't declare a port class.
+struct CustomPort;
+
+impl PortType for CustomPort {
+    type InputPortType = ();
+    type OutputPortType = ();
+
+    unsafe fn input_from_raw(_: std::ptr::NonNull<std::ffi::c_void>, _: u32) {}
+
+    unsafe fn output_from_raw(_: std::ptr::NonNull<std::ffi::c_void>, _: u32) {}
+}
+
+/// A port handle of another crate, which doesn't declare it's direction or class.
+struct CustomHandle;
+
+impl PortHandle for CustomHandle {
+    unsafe fn from_raw(_: *mut std::ffi::c_void, _: u32, _: u32) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn get_index(&self) -> u32 {
+        0
+    }
+}
+
+#[derive(PortCollection)]
+#[allow(dead_code)]
+struct CustomPorts {
+    custom: InputPort<CustomPort>,
+    handle: CustomHandle,
+}
+
+#[test]
+fn test_custom_ports() {
+    let ports = CustomPorts::PORTS;
+    assert_eq!(Some(PortDirection::Input), ports[0].direction);
+    assert_eq!(None, ports[0].class);
+    assert_eq!(None, ports[1].direction);
+    assert_eq!(None, ports[1].class);
+}
+
+#[test]
+fn test_iri() {
+    assert_eq!("<urn:amp#gain>", iri(b"urn:amp#gain\0"));
+    assert_eq!(
+        "<file:///my%20samples/%22kick%22.wav>",
+        iri(b"file:///my samples/\"kick\".wav")
+    );
+    assert_eq!("<urn:%3Cä%3E%0A>", iri("urn:<ä>\n".as_bytes()));
+}
+
+#[test]
+fn test_number() {
+    assert_eq!("0.0", number(0.0));
+    assert_eq!("-90.5", number(-90.5));
+    assert_eq!("1e-5", number(1e-5));
+    assert_eq!("1e20", number(1e20));
+    assert_eq!(
+        "\"INF\"^^<http://www.w3.org/2001/XMLSchema#float>",
+        number(f32::INFINITY)
+    );
+    assert_eq!(
+        "\"-INF\"^^<http://www.w3.org/2001/XMLSchema#float>",
+        number(f32::NEG_INFINITY)
+    );
+    assert_eq!(
+        "\"NaN\"^^<http://www.w3.org/2001/XMLSchema#float>",
+        number(f32::NAN)
+    );
+}
+
+#[test]
+fn test_clamp() {
+    let gain = PortMetadata::find(AmpPorts::PORTS, "gain").unwrap();
+    assert_eq!(24.0, gain.clamp(30.0));
+    assert_eq!(-90.0, gain.clamp(-100.0));
+    assert_eq!(3.0, gain.clamp(3.0));
+    assert!(gain.contains(0.0));
+    assert!(!gain.contains(25.0));
+
+    let output = PortMetadata::find(AmpPorts::PORTS, "out").unwrap();
+    assert_eq!(2, output.index);
+    assert_eq!(1000.0, output.clamp(1000.0));
+
+    assert!(PortMetadata::find(AmpPorts::PORTS, "volume").is_none());
+}
+
+#[test]
+fn test_features() {
+    assert_eq!(
+        vec![
+            FeatureRequirement::required::<InPlaceBroken>(),
+            FeatureRequirement::required::<IsLive>(),
+            FeatureRequirement::optional::<HardRTCapable>(),
+        ],
+        features::<Amp>()
+    );
+}
+
+#[test]
+fn test_manifest() {
+    let mut manifest = Manifest::new();
+    manifest.add::<Amp>("libamp.so", "amp.ttl");
+    assert_eq!(
+        "@prefix doap: <http://usefulinc.com/ns/doap#> .
+@prefix lv2:  <http://lv2plug.in/ns/lv2core#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+<http://lv2plug.in/plugins.rs/example_amp>
+    a lv2:Plugin ;
+    lv2:binary <libamp.so> ;
+    rdfs:seeAlso <amp.ttl> .
+",
+        manifest.to_string()
+    );
+}
+
+#[test]
+fn test_manifest_preset() {
+    let mut manifest = Manifest::new();
+    manifest.add_preset::<Amp>("urn:amp#loud", "presets.ttl");
+    assert!(manifest.to_string().ends_with(
+        "
+<urn:amp#loud>
+    a <http://lv2plug.in/ns/ext/presets#Preset> ;
+    lv2:appliesTo <http://lv2plug.in/plugins.rs/example_amp> ;
+    rdfs:seeAlso <presets.ttl> .
+"
+    ));
+}
+
+#[test]
+fn test_manifest_escaping() {
+    let mut manifest = Manifest::new();
+    manifest.add::<Amp>("lib amp>.so", "descriptions\\amp.ttl");
+    manifest.add_preset::<Amp>("urn:amp#very loud", "my presets.ttl");
+    let manifest = manifest.to_string();
+    assert!(manifest.contains("lv2:binary <lib%20amp%3E.so> ;"));
+    assert!(manifest.contains("rdfs:seeAlso <descriptions%5Camp.ttl> ."));
+    assert!(manifest.contains("\n<urn:amp#very%20loud>\n"));
+    assert!(manifest.contains("rdfs:seeAlso <my%20presets.ttl> ."));
+}
+
+#[test]
+fn test_plugin_description() {
+    assert_eq!(
+        "@prefix doap: <http://usefulinc.com/ns/doap#> .
+@prefix lv2:  <http://lv2plug.in/ns/lv2core#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+<http://lv2plug.in/plugins.rs/example_amp>
+    a lv2:Plugin , <http://lv2plug.in/ns/lv2core#AmplifierPlugin> , <http://lv2plug.in/ns/lv2core#UtilityPlugin> ;
+    doap:name \"Simple \\\"Amplifier\\\"\" ;
+    lv2:project <https://github.com/RustAudio/rust-lv2> ;
+    doap:license <http://opensource.org/licenses/isc> ;
+    lv2:requiredFeature <http://lv2plug.in/ns/lv2core#inPlaceBroken> , <http://lv2plug.in/ns/lv2core#isLive> ;
+    lv2:optionalFeature <http://lv2plug.in/ns/lv2core#hardRTCapable> ;
+    lv2:extensionData <urn:rust-lv2:extension> ;
+    lv2:port [
+        a <http://lv2plug.in/ns/lv2core#InputPort> , <http://lv2plug.in/ns/lv2core#ControlPort> ;
+        lv2:index 0 ;
+        lv2:symbol \"gain\" ;
+        lv2:name \"Gain\" ;
+        lv2:default 0.0 ;
+        lv2:minimum -90.0 ;
+        lv2:maximum 24.0 ;
+        <http://lv2plug.in/ns/extensions/units#unit> <http://lv2plug.in/ns/extensions/units#db>
+    ] , [
+        a <http://lv2plug.in/ns/lv2core#InputPort> , <http://lv2plug.in/ns/lv2core#AudioPort> ;
+        lv2:index 1 ;
+        lv2:symbol \"in\" ;
+        lv2:name \"In\"
+    ] , [
+        a <http://lv2plug.in/ns/lv2core#OutputPort> , <http://lv2plug.in/ns/lv2core#AudioPort> ;
+        lv2:index 2 ;
+        lv2:symbol \"out\" ;
+        lv2:name \"Out\"
+    ] , [
+        a <http://lv2plug.in/ns/lv2core#InputPort> , <http://lv2plug.in/ns/lv2core#ControlPort> ;
+        lv2:index 3 ;
+        lv2:symbol \"enabled\" ;
+        lv2:name \"enabled\" ;
+        lv2:default 1.0 ;
+        lv2:portProperty <http://lv2plug.in/ns/lv2core#toggled> ;
+        lv2:portProperty <http://lv2plug.in/ns/ext/port-props#notAutomatic> ;
+        lv2:portProperty <http://lv2plug.in/ns/lv2core#connectionOptional>
+    ] .
+",
+        plugin_description::<Amp>()
+    );
+}
+
+#[test]
+fn test_write_bundle() {
+    let bundle = std::env::temp_dir().join(format!("rust-lv2-turtle-{}", std::process::id()));
+    write_bundle::<Amp>(&bundle, &binary_name("amp"), "amp.ttl").unwrap();
+    let manifest = std::fs::read_to_string(bundle.join("manifest.ttl")).unwrap();
+    assert!(manifest.contains(&binary_name("amp")));
+    let description = std::fs::read_to_string(bundle.join("amp.ttl")).unwrap();
+    assert_eq!(plugin_description::<Amp>(), description);
+    std::fs::remove_dir_all(bundle).unwrap();
+}
+
+struct StereoGroup;
+
+unsafe impl UriBound for StereoGroup {
+    const URI: &'static [u8] = b"http://lv2plug.in/ns/ext/port-groups#StereoGroup\0";
+}
+
+struct Left;
+
+unsafe impl UriBound for Left {
+    const URI: &'static [u8] = b"http://lv2plug.in/ns/ext/port-groups#left\0";
+}
+
+struct Right;
+
+unsafe impl UriBound for Right {
+    const URI: &'static [u8] = b"http://lv2plug.in/ns/ext/port-groups#right\0";
+}
+
+#[derive(PortCollection)]
+#[lv2(group(symbol = "main_in", name = "Input", class = StereoGroup, main))]
+#[lv2(group(symbol = "main_out", name = "Output", class = StereoGroup, main))]
+#[lv2(group(symbol = "sidechain", name = "Sidechain", class = StereoGroup, side_chain_of = "main_in"))]
+#[allow(dead_code)]
+struct CompressorPorts {
+    #[lv2(group = "main_in", designation = Left)]
+    in_left: InputPort<Audio>,
+    #[lv2(group = "main_in", designation = Right)]
+    in_right: InputPort<Audio>,
+    #[lv2(group = "main_out", designation = Left)]
+    out_left: OutputPort<Audio>,
+    #[lv2(group = "main_out", designation = Right)]
+    out_right: OutputPort<Audio>,
+    #[lv2(group = "sidechain", designation = Left)]
+    sidechain_left: InputPort<Audio>,
+    #[lv2(group = "sidechain", designation = Right)]
+    sidechain_right: InputPort<Audio>,
+}
+
+#[uri("urn:compressor")]
+#[derive(PluginMetadata)]
+#[lv2(name = "Compressor", class = CompressorPlugin)]
+struct Compressor;
+
+impl Plugin for Compressor {
+    type Ports = CompressorPorts;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, _: &mut CompressorPorts, _: &mut (), _: u32) {}
+}
+
+#[test]
+fn test_group_metadata() {
+    let ports = CompressorPorts::PORTS;
+    let groups = CompressorPorts::GROUPS;
+    assert_eq!(3, groups.len());
+    assert!(AmpPorts::GROUPS.is_empty());
+
+    let main_in = GroupMetadata::find(groups, "main_in").unwrap();
+    assert_eq!("Input", main_in.name);
+    assert_eq!(&[StereoGroup::URI as &[u8]], main_in.classes);
+    assert!(main_in.main);
+    assert_eq!(Some(PortDirection::Input), main_in.direction(ports));
+    let indices: Vec<u32> = main_in.ports(ports).map(|port| port.index).collect();
+    assert_eq!(vec![0, 1], indices);
+
+    let sidechain = GroupMetadata::find(groups, "sidechain").unwrap();
+    assert!(!sidechain.main);
+    assert_eq!(Some("main_in"), sidechain.side_chain_of);
+
+    assert_eq!(Some("main_out"), ports[3].group);
+    assert_eq!(Some(Right::URI as &[u8]), ports[3].designation);
+    assert_eq!(None, AmpPorts::PORTS[0].group);
+}
+
+#[test]
+fn test_group_description() {
+    let description = plugin_description::<Compressor>();
+    assert!(description.contains(
+        "
+    <http://lv2plug.in/ns/ext/port-groups#mainInput> <urn:compressor#main_in> ;
+    <http://lv2plug.in/ns/ext/port-groups#mainOutput> <urn:compressor#main_out> ;
+    lv2:port ["
+    ));
+    assert!(description.contains(
+        "
+        lv2:symbol \"out_right\" ;
+        lv2:name \"out_right\" ;
+        <http://lv2plug.in/ns/ext/port-groups#group> <urn:compressor#main_out> ;
+        lv2:designation <http://lv2plug.in/ns/ext/port-groups#right>
+    ]"
+    ));
+    assert!(description.ends_with(
+        "
+<urn:compressor#main_in>
+    a <http://lv2plug.in/ns/ext/port-groups#Group> , <http://lv2plug.in/ns/ext/port-groups#StereoGroup> , <http://lv2plug.in/ns/ext/port-groups#InputGroup> ;
+    lv2:symbol \"main_in\" ;
+    lv2:name \"Input\" .
+
+<urn:compressor#main_out>
+    a <http://lv2plug.in/ns/ext/port-groups#Group> , <http://lv2plug.in/ns/ext/port-groups#StereoGroup> , <http://lv2plug.in/ns/ext/port-groups#OutputGroup> ;
+    lv2:symbol \"main_out\" ;
+    lv2:name \"Output\" .
+
+<urn:compressor#sidechain>
+    a <http://lv2plug.in/ns/ext/port-groups#Group> , <http://lv2plug.in/ns/ext/port-groups#StereoGroup> , <http://lv2plug.in/ns/ext/port-groups#InputGroup> ;
+    lv2:symbol \"sidechain\" ;
+    lv2:name \"Sidechain\" ;
+    <http://lv2plug.in/ns/ext/port-groups#sideChainOf> <urn:compressor#main_in> .
+"
+    ));
+}
@@ -0,0 +1,7 @@
+fn main() {
+    println!("cargo:rerun-if-changed=c/log.c");
+    cc::Build::new()
+        .file("c/log.c")
+        .include("../sys")
+        .compile("lv2_host_test_log");
+}
@@ -0,0 +1,49 @@
+// The `printf` and `vprintf` functions of the test host's `log:log` feature.
+//
+// They are variadic, which can't be implemented in stable Rust, so they format the message here and pass it to the Rust side via the handle.
+
+#include <stdarg.h>
+#include <stdio.h>
+#include <stdlib.h>
+
+#include "lv2/log/log.h"
+
+typedef struct {
+    void* data;
+    int (*record)(void* data, LV2_URID type, const char* message);
+} HostTestLog;
+
+static int host_test_vprintf(LV2_Log_Handle handle, LV2_URID type, const char* fmt, va_list ap) {
+    HostTestLog* log = (HostTestLog*)handle;
+
+    va_list ap_len;
+    va_copy(ap_len, ap);
+    int len = vsnprintf(NULL, 0, fmt, ap_len);
+    va_end(ap_len);
+    if (len < 0) {
+        return len;
+    }
+
+    char* message = malloc((size_t)len + 1);
+    if (message == NULL) {
+        return -1;
+    }
+    vsnprintf(message, (size_t)len + 1, fmt, ap);
+    int result = log->record(log->data, type, message);
+    free(message);
+    return result;
+}
+
+static int host_test_printf(LV2_Log_Handle handle, LV2_URID type, const char* fmt, ...) {
+    va_list ap;
+    va_start(ap, fmt);
+    int result = host_test_vprintf(handle, type, fmt, ap);
+    va_end(ap);
+    return result;
+}
+
+void lv2_host_test_log_init(LV2_Log_Log* log, HostTestLog* handle) {
+    log->handle = handle;
+    log->printf = host_test_printf;
+    log->vprintf = host_test_vprintf;
+}
@@ -0,0 +1,610 @@
+//! Reading plugin descriptions from the Turtle files of a bundle.
+//!
+//! The parser understands the subset of Turtle that is used by plugin bundles: Prefix and base directives, IRIs, prefixed names, blank nodes, literals and collections. It doesn't validate the data and simply collects all triples.
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const LV2: &str = "http://lv2plug.in/ns/lv2core#";
+const RDF_TYPE: &str = "http://www.w3.org/1999/02/22-rdf-syntax-ns#type";
+const RDFS_SEE_ALSO: &str = "http://www.w3.org/2000/01/rdf-schema#seeAlso";
+const ATOM_PORT: &str = "http://lv2plug.in/ns/ext/atom#AtomPort";
+
+/// A node of the graph.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Node {
+    Iri(String),
+    Blank(usize),
+    Literal(String),
+}
+
+impl Node {
+    fn as_iri(&self) -> Option<&str> {
+        match self {
+            Node::Iri(iri) => Some(iri),
+            _ => None,
+        }
+    }
+
+    fn as_literal(&self) -> Option<&str> {
+        match self {
+            Node::Literal(literal) => Some(literal),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Token {
+    Iri(String),
+    PrefixedName(String, String),
+    Blank(String),
+    Literal(String),
+    Punct(char),
+    A,
+    Prefix,
+    Base,
+}
+
+/// Split a Turtle document into tokens.
+fn tokenize(source: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut chars = source.chars().peekable();
+    // Whether the next IRI or name is the datatype of a literal.
+    let mut datatype = false;
+    while let Some(&c) = chars.peek() {
+        match c {
+            c if c.is_whitespace() => {
+                chars.next();
+            }
+            '#' => while chars.next().is_some_and(|c| c != '\n') {},
+            '<' => {
+                chars.next();
+                let iri: String = chars.by_ref().take_while(|c| *c != '>').collect();
+                if !std::mem::take(&mut datatype) {
+                    tokens.push(Token::Iri(iri));
+                }
+            }
+            '"' | '\'' => {
+                chars.next();
+                let long = chars.clone().take(2).filter(|next| *next == c).count() == 2;
+                if long {
+                    chars.next();
+                    chars.next();
+                }
+                let mut literal = String::new();
+                loop {
+                    match chars.next() {
+                        Some('\\') => match chars.next() {
+                            Some('n') => literal.push('\n'),
+                            Some('r') => literal.push('\r'),
+                            Some('t') => literal.push('\t'),
+                            Some(escaped) => literal.push(escaped),
+                            None => return Err("Unterminated string literal".to_owned()),
+                        },
+                        Some(next) if next == c => {
+                            if !long {
+                                break;
+                            }
+                            if chars.clone().take(2).filter(|next| *next == c).count() == 2 {
+                                chars.next();
+                                chars.next();
+                                break;
+                            }
+                            literal.push(next);
+                        }
+                        Some(next) => literal.push(next),
+                        None => return Err("Unterminated string literal".to_owned()),
+                    }
+                }
+                // Language tags and datatypes are ignored.
+                if chars.peek() == Some(&'@') {
+                    chars.next();
+                    while chars
+                        .peek()
+                        .is_some_and(|c| c.is_alphanumeric() || *c == '-')
+                    {
+                        chars.next();
+                    }
+                } else if chars.clone().take(2).filter(|next| *next == '^').count() == 2 {
+                    chars.next();
+                    chars.next();
+                    datatype = true;
+                }
+                tokens.push(Token::Literal(literal));
+            }
+            '.' | ';' | ',' | '[' | ']' | '(' | ')' => {
+                chars.next();
+                tokens.push(Token::Punct(c));
+            }
+            _ => {
+                let mut word = String::new();
+                while let Some(&c) = chars.peek() {
+                    if c.is_whitespace() || "<\"';,[]()#".contains(c) {
+                        break;
+                    }
+                    // A dot ends the word if it isn't followed by a name or a digit.
+                    if c == '.' {
+                        let mut ahead = chars.clone();
+                        ahead.next();
+                        if !ahead
+                            .peek()
+                            .is_some_and(|c| c.is_alphanumeric() || *c == '_')
+                        {
+                            break;
+                        }
+                    }
+                    word.push(c);
+                    chars.next();
+                }
+                let token = if word == "a" {
+                    Token::A
+                } else if word == "@prefix" || word.eq_ignore_ascii_case("prefix") {
+                    Token::Prefix
+                } else if word == "@base" || word.eq_ignore_ascii_case("base") {
+                    Token::Base
+                } else if let Some(label) = word.strip_prefix("_:") {
+                    Token::Blank(label.to_owned())
+                } else if word
+                    .starts_with(|c: char| c.is_ascii_digit() || c == '-' || c == '+' || c == '.')
+                    || word == "true"
+                    || word == "false"
+                {
+                    Token::Literal(word)
+                } else if let Some((prefix, local)) = word.split_once(':') {
+                    Token::PrefixedName(prefix.to_owned(), local.to_owned())
+                } else {
+                    return Err(format!("Unexpected token `{}`", word));
+                };
+                if !std::mem::take(&mut datatype) {
+                    tokens.push(token);
+                }
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+/// A set of triples.
+#[derive(Default)]
+pub struct Graph {
+    triples: Vec<(Node, String, Node)>,
+    blank_nodes: usize,
+}
+
+/// The state of parsing a single document.
+struct Parser<'a> {
+    graph: &'a mut Graph,
+    tokens: std::iter::Peekable<std::vec::IntoIter<Token>>,
+    prefixes: HashMap<String, String>,
+    labels: HashMap<String, usize>,
+    base: String,
+}
+
+impl<'a> Parser<'a> {
+    fn next(&mut self) -> Result<Token, String> {
+        self.tokens
+            .next()
+            .ok_or_else(|| "Unexpected end of document".to_owned())
+    }
+
+    fn expect(&mut self, punct: char) -> Result<(), String> {
+        match self.next()? {
+            Token::Punct(c) if c == punct => Ok(()),
+            token => Err(format!("Expected `{}`, found {:?}", punct, token)),
+        }
+    }
+
+    fn is_next(&mut self, punct: char) -> bool {
+        self.tokens.peek() == Some(&Token::Punct(punct))
+    }
+
+    fn resolve(&self, iri: String) -> String {
+        if iri.contains(':') {
+            iri
+        } else {
+            format!("{}{}", self.base, iri)
+        }
+    }
+
+    fn new_blank(&mut self) -> Node {
+        self.graph.blank_nodes += 1;
+        Node::Blank(self.graph.blank_nodes)
+    }
+
+    fn node(&mut self, token: Token) -> Result<Node, String> {
+        match token {
+            Token::Iri(iri) => Ok(Node::Iri(self.resolve(iri))),
+            Token::PrefixedName(prefix, local) => match self.prefixes.get(&prefix) {
+                Some(namespace) => Ok(Node::Iri(format!("{}{}", namespace, local))),
+                None => Err(format!("Unknown prefix `{}`", prefix)),
+            },
+            Token::Blank(label) => match self.labels.get(&label) {
+                Some(blank) => Ok(Node::Blank(*blank)),
+                None => {
+                    let blank = self.new_blank();
+                    if let Node::Blank(id) = blank {
+                        self.labels.insert(label, id);
+                    }
+                    Ok(blank)
+                }
+            },
+            Token::Literal(literal) => Ok(Node::Literal(literal)),
+            Token::Punct('[') => {
+                let blank = self.new_blank();
+                if !self.is_next(']') {
+                    self.predicate_object_list(&blank)?;
+                }
+                self.expect(']')?;
+                Ok(blank)
+            }
+            Token::Punct('(') => {
+                // The items of collections are not needed by the renderer.
+                while !self.is_next(')') {
+                    let token = self.next()?;
+                    self.node(token)?;
+                }
+                self.expect(')')?;
+                Ok(self.new_blank())
+            }
+            token => Err(format!("Unexpected {:?}", token)),
+        }
+    }
+
+    fn predicate_object_list(&mut self, subject: &Node) -> Result<(), String> {
+        loop {
+            let predicate = match self.next()? {
+                Token::A => RDF_TYPE.to_owned(),
+                token => match self.node(token)? {
+                    Node::Iri(iri) => iri,
+                    node => return Err(format!("Invalid predicate {:?}", node)),
+                },
+            };
+            loop {
+                let token = self.next()?;
+                let object = self.node(token)?;
+                self.graph
+                    .triples
+                    .push((subject.clone(), predicate.clone(), object));
+                if !self.is_next(',') {
+                    break;
+                }
+                self.next()?;
+            }
+            if !self.is_next(';') {
+                return Ok(());
+            }
+            while self.is_next(';') {
+                self.next()?;
+            }
+            if self.is_next('.') || self.is_next(']') {
+                return Ok(());
+            }
+        }
+    }
+
+    fn parse(&mut self) -> Result<(), String> {
+        while let Some(token) = self.tokens.next() {
+            match token {
+                Token::Prefix => {
+                    let prefix = match self.next()? {
+                        Token::PrefixedName(prefix, local) if local.is_empty() => prefix,
+                        token => return Err(format!("Invalid prefix {:?}", token)),
+                    };
+                    let namespace = match self.next()? {
+                        Token::Iri(iri) => self.resolve(iri),
+                        token => return Err(format!("Invalid namespace {:?}", token)),
+                    };
+                    self.prefixes.insert(prefix, namespace);
+                    if self.is_next('.') {
+                        self.next()?;
+                    }
+                }
+                Token::Base => {
+                    self.base = match self.next()? {
+                        Token::Iri(iri) => self.resolve(iri),
+                        token => return Err(format!("Invalid base {:?}", token)),
+                    };
+                    if self.is_next('.') {
+                        self.next()?;
+                    }
+                }
+                token => {
+                    let blank_subject = token == Token::Punct('[');
+                    let subject = self.node(token)?;
+                    if !(blank_subject && self.is_next('.')) {
+                        self.predicate_object_list(&subject)?;
+                    }
+                    self.expect('.')?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Graph {
+    /// Parse a document and add it's triples to the graph.
+    ///
+    /// Relative IRIs are resolved against the base, which should end with a slash.
+    pub fn parse(&mut self, source: &str, base: &str) -> Result<(), String> {
+        Parser {
+            tokens: tokenize(source)?.into_iter().peekable(),
+            graph: self,
+            prefixes: HashMap::new(),
+            labels: HashMap::new(),
+            base: base.to_owned(),
+        }
+        .parse()
+    }
+
+    /// Iterate over all objects of a subject and a predicate.
+    pub fn objects<'a: 'b, 'b>(
+        &'a self,
+        subject: &'b Node,
+        predicate: &'b str,
+    ) -> impl Iterator<Item = &'a Node> + 'b {
+        self.triples
+            .iter()
+            .filter(move |(s, p, _)| s == subject && p == predicate)
+            .map(|(_, _, object)| object)
+    }
+
+    /// Iterate over all subjects with the given type.
+    pub fn instances<'a>(&'a self, class: &'a str) -> impl Iterator<Item = &'a Node> + 'a {
+        self.triples
+            .iter()
+            .filter(move |(_, p, o)| p == RDF_TYPE && o.as_iri() == Some(class))
+            .map(|(subject, _, _)| subject)
+    }
+
+    fn has_type(&self, subject: &Node, class: &str) -> bool {
+        self.objects(subject, RDF_TYPE)
+            .any(|object| object.as_iri() == Some(class))
+    }
+}
+
+/// The kind of data a port carries.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PortKind {
+    Audio,
+    Control,
+    CV,
+    Atom,
+}
+
+/// The description of a port.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortInfo {
+    pub index: u32,
+    pub symbol: String,
+    pub input: bool,
+    pub kind: Option<PortKind>,
+    pub default: Option<f32>,
+    pub optional: bool,
+}
+
+/// The description of a plugin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PluginInfo {
+    pub uri: String,
+    pub binary: PathBuf,
+    pub ports: Vec<PortInfo>,
+}
+
+impl PluginInfo {
+    /// Read the description of a plugin from the graph.
+    fn from_graph(graph: &Graph, uri: &str) -> Result<Self, String> {
+        let subject = Node::Iri(uri.to_owned());
+        let binary_predicate = format!("{}binary", LV2);
+        let binary = graph
+            .objects(&subject, &binary_predicate)
+            .find_map(Node::as_iri)
+            .and_then(|binary| binary.strip_prefix("file://"))
+            .ok_or_else(|| format!("The plugin <{}> has no binary", uri))?;
+
+        let mut ports = Vec::new();
+        for port in graph.objects(&subject, &format!("{}port", LV2)) {
+            let value = |predicate: &str| -> Option<String> {
+                let predicate = format!("{}{}", LV2, predicate);
+                let value = graph.objects(port, &predicate).find_map(Node::as_literal);
+                value.map(str::to_owned)
+            };
+            let index = value("index")
+                .and_then(|index| index.parse().ok())
+                .ok_or_else(|| format!("A port of <{}> has no valid index", uri))?;
+            let symbol = value("symbol")
+                .ok_or_else(|| format!("The port {} of <{}> has no symbol", index, uri))?;
+            let is = |class: &str| graph.has_type(port, class);
+            let kind = if is(&format!("{}AudioPort", LV2)) {
+                Some(PortKind::Audio)
+            } else if is(&format!("{}ControlPort", LV2)) {
+                Some(PortKind::Control)
+            } else if is(&format!("{}CVPort", LV2)) {
+                Some(PortKind::CV)
+            } else if is(ATOM_PORT) {
+                Some(PortKind::Atom)
+            } else {
+                None
+            };
+            let optional = graph
+                .objects(port, &format!("{}portProperty", LV2))
+                .any(|property| property.as_iri() == Some(&format!("{}connectionOptional", LV2)));
+            ports.push(PortInfo {
+                index,
+                symbol,
+                input: is(&format!("{}InputPort", LV2)),
+                kind,
+                default: value("default").and_then(|default| default.parse().ok()),
+                optional,
+            });
+        }
+        ports.sort_by_key(|port| port.index);
+
+        Ok(Self {
+            uri: uri.to_owned(),
+            binary: PathBuf::from(binary),
+            ports,
+        })
+    }
+
+    /// Load the description of a plugin from a bundle.
+    ///
+    /// If no URI is given, the bundle has to contain exactly one plugin.
+    pub fn load(bundle: &Path, uri: Option<&str>) -> Result<Self, String> {
+        let bundle = bundle
+            .canonicalize()
+            .map_err(|err| format!("Can't open bundle {}: {}", bundle.display(), err))?;
+        let base = format!("file://{}/", bundle.display());
+
+        let mut graph = Graph::default();
+        let read = |graph: &mut Graph, path: &Path| -> Result<(), String> {
+            let source = std::fs::read_to_string(path)
+                .map_err(|err| format!("Can't read {}: {}", path.display(), err))?;
+            graph
+                .parse(&source, &base)
+                .map_err(|err| format!("Can't parse {}: {}", path.display(), err))
+        };
+        read(&mut graph, &bundle.join("manifest.ttl"))?;
+
+        let plugin_class = format!("{}Plugin", LV2);
+        let plugins: Vec<String> = graph
+            .instances(&plugin_class)
+            .filter_map(Node::as_iri)
+            .map(str::to_owned)
+            .collect();
+        let uri = match uri {
+            Some(uri) if plugins.iter().any(|plugin| plugin == uri) => uri.to_owned(),
+            Some(uri) => return Err(format!("The bundle doesn't contain the plugin <{}>", uri)),
+            None if plugins.len() == 1 => plugins[0].clone(),
+            None => {
+                return Err(format!(
+                    "The bundle contains {} plugins, please choose one of: {}",
+                    plugins.len(),
+                    plugins.join(", ")
+                ))
+            }
+        };
+
+        let see_also: Vec<PathBuf> = graph
+            .objects(&Node::Iri(uri.clone()), RDFS_SEE_ALSO)
+            .filter_map(Node::as_iri)
+            .filter_map(|file| file.strip_prefix("file://"))
+            .map(PathBuf::from)
+            .collect();
+        for file in see_also {
+            read(&mut graph, &file)?;
+        }
+
+        Self::from_graph(&graph, &uri)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DESCRIPTION: &str = r#"
+@prefix doap: <http://usefulinc.com/ns/doap#> .
+@prefix lv2:  <http://lv2plug.in/ns/lv2core#> .
+@prefix atom: <http://lv2plug.in/ns/ext/atom#> .
+@prefix rdf:  <http://www.w3.org/1999/02/22-rdf-syntax-ns#> .
+@prefix rdfs: <http://www.w3.org/2000/01/rdf-schema#> .
+
+<urn:amp>
+    a lv2:Plugin , lv2:AmplifierPlugin ;
+    lv2:binary <libamp.so> ;
+    doap:name "Simple \"Amp\"" , """Long
+name"""@en ;
+    lv2:port [
+        a lv2:InputPort , lv2:ControlPort ;
+        lv2:index 0 ;
+        lv2:symbol "gain" ;
+        lv2:default -3.5 ;
+        lv2:scalePoint [ rdfs:label "zero" ; rdf:value 0.0 ] ;
+    ] , [
+        a lv2:OutputPort , lv2:AudioPort ;
+        lv2:index 2 ;
+        lv2:symbol "out"
+    ] , [
+        a lv2:InputPort ,
+            atom:AtomPort ;
+        lv2:index 1 ;
+        lv2:symbol "midi" ;
+        lv2:portProperty lv2:connectionOptional
+    ] . # A comment.
+"#;
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            vec![
+                Token::Iri("urn:a".to_owned()),
+                Token::A,
+                Token::PrefixedName("lv2".to_owned(), "Plugin".to_owned()),
+                Token::Punct(';'),
+                Token::PrefixedName("lv2".to_owned(), "index".to_owned()),
+                Token::Literal("1.5e3".to_owned()),
+                Token::Punct(','),
+                Token::Literal("x".to_owned()),
+                Token::Punct('.'),
+            ],
+            tokenize("<urn:a> a lv2:Plugin ; lv2:index 1.5e3 , \"x\"^^xsd:string .").unwrap()
+        );
+    }
+
+    #[test]
+    fn test_plugin_info() {
+        let mut graph = Graph::default();
+        graph.parse(DESCRIPTION, "file:///bundle/").unwrap();
+        let info = PluginInfo::from_graph(&graph, "urn:amp").unwrap();
+
+        assert_eq!(PathBuf::from("/bundle/libamp.so"), info.binary);
+        assert_eq!(
+            vec![
+                PortInfo {
+                    index: 0,
+                    symbol: "gain".to_owned(),
+                    input: true,
+                    kind: Some(PortKind::Control),
+                    default: Some(-3.5),
+                    optional: false,
+                },
+                PortInfo {
+                    index: 1,
+                    symbol: "midi".to_owned(),
+                    input: true,
+                    kind: Some(PortKind::Atom),
+                    default: None,
+                    optional: true,
+                },
+                PortInfo {
+                    index: 2,
+                    symbol: "out".to_owned(),
+                    input: false,
+                    kind: Some(PortKind::Audio),
+                    default: None,
+                    optional: false,
+                },
+            ],
+            info.ports
+        );
+
+        let plugin = Node::Iri("urn:amp".to_owned());
+        let names: Vec<&Node> = graph
+            .objects(&plugin, "http://usefulinc.com/ns/doap#name")
+            .collect();
+        assert_eq!(
+            vec![
+                &Node::Literal("Simple \"Amp\"".to_owned()),
+                &Node::Literal("Long\nname".to_owned())
+            ],
+            names
+        );
+    }
+
+    #[test]
+    fn test_unknown_prefix() {
+        let mut graph = Graph::default();
+        assert!(graph.parse("<urn:a> a foo:Bar .", "file:///").is_err());
+    }
+}
@@ -0,0 +1,372 @@
+mod bundle;
+mod midi;
+
+use bundle::{PluginInfo, PortKind};
+use clap::*;
+use lv2_host_test::{HostSettings, TestHost};
+use midi::{MidiEvent, SequenceURIDs};
+use std::path::{Path, PathBuf};
+use urid::*;
+
+/// The options of a rendering run.
+struct RenderOptions<'a> {
+    bundle: &'a Path,
+    plugin: Option<&'a str>,
+    input: Option<&'a Path>,
+    output: &'a Path,
+    midi: Option<&'a Path>,
+    controls: Vec<(&'a str, f32)>,
+    sample_rate: u32,
+    block_length: u32,
+    tail: f64,
+}
+
+/// Parse a `symbol=value` control assignment.
+fn parse_control(control: &str) -> Result<(&str, f32), String> {
+    let (symbol, value) = control
+        .split_once('=')
+        .ok_or_else(|| format!("Invalid control `{}`, expected `symbol=value`", control))?;
+    let value = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid value of control `{}`", symbol))?;
+    Ok((symbol.trim(), value))
+}
+
+/// Read all channels of a WAV file as floating-point samples.
+fn read_wav(path: &Path) -> Result<(hound::WavSpec, Vec<Vec<f32>>), String> {
+    let error = |err: hound::Error| format!("Can't read {}: {}", path.display(), err);
+    let mut reader = hound::WavReader::open(path).map_err(error)?;
+    let spec = reader.spec();
+    let samples: Vec<f32> = match spec.sample_format {
+        hound::SampleFormat::Float => reader.samples::<f32>().collect::<Result<_, _>>(),
+        hound::SampleFormat::Int => {
+            let scale = (1u64 << (spec.bits_per_sample - 1)) as f32;
+            reader
+                .samples::<i32>()
+                .map(|sample| sample.map(|sample| sample as f32 / scale))
+                .collect()
+        }
+    }
+    .map_err(error)?;
+
+    let channels = usize::from(spec.channels);
+    let channels = (0..channels)
+        .map(|channel| {
+            samples
+                .iter()
+                .skip(channel)
+                .step_by(channels)
+                .copied()
+                .collect()
+        })
+        .collect();
+    Ok((spec, channels))
+}
+
+/// Write the channels to a 32-bit floating-point WAV file.
+fn write_wav(path: &Path, sample_rate: u32, channels: &[Vec<f32>]) -> Result<(), String> {
+    let error = |err: hound::Error| format!("Can't write {}: {}", path.display(), err);
+    let spec = hound::WavSpec {
+        channels: channels.len() as u16,
+        sample_rate,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let mut writer = hound::WavWriter::create(path, spec).map_err(error)?;
+    let frames = channels.first().map_or(0, Vec::len);
+    for frame in 0..frames {
+        for channel in channels {
+            writer.write_sample(channel[frame]).map_err(error)?;
+        }
+    }
+    writer.finalize().map_err(error)
+}
+
+/// Map a null-terminated URI with the host's mapper.
+fn map(host: &TestHost, uri: &[u8]) -> u32 {
+    host.mapper()
+        .map_uri(Uri::from_bytes_with_nul(uri).unwrap())
+        .unwrap()
+        .get()
+}
+
+/// Render the input files with the plugin and write the output file.
+fn render(options: &RenderOptions) -> Result<(), String> {
+    let info = PluginInfo::load(options.bundle, options.plugin)?;
+
+    let (sample_rate, inputs) = match options.input {
+        Some(input) => {
+            let (spec, channels) = read_wav(input)?;
+            (spec.sample_rate, channels)
+        }
+        None => (options.sample_rate, Vec::new()),
+    };
+    let events = match options.midi {
+        Some(midi) => midi::read_file(midi, f64::from(sample_rate))?,
+        None => Vec::new(),
+    };
+    let length = inputs
+        .first()
+        .map_or(0, Vec::len)
+        .max(events.last().map_or(0, |event| event.frame as usize + 1))
+        + (options.tail * f64::from(sample_rate)) as usize;
+    if length == 0 {
+        return Err("There is nothing to render, please provide an input or a tail".to_owned());
+    }
+
+    // The library has to outlive the host, since the descriptor is borrowed from it.
+    let library = unsafe { libloading::Library::new(&info.binary) }
+        .map_err(|err| format!("Can't load {}: {}", info.binary.display(), err))?;
+    let lv2_descriptor = unsafe {
+        library
+            .get::<unsafe extern "C" fn(u32) -> *const lv2_sys::LV2_Descriptor>(b"lv2_descriptor\0")
+    }
+    .map_err(|err| format!("Can't find lv2_descriptor: {}", err))?;
+
+    let settings = HostSettings {
+        bundle_path: options.bundle.to_owned(),
+        sample_rate: f64::from(sample_rate),
+        max_block_length: options.block_length,
+        ..Default::default()
+    };
+    let uri = UriBuf::new(info.uri.as_str()).map_err(|err| err.to_string())?;
+    let mut host = unsafe { TestHost::from_library(*lv2_descriptor, &uri, settings) }
+        .ok_or_else(|| format!("Can't instantiate <{}>", info.uri))?;
+
+    for (symbol, _) in options.controls.iter() {
+        if !info.ports.iter().any(|port| {
+            port.symbol == *symbol && port.input && port.kind == Some(PortKind::Control)
+        }) {
+            return Err(format!("The plugin has no control input `{}`", symbol));
+        }
+    }
+
+    let mut audio_inputs = Vec::new();
+    let mut audio_outputs = Vec::new();
+    let mut midi_input = None;
+    for port in info.ports.iter() {
+        match (port.kind, port.input) {
+            (Some(PortKind::Audio), true) => {
+                host.connect_audio(port.index);
+                audio_inputs.push(port.index);
+            }
+            (Some(PortKind::Audio), false) => {
+                host.connect_audio(port.index);
+                audio_outputs.push(port.index);
+            }
+            (Some(PortKind::CV), _) => {
+                host.connect_audio(port.index);
+            }
+            (Some(PortKind::Control), true) => {
+                let value = options
+                    .controls
+                    .iter()
+                    .rev()
+                    .find(|(symbol, _)| *symbol == port.symbol)
+                    .map(|(_, value)| *value);
+                host.set_control(port.index, value.or(port.default).unwrap_or(0.0));
+            }
+            (Some(PortKind::Control), false) => host.set_control(port.index, 0.0),
+            (Some(PortKind::Atom), true) => {
+                host.connect_atom_input(port.index);
+                midi_input.get_or_insert(port.index);
+            }
+            (Some(PortKind::Atom), false) => host.connect_atom_output(port.index),
+            (None, _) if port.optional => (),
+            (None, _) => {
+                return Err(format!(
+                    "The port `{}` has an unsupported type",
+                    port.symbol
+                ))
+            }
+        }
+    }
+    if options.midi.is_some() && midi_input.is_none() {
+        return Err("The plugin has no atom input for MIDI events".to_owned());
+    }
+    if !inputs.is_empty() && audio_inputs.is_empty() {
+        return Err("The plugin has no audio inputs".to_owned());
+    }
+
+    let urids = SequenceURIDs {
+        sequence: map(&host, lv2_sys::LV2_ATOM__Sequence),
+        frame: map(&host, lv2_sys::LV2_UNITS__frame),
+        midi_event: map(&host, lv2_sys::LV2_MIDI__MidiEvent),
+    };
+
+    let mut outputs: Vec<Vec<f32>> = vec![Vec::with_capacity(length); audio_outputs.len()];
+    let mut next_event = 0;
+    let mut dropped = 0;
+    host.activate();
+    for start in (0..length).step_by(options.block_length as usize) {
+        let frames = (length - start).min(options.block_length as usize);
+
+        // A mono file is fed into all audio inputs, otherwise every channel is fed into one input.
+        for (i, index) in audio_inputs.iter().enumerate() {
+            let buffer = &mut host.audio_mut(*index).unwrap()[..frames];
+            let channel = match inputs.len() {
+                1 => inputs.first(),
+                _ => inputs.get(i),
+            };
+            match channel {
+                Some(channel) => {
+                    for (frame, sample) in buffer.iter_mut().enumerate() {
+                        *sample = channel.get(start + frame).copied().unwrap_or(0.0);
+                    }
+                }
+                None => buffer.fill(0.0),
+            }
+        }
+
+        if let Some(index) = midi_input {
+            let first_event = next_event;
+            while events
+                .get(next_event)
+                .is_some_and(|event| event.frame < (start + frames) as u64)
+            {
+                next_event += 1;
+            }
+            let block_events: Vec<MidiEvent> = events[first_event..next_event]
+                .iter()
+                .map(|event| MidiEvent {
+                    frame: event.frame - start as u64,
+                    data: event.data.clone(),
+                })
+                .collect();
+            dropped += midi::write_sequence(host.atom_mut(index).unwrap(), urids, &block_events);
+        }
+
+        host.run(frames as u32);
+        host.work();
+
+        for (output, index) in outputs.iter_mut().zip(audio_outputs.iter()) {
+            output.extend_from_slice(&host.audio(*index).unwrap()[..frames]);
+        }
+        for message in host.take_log_messages() {
+            let entry = host
+                .mapper()
+                .unmap(message.entry)
+                .map(|entry| entry.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            eprintln!(
+                "[{}] {}",
+                entry.rsplit('#').next().unwrap_or(""),
+                message.message.trim_end()
+            );
+        }
+    }
+    if dropped > 0 {
+        eprintln!(
+            "Warning: {} MIDI events didn't fit into the sequence buffers",
+            dropped
+        );
+    }
+
+    write_wav(options.output, sample_rate, &outputs)
+}
+
+fn main() {
+    let matches = Command::new("lv2render")
+        .about("Render audio files offline with compiled LV2 plugins")
+        .version("0.1.0")
+        .arg(
+            Arg::new("bundle")
+                .help("The path to the plugin bundle")
+                .value_name("BUNDLE")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("plugin")
+                .help("The URI of the plugin, required if the bundle contains several plugins")
+                .short('p')
+                .long("plugin")
+                .value_name("URI"),
+        )
+        .arg(
+            Arg::new("input")
+                .help("The WAV file whose channels are fed into the audio inputs")
+                .short('i')
+                .long("input")
+                .value_name("WAV")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("output")
+                .help("The WAV file to write the audio outputs to")
+                .short('o')
+                .long("output")
+                .value_name("WAV")
+                .required(true)
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("midi")
+                .help("The MIDI file whose events are fed into the first atom input")
+                .short('m')
+                .long("midi")
+                .value_name("MIDI")
+                .value_parser(value_parser!(PathBuf)),
+        )
+        .arg(
+            Arg::new("control")
+                .help("Set a control input, for example `gain=-6`")
+                .short('c')
+                .long("control")
+                .value_name("SYMBOL=VALUE")
+                .action(ArgAction::Append),
+        )
+        .arg(
+            Arg::new("sample-rate")
+                .help("The sample rate if there is no input file")
+                .short('r')
+                .long("sample-rate")
+                .value_name("HZ")
+                .default_value("48000")
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            Arg::new("block-length")
+                .help("The maximal number of frames per block")
+                .short('b')
+                .long("block-length")
+                .value_name("FRAMES")
+                .default_value("512")
+                .value_parser(value_parser!(u32).range(1..)),
+        )
+        .arg(
+            Arg::new("tail")
+                .help("Additional seconds to render after the end of the input")
+                .short('t')
+                .long("tail")
+                .value_name("SECONDS")
+                .default_value("0")
+                .value_parser(value_parser!(f64)),
+        )
+        .get_matches();
+
+    let controls: Result<Vec<(&str, f32)>, String> = matches
+        .get_many::<String>("control")
+        .unwrap_or_default()
+        .map(|control| parse_control(control))
+        .collect();
+    let result = controls.and_then(|controls| {
+        render(&RenderOptions {
+            bundle: matches.get_one::<PathBuf>("bundle").unwrap(),
+            plugin: matches.get_one::<String>("plugin").map(String::as_str),
+            input: matches.get_one::<PathBuf>("input").map(PathBuf::as_path),
+            output: matches.get_one::<PathBuf>("output").unwrap(),
+            midi: matches.get_one::<PathBuf>("midi").map(PathBuf::as_path),
+            controls,
+            sample_rate: *matches.get_one::<u32>("sample-rate").unwrap(),
+            block_length: *matches.get_one::<u32>("block-length").unwrap(),
+            tail: matches.get_one::<f64>("tail").unwrap().max(0.0),
+        })
+    });
+
+    if let Err(err) = result {
+        eprintln!("Error: {}", err);
+        std::process::exit(1);
+    }
+}
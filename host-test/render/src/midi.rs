@@ -0,0 +1,194 @@
+//! Conversion of MIDI files to atom sequences.
+use midly::{MetaMessage, Smf, Timing, TrackEventKind};
+use std::path::Path;
+
+/// A MIDI message with it's time stamp in frames.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MidiEvent {
+    pub frame: u64,
+    pub data: Vec<u8>,
+}
+
+/// Read all channel messages of a MIDI file and convert their time stamps to frames.
+///
+/// The events of all tracks are merged and sorted by time. Tempo changes are respected for files with metrical timing.
+pub fn read_file(path: &Path, sample_rate: f64) -> Result<Vec<MidiEvent>, String> {
+    let bytes =
+        std::fs::read(path).map_err(|err| format!("Can't read {}: {}", path.display(), err))?;
+    let smf =
+        Smf::parse(&bytes).map_err(|err| format!("Can't parse {}: {}", path.display(), err))?;
+    Ok(convert(&smf, sample_rate))
+}
+
+/// Convert the events of a parsed MIDI file.
+fn convert(smf: &Smf, sample_rate: f64) -> Vec<MidiEvent> {
+    // Collect all events with their absolute tick. The sort is stable, so the order of events with the same tick is kept.
+    let mut events: Vec<(u64, &TrackEventKind)> = Vec::new();
+    for track in smf.tracks.iter() {
+        let mut tick: u64 = 0;
+        for event in track.iter() {
+            tick += u64::from(event.delta.as_int());
+            events.push((tick, &event.kind));
+        }
+    }
+    events.sort_by_key(|(tick, _)| *tick);
+
+    // Microseconds per quarter note, 120 BPM by default.
+    let mut tempo: f64 = 500_000.0;
+    let mut last_tick: u64 = 0;
+    let mut seconds: f64 = 0.0;
+    let mut converted = Vec::new();
+    for (tick, kind) in events {
+        seconds += match smf.header.timing {
+            Timing::Metrical(ppq) => {
+                (tick - last_tick) as f64 * tempo / (f64::from(ppq.as_int()) * 1_000_000.0)
+            }
+            Timing::Timecode(fps, subframes) => {
+                (tick - last_tick) as f64 / (f64::from(fps.as_f32()) * f64::from(subframes))
+            }
+        };
+        last_tick = tick;
+
+        match kind {
+            TrackEventKind::Meta(MetaMessage::Tempo(new_tempo)) => {
+                tempo = f64::from(new_tempo.as_int());
+            }
+            TrackEventKind::Midi { .. } => {
+                let mut data = Vec::new();
+                if let Some(event) = kind.as_live_event() {
+                    if event.write_std(&mut data).is_ok() {
+                        converted.push(MidiEvent {
+                            frame: (seconds * sample_rate).round() as u64,
+                            data,
+                        });
+                    }
+                }
+            }
+            _ => (),
+        }
+    }
+    converted
+}
+
+/// The URIDs needed to write a sequence.
+#[derive(Clone, Copy)]
+pub struct SequenceURIDs {
+    pub sequence: u32,
+    pub frame: u32,
+    pub midi_event: u32,
+}
+
+fn write_u32(buffer: &mut [u8], offset: usize, value: u32) {
+    buffer[offset..offset + 4].copy_from_slice(&value.to_ne_bytes());
+}
+
+/// Write MIDI events into an atom sequence.
+///
+/// The time stamps of the events are relative to the start of the buffer. Events that don't fit into the buffer are dropped and their number is returned.
+pub fn write_sequence(buffer: &mut [u8], urids: SequenceURIDs, events: &[MidiEvent]) -> usize {
+    // The atom header and the sequence body header.
+    let mut offset = 16;
+    let mut dropped = 0;
+    for event in events {
+        let size = 16 + ((event.data.len() + 7) & !7);
+        if offset + size > buffer.len() {
+            dropped += 1;
+            continue;
+        }
+        buffer[offset..offset + 8].copy_from_slice(&(event.frame as i64).to_ne_bytes());
+        write_u32(buffer, offset + 8, event.data.len() as u32);
+        write_u32(buffer, offset + 12, urids.midi_event);
+        buffer[offset + 16..offset + 16 + event.data.len()].copy_from_slice(&event.data);
+        buffer[offset + 16 + event.data.len()..offset + size].fill(0);
+        offset += size;
+    }
+    write_u32(buffer, 0, (offset - 8) as u32);
+    write_u32(buffer, 4, urids.sequence);
+    write_u32(buffer, 8, urids.frame);
+    write_u32(buffer, 12, 0);
+    dropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use midly::num::{u15, u24, u28, u4, u7};
+    use midly::{Format, Header, MidiMessage, TrackEvent};
+
+    fn note_on(delta: u32, key: u8) -> TrackEvent<'static> {
+        TrackEvent {
+            delta: u28::new(delta),
+            kind: TrackEventKind::Midi {
+                channel: u4::new(1),
+                message: MidiMessage::NoteOn {
+                    key: u7::new(key),
+                    vel: u7::new(100),
+                },
+            },
+        }
+    }
+
+    #[test]
+    fn test_convert() {
+        let mut smf = Smf::new(Header::new(Format::Parallel, Timing::Metrical(u15::new(4))));
+        smf.tracks.push(vec![
+            note_on(0, 60),
+            TrackEvent {
+                delta: u28::new(4),
+                kind: TrackEventKind::Meta(MetaMessage::Tempo(u24::new(1_000_000))),
+            },
+            note_on(4, 62),
+        ]);
+        smf.tracks.push(vec![note_on(2, 64)]);
+
+        // One quarter note lasts half a second, and one second after the tempo change.
+        assert_eq!(
+            vec![
+                MidiEvent {
+                    frame: 0,
+                    data: vec![0x91, 60, 100],
+                },
+                MidiEvent {
+                    frame: 250,
+                    data: vec![0x91, 64, 100],
+                },
+                MidiEvent {
+                    frame: 1500,
+                    data: vec![0x91, 62, 100],
+                },
+            ],
+            convert(&smf, 1000.0)
+        );
+    }
+
+    #[test]
+    fn test_write_sequence() {
+        let urids = SequenceURIDs {
+            sequence: 1,
+            frame: 2,
+            midi_event: 3,
+        };
+        let events = [
+            MidiEvent {
+                frame: 5,
+                data: vec![0x90, 60, 100],
+            },
+            MidiEvent {
+                frame: 7,
+                data: vec![0x80, 60, 0],
+            },
+        ];
+        let mut buffer = vec![0xffu8; 48];
+        assert_eq!(1, write_sequence(&mut buffer, urids, &events));
+
+        let read_u32 =
+            |offset: usize| u32::from_ne_bytes(buffer[offset..offset + 4].try_into().unwrap());
+        assert_eq!(32, read_u32(0));
+        assert_eq!(1, read_u32(4));
+        assert_eq!(2, read_u32(8));
+        assert_eq!(5, i64::from_ne_bytes(buffer[16..24].try_into().unwrap()));
+        assert_eq!(3, read_u32(24));
+        assert_eq!(3, read_u32(28));
+        assert_eq!(&[0x90, 60, 100, 0, 0, 0, 0, 0], &buffer[32..40]);
+    }
+}
@@ -0,0 +1,91 @@
+//! A plugin rendered by the tests of `lv2render`.
+//!
+//! The plugin amplifies it's audio input and adds a constant level while a note is held. The level is the velocity of the note, divided by 128, and changes at the exact frame of the MIDI event.
+use lv2_atom::prelude::*;
+use lv2_core::prelude::*;
+use lv2_midi::prelude::*;
+use lv2_units::prelude::*;
+use lv2_urid::*;
+use urid::*;
+
+#[derive(PortCollection)]
+pub struct Ports {
+    #[lv2(name = "Gain", default = 0.0, min = -90.0, max = 24.0)]
+    gain: InputPort<Control>,
+    #[lv2(symbol = "in", name = "In")]
+    input: InputPort<Audio>,
+    #[lv2(symbol = "out", name = "Out")]
+    output: OutputPort<Audio>,
+    #[lv2(name = "MIDI")]
+    midi: InputPort<AtomPort>,
+}
+
+#[derive(FeatureCollection)]
+pub struct Features<'a> {
+    map: LV2Map<'a>,
+}
+
+#[derive(URIDCollection)]
+pub struct URIDs {
+    atom: AtomURIDCollection,
+    midi: MidiURIDCollection,
+    unit: UnitURIDCollection,
+}
+
+#[uri("urn:rust-lv2:lv2render-fixture")]
+#[derive(PluginMetadata)]
+#[lv2(name = "lv2render fixture")]
+pub struct Fixture {
+    urids: URIDs,
+    level: f32,
+}
+
+impl Plugin for Fixture {
+    type Ports = Ports;
+    type InitFeatures = Features<'static>;
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+        Some(Self {
+            urids: features.map.populate_collection()?,
+            level: 0.0,
+        })
+    }
+
+    fn run(&mut self, ports: &mut Ports, _: &mut (), sample_count: u32) {
+        let coef = if *(ports.gain) > -90.0 {
+            10.0_f32.powf(*(ports.gain) * 0.05)
+        } else {
+            0.0
+        };
+
+        let mut events = ports
+            .midi
+            .read(self.urids.atom.sequence, self.urids.unit.beat)
+            .into_iter()
+            .flatten()
+            .filter_map(|(timestamp, atom)| {
+                let frame = timestamp.as_frames()?;
+                let message = atom.read(self.urids.midi.raw, ())?;
+                Some((frame, message))
+            })
+            .peekable();
+
+        for frame in 0..sample_count as usize {
+            while let Some((_, message)) = events.next_if(|(time, _)| *time <= frame as i64) {
+                match message {
+                    [status, _, velocity] if status & 0xf0 == 0x90 && *velocity > 0 => {
+                        self.level = f32::from(*velocity) / 128.0;
+                    }
+                    [status, _, _] if status & 0xf0 == 0x80 || status & 0xf0 == 0x90 => {
+                        self.level = 0.0;
+                    }
+                    _ => (),
+                }
+            }
+            ports.output[frame] = ports.input[frame] * coef + self.level;
+        }
+    }
+}
+
+lv2_descriptors!(Fixture);
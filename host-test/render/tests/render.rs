@@ -0,0 +1,170 @@
+use lv2_core::turtle::{binary_name, write_bundle};
+use lv2render_fixture::Fixture;
+use midly::num::{u15, u28, u4, u7};
+use midly::{Format, Header, MidiMessage, Smf, Timing, TrackEvent, TrackEventKind};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::OnceLock;
+
+const SAMPLE_RATE: u32 = 48000;
+const LENGTH: usize = 1200;
+
+/// Write a bundle with the description of the fixture plugin and copy it's library into it.
+///
+/// The bundle is only written once, since the tests run concurrently.
+fn fixture_bundle() -> &'static Path {
+    static BUNDLE: OnceLock<PathBuf> = OnceLock::new();
+    BUNDLE.get_or_init(|| {
+        let bundle = Path::new(env!("CARGO_TARGET_TMPDIR")).join("fixture.lv2");
+        let binary = binary_name("lv2render_fixture");
+        write_bundle::<Fixture>(&bundle, &binary, "fixture.ttl").unwrap();
+
+        // The library is built as a dependency of this test and is placed next to it.
+        let library = std::env::current_exe()
+            .unwrap()
+            .parent()
+            .unwrap()
+            .join(&binary);
+        std::fs::copy(library, bundle.join(&binary)).unwrap();
+        bundle
+    })
+}
+
+fn write_input(path: &Path) -> Vec<f32> {
+    let spec = hound::WavSpec {
+        channels: 1,
+        sample_rate: SAMPLE_RATE,
+        bits_per_sample: 32,
+        sample_format: hound::SampleFormat::Float,
+    };
+    let samples: Vec<f32> = (0..LENGTH)
+        .map(|frame| (frame as f32 * 440.0 * std::f32::consts::TAU / SAMPLE_RATE as f32).sin())
+        .collect();
+    let mut writer = hound::WavWriter::create(path, spec).unwrap();
+    for sample in samples.iter() {
+        writer.write_sample(*sample).unwrap();
+    }
+    writer.finalize().unwrap();
+    samples
+}
+
+fn note(delta: u32, message: MidiMessage) -> TrackEvent<'static> {
+    TrackEvent {
+        delta: u28::new(delta),
+        kind: TrackEventKind::Midi {
+            channel: u4::new(0),
+            message,
+        },
+    }
+}
+
+/// Write a MIDI file with a single note from frame 500 to frame 1000.
+fn write_midi(path: &Path) {
+    // With 480 ticks per quarter note and 120 BPM, a tick lasts 50 frames.
+    let mut smf = Smf::new(Header::new(
+        Format::SingleTrack,
+        Timing::Metrical(u15::new(480)),
+    ));
+    smf.tracks.push(vec![
+        note(
+            10,
+            MidiMessage::NoteOn {
+                key: u7::new(60),
+                vel: u7::new(64),
+            },
+        ),
+        note(
+            10,
+            MidiMessage::NoteOff {
+                key: u7::new(60),
+                vel: u7::new(0),
+            },
+        ),
+    ]);
+    smf.save(path).unwrap();
+}
+
+fn render(bundle: &Path, output: &Path, args: &[&std::ffi::OsStr]) -> Vec<f32> {
+    let result = Command::new(env!("CARGO_BIN_EXE_lv2render"))
+        .arg(bundle)
+        .arg("-o")
+        .arg(output)
+        .args(args)
+        .output()
+        .unwrap();
+    assert!(
+        result.status.success(),
+        "{}",
+        String::from_utf8_lossy(&result.stderr)
+    );
+
+    let mut reader = hound::WavReader::open(output).unwrap();
+    assert_eq!(1, reader.spec().channels);
+    assert_eq!(SAMPLE_RATE, reader.spec().sample_rate);
+    reader.samples::<f32>().map(Result::unwrap).collect()
+}
+
+#[test]
+fn test_render() {
+    let bundle = fixture_bundle();
+    let tmp = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let input = tmp.join("fixture-input.wav");
+    let samples = write_input(&input);
+
+    // The default gain of 0 dB leaves the signal untouched.
+    let output = render(
+        bundle,
+        &tmp.join("fixture-unity.wav"),
+        &["-i".as_ref(), input.as_ref()],
+    );
+    assert_eq!(samples, output);
+
+    let output = render(
+        bundle,
+        &tmp.join("fixture-quiet.wav"),
+        &[
+            "-i".as_ref(),
+            input.as_ref(),
+            "-c".as_ref(),
+            "gain=-6".as_ref(),
+        ],
+    );
+    assert_eq!(samples.len(), output.len());
+    let coef = 10.0_f32.powf(-6.0 * 0.05);
+    for (input, output) in samples.iter().zip(output.iter()) {
+        assert!((input * coef - output).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_render_midi() {
+    let bundle = fixture_bundle();
+    let tmp = Path::new(env!("CARGO_TARGET_TMPDIR"));
+    let input = tmp.join("fixture-midi-input.wav");
+    let samples = write_input(&input);
+    let midi = tmp.join("fixture-note.mid");
+    write_midi(&midi);
+
+    // The note ends in the second block, so it's time stamp has to be relative to the block.
+    let output = render(
+        bundle,
+        &tmp.join("fixture-note.wav"),
+        &[
+            "-i".as_ref(),
+            input.as_ref(),
+            "-m".as_ref(),
+            midi.as_ref(),
+            "-c".as_ref(),
+            "gain=-90".as_ref(),
+        ],
+    );
+    assert_eq!(samples.len(), output.len());
+    for (frame, sample) in output.iter().enumerate() {
+        let expected = if (500..1000).contains(&frame) {
+            0.5
+        } else {
+            0.0
+        };
+        assert_eq!(expected, *sample, "frame {}", frame);
+    }
+}
@@ -0,0 +1,316 @@
+//! The host features provided by the test host.
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::ffi::{c_void, CStr};
+use std::os::raw::{c_char, c_int};
+use std::path::PathBuf;
+use urid::*;
+
+/// A message the plugin has sent to the log.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LogMessage {
+    /// The URID of the entry type, like `log:Error` or `log:Note`.
+    pub entry: URID,
+    /// The formatted message.
+    pub message: String,
+}
+
+/// The settings of the test host and the options passed to the plugin.
+#[derive(Clone, Debug, PartialEq)]
+pub struct HostSettings {
+    /// The bundle path passed to the plugin.
+    pub bundle_path: PathBuf,
+    /// The sample rate of the plugin.
+    pub sample_rate: f64,
+    /// The minimal number of frames per `run` call.
+    pub min_block_length: u32,
+    /// The maximal number of frames per `run` call, which is also the length of all audio buffers.
+    pub max_block_length: u32,
+    /// The size of atom port buffers, in bytes.
+    pub sequence_size: u32,
+}
+
+impl Default for HostSettings {
+    fn default() -> Self {
+        Self {
+            bundle_path: PathBuf::new(),
+            sample_rate: 44100.0,
+            min_block_length: 1,
+            max_block_length: 512,
+            sequence_size: 8192,
+        }
+    }
+}
+
+/// The handle of the log feature, which is passed to the C functions in `c/log.c`.
+#[repr(C)]
+struct LogHandle {
+    data: *mut c_void,
+    record: unsafe extern "C" fn(*mut c_void, u32, *const c_char) -> c_int,
+}
+
+extern "C" {
+    /// Set the handle and the variadic `printf` and `vprintf` functions of the log feature.
+    ///
+    /// The functions format the message in C and pass it to the `record` function of the handle.
+    fn lv2_host_test_log_init(log: *mut sys::LV2_Log_Log, handle: *mut LogHandle);
+}
+
+/// All host features and their internal data.
+///
+/// This struct is always boxed since the raw feature structs point to it and to each other.
+pub(crate) struct HostFeatures {
+    pub mapper: HashURIDMapper,
+    pub messages: RefCell<Vec<LogMessage>>,
+    pub scheduled: RefCell<VecDeque<Vec<u8>>>,
+    pub responses: RefCell<VecDeque<Vec<u8>>>,
+    option_values: [i32; 4],
+    sample_rate: f32,
+    map: sys::LV2_URID_Map,
+    unmap: sys::LV2_URID_Unmap,
+    log: sys::LV2_Log_Log,
+    log_handle: LogHandle,
+    schedule: sys::LV2_Worker_Schedule,
+    options: Vec<sys::LV2_Options_Option>,
+    features: Vec<sys::LV2_Feature>,
+    feature_ptrs: Vec<*const sys::LV2_Feature>,
+}
+
+impl HostFeatures {
+    /// Create all features for the given settings.
+    pub fn new(settings: &HostSettings) -> Box<Self> {
+        let mut features = Box::new(Self {
+            mapper: HashURIDMapper::new(),
+            messages: RefCell::new(Vec::new()),
+            scheduled: RefCell::new(VecDeque::new()),
+            responses: RefCell::new(VecDeque::new()),
+            option_values: [
+                settings.min_block_length as i32,
+                settings.max_block_length as i32,
+                settings.max_block_length as i32,
+                settings.sequence_size as i32,
+            ],
+            sample_rate: settings.sample_rate as f32,
+            map: sys::LV2_URID_Map {
+                handle: std::ptr::null_mut(),
+                map: Some(Self::extern_map),
+            },
+            unmap: sys::LV2_URID_Unmap {
+                handle: std::ptr::null_mut(),
+                unmap: Some(Self::extern_unmap),
+            },
+            log: sys::LV2_Log_Log {
+                handle: std::ptr::null_mut(),
+                printf: None,
+                vprintf: None,
+            },
+            log_handle: LogHandle {
+                data: std::ptr::null_mut(),
+                record: Self::extern_record,
+            },
+            schedule: sys::LV2_Worker_Schedule {
+                handle: std::ptr::null_mut(),
+                schedule_work: Some(Self::extern_schedule_work),
+            },
+            options: Vec::new(),
+            features: Vec::new(),
+            feature_ptrs: Vec::new(),
+        });
+
+        let handle = features.as_mut() as *mut Self as *mut c_void;
+        features.map.handle = handle;
+        features.unmap.handle = handle;
+        features.log_handle.data = handle;
+        unsafe { lv2_host_test_log_init(&mut features.log, &mut features.log_handle) };
+        features.schedule.handle = handle;
+
+        let int = features.map(sys::LV2_ATOM__Int).get();
+        let float = features.map(sys::LV2_ATOM__Float).get();
+        let keys: [&[u8]; 4] = [
+            sys::LV2_BUF_SIZE__minBlockLength,
+            sys::LV2_BUF_SIZE__maxBlockLength,
+            sys::LV2_BUF_SIZE__nominalBlockLength,
+            sys::LV2_BUF_SIZE__sequenceSize,
+        ];
+        let mut options: Vec<sys::LV2_Options_Option> = keys
+            .iter()
+            .zip(features.option_values.iter())
+            .map(|(key, value)| sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                subject: 0,
+                key: features.map(key).get(),
+                size: std::mem::size_of::<i32>() as u32,
+                type_: int,
+                value: value as *const i32 as *const c_void,
+            })
+            .collect();
+        options.push(sys::LV2_Options_Option {
+            context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+            subject: 0,
+            key: features.map(sys::LV2_PARAMETERS__sampleRate).get(),
+            size: std::mem::size_of::<f32>() as u32,
+            type_: float,
+            value: &features.sample_rate as *const f32 as *const c_void,
+        });
+        options.push(sys::LV2_Options_Option {
+            context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+            subject: 0,
+            key: 0,
+            size: 0,
+            type_: 0,
+            value: std::ptr::null(),
+        });
+        features.options = options;
+
+        let raw_features = vec![
+            Self::feature(sys::LV2_URID__map, &features.map),
+            Self::feature(sys::LV2_URID__unmap, &features.unmap),
+            Self::feature(sys::LV2_LOG__log, &features.log),
+            Self::feature(sys::LV2_WORKER__schedule, &features.schedule),
+            Self::feature(sys::LV2_OPTIONS__options, features.options.as_ptr()),
+            Self::feature(
+                sys::LV2_BUF_SIZE__boundedBlockLength,
+                std::ptr::null::<()>(),
+            ),
+        ];
+        features.features = raw_features;
+        let mut feature_ptrs: Vec<*const sys::LV2_Feature> = features
+            .features
+            .iter()
+            .map(|feature| feature as *const sys::LV2_Feature)
+            .collect();
+        feature_ptrs.push(std::ptr::null());
+        features.feature_ptrs = feature_ptrs;
+
+        features
+    }
+
+    fn feature<T>(uri: &'static [u8], data: *const T) -> sys::LV2_Feature {
+        sys::LV2_Feature {
+            URI: uri.as_ptr() as *const c_char,
+            data: data as *mut c_void,
+        }
+    }
+
+    /// Return the null-terminated array of features.
+    pub fn as_ptr(&self) -> *const *const sys::LV2_Feature {
+        self.feature_ptrs.as_ptr()
+    }
+
+    /// Map a null-terminated URI.
+    pub fn map(&self, uri: &[u8]) -> URID {
+        let uri = Uri::from_bytes_with_nul(uri).expect("URI is not null-terminated");
+        self.mapper.map_uri(uri).expect("URID map is exhausted")
+    }
+
+    unsafe extern "C" fn extern_map(handle: *mut c_void, uri: *const c_char) -> u32 {
+        let features = &*(handle as *const Self);
+        features
+            .mapper
+            .map_uri(Uri::from_ptr(uri))
+            .map_or(0, URID::get)
+    }
+
+    unsafe extern "C" fn extern_unmap(handle: *mut c_void, urid: u32) -> *const c_char {
+        let features = &*(handle as *const Self);
+        URID::<()>::new(urid)
+            .and_then(|urid| features.mapper.unmap(urid))
+            .map_or(std::ptr::null(), |uri| uri.as_ptr())
+    }
+
+    unsafe extern "C" fn extern_record(
+        handle: *mut c_void,
+        entry: u32,
+        message: *const c_char,
+    ) -> c_int {
+        let features = &*(handle as *const Self);
+        let entry = match URID::new(entry) {
+            Some(entry) => entry,
+            None => return -1,
+        };
+        let message = CStr::from_ptr(message).to_string_lossy().into_owned();
+        let len = message.len() as c_int;
+        features
+            .messages
+            .borrow_mut()
+            .push(LogMessage { entry, message });
+        len.max(1)
+    }
+
+    unsafe extern "C" fn extern_schedule_work(
+        handle: *mut c_void,
+        size: u32,
+        data: *const c_void,
+    ) -> sys::LV2_Worker_Status {
+        let features = &*(handle as *const Self);
+        let data = match message_bytes(size, data) {
+            Some(data) => data,
+            None => return sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+        };
+        features.scheduled.borrow_mut().push_back(data.to_vec());
+        sys::LV2_Worker_Status_LV2_WORKER_SUCCESS
+    }
+
+    /// Respond function passed to the `work` method of the plugin.
+    pub unsafe extern "C" fn extern_respond(
+        handle: *mut c_void,
+        size: u32,
+        data: *const c_void,
+    ) -> sys::LV2_Worker_Status {
+        let features = &*(handle as *const Self);
+        let data = match message_bytes(size, data) {
+            Some(data) => data,
+            None => return sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+        };
+        features.responses.borrow_mut().push_back(data.to_vec());
+        sys::LV2_Worker_Status_LV2_WORKER_SUCCESS
+    }
+}
+
+/// Create a slice from the message passed by the plugin.
+///
+/// Empty messages may be passed as a null pointer.
+unsafe fn message_bytes<'a>(size: u32, data: *const c_void) -> Option<&'a [u8]> {
+    if size == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(data as *const u8, size as usize))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_messages() {
+        let features = HostFeatures::new(&HostSettings::default());
+        let handle = features.as_ref() as *const HostFeatures as *mut c_void;
+
+        unsafe {
+            assert_eq!(
+                sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
+                HostFeatures::extern_schedule_work(handle, 0, std::ptr::null())
+            );
+            assert_eq!(
+                sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
+                HostFeatures::extern_respond(handle, 0, std::ptr::null())
+            );
+            assert_eq!(
+                sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+                HostFeatures::extern_respond(handle, 4, std::ptr::null())
+            );
+        }
+        assert_eq!(
+            Some(Vec::new()),
+            features.scheduled.borrow_mut().pop_front()
+        );
+        assert_eq!(
+            Some(Vec::new()),
+            features.responses.borrow_mut().pop_front()
+        );
+        assert!(features.responses.borrow().is_empty());
+    }
+}
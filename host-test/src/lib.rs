@@ -0,0 +1,526 @@
+//! In-process LV2 host to test plugins without a real host.
+//!
+//! Testing a plugin end-to-end usually requires assembling raw feature arrays and port pointers by hand. The [`TestHost`](struct.TestHost.html) does this for you: It instantiates a plugin with a set of common host features, connects typed buffers to it's ports and runs it block by block. The provided features are:
+//!
+//! * `urid:map` and `urid:unmap`, backed by a `HashURIDMapper`.
+//! * `log:log`, which records all messages.
+//! * `work:schedule`, whose jobs are executed when [`work`](struct.TestHost.html#method.work) is called.
+//! * `opts:options`, containing the sample rate and the block lengths.
+//! * `bufsz:boundedBlockLength`.
+//!
+//! The state of the plugin can be saved and restored with an in-memory [`Storage`](../lv2_state/struct.Storage.html).
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_host_test::*;
+//! use urid::*;
+//!
+//! #[derive(PortCollection)]
+//! struct Ports {
+//!     gain: InputPort<Control>,
+//!     input: InputPort<Audio>,
+//!     output: OutputPort<Audio>,
+//! }
+//!
+//! #[uri("urn:my-amp")]
+//! struct Amp;
+//!
+//! impl Plugin for Amp {
+//!     type Ports = Ports;
+//!     type InitFeatures = ();
+//!     type AudioFeatures = ();
+//!
+//!     fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+//!         Some(Self)
+//!     }
+//!
+//!     fn run(&mut self, ports: &mut Ports, _: &mut (), _: u32) {
+//!         for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+//!             *output = *input * *ports.gain;
+//!         }
+//!     }
+//! }
+//!
+//! lv2_descriptors!(Amp);
+//!
+//! let mut host = TestHost::new::<Amp>(HostSettings::default()).unwrap();
+//! host.set_control(0, 0.5);
+//! host.connect_audio(1).fill(1.0);
+//! host.connect_audio(2);
+//!
+//! host.activate();
+//! host.run(64);
+//! assert!(host.audio(2).unwrap()[..64].iter().all(|sample| *sample == 0.5));
+//! ```
+extern crate lv2_core as core;
+extern crate lv2_state as state;
+extern crate lv2_sys as sys;
+
+mod features;
+
+pub use features::{HostSettings, LogMessage};
+
+use core::plugin::{Plugin, PluginInstanceDescriptor};
+use features::HostFeatures;
+use state::{StateErr, Storage};
+use std::collections::BTreeMap;
+use std::ffi::{c_void, CString};
+use urid::*;
+
+/// A buffer connected to a port of the plugin.
+enum PortBuffer {
+    Control(Box<f32>),
+    Audio(Vec<f32>),
+    AtomInput(Vec<u64>),
+    AtomOutput(Vec<u64>),
+}
+
+impl PortBuffer {
+    fn as_mut_ptr(&mut self) -> *mut c_void {
+        match self {
+            PortBuffer::Control(value) => value.as_mut() as *mut f32 as *mut c_void,
+            PortBuffer::Audio(buffer) => buffer.as_mut_ptr() as *mut c_void,
+            PortBuffer::AtomInput(buffer) | PortBuffer::AtomOutput(buffer) => {
+                buffer.as_mut_ptr() as *mut c_void
+            }
+        }
+    }
+}
+
+/// Write an atom header to the beginning of an atom buffer.
+fn write_atom_header(buffer: &mut [u64], size: u32, type_: URID) {
+    buffer[0] = u64::from_ne_bytes(
+        [size.to_ne_bytes(), type_.get().to_ne_bytes()]
+            .concat()
+            .try_into()
+            .unwrap(),
+    );
+}
+
+/// Reinterpret an atom buffer as bytes.
+fn atom_bytes(buffer: &[u64]) -> &[u8] {
+    unsafe { std::slice::from_raw_parts(buffer.as_ptr() as *const u8, buffer.len() * 8) }
+}
+
+/// An instantiated plugin together with it's host features and port buffers.
+///
+/// All buffers are owned by the host and are connected to the plugin when they are created. Audio buffers are as long as the maximal block length and atom buffers are as big as the sequence size of the [`HostSettings`](struct.HostSettings.html). The plugin is deactivated and dropped when the host is dropped.
+pub struct TestHost {
+    descriptor: &'static sys::LV2_Descriptor,
+    handle: sys::LV2_Handle,
+    settings: HostSettings,
+    features: Box<HostFeatures>,
+    ports: BTreeMap<u32, PortBuffer>,
+    active: bool,
+}
+
+impl TestHost {
+    /// Instantiate a plugin type of this crate.
+    ///
+    /// The plugin has to be exported with the `lv2_descriptors` macro. `None` is returned if the instantiation fails, for example because of a missing feature.
+    pub fn new<P: PluginInstanceDescriptor>(settings: HostSettings) -> Option<Self> {
+        unsafe { Self::from_descriptor(&P::DESCRIPTOR, settings) }
+    }
+
+    /// Instantiate a plugin from a `lv2_descriptor` function, like the one exported by a plugin library.
+    ///
+    /// # Safety
+    ///
+    /// The function has to return valid descriptors that live as long as the host, which is the case for the `lv2_descriptor` function of a loaded library.
+    pub unsafe fn from_library(
+        lv2_descriptor: unsafe extern "C" fn(u32) -> *const sys::LV2_Descriptor,
+        uri: &Uri,
+        settings: HostSettings,
+    ) -> Option<Self> {
+        let descriptor = (0..)
+            .map(|index| lv2_descriptor(index))
+            .take_while(|descriptor| !descriptor.is_null())
+            .find(|descriptor| Uri::from_ptr((**descriptor).URI) == uri)?;
+        Self::from_descriptor(descriptor, settings)
+    }
+
+    /// Instantiate a plugin from a raw descriptor.
+    ///
+    /// # Safety
+    ///
+    /// The descriptor has to be valid and has to live as long as the host.
+    pub unsafe fn from_descriptor(
+        descriptor: *const sys::LV2_Descriptor,
+        settings: HostSettings,
+    ) -> Option<Self> {
+        let descriptor = descriptor.as_ref()?;
+        let features = HostFeatures::new(&settings);
+        let bundle_path = CString::new(settings.bundle_path.to_str()?).ok()?;
+        let handle = (descriptor.instantiate?)(
+            descriptor,
+            settings.sample_rate,
+            bundle_path.as_ptr(),
+            features.as_ptr(),
+        );
+        if handle.is_null() {
+            return None;
+        }
+        Some(Self {
+            descriptor,
+            handle,
+            settings,
+            features,
+            ports: BTreeMap::new(),
+            active: false,
+        })
+    }
+
+    /// Return the URI of the plugin.
+    pub fn uri(&self) -> &Uri {
+        unsafe { Uri::from_ptr(self.descriptor.URI) }
+    }
+
+    /// Return the settings of the host.
+    pub fn settings(&self) -> &HostSettings {
+        &self.settings
+    }
+
+    /// Return the URID mapper of the host.
+    pub fn mapper(&self) -> &HashURIDMapper {
+        &self.features.mapper
+    }
+
+    /// Map a URI bound type to it's URID.
+    pub fn map<T: UriBound + ?Sized>(&self) -> URID<T> {
+        self.features.mapper.map_type().unwrap()
+    }
+
+    /// Create a URID collection with the host's mapper.
+    pub fn populate_collection<C: URIDCollection>(&self) -> Option<C> {
+        self.features.mapper.populate_collection()
+    }
+
+    /// Return a reference to the plugin.
+    ///
+    /// `None` is returned if the URI of the plugin doesn't match the URI of `P`.
+    pub fn plugin<P: Plugin>(&self) -> Option<&P> {
+        if self.uri() == P::uri() {
+            // A plugin instance has the plugin as it's first field.
+            unsafe { (self.handle as *const P).as_ref() }
+        } else {
+            None
+        }
+    }
+
+    /// Return a mutable reference to the plugin.
+    ///
+    /// `None` is returned if the URI of the plugin doesn't match the URI of `P`.
+    pub fn plugin_mut<P: Plugin>(&mut self) -> Option<&mut P> {
+        if self.uri() == P::uri() {
+            unsafe { (self.handle as *mut P).as_mut() }
+        } else {
+            None
+        }
+    }
+
+    /// Return the worker interface of the plugin, if it has one.
+    pub fn worker_interface(&self) -> Option<sys::LV2_Worker_Interface> {
+        unsafe { self.extension_data(sys::LV2_WORKER__interface) }
+    }
+
+    /// Return the state interface of the plugin, if it has one.
+    pub fn state_interface(&self) -> Option<sys::LV2_State_Interface> {
+        unsafe { self.extension_data(sys::LV2_STATE__interface) }
+    }
+
+    /// Return a copy of the extension data of the plugin for the given extension.
+    ///
+    /// # Safety
+    ///
+    /// `uri` has to be null-terminated and `T` has to be the interface struct of the extension.
+    unsafe fn extension_data<T: Copy>(&self, uri: &[u8]) -> Option<T> {
+        let uri = Uri::from_bytes_with_nul(uri).ok()?;
+        let extension_data = self.descriptor.extension_data?;
+        (extension_data(uri.as_ptr()) as *const T).as_ref().copied()
+    }
+
+    fn connect(&mut self, index: u32, buffer: PortBuffer) -> &mut PortBuffer {
+        let buffer = match self.ports.entry(index) {
+            std::collections::btree_map::Entry::Occupied(mut entry) => {
+                entry.insert(buffer);
+                entry.into_mut()
+            }
+            std::collections::btree_map::Entry::Vacant(entry) => entry.insert(buffer),
+        };
+        if let Some(connect_port) = self.descriptor.connect_port {
+            unsafe { (connect_port)(self.handle, index, buffer.as_mut_ptr()) };
+        }
+        buffer
+    }
+
+    /// Set the value of a control port, connecting it if necessary.
+    pub fn set_control(&mut self, index: u32, value: f32) {
+        match self.ports.get_mut(&index) {
+            Some(PortBuffer::Control(control)) => **control = value,
+            _ => {
+                self.connect(index, PortBuffer::Control(Box::new(value)));
+            }
+        }
+    }
+
+    /// Return the value of a control port, or `None` if it isn't connected to a control buffer.
+    pub fn control(&self, index: u32) -> Option<f32> {
+        match self.ports.get(&index) {
+            Some(PortBuffer::Control(control)) => Some(**control),
+            _ => None,
+        }
+    }
+
+    /// Connect a new, silent audio or CV buffer to a port and return it.
+    pub fn connect_audio(&mut self, index: u32) -> &mut [f32] {
+        let buffer = vec![0.0; self.settings.max_block_length as usize];
+        match self.connect(index, PortBuffer::Audio(buffer)) {
+            PortBuffer::Audio(buffer) => buffer.as_mut_slice(),
+            _ => unreachable!(),
+        }
+    }
+
+    /// Return the audio buffer of a port.
+    pub fn audio(&self, index: u32) -> Option<&[f32]> {
+        match self.ports.get(&index) {
+            Some(PortBuffer::Audio(buffer)) => Some(buffer.as_slice()),
+            _ => None,
+        }
+    }
+
+    /// Return the mutable audio buffer of a port, for example to write the input of the next block.
+    pub fn audio_mut(&mut self, index: u32) -> Option<&mut [f32]> {
+        match self.ports.get_mut(&index) {
+            Some(PortBuffer::Audio(buffer)) => Some(buffer.as_mut_slice()),
+            _ => None,
+        }
+    }
+
+    /// Connect a new atom input buffer to a port.
+    ///
+    /// The buffer initially contains an empty `atom:Sequence`. It's content can be replaced with [`atom_mut`](#method.atom_mut).
+    pub fn connect_atom_input(&mut self, index: u32) {
+        let mut buffer = vec![0u64; self.settings.sequence_size as usize / 8];
+        write_atom_header(&mut buffer, 8, self.map::<AtomSequence>().into_general());
+        self.connect(index, PortBuffer::AtomInput(buffer));
+    }
+
+    /// Connect a new atom output buffer to a port.
+    ///
+    /// Before every `run`, the buffer is reset to an `atom:Chunk` that spans the whole buffer, as required by the atom specification.
+    pub fn connect_atom_output(&mut self, index: u32) {
+        let buffer = vec![0u64; self.settings.sequence_size as usize / 8];
+        self.connect(index, PortBuffer::AtomOutput(buffer));
+    }
+
+    /// Return the content of an atom buffer, starting with the atom header.
+    pub fn atom(&self, index: u32) -> Option<&[u8]> {
+        match self.ports.get(&index) {
+            Some(PortBuffer::AtomInput(buffer)) | Some(PortBuffer::AtomOutput(buffer)) => {
+                Some(atom_bytes(buffer))
+            }
+            _ => None,
+        }
+    }
+
+    /// Return the mutable content of an atom input buffer.
+    ///
+    /// The buffer is aligned to 64 bits and can be written with the space types of `lv2-atom`.
+    pub fn atom_mut(&mut self, index: u32) -> Option<&mut [u8]> {
+        match self.ports.get_mut(&index) {
+            Some(PortBuffer::AtomInput(buffer)) => Some(unsafe {
+                std::slice::from_raw_parts_mut(buffer.as_mut_ptr() as *mut u8, buffer.len() * 8)
+            }),
+            _ => None,
+        }
+    }
+
+    /// Activate the plugin, if it isn't active yet.
+    pub fn activate(&mut self) {
+        if !self.active {
+            if let Some(activate) = self.descriptor.activate {
+                unsafe { (activate)(self.handle) };
+            }
+            self.active = true;
+        }
+    }
+
+    /// Deactivate the plugin, if it is active.
+    pub fn deactivate(&mut self) {
+        if self.active {
+            if let Some(deactivate) = self.descriptor.deactivate {
+                unsafe { (deactivate)(self.handle) };
+            }
+            self.active = false;
+        }
+    }
+
+    /// Run the plugin for one block.
+    ///
+    /// Before the plugin is run, all pending worker responses are delivered and atom output buffers are reset. After the plugin has run, the `end_run` method of the worker interface is called.
+    ///
+    /// # Panics
+    ///
+    /// This method panics if the plugin isn't active or if the sample count is greater than the maximal block length.
+    pub fn run(&mut self, sample_count: u32) {
+        assert!(
+            self.active,
+            "The plugin has to be activated before it's run"
+        );
+        assert!(
+            sample_count <= self.settings.max_block_length,
+            "The sample count exceeds the maximal block length"
+        );
+
+        let worker = self.worker_interface();
+        if let Some(work_response) = worker.and_then(|worker| worker.work_response) {
+            loop {
+                let response = self.features.responses.borrow_mut().pop_front();
+                match response {
+                    Some(response) => unsafe {
+                        (work_response)(
+                            self.handle,
+                            response.len() as u32,
+                            response.as_ptr() as *const c_void,
+                        );
+                    },
+                    None => break,
+                }
+            }
+        }
+
+        let chunk = self.map::<AtomChunk>().into_general();
+        for buffer in self.ports.values_mut() {
+            if let PortBuffer::AtomOutput(buffer) = buffer {
+                let size = (buffer.len() * 8 - 8) as u32;
+                write_atom_header(buffer, size, chunk);
+            }
+        }
+
+        if let Some(run) = self.descriptor.run {
+            unsafe { (run)(self.handle, sample_count) };
+        }
+
+        if let Some(end_run) = worker.and_then(|worker| worker.end_run) {
+            unsafe { (end_run)(self.handle) };
+        }
+    }
+
+    /// Return the number of scheduled, but not yet executed worker jobs.
+    pub fn scheduled_work(&self) -> usize {
+        self.features.scheduled.borrow().len()
+    }
+
+    /// Execute all scheduled worker jobs and return their number.
+    ///
+    /// The responses of the jobs are delivered before the next `run`.
+    pub fn work(&mut self) -> usize {
+        let work = match self.worker_interface().and_then(|worker| worker.work) {
+            Some(work) => work,
+            None => return 0,
+        };
+        let mut executed = 0;
+        loop {
+            let job = self.features.scheduled.borrow_mut().pop_front();
+            let job = match job {
+                Some(job) => job,
+                None => break,
+            };
+            unsafe {
+                (work)(
+                    self.handle,
+                    Some(HostFeatures::extern_respond),
+                    self.features.as_ref() as *const HostFeatures as *mut c_void,
+                    job.len() as u32,
+                    job.as_ptr() as *const c_void,
+                )
+            };
+            executed += 1;
+        }
+        executed
+    }
+
+    /// Return all messages the plugin has logged so far.
+    pub fn log_messages(&self) -> Vec<LogMessage> {
+        self.features.messages.borrow().clone()
+    }
+
+    /// Remove and return all messages the plugin has logged so far.
+    pub fn take_log_messages(&mut self) -> Vec<LogMessage> {
+        std::mem::take(&mut *self.features.messages.borrow_mut())
+    }
+
+    /// Save the state of the plugin.
+    ///
+    /// `StateErr::Unknown` is returned if the plugin doesn't provide the state interface.
+    pub fn save(&mut self) -> Result<Storage, StateErr> {
+        let save = self
+            .state_interface()
+            .and_then(|interface| interface.save)
+            .ok_or(StateErr::Unknown)?;
+        let mut storage = Storage::default();
+        StateErr::from(unsafe {
+            (save)(
+                self.handle,
+                Some(Storage::extern_store),
+                &mut storage as *mut Storage as *mut c_void,
+                (sys::LV2_State_Flags::LV2_STATE_IS_POD
+                    | sys::LV2_State_Flags::LV2_STATE_IS_PORTABLE)
+                    .into(),
+                self.features.as_ptr(),
+            )
+        })?;
+        Ok(storage)
+    }
+
+    /// Restore the state of the plugin.
+    ///
+    /// `StateErr::Unknown` is returned if the plugin doesn't provide the state interface.
+    pub fn restore(&mut self, storage: &mut Storage) -> Result<(), StateErr> {
+        let restore = self
+            .state_interface()
+            .and_then(|interface| interface.restore)
+            .ok_or(StateErr::Unknown)?;
+        StateErr::from(unsafe {
+            (restore)(
+                self.handle,
+                Some(Storage::extern_retrieve),
+                storage as *mut Storage as *mut c_void,
+                (sys::LV2_State_Flags::LV2_STATE_IS_POD
+                    | sys::LV2_State_Flags::LV2_STATE_IS_PORTABLE)
+                    .into(),
+                self.features.as_ptr(),
+            )
+        })
+    }
+}
+
+impl Drop for TestHost {
+    fn drop(&mut self) {
+        self.deactivate();
+        if let Some(cleanup) = self.descriptor.cleanup {
+            unsafe { (cleanup)(self.handle) };
+        }
+    }
+}
+
+/// The `atom:Chunk` type, which marks free space in atom output buffers.
+struct AtomChunk;
+
+unsafe impl UriBound for AtomChunk {
+    const URI: &'static [u8] = sys::LV2_ATOM__Chunk;
+}
+
+/// The `atom:Sequence` type, which is the initial content of atom input buffers.
+struct AtomSequence;
+
+unsafe impl UriBound for AtomSequence {
+    const URI: &'static [u8] = sys::LV2_ATOM__Sequence;
+}
+
+/// Prelude of `lv2_host_test` for wildcard usage.
+pub mod prelude {
+    pub use crate::{HostSettings, LogMessage, TestHost};
+}
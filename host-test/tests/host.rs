@@ -0,0 +1,234 @@
+use lv2_atom::prelude::*;
+use lv2_buf_size::*;
+use lv2_core::prelude::*;
+use lv2_host_test::*;
+use lv2_log::*;
+use lv2_options::*;
+use lv2_state::*;
+use lv2_urid::*;
+use lv2_worker::*;
+use std::any::Any;
+use urid::*;
+
+#[derive(PortCollection)]
+struct Ports {
+    gain: InputPort<Control>,
+    input: InputPort<Audio>,
+    output: OutputPort<Audio>,
+    level: OutputPort<Control>,
+}
+
+#[derive(FeatureCollection)]
+struct InitFeatures<'a> {
+    map: LV2Map<'a>,
+    options: Options<'a>,
+    log: Log<'a>,
+}
+
+#[derive(FeatureCollection)]
+struct AudioFeatures<'a> {
+    schedule: Schedule<'a, Tester>,
+}
+
+#[derive(URIDCollection)]
+struct URIDs {
+    atom: AtomURIDCollection,
+    buf_size: BufSizeURIDCollection,
+    log: LogURIDCollection,
+}
+
+struct Counter;
+
+unsafe impl UriBound for Counter {
+    const URI: &'static [u8] = b"urn:rust-lv2:host-test:counter\0";
+}
+
+#[uri("urn:rust-lv2:host-test")]
+struct Tester {
+    urids: URIDs,
+    counter: URID<Counter>,
+    sizes: BufferSizes,
+    runs: i32,
+    response: Option<i32>,
+    ended: u32,
+}
+
+impl Plugin for Tester {
+    type Ports = Ports;
+    type InitFeatures = InitFeatures<'static>;
+    type AudioFeatures = AudioFeatures<'static>;
+
+    fn new(_: &PluginInfo, features: &mut InitFeatures<'static>) -> Option<Self> {
+        let urids: URIDs = features.map.populate_collection()?;
+        let sizes = BufferSizes::from_options(&features.options, &urids.buf_size, &urids.atom);
+        features
+            .log
+            .print_cstr(urids.log.note, c"instantiated")
+            .ok()?;
+        Some(Self {
+            counter: features.map.map_type()?,
+            urids,
+            sizes,
+            runs: 0,
+            response: None,
+            ended: 0,
+        })
+    }
+
+    fn run(&mut self, ports: &mut Ports, features: &mut AudioFeatures<'static>, _: u32) {
+        let mut level: f32 = 0.0;
+        for (input, output) in ports.input.iter().zip(ports.output.iter_mut()) {
+            *output = *input * *ports.gain;
+            level = level.max(output.abs());
+        }
+        **ports.level = level;
+
+        self.runs += 1;
+        features.schedule.schedule_work(self.runs).unwrap();
+    }
+
+    fn extension_data(uri: &Uri) -> Option<&'static dyn Any> {
+        match_extensions![uri, WorkerDescriptor<Self>, StateDescriptor<Self>]
+    }
+}
+
+impl Worker for Tester {
+    type WorkData = i32;
+    type ResponseData = i32;
+
+    fn work(response_handler: &ResponseHandler<Self>, data: i32) -> Result<(), WorkerError> {
+        response_handler
+            .respond(data * 2)
+            .map_err(|_| WorkerError::Unknown)
+    }
+
+    fn work_response(
+        &mut self,
+        data: i32,
+        _: &mut AudioFeatures<'static>,
+    ) -> Result<(), WorkerError> {
+        self.response = Some(data);
+        Ok(())
+    }
+
+    fn end_run(&mut self, _: &mut AudioFeatures<'static>) -> Result<(), WorkerError> {
+        self.ended += 1;
+        Ok(())
+    }
+}
+
+impl State for Tester {
+    type StateFeatures = ();
+
+    fn save(&self, mut store: StoreHandle, _: ()) -> Result<(), StateErr> {
+        store
+            .draft(self.counter)
+            .init(self.urids.atom.int, self.runs)?;
+        store.commit_all()
+    }
+
+    fn restore(&mut self, store: RetrieveHandle, _: ()) -> Result<(), StateErr> {
+        self.runs = store
+            .retrieve(self.counter)?
+            .read(self.urids.atom.int, ())?;
+        Ok(())
+    }
+}
+
+lv2_descriptors!(Tester);
+
+fn host() -> TestHost {
+    let settings = HostSettings {
+        max_block_length: 64,
+        ..Default::default()
+    };
+    let mut host = TestHost::new::<Tester>(settings).unwrap();
+    host.set_control(0, 0.5);
+    host.connect_audio(1);
+    host.connect_audio(2);
+    host.set_control(3, 0.0);
+    host
+}
+
+#[test]
+fn test_instantiation() {
+    let host = host();
+    assert_eq!(Tester::uri(), host.uri());
+
+    let plugin = host.plugin::<Tester>().unwrap();
+    assert_eq!(Some(1), plugin.sizes.min_block_length);
+    assert_eq!(Some(64), plugin.sizes.max_block_length);
+    assert_eq!(Some(8192), plugin.sizes.sequence_size);
+
+    let note = host.map::<Note>().into_general();
+    assert_eq!(
+        vec![LogMessage {
+            entry: note,
+            message: "instantiated".to_owned(),
+        }],
+        host.log_messages()
+    );
+}
+
+#[test]
+fn test_run() {
+    let mut host = host();
+    for (i, sample) in host.audio_mut(1).unwrap().iter_mut().enumerate() {
+        *sample = i as f32;
+    }
+
+    host.activate();
+    host.run(64);
+
+    let output = host.audio(2).unwrap();
+    for (i, sample) in output.iter().enumerate() {
+        assert_eq!(i as f32 * 0.5, *sample);
+    }
+    assert_eq!(Some(31.5), host.control(3));
+}
+
+#[test]
+#[should_panic]
+fn test_run_exceeds_block_length() {
+    let mut host = host();
+    host.activate();
+    host.run(65);
+}
+
+#[test]
+fn test_worker() {
+    let mut host = host();
+    assert!(host.worker_interface().is_some());
+    host.activate();
+
+    host.run(16);
+    host.run(16);
+    assert_eq!(2, host.scheduled_work());
+    assert_eq!(2, host.plugin::<Tester>().unwrap().ended);
+
+    assert_eq!(2, host.work());
+    assert_eq!(0, host.scheduled_work());
+    assert_eq!(None, host.plugin::<Tester>().unwrap().response);
+
+    host.run(16);
+    assert_eq!(Some(4), host.plugin::<Tester>().unwrap().response);
+}
+
+#[test]
+fn test_state() {
+    let mut host = host();
+    host.activate();
+    host.run(16);
+    host.run(16);
+
+    let mut storage = host.save().unwrap();
+    host.run(16);
+    assert_eq!(3, host.plugin::<Tester>().unwrap().runs);
+
+    host.restore(&mut storage).unwrap();
+    assert_eq!(2, host.plugin::<Tester>().unwrap().runs);
+
+    let mut other = self::host();
+    other.restore(&mut storage).unwrap();
+    assert_eq!(2, other.plugin::<Tester>().unwrap().runs);
+}
@@ -0,0 +1,146 @@
+use std::ffi::CStr;
+use std::fmt;
+
+/// A fixed-capacity buffer to format log messages without allocating.
+///
+/// The buffer lives on the stack or in pre-allocated memory and implements [`fmt::Write`](https://doc.rust-lang.org/std/fmt/trait.Write.html), so messages can be formatted with `write!`. Messages that don't fit into the buffer are truncated at the last character that fits. One byte of the capacity is reserved for the terminating null byte.
+///
+/// ```
+/// use lv2_log::LogBuffer;
+/// use std::fmt::Write;
+///
+/// let mut buffer = LogBuffer::<16>::new();
+/// write!(buffer, "{} + {} = {}", 1, 2, 1 + 2).unwrap();
+/// assert_eq!("1 + 2 = 3", buffer.as_str());
+///
+/// write!(buffer, ", which is a lot").unwrap();
+/// assert_eq!("1 + 2 = 3, whic", buffer.as_str());
+/// assert!(buffer.is_truncated());
+/// ```
+///
+/// The capacity has to be at least one byte for the null byte, which is checked at compile time:
+///
+/// ```compile_fail
+/// let buffer = lv2_log::LogBuffer::<0>::new();
+/// ```
+#[derive(Clone)]
+pub struct LogBuffer<const N: usize> {
+    data: [u8; N],
+    len: usize,
+    truncated: bool,
+}
+
+impl<const N: usize> LogBuffer<N> {
+    /// Create a new, empty buffer.
+    pub const fn new() -> Self {
+        const { assert!(N > 0, "a log buffer needs space for the null byte") };
+        Self {
+            data: [0; N],
+            len: 0,
+            truncated: false,
+        }
+    }
+
+    /// Format a message into a new buffer.
+    pub fn format(args: fmt::Arguments) -> Self {
+        let mut buffer = Self::new();
+        let _ = fmt::write(&mut buffer, args);
+        buffer
+    }
+
+    /// Remove the message from the buffer.
+    pub fn clear(&mut self) {
+        self.len = 0;
+        self.truncated = false;
+    }
+
+    /// The length of the message in bytes, without the null byte.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Check if the buffer contains no message.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Check if a part of the message was cut off because the buffer was full.
+    pub fn is_truncated(&self) -> bool {
+        self.truncated
+    }
+
+    /// The message as a string slice.
+    pub fn as_str(&self) -> &str {
+        // The buffer is only written with whole characters.
+        unsafe { std::str::from_utf8_unchecked(&self.data[..self.len]) }
+    }
+
+    /// The message as a null-terminated C string.
+    ///
+    /// If the message contains a null byte, the string ends there.
+    pub fn as_cstr(&mut self) -> &CStr {
+        self.data[self.len] = 0;
+        CStr::from_bytes_until_nul(&self.data[..=self.len]).unwrap()
+    }
+}
+
+impl<const N: usize> Default for LogBuffer<N> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<const N: usize> fmt::Write for LogBuffer<N> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        let free = N.saturating_sub(self.len + 1);
+        let mut len = s.len().min(free);
+        if len < s.len() {
+            while !s.is_char_boundary(len) {
+                len -= 1;
+            }
+            self.truncated = true;
+        }
+        self.data[self.len..self.len + len].copy_from_slice(&s.as_bytes()[..len]);
+        self.len += len;
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for LogBuffer<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(self.as_str(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fmt::Write;
+
+    #[test]
+    fn test_truncation() {
+        let mut buffer = LogBuffer::<8>::new();
+        assert!(buffer.is_empty());
+        assert_eq!(b"", buffer.as_cstr().to_bytes());
+
+        write!(buffer, "añb").unwrap();
+        assert_eq!("añb", buffer.as_str());
+        assert_eq!(4, buffer.len());
+        assert!(!buffer.is_truncated());
+
+        // "ñ" doesn't fit anymore and isn't split.
+        write!(buffer, "cdñ").unwrap();
+        assert_eq!("añbcd", buffer.as_str());
+        assert!(buffer.is_truncated());
+        assert_eq!("añbcd".as_bytes(), buffer.as_cstr().to_bytes());
+
+        buffer.clear();
+        assert!(!buffer.is_truncated());
+        let buffer = LogBuffer::<8>::format(format_args!("{}-{}", 1, 2));
+        assert_eq!("1-2", buffer.as_str());
+
+        // Interior null bytes end the C string.
+        let mut buffer = LogBuffer::<8>::format(format_args!("a\0b"));
+        assert_eq!(b"a", buffer.as_cstr().to_bytes());
+    }
+}
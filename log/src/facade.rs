@@ -0,0 +1,511 @@
+use crate::{Log, LogBuffer, LogURIDCollection};
+use std::ffi::CStr;
+use std::fmt;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+/// The verbosity of a module.
+///
+/// The levels of the `log` and `tracing` crates are mapped to the entry types of the host: Errors are printed as `Error`, warnings as `Warning`, infos as `Note` and debugging messages and traces as `Trace`.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum LevelFilter {
+    Off,
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+#[cfg(feature = "log")]
+impl From<log::Level> for LevelFilter {
+    fn from(level: log::Level) -> Self {
+        match level {
+            log::Level::Error => LevelFilter::Error,
+            log::Level::Warn => LevelFilter::Warn,
+            log::Level::Info => LevelFilter::Info,
+            log::Level::Debug => LevelFilter::Debug,
+            log::Level::Trace => LevelFilter::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<log::LevelFilter> for LevelFilter {
+    fn from(level: log::LevelFilter) -> Self {
+        match level.to_level() {
+            Some(level) => level.into(),
+            None => LevelFilter::Off,
+        }
+    }
+}
+
+#[cfg(feature = "log")]
+impl From<LevelFilter> for log::LevelFilter {
+    fn from(level: LevelFilter) -> Self {
+        match level {
+            LevelFilter::Off => log::LevelFilter::Off,
+            LevelFilter::Error => log::LevelFilter::Error,
+            LevelFilter::Warn => log::LevelFilter::Warn,
+            LevelFilter::Info => log::LevelFilter::Info,
+            LevelFilter::Debug => log::LevelFilter::Debug,
+            LevelFilter::Trace => log::LevelFilter::Trace,
+        }
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl From<tracing_core::Level> for LevelFilter {
+    fn from(level: tracing_core::Level) -> Self {
+        match level {
+            tracing_core::Level::ERROR => LevelFilter::Error,
+            tracing_core::Level::WARN => LevelFilter::Warn,
+            tracing_core::Level::INFO => LevelFilter::Info,
+            tracing_core::Level::DEBUG => LevelFilter::Debug,
+            tracing_core::Level::TRACE => LevelFilter::Trace,
+        }
+    }
+}
+
+/// Routes the messages of the `log` and `tracing` crates to the host.
+///
+/// The host logger is created with the [`Log`](struct.Log.html) feature and the URIDs of the entry types. It is configured with a default level and levels for single modules, where the level of the longest matching module path is used. Then, it is installed with [`install`](#method.install) until the returned guard is dropped.
+///
+/// Since the loggers of the `log` and `tracing` crates are process-wide, there is only one active host logger per process, which prints the messages of all plugin instances in the process. Every instance installs it's own logger when it's instantiated and drops the guard before it's destroyed. The most recently installed logger is the active one and when it's guard is dropped, the previous logger that is still installed becomes active again. Therefore, the messages of all instances go to a host that's still alive, but they may go to the host of another instance.
+///
+/// Messages with the `Trace` entry type are formatted into a [`LogBuffer`](struct.LogBuffer.html) on the stack and printed directly, since the specification allows hosts to be called from real-time threads for traces. All other messages may allocate and should not be logged from real-time threads.
+///
+/// ```
+/// # use lv2_log::*;
+/// # use urid::*;
+/// fn instantiate(log: &Log<'static>, urids: LogURIDCollection) -> LoggerGuard {
+///     HostLogger::new(log, urids)
+///         .with_level(LevelFilter::Warn)
+///         .with_module_level("my_plugin::dsp", LevelFilter::Trace)
+///         .install()
+/// }
+/// ```
+pub struct HostLogger {
+    log: Log<'static>,
+    urids: LogURIDCollection,
+    level: LevelFilter,
+    modules: Vec<(String, LevelFilter)>,
+    id: usize,
+}
+
+// SAFETY: The log feature only contains the host's handle and function pointers. The specification allows it's functions to be called from any thread, as long as the plugin instance exists, and the `LoggerGuard` removes the logger before the instance is destroyed.
+unsafe impl Send for HostLogger {}
+unsafe impl Sync for HostLogger {}
+
+/// All installed loggers, the last one being the active one.
+static INSTALLED: RwLock<Vec<HostLogger>> = RwLock::new(Vec::new());
+static NEXT_ID: AtomicUsize = AtomicUsize::new(0);
+
+impl HostLogger {
+    /// Create a new host logger, with a default level of `Info`.
+    pub fn new(log: &Log<'static>, urids: LogURIDCollection) -> Self {
+        Self {
+            log: *log,
+            urids,
+            level: LevelFilter::Info,
+            modules: Vec::new(),
+            id: NEXT_ID.fetch_add(1, Ordering::Relaxed),
+        }
+    }
+
+    /// Set the level of all modules without a level of their own.
+    pub fn with_level(mut self, level: LevelFilter) -> Self {
+        self.level = level;
+        self
+    }
+
+    /// Set the level of a module and all of it's sub-modules.
+    pub fn with_module_level(mut self, module: &str, level: LevelFilter) -> Self {
+        self.modules.retain(|(path, _)| path != module);
+        self.modules.push((module.to_owned(), level));
+        self
+    }
+
+    /// The level of the module with the given path.
+    pub fn level(&self, module: &str) -> LevelFilter {
+        self.modules
+            .iter()
+            .filter(|(path, _)| {
+                module.starts_with(path.as_str())
+                    && (module.len() == path.len() || module[path.len()..].starts_with("::"))
+            })
+            .max_by_key(|(path, _)| path.len())
+            .map(|(_, level)| *level)
+            .unwrap_or(self.level)
+    }
+
+    /// The most verbose level of all modules.
+    pub fn max_level(&self) -> LevelFilter {
+        self.modules
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.level, LevelFilter::max)
+    }
+
+    /// Install the logger and make it the active logger of the process.
+    ///
+    /// The previously active logger stays installed and becomes active again when this logger is removed. Since the host's logging feature is only valid during the lifetime of the plugin instance, the logger is removed when the guard is dropped.
+    ///
+    /// With the `log` feature, this also registers the logger with the `log` crate if no other logger is set. With the `tracing` feature, the [`HostLayer`](struct.HostLayer.html) has to be added to your subscriber.
+    pub fn install(self) -> LoggerGuard {
+        let id = self.id;
+        #[cfg(feature = "log")]
+        let _ = log::set_logger(&LOG_FACADE);
+        let mut installed = INSTALLED.write().unwrap_or_else(|err| err.into_inner());
+        installed.push(self);
+        Self::update_max_level(&installed);
+        LoggerGuard { id }
+    }
+
+    /// Set the maximal level of the `log` crate to the one of the active logger.
+    #[allow(unused_variables)]
+    fn update_max_level(installed: &[HostLogger]) {
+        #[cfg(feature = "log")]
+        log::set_max_level(
+            installed
+                .last()
+                .map_or(LevelFilter::Off, HostLogger::max_level)
+                .into(),
+        );
+    }
+
+    /// Print a message through the host if the level of the module allows it.
+    fn print<F>(&self, level: LevelFilter, module: &str, write: F)
+    where
+        F: FnOnce(&mut dyn Write) -> fmt::Result,
+    {
+        if level == LevelFilter::Off || level > self.level(module) {
+            return;
+        }
+        let log = self.log;
+        match level {
+            LevelFilter::Error => Self::print_owned(&log, self.urids.error.get(), write),
+            LevelFilter::Warn => Self::print_owned(&log, self.urids.warning.get(), write),
+            LevelFilter::Info => Self::print_owned(&log, self.urids.note.get(), write),
+            _ => {
+                let mut buffer = LogBuffer::<1024>::new();
+                let _ = write(&mut buffer);
+                let _ = log.print_raw(self.urids.trace.get(), buffer.as_cstr());
+            }
+        }
+    }
+
+    fn print_owned<F>(log: &Log, entry: u32, write: F)
+    where
+        F: FnOnce(&mut dyn Write) -> fmt::Result,
+    {
+        let mut message = String::new();
+        let _ = write(&mut message);
+        message.push('\0');
+        if let Ok(message) = CStr::from_bytes_until_nul(message.as_bytes()) {
+            let _ = log.print_raw(entry, message);
+        }
+    }
+
+    /// Call a function with the active logger.
+    ///
+    /// This never blocks: If a logger is currently installed or removed, the function isn't called.
+    fn with_current<F: FnOnce(&HostLogger)>(function: F) {
+        if let Ok(installed) = INSTALLED.try_read() {
+            if let Some(logger) = installed.last() {
+                function(logger);
+            }
+        }
+    }
+}
+
+impl fmt::Debug for HostLogger {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("HostLogger")
+            .field("urids", &self.urids)
+            .field("level", &self.level)
+            .field("modules", &self.modules)
+            .finish()
+    }
+}
+
+/// Removes the installed host logger when dropped.
+///
+/// Loggers that were installed before or after this one remain installed.
+#[must_use = "the logger is removed when the guard is dropped"]
+#[derive(Debug)]
+pub struct LoggerGuard {
+    id: usize,
+}
+
+impl Drop for LoggerGuard {
+    fn drop(&mut self) {
+        let mut installed = INSTALLED.write().unwrap_or_else(|err| err.into_inner());
+        installed.retain(|logger| logger.id != self.id);
+        HostLogger::update_max_level(&installed);
+    }
+}
+
+#[cfg(feature = "log")]
+struct LogFacade;
+
+#[cfg(feature = "log")]
+static LOG_FACADE: LogFacade = LogFacade;
+
+#[cfg(feature = "log")]
+impl log::Log for LogFacade {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        let mut enabled = false;
+        HostLogger::with_current(|logger| {
+            enabled = LevelFilter::from(metadata.level()) <= logger.level(metadata.target());
+        });
+        enabled
+    }
+
+    fn log(&self, record: &log::Record) {
+        HostLogger::with_current(|logger| {
+            logger.print(record.level().into(), record.target(), |out| {
+                out.write_fmt(*record.args())
+            });
+        });
+    }
+
+    fn flush(&self) {}
+}
+
+/// A `tracing` layer that prints events through the installed [`HostLogger`](struct.HostLogger.html).
+///
+/// The message of an event is printed first, followed by it's other fields as `key=value` pairs. Spans are ignored.
+///
+/// ```
+/// use tracing_subscriber::prelude::*;
+///
+/// let subscriber = tracing_subscriber::registry().with(lv2_log::HostLayer);
+/// tracing::subscriber::set_global_default(subscriber).unwrap();
+/// ```
+#[cfg(feature = "tracing")]
+#[derive(Clone, Copy, Default, Debug)]
+pub struct HostLayer;
+
+#[cfg(feature = "tracing")]
+impl<S: tracing_core::Subscriber> tracing_subscriber::Layer<S> for HostLayer {
+    fn on_event(
+        &self,
+        event: &tracing_core::Event<'_>,
+        _ctx: tracing_subscriber::layer::Context<'_, S>,
+    ) {
+        let metadata = event.metadata();
+        HostLogger::with_current(|logger| {
+            logger.print((*metadata.level()).into(), metadata.target(), |out| {
+                let mut visitor = FieldVisitor {
+                    out,
+                    result: Ok(()),
+                    empty: true,
+                };
+                event.record(&mut visitor);
+                visitor.result
+            });
+        });
+    }
+}
+
+/// Writes the fields of a `tracing` event.
+#[cfg(feature = "tracing")]
+struct FieldVisitor<'a> {
+    out: &'a mut dyn Write,
+    result: fmt::Result,
+    empty: bool,
+}
+
+#[cfg(feature = "tracing")]
+impl<'a> tracing_core::field::Visit for FieldVisitor<'a> {
+    fn record_debug(&mut self, field: &tracing_core::Field, value: &dyn fmt::Debug) {
+        if self.result.is_err() {
+            return;
+        }
+        let separator = if self.empty { "" } else { " " };
+        self.result = if field.name() == "message" {
+            write!(self.out, "{}{:?}", separator, value)
+        } else {
+            write!(self.out, "{}{}={:?}", separator, field.name(), value)
+        };
+        self.empty = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::MockLog;
+    use std::sync::{Mutex, MutexGuard};
+    use urid::*;
+
+    /// Serializes the tests that install loggers, since they share the installed loggers.
+    fn lock() -> MutexGuard<'static, ()> {
+        static LOCK: Mutex<()> = Mutex::new(());
+        LOCK.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    fn entries(mock: &MockLog) -> Vec<(u32, String)> {
+        mock.take()
+            .into_iter()
+            .map(|(urid, message)| (urid.get(), message))
+            .collect()
+    }
+
+    fn logger() -> (HostLogger, &'static MockLog) {
+        let mock: &'static MockLog = Box::leak(Box::new(MockLog::new()));
+        let urids = LogURIDCollection {
+            error: unsafe { URID::new_unchecked(1) },
+            note: unsafe { URID::new_unchecked(2) },
+            trace: unsafe { URID::new_unchecked(3) },
+            warning: unsafe { URID::new_unchecked(4) },
+        };
+        (HostLogger::new(&mock.log(), urids), mock)
+    }
+
+    #[test]
+    fn test_module_levels() {
+        let (logger, mock) = logger();
+        let logger = logger
+            .with_level(LevelFilter::Warn)
+            .with_module_level("plugin", LevelFilter::Info)
+            .with_module_level("plugin::dsp", LevelFilter::Trace)
+            .with_module_level("plugin::dsp::filter", LevelFilter::Off)
+            .with_module_level("plugin", LevelFilter::Error);
+
+        assert_eq!(LevelFilter::Warn, logger.level("other"));
+        assert_eq!(LevelFilter::Error, logger.level("plugin"));
+        assert_eq!(LevelFilter::Error, logger.level("plugin::ui"));
+        assert_eq!(LevelFilter::Warn, logger.level("plugins"));
+        assert_eq!(LevelFilter::Trace, logger.level("plugin::dsp"));
+        assert_eq!(LevelFilter::Trace, logger.level("plugin::dsp::delay"));
+        assert_eq!(LevelFilter::Off, logger.level("plugin::dsp::filter"));
+        assert_eq!(LevelFilter::Trace, logger.max_level());
+
+        // Disabled messages aren't even formatted.
+        logger.print(LevelFilter::Debug, "plugin::ui", |_| panic!());
+        logger.print(LevelFilter::Error, "plugin::dsp::filter", |_| panic!());
+        assert!(mock.entries().is_empty());
+    }
+
+    #[test]
+    fn test_print() {
+        let (logger, mock) = logger();
+        let logger = logger.with_level(LevelFilter::Trace);
+        let levels = [
+            LevelFilter::Error,
+            LevelFilter::Warn,
+            LevelFilter::Info,
+            LevelFilter::Debug,
+            LevelFilter::Trace,
+        ];
+        for level in levels.iter() {
+            logger.print(*level, "plugin", |out| write!(out, "{:?}", level));
+        }
+
+        assert_eq!(
+            vec![
+                (1, "Error".to_owned()),
+                (4, "Warn".to_owned()),
+                (2, "Info".to_owned()),
+                (3, "Debug".to_owned()),
+                (3, "Trace".to_owned()),
+            ],
+            entries(mock)
+        );
+    }
+
+    fn active() -> Option<usize> {
+        let mut active = None;
+        HostLogger::with_current(|logger| active = Some(logger.id));
+        active
+    }
+
+    #[test]
+    fn test_install() {
+        let _lock = lock();
+        let first = logger().0.install();
+        let second = logger().0.with_level(LevelFilter::Off).install();
+        assert_eq!(Some(second.id), active());
+
+        // The first guard doesn't remove the second logger.
+        drop(first);
+        assert_eq!(Some(second.id), active());
+
+        // The previous logger becomes active again.
+        let third = logger().0.install();
+        let fourth = logger().0.install();
+        assert_eq!(Some(fourth.id), active());
+        drop(fourth);
+        assert_eq!(Some(third.id), active());
+
+        drop(second);
+        drop(third);
+        assert_eq!(None, active());
+    }
+
+    #[cfg(feature = "log")]
+    #[test]
+    fn test_log_facade() {
+        let _lock = lock();
+        let (logger, mock) = logger();
+        let guard = logger
+            .with_level(LevelFilter::Debug)
+            .with_module_level("quiet", LevelFilter::Off)
+            .install();
+        assert_eq!(log::LevelFilter::Debug, log::max_level());
+
+        let long = "x".repeat(2000);
+        log::error!("error {}", 1);
+        log::warn!("warning");
+        log::info!("{}", long);
+        log::debug!("{}", long);
+        log::trace!("hidden");
+        log::error!(target: "quiet", "hidden");
+        assert_eq!(
+            vec![
+                (1, "error 1".to_owned()),
+                (4, "warning".to_owned()),
+                (2, long.clone()),
+                // Debugging messages are formatted into a `LogBuffer` and truncated.
+                (3, long[..1023].to_owned()),
+            ],
+            entries(mock)
+        );
+
+        drop(guard);
+        assert_eq!(log::LevelFilter::Off, log::max_level());
+        log::error!("hidden");
+        assert!(mock.entries().is_empty());
+    }
+
+    #[cfg(feature = "tracing")]
+    #[test]
+    fn test_host_layer() {
+        use tracing_subscriber::prelude::*;
+
+        let _lock = lock();
+        let (logger, mock) = logger();
+        let _guard = logger.with_level(LevelFilter::Debug).install();
+
+        let subscriber = tracing_subscriber::registry().with(HostLayer);
+        tracing::subscriber::with_default(subscriber, || {
+            tracing::error!(code = 2);
+            tracing::warn!(voices = 4, stolen = true, "voice {}", "stolen");
+            tracing::info!(name = "kick", "sample loaded");
+            tracing::debug!("{}", "x".repeat(2000));
+            tracing::trace!("hidden");
+        });
+        assert_eq!(
+            vec![
+                (1, "code=2".to_owned()),
+                (4, "voice stolen voices=4 stolen=true".to_owned()),
+                (2, "sample loaded name=\"kick\"".to_owned()),
+                (3, "x".repeat(1023)),
+            ],
+            entries(mock)
+        );
+    }
+}
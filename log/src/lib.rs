@@ -77,6 +77,34 @@
 //!
 //! lv2_descriptors!(logger);
 //! ```
+//!
+//! # Formatting and real-time safety
+//!
+//! Messages can be formatted with the [`log!`](macro.log.html) macro, which formats them into a fixed-size [`LogBuffer`](struct.LogBuffer.html) on the stack instead of allocating a string.
+//!
+//! Since the host's logging function isn't real-time safe, messages from `run` should be queued in a [`LogQueue`](struct.LogQueue.html). The queue is flushed through the host from a non-real-time context, for example from the worker thread or in `deactivate`.
+//!
+//! # `log` and `tracing` backends
+//!
+//! With the `log` and `tracing` features, messages of these crates can be routed to the host with a [`HostLogger`](struct.HostLogger.html). The `log` backend is registered when the host logger is installed, while the `tracing` backend is a layer called `HostLayer` that has to be added to your subscriber.
+//!
+//! # Testing
+//!
+//! With the `mock` feature, a [`MockLog`](struct.MockLog.html) can create `Log` features that record all messages in memory, so tests can check what a plugin has logged.
+
+mod buffer;
+#[cfg(any(feature = "log", feature = "tracing"))]
+mod facade;
+#[cfg(any(test, feature = "mock"))]
+mod mock;
+mod queue;
+
+pub use buffer::LogBuffer;
+#[cfg(any(feature = "log", feature = "tracing"))]
+pub use facade::*;
+#[cfg(any(test, feature = "mock"))]
+pub use mock::MockLog;
+pub use queue::LogQueue;
 
 use lv2_core::feature::{Feature, ThreadingClass};
 use std::error;
@@ -117,8 +145,24 @@ unsafe impl UriBound for Warning {
 /// indicate the logging level.
 ///
 /// Plugin implementers can implement this on custom URID marker to define and
-/// use additional logging level.
-//
+/// use additional logging level. The URID of the marker is passed to the host
+/// as the type of the message. Hosts that don't know the type may handle the
+/// message like a note or ignore it.
+///
+/// ```
+/// use lv2_log::*;
+/// use urid::*;
+///
+/// /// A custom entry type for messages about the DSP load.
+/// #[uri("urn:my-plugin:log#Load")]
+/// pub struct Load;
+///
+/// impl Entry for Load {}
+///
+/// fn report_load(log: &Log, load: URID<Load>, percent: f32) {
+///     let _ = log!(log, load, "DSP load at {:.0}%", percent);
+/// }
+/// ```
 pub trait Entry: UriBound {}
 
 impl Entry for Error {}
@@ -137,29 +181,48 @@ impl fmt::Display for PrintError {
 }
 impl error::Error for PrintError {}
 
-//replace the lv2_sys::Log_Log to avoid checking if function pointers are null
+/// The raw logging function of the host.
+type PrintFn = unsafe extern "C" fn(
+    handle: lv2_sys::LV2_Log_Handle,
+    type_: lv2_sys::LV2_URID,
+    fmt: *const c_char,
+    ...
+) -> c_int;
+
+/// The raw logging function of the host, with a `va_list`.
+type VPrintFn = unsafe extern "C" fn(
+    handle: lv2_sys::LV2_Log_Handle,
+    type_: lv2_sys::LV2_URID,
+    fmt: *const c_char,
+    ap: *mut c_void, //should be *mut ffi::VaList but it's not yet stable
+) -> c_int;
+
+//replace the lv2_sys::Log_Log to express the function pointers with the
+//null-pointer optimization of `Option`
 #[repr(C)]
 struct LogInternal {
     handle: lv2_sys::LV2_Log_Handle,
-    printf: unsafe extern "C" fn(
-        handle: lv2_sys::LV2_Log_Handle,
-        type_: lv2_sys::LV2_URID,
-        fmt: *const c_char,
-        ...
-    ) -> c_int,
-    //placeholder, not useable yet
-    vprintf: unsafe extern "C" fn(
-        handle: lv2_sys::LV2_Log_Handle,
-        type_: lv2_sys::LV2_URID,
-        fmt: *const c_char,
-        ap: *mut core::ffi::c_void, //should be *mut ffi::VaList but it's not yet stable
-    ) -> c_int,
+    printf: Option<PrintFn>,
+    vprintf: Option<VPrintFn>,
+}
+
+/// Where the messages of a `Log` go to.
+#[derive(Clone, Copy)]
+enum Backend<'a> {
+    /// The feature of the host. `from_feature_ptr` only creates it if `printf` isn't null.
+    Host(&'a LogInternal),
+    #[cfg(any(test, feature = "mock"))]
+    Mock(&'a MockLog),
 }
 
 /// Feature allowing to print log messages through the host.
-#[repr(transparent)]
+///
+/// The feature is only created if the host provides a `printf` function. The `vprintf` function is optional.
+///
+/// Since messages can also be sent to a [`MockLog`](struct.MockLog.html), `Log` isn't `#[repr(transparent)]` anymore and isn't layout-compatible with a pointer to the host's `LV2_Log_Log` struct. Use [`Feature::from_feature_ptr`](../lv2_core/feature/trait.Feature.html#tymethod.from_feature_ptr) to create it from a raw feature pointer instead of casting the pointer.
+#[derive(Clone, Copy)]
 pub struct Log<'a> {
-    internal: &'a LogInternal,
+    backend: Backend<'a>,
 }
 
 unsafe impl<'a> UriBound for Log<'a> {
@@ -168,9 +231,11 @@ unsafe impl<'a> UriBound for Log<'a> {
 
 unsafe impl<'a> Feature for Log<'a> {
     unsafe fn from_feature_ptr(feature: *const c_void, _class: ThreadingClass) -> Option<Self> {
-        (feature as *const LogInternal)
-            .as_ref()
-            .map(|internal| Self { internal })
+        let internal = (feature as *const LogInternal).as_ref()?;
+        internal.printf?;
+        Some(Self {
+            backend: Backend::Host(internal),
+        })
     }
 }
 
@@ -195,20 +260,103 @@ impl<'a> Log<'a> {
     /// `HardRTCapable` plugins should not call this function in
     /// their `run()` context.
     pub fn print_cstr(&self, entry: URID<impl Entry>, message: &CStr) -> Result<(), PrintError> {
-        let res = unsafe {
-            (self.internal.printf)(
-                self.internal.handle,
-                entry.get(),
-                "%s\0" as *const _ as *const c_char,
-                message.as_ptr(),
-            )
-        };
-        if res > 0 {
-            Ok(())
-        } else {
-            Err(PrintError)
+        self.print_raw(entry.get(), message)
+    }
+
+    /// Format a message and send it to the host.
+    ///
+    /// The message is formatted into a [`LogBuffer`](struct.LogBuffer.html) of 1024 bytes on the stack, so no memory is allocated, and longer messages are truncated. Usually, this method is called with the [`log!`](macro.log.html) macro.
+    ///
+    /// # Real-Time safety
+    ///
+    /// Just like [`print_cstr`](#method.print_cstr), this function is not real-time safe since the host may not be. Use a [`LogQueue`](struct.LogQueue.html) to log messages from real-time threads.
+    pub fn print_fmt(
+        &self,
+        entry: URID<impl Entry>,
+        args: fmt::Arguments,
+    ) -> Result<(), PrintError> {
+        self.print_raw(entry.get(), LogBuffer::<1024>::format(args).as_cstr())
+    }
+
+    /// Send a message with a C format string and a `va_list` to the host.
+    ///
+    /// This is useful to forward the messages of C libraries to the host. Returns an error if the host doesn't provide a `vprintf` function.
+    ///
+    /// # Safety
+    ///
+    /// `args` has to be the platform's `va_list` value, as it is passed to C functions, and has to match the format string.
+    pub unsafe fn vprintf(
+        &self,
+        entry: URID<impl Entry>,
+        format: &CStr,
+        args: *mut c_void,
+    ) -> Result<(), PrintError> {
+        match self.backend {
+            Backend::Host(internal) => {
+                let vprintf = internal.vprintf.ok_or(PrintError)?;
+                check_result(vprintf(internal.handle, entry.get(), format.as_ptr(), args))
+            }
+            #[cfg(any(test, feature = "mock"))]
+            Backend::Mock(_) => Err(PrintError),
         }
     }
+
+    /// Check if the host provides a `vprintf` function.
+    pub fn has_vprintf(&self) -> bool {
+        match self.backend {
+            Backend::Host(internal) => internal.vprintf.is_some(),
+            #[cfg(any(test, feature = "mock"))]
+            Backend::Mock(_) => false,
+        }
+    }
+
+    pub(crate) fn print_raw(&self, entry: u32, message: &CStr) -> Result<(), PrintError> {
+        match self.backend {
+            Backend::Host(internal) => {
+                let printf = internal.printf.ok_or(PrintError)?;
+                check_result(unsafe {
+                    printf(
+                        internal.handle,
+                        entry,
+                        "%s\0" as *const _ as *const c_char,
+                        message.as_ptr(),
+                    )
+                })
+            }
+            #[cfg(any(test, feature = "mock"))]
+            Backend::Mock(mock) => {
+                mock.record(entry, message);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Convert the return value of the host's functions.
+fn check_result(res: c_int) -> Result<(), PrintError> {
+    if res > 0 {
+        Ok(())
+    } else {
+        Err(PrintError)
+    }
+}
+
+/// Format a log message and print it.
+///
+/// The first argument is the logger, which can be a [`Log`](struct.Log.html) feature or a [`LogQueue`](struct.LogQueue.html), and the second argument is the URID of the entry type. The remaining arguments are formatted like with `format!`. The macro returns the result of the `print_fmt` method of the logger.
+///
+/// ```
+/// # use lv2_log::*;
+/// # use urid::*;
+/// fn report(log: &Log, urids: &LogURIDCollection, gain: f32) {
+///     let _ = log!(log, urids.note, "gain changed to {:.1} dB", gain);
+/// }
+/// ```
+#[macro_export]
+macro_rules! log {
+    ($logger:expr, $entry:expr, $($arg:tt)+) => {
+        $logger.print_fmt($entry, ::std::format_args!($($arg)+))
+    };
 }
 
 /// A URID cache containing all useful log properties.
@@ -223,18 +371,100 @@ pub struct LogURIDCollection {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use lv2_core::feature::Feature;
+    use std::ptr::null_mut;
+
+    #[uri("urn:rust-lv2:log-test#Custom")]
+    struct Custom;
+
+    impl Entry for Custom {}
+
+    unsafe extern "C" fn vprintf(
+        handle: lv2_sys::LV2_Log_Handle,
+        type_: lv2_sys::LV2_URID,
+        fmt: *const c_char,
+        _ap: *mut c_void,
+    ) -> c_int {
+        let records = &mut *(handle as *mut Vec<(u32, String)>);
+        records.push((type_, CStr::from_ptr(fmt).to_str().unwrap().to_owned()));
+        1
+    }
+
     fn _should_compile() {
-        let fake_logger = unsafe {
-            Log {
-                internal: &*(0xDEFEC8 as *const LogInternal),
-            }
+        let internal = LogInternal {
+            handle: null_mut(),
+            printf: None,
+            vprintf: None,
+        };
+        let fake_logger = Log {
+            backend: Backend::Host(&internal),
         };
         let urid = unsafe { URID::<Error>::new_unchecked(42) };
         let message = CStr::from_bytes_with_nul(b"message\0").unwrap();
 
         let _ = fake_logger.print_cstr(urid, message);
+        let _ = log!(fake_logger, urid, "message {}", 42);
+    }
+
+    #[test]
+    fn test_from_feature_ptr() {
+        let mut records: Vec<(u32, String)> = Vec::new();
+        let mut internal = LogInternal {
+            handle: &mut records as *mut _ as lv2_sys::LV2_Log_Handle,
+            printf: None,
+            vprintf: Some(vprintf),
+        };
+
+        // `printf` is required.
+        let feature = unsafe {
+            Log::from_feature_ptr(
+                &internal as *const _ as *const c_void,
+                ThreadingClass::Audio,
+            )
+        };
+        assert!(feature.is_none());
+        assert!(unsafe { Log::from_feature_ptr(null_mut(), ThreadingClass::Audio) }.is_none());
+
+        // A variadic `printf` can't be defined in stable Rust, so the feature is created without one to test `vprintf`.
+        let log = Log {
+            backend: Backend::Host(&internal),
+        };
+        assert!(log.has_vprintf());
+
+        let custom = unsafe { URID::<Custom>::new_unchecked(42) };
+        let format = CStr::from_bytes_with_nul(b"%d\0").unwrap();
+        assert_eq!(Ok(()), unsafe { log.vprintf(custom, format, null_mut()) });
+        assert_eq!(vec![(42, "%d".to_owned())], records);
+
+        // `vprintf` is optional.
+        internal.vprintf = None;
+        let log = Log {
+            backend: Backend::Host(&internal),
+        };
+        assert!(!log.has_vprintf());
+        assert_eq!(Err(PrintError), unsafe {
+            log.vprintf(custom, format, null_mut())
+        });
     }
 
     #[test]
-    fn it_works() {}
+    fn test_custom_entry() {
+        let mock = MockLog::new();
+        let log = mock.log();
+        let custom = unsafe { URID::<Custom>::new_unchecked(42) };
+        let error = unsafe { URID::<Error>::new_unchecked(1) };
+
+        log!(log, custom, "custom {}", 1).unwrap();
+        log.print_cstr(error, CStr::from_bytes_with_nul(b"error\0").unwrap())
+            .unwrap();
+
+        assert_eq!(vec!["custom 1".to_owned()], mock.messages(custom));
+        assert_eq!(
+            vec![
+                (custom.into_general(), "custom 1".to_owned()),
+                (error.into_general(), "error".to_owned())
+            ],
+            mock.entries()
+        );
+    }
 }
@@ -0,0 +1,73 @@
+use crate::{Backend, Entry, Log};
+use std::ffi::CStr;
+use std::sync::Mutex;
+use urid::URID;
+
+/// An in-memory logger for tests.
+///
+/// The mock creates [`Log`](struct.Log.html) features that record all printed messages instead of sending them to a host, so tests of plugins can assert on what was logged. It's only available with the `mock` feature.
+///
+/// Messages are recorded with their entry type and as they were sent to the host, after truncation. Messages sent with `vprintf` are rejected since they can't be formatted in Rust.
+///
+/// ```
+/// use lv2_log::*;
+/// use urid::*;
+///
+/// let map = HashURIDMapper::new();
+/// let urids: LogURIDCollection = map.populate_collection().unwrap();
+///
+/// let mock = MockLog::new();
+/// let log = mock.log();
+/// log!(log, urids.warning, "clipping at {} dB", 3).unwrap();
+///
+/// assert_eq!(vec!["clipping at 3 dB".to_owned()], mock.messages(urids.warning));
+/// assert!(mock.messages(urids.error).is_empty());
+/// ```
+#[derive(Default, Debug)]
+pub struct MockLog {
+    entries: Mutex<Vec<(URID, String)>>,
+}
+
+impl MockLog {
+    /// Create a new mock without recorded messages.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a log feature that records to this mock.
+    pub fn log(&self) -> Log<'_> {
+        Log {
+            backend: Backend::Mock(self),
+        }
+    }
+
+    /// All recorded messages, with the URID of their entry type.
+    pub fn entries(&self) -> Vec<(URID, String)> {
+        self.lock().clone()
+    }
+
+    /// All recorded messages of the given entry type.
+    pub fn messages(&self, entry: URID<impl Entry>) -> Vec<String> {
+        self.lock()
+            .iter()
+            .filter(|(urid, _)| *urid == entry)
+            .map(|(_, message)| message.clone())
+            .collect()
+    }
+
+    /// Remove and return all recorded messages.
+    pub fn take(&self) -> Vec<(URID, String)> {
+        std::mem::take(&mut *self.lock())
+    }
+
+    pub(crate) fn record(&self, entry: u32, message: &CStr) {
+        if let Some(entry) = URID::new(entry) {
+            self.lock()
+                .push((entry, message.to_string_lossy().into_owned()));
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, Vec<(URID, String)>> {
+        self.entries.lock().unwrap_or_else(|err| err.into_inner())
+    }
+}
@@ -0,0 +1,257 @@
+use crate::{Entry, Log, LogBuffer, PrintError};
+use std::cell::UnsafeCell;
+use std::ffi::CStr;
+use std::fmt;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use urid::URID;
+
+/// A lock-free queue to log messages from real-time threads.
+///
+/// Printing a message through the host is not real-time safe, since the host may allocate memory, take locks or do IO. Instead, a real-time thread can format it's messages into this queue and they are printed later from a non-real-time context, for example from the worker or in `deactivate`.
+///
+/// All memory is allocated when the queue is created: It holds a fixed number of messages, each with a capacity of `N` bytes including the null byte. Longer messages are truncated and messages that don't fit into the queue anymore are dropped and counted. The queue never blocks: If two threads try to push at the same time, one message is dropped, and if two threads try to flush at the same time, one of them returns without printing.
+///
+/// The queue is `Sync`, so it can be shared with the worker in an `Arc` or stored in a static.
+///
+/// ```
+/// use lv2_log::*;
+/// use std::fmt::Write;
+/// use urid::*;
+///
+/// let queue = LogQueue::<128>::new(2);
+/// let note = unsafe { URID::<Note>::new_unchecked(1) };
+///
+/// // In `run`:
+/// log!(queue, note, "gain changed to {} dB", -6.0).unwrap();
+/// log!(queue, note, "gain changed to {} dB", -3.0).unwrap();
+/// assert!(log!(queue, note, "gain changed to {} dB", 0.0).is_err());
+///
+/// // In a non-real-time context. Usually, you would call `flush` with the host's `Log` feature.
+/// let mut messages = String::new();
+/// let printed = queue.flush_with(|_, message| {
+///     writeln!(messages, "{}", message.to_str().unwrap()).unwrap();
+/// });
+/// assert_eq!(2, printed);
+/// assert_eq!("gain changed to -6 dB\ngain changed to -3 dB\n", messages);
+/// assert_eq!(1, queue.take_dropped());
+/// ```
+pub struct LogQueue<const N: usize> {
+    slots: Box<[UnsafeCell<(u32, LogBuffer<N>)>]>,
+    write: AtomicUsize,
+    read: AtomicUsize,
+    pushing: AtomicBool,
+    flushing: AtomicBool,
+    dropped: AtomicUsize,
+}
+
+// The slots are only accessed by the thread that holds the `pushing` or `flushing` flag and the indices assure that these threads never access the same slot.
+unsafe impl<const N: usize> Sync for LogQueue<N> {}
+
+impl<const N: usize> LogQueue<N> {
+    /// Create a new queue that can hold `capacity` messages.
+    ///
+    /// This allocates memory and therefore isn't real-time safe.
+    pub fn new(capacity: usize) -> Self {
+        const { assert!(N > 0, "a log buffer needs space for the null byte") };
+        Self {
+            slots: (0..capacity)
+                .map(|_| UnsafeCell::new((0, LogBuffer::new())))
+                .collect(),
+            write: AtomicUsize::new(0),
+            read: AtomicUsize::new(0),
+            pushing: AtomicBool::new(false),
+            flushing: AtomicBool::new(false),
+            dropped: AtomicUsize::new(0),
+        }
+    }
+
+    /// The number of messages the queue can hold.
+    pub fn capacity(&self) -> usize {
+        self.slots.len()
+    }
+
+    /// The number of messages that are waiting to be printed.
+    pub fn len(&self) -> usize {
+        self.write
+            .load(Ordering::Acquire)
+            .wrapping_sub(self.read.load(Ordering::Acquire))
+    }
+
+    /// Check if no messages are waiting to be printed.
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Format a message and queue it.
+    ///
+    /// This method is real-time safe. If the queue is full, the message is dropped, counted and an error is returned.
+    ///
+    /// This method has the same signature as [`Log::print_fmt`](struct.Log.html#method.print_fmt), so it can be used with the [`log!`](macro.log.html) macro.
+    pub fn print_fmt(
+        &self,
+        entry: URID<impl Entry>,
+        args: fmt::Arguments,
+    ) -> Result<(), PrintError> {
+        if self
+            .pushing
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            return Err(PrintError);
+        }
+
+        let write = self.write.load(Ordering::Relaxed);
+        let read = self.read.load(Ordering::Acquire);
+        let result = if write.wrapping_sub(read) < self.slots.len() {
+            let slot = unsafe { &mut *self.slots[write % self.slots.len()].get() };
+            slot.0 = entry.get();
+            slot.1.clear();
+            let _ = fmt::write(&mut slot.1, args);
+            self.write.store(write.wrapping_add(1), Ordering::Release);
+            Ok(())
+        } else {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+            Err(PrintError)
+        };
+
+        self.pushing.store(false, Ordering::Release);
+        result
+    }
+
+    /// Print all queued messages through the host.
+    ///
+    /// This method is not real-time safe. It returns the number of printed messages.
+    pub fn flush(&self, log: &Log) -> usize {
+        self.flush_with(|entry, message| {
+            let _ = log.print_raw(entry, message);
+        })
+    }
+
+    /// Pass all queued messages to a function, together with the raw URID of their entry type.
+    ///
+    /// This can be used to print messages somewhere else than the host's log. It returns the number of handled messages.
+    pub fn flush_with<F: FnMut(u32, &CStr)>(&self, mut handler: F) -> usize {
+        if self
+            .flushing
+            .compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+            .is_err()
+        {
+            return 0;
+        }
+
+        let mut count = 0;
+        let mut read = self.read.load(Ordering::Relaxed);
+        while read != self.write.load(Ordering::Acquire) {
+            let slot = unsafe { &mut *self.slots[read % self.slots.len()].get() };
+            handler(slot.0, slot.1.as_cstr());
+            read = read.wrapping_add(1);
+            self.read.store(read, Ordering::Release);
+            count += 1;
+        }
+
+        self.flushing.store(false, Ordering::Release);
+        count
+    }
+
+    /// The number of messages that were dropped since the counter was last reset.
+    pub fn dropped(&self) -> usize {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Return the number of dropped messages and reset the counter.
+    pub fn take_dropped(&self) -> usize {
+        self.dropped.swap(0, Ordering::Relaxed)
+    }
+}
+
+impl<const N: usize> fmt::Debug for LogQueue<N> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LogQueue")
+            .field("capacity", &self.capacity())
+            .field("len", &self.len())
+            .field("dropped", &self.dropped())
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Error, MockLog, Note};
+    use std::sync::Arc;
+    use std::thread;
+
+    #[test]
+    fn test_queue() {
+        let queue = LogQueue::<8>::new(3);
+        let note = unsafe { URID::<Note>::new_unchecked(1) };
+        let error = unsafe { URID::<Error>::new_unchecked(2) };
+        assert_eq!(3, queue.capacity());
+        assert!(queue.is_empty());
+
+        // Wrap around a few times.
+        for round in 0..4 {
+            assert_eq!(Ok(()), crate::log!(queue, note, "{}", round));
+            assert_eq!(Ok(()), crate::log!(queue, error, "too long message"));
+            assert_eq!(2, queue.len());
+
+            let mut messages = Vec::new();
+            assert_eq!(
+                2,
+                queue.flush_with(|entry, message| {
+                    messages.push((entry, message.to_str().unwrap().to_owned()))
+                })
+            );
+            assert_eq!(
+                vec![(1, round.to_string()), (2, "too lon".to_owned())],
+                messages
+            );
+            assert!(queue.is_empty());
+        }
+
+        for _ in 0..3 {
+            crate::log!(queue, note, "message").unwrap();
+        }
+        assert_eq!(Err(PrintError), crate::log!(queue, note, "dropped"));
+        assert_eq!(Err(PrintError), crate::log!(queue, note, "dropped"));
+        assert_eq!(2, queue.dropped());
+        assert_eq!(2, queue.take_dropped());
+        assert_eq!(0, queue.dropped());
+        assert_eq!(3, queue.flush_with(|_, _| ()));
+
+        // Flush through a log feature.
+        let mock = MockLog::new();
+        crate::log!(queue, error, "failed").unwrap();
+        assert_eq!(1, queue.flush(&mock.log()));
+        assert_eq!(vec!["failed".to_owned()], mock.messages(error));
+    }
+
+    #[test]
+    fn test_threads() {
+        const MESSAGES: usize = 1000;
+
+        let queue = Arc::new(LogQueue::<16>::new(64));
+        let producer = {
+            let queue = queue.clone();
+            thread::spawn(move || {
+                let note = unsafe { URID::<Note>::new_unchecked(1) };
+                for i in 0..MESSAGES {
+                    while crate::log!(queue, note, "{}", i).is_err() {
+                        thread::yield_now();
+                    }
+                }
+            })
+        };
+
+        let mut received = Vec::new();
+        while received.len() < MESSAGES {
+            queue.flush_with(|_, message| {
+                received.push(message.to_str().unwrap().parse::<usize>().unwrap())
+            });
+        }
+        producer.join().unwrap();
+
+        assert_eq!((0..MESSAGES).collect::<Vec<usize>>(), received);
+    }
+}
@@ -0,0 +1,285 @@
+//! Thin but safe wrapper for the options feature.
+use crate::{OptionsContext, OptionsErr};
+use atom::prelude::*;
+use atom::space::Space;
+use core::feature::Feature;
+use core::prelude::*;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use urid::*;
+
+/// Host feature that provides a static list of options.
+///
+/// The host passes this feature when instantiating a plugin. The contained values are only guaranteed to be valid while the plugin's [`new`](../lv2_core/plugin/trait.Plugin.html#tymethod.new) method runs, so they have to be copied if the plugin needs them later on.
+#[repr(transparent)]
+pub struct Options<'a> {
+    internal: &'a sys::LV2_Options_Option,
+}
+
+unsafe impl<'a> UriBound for Options<'a> {
+    const URI: &'static [u8] = sys::LV2_OPTIONS__options;
+}
+
+unsafe impl<'a> Feature for Options<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self> {
+        if class != ThreadingClass::Audio {
+            (feature as *const sys::LV2_Options_Option)
+                .as_ref()
+                .map(|internal| Self { internal })
+        } else {
+            panic!("The options feature isn't allowed in the audio threading class");
+        }
+    }
+}
+
+impl<'a> Options<'a> {
+    /// Create the feature from the first element of a zero-terminated options array.
+    ///
+    /// # Safety
+    ///
+    /// The array has to be terminated by an option with a key and value of zero, and the value of every option has to point to `size` readable bytes that stay valid for the lifetime `'a`.
+    pub unsafe fn new(internal: &'a sys::LV2_Options_Option) -> Self {
+        Self { internal }
+    }
+
+    /// Iterate over all options that were passed by the host.
+    pub fn iter(&self) -> OptionsIter<'a> {
+        OptionsIter {
+            current: self.internal,
+            lifetime: PhantomData,
+        }
+    }
+
+    /// Try to find the instance option with the given key.
+    pub fn get<K: ?Sized>(&self, key: URID<K>) -> Option<OptionValue<'a>> {
+        self.iter()
+            .find(|option| option.context() == OptionsContext::Instance && option.key() == key)
+    }
+
+    /// Try to find the instance option with the given key and interpret it as an atom.
+    ///
+    /// `None` is returned if there is no such option or if it doesn't have the desired type.
+    pub fn read<K: ?Sized, A: Atom<'a, 'a>>(
+        &self,
+        key: URID<K>,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Option<A::ReadHandle> {
+        self.get(key)?.read(urid, parameter).ok()
+    }
+}
+
+/// Iterator over the options of the [`Options`](struct.Options.html) feature.
+///
+/// Options with an invalid context or an invalid type are skipped.
+pub struct OptionsIter<'a> {
+    current: *const sys::LV2_Options_Option,
+    lifetime: PhantomData<&'a sys::LV2_Options_Option>,
+}
+
+impl<'a> Iterator for OptionsIter<'a> {
+    type Item = OptionValue<'a>;
+
+    fn next(&mut self) -> Option<OptionValue<'a>> {
+        loop {
+            let raw = unsafe { self.current.as_ref() }?;
+            if raw.key == 0 && raw.value.is_null() {
+                return None;
+            }
+            self.current = unsafe { self.current.add(1) };
+            // The values are valid, which is required by `Options::new`.
+            if let Some(value) = unsafe { OptionValue::from_raw(raw) } {
+                return Some(value);
+            }
+        }
+    }
+}
+
+/// Reading handle for a single option.
+///
+/// This handle contains the subject, the key, the type and the data of an option and can interpret the data as an atom.
+pub struct OptionValue<'a> {
+    context: OptionsContext,
+    key: URID,
+    type_: URID,
+    body: Space<'a>,
+}
+
+impl<'a> OptionValue<'a> {
+    /// Create a new reading handle with the given context, key, type and data.
+    pub fn new<K: ?Sized, T: ?Sized>(
+        context: OptionsContext,
+        key: URID<K>,
+        type_: URID<T>,
+        body: Space<'a>,
+    ) -> Self {
+        Self {
+            context,
+            key: key.into_general(),
+            type_: type_.into_general(),
+            body,
+        }
+    }
+
+    /// Create a reading handle from a raw option.
+    ///
+    /// `None` is returned if the context, the key or the type of the option is invalid.
+    ///
+    /// # Safety
+    ///
+    /// If `value` isn't null, it has to point to `size` readable bytes that stay valid for the lifetime `'a`.
+    pub unsafe fn from_raw(raw: &'a sys::LV2_Options_Option) -> Option<Self> {
+        let context = OptionsContext::from_raw(raw.context, raw.subject)?;
+        let key = URID::new(raw.key)?;
+        let type_ = URID::new(raw.type_)?;
+        let body = if raw.value.is_null() {
+            Space::from_slice(&[])
+        } else {
+            Space::from_slice(std::slice::from_raw_parts(
+                raw.value as *const u8,
+                raw.size as usize,
+            ))
+        };
+        Some(Self::new(context, key, type_, body))
+    }
+
+    /// Return the subject of the option.
+    pub fn context(&self) -> OptionsContext {
+        self.context
+    }
+
+    /// Return the key of the option.
+    pub fn key(&self) -> URID {
+        self.key
+    }
+
+    /// Return the type of the option's value.
+    pub fn type_(&self) -> URID {
+        self.type_
+    }
+
+    /// Return the data of the option.
+    pub fn body(&self) -> Space<'a> {
+        self.body
+    }
+
+    /// Try to interpret the option as an atom.
+    ///
+    /// This works like any atom reader: You pass the URID of the atom type as well as the type-specific argument, and if the desired type is the actual type of the data, a read handle is returned.
+    ///
+    /// If the desired and actual data types don't match, `Err(OptionsErr::BadValue)` is returned.
+    pub fn read<A: Atom<'a, 'a>>(
+        &self,
+        urid: URID<A>,
+        parameter: A::ReadParameter,
+    ) -> Result<A::ReadHandle, OptionsErr> {
+        if urid == self.type_ {
+            A::read(self.body, parameter).ok_or(OptionsErr::BadValue)
+        } else {
+            Err(OptionsErr::BadValue)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use std::ffi::c_void;
+    use std::ptr::null;
+    use urid::*;
+
+    fn option<T>(
+        context: sys::LV2_Options_Context,
+        subject: u32,
+        key: u32,
+        type_: u32,
+        value: &T,
+    ) -> sys::LV2_Options_Option {
+        sys::LV2_Options_Option {
+            context,
+            subject,
+            key,
+            size: std::mem::size_of::<T>() as u32,
+            type_,
+            value: value as *const T as *const c_void,
+        }
+    }
+
+    #[test]
+    fn test_options_feature() {
+        let map = HashURIDMapper::new();
+        let urids = map.populate_collection::<AtomURIDCollection>().unwrap();
+        let options_urids = map.populate_collection::<OptionsURIDCollection>().unwrap();
+
+        let sample_rate: f32 = 44100.0;
+        let port_value: i32 = 42;
+        let bad_type: f32 = 1.0;
+
+        let raw_options = [
+            option(
+                sys::LV2_Options_Context_LV2_OPTIONS_PORT,
+                1,
+                options_urids.sample_rate.get(),
+                urids.float.get(),
+                &48000.0f32,
+            ),
+            option(
+                sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                0,
+                options_urids.sample_rate.get(),
+                urids.float.get(),
+                &sample_rate,
+            ),
+            option(
+                sys::LV2_Options_Context_LV2_OPTIONS_PORT,
+                3,
+                options_urids.supported_option.get(),
+                urids.int.get(),
+                &port_value,
+            ),
+            option(
+                sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                0,
+                options_urids.required_option.get(),
+                0,
+                &bad_type,
+            ),
+            sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                subject: 0,
+                key: 0,
+                size: 0,
+                type_: 0,
+                value: null(),
+            },
+        ];
+
+        let options = unsafe { Options::new(&raw_options[0]) };
+        assert_eq!(3, options.iter().count());
+
+        let value = options.get(options_urids.sample_rate).unwrap();
+        assert_eq!(OptionsContext::Instance, value.context());
+        assert_eq!(urids.float, value.type_());
+        assert_eq!(44100.0, value.read(urids.float, ()).unwrap());
+        assert_eq!(Err(OptionsErr::BadValue), value.read(urids.int, ()));
+
+        assert_eq!(
+            Some(44100.0),
+            options.read(options_urids.sample_rate, urids.float, ())
+        );
+        assert_eq!(
+            None,
+            options.read(options_urids.sample_rate, urids.double, ())
+        );
+        assert!(options.get(options_urids.supported_option).is_none());
+        assert!(options.get(options_urids.required_option).is_none());
+
+        let port_option = options
+            .iter()
+            .find(|option| option.context() == OptionsContext::Port(3))
+            .unwrap();
+        assert_eq!(options_urids.supported_option, port_option.key());
+        assert_eq!(42, port_option.read(urids.int, ()).unwrap());
+    }
+}
@@ -0,0 +1,299 @@
+use crate::{OptionValue, OptionsContext, OptionsErr};
+use core::extension::ExtensionDescriptor;
+use core::prelude::*;
+use std::ffi::c_void;
+use std::marker::PhantomData;
+use urid::*;
+
+/// A plugin extension that lets the host get and set options of the plugin dynamically.
+///
+/// This extension contains two new methods: [`get`](#tymethod.get) and [`set`](#tymethod.set). The host calls them with a list of options and the framework calls these methods once for every option in the list.
+pub trait OptionsInterface: Plugin {
+    /// Get the value of an option.
+    ///
+    /// The request contains the subject and the key of the requested option. If the plugin knows the option, it has to [`respond`](struct.OptionRequest.html#method.respond) with a reference to the value.
+    fn get<'a>(&'a self, request: &mut OptionRequest<'a>) -> Result<(), OptionsErr>;
+
+    /// Set the value of an option.
+    ///
+    /// This method is in the instantiation threading class, so no other method of the plugin is called concurrently.
+    fn set(&mut self, option: OptionValue) -> Result<(), OptionsErr>;
+}
+
+/// Handle to answer an option request of the host.
+///
+/// It contains the subject and the key of the requested option and is used to return the value to the host.
+pub struct OptionRequest<'a> {
+    context: OptionsContext,
+    key: URID,
+    internal: &'a mut sys::LV2_Options_Option,
+}
+
+impl<'a> OptionRequest<'a> {
+    /// Create a request handle for the given raw option.
+    ///
+    /// `None` is returned if the context or the key of the option is invalid.
+    pub fn new(internal: &'a mut sys::LV2_Options_Option) -> Option<Self> {
+        let context = OptionsContext::from_raw(internal.context, internal.subject)?;
+        let key = URID::new(internal.key)?;
+        Some(Self {
+            context,
+            key,
+            internal,
+        })
+    }
+
+    /// Return the subject of the requested option.
+    pub fn context(&self) -> OptionsContext {
+        self.context
+    }
+
+    /// Return the key of the requested option.
+    pub fn key(&self) -> URID {
+        self.key
+    }
+
+    /// Answer the request with the given value.
+    ///
+    /// The value is passed to the host as a reference, which is why it needs to live as long as the plugin is borrowed. The type URID describes the type of the data, for example the `Float` atom for a `f32` value.
+    pub fn respond<T: ?Sized, V: ?Sized>(&mut self, type_: URID<T>, value: &'a V) {
+        self.internal.type_ = type_.get();
+        self.internal.size = std::mem::size_of_val(value) as u32;
+        self.internal.value = value as *const V as *const c_void;
+    }
+}
+
+/// Raw wrapper of the [`OptionsInterface`](trait.OptionsInterface.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct OptionsDescriptor<P: OptionsInterface> {
+    plugin: PhantomData<P>,
+}
+
+unsafe impl<P: OptionsInterface> UriBound for OptionsDescriptor<P> {
+    const URI: &'static [u8] = sys::LV2_OPTIONS__interface;
+}
+
+impl<P: OptionsInterface> OptionsDescriptor<P> {
+    /// Handle a get request by the host.
+    ///
+    /// The plugin's [`get`](trait.OptionsInterface.html#tymethod.get) method is called for every option in the zero-terminated list and the returned statuses are combined.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_get(
+        instance: sys::LV2_Handle,
+        options: *mut sys::LV2_Options_Option,
+    ) -> u32 {
+        let plugin: &P = if let Some(plugin) = (instance as *const P).as_ref() {
+            plugin
+        } else {
+            return sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN;
+        };
+
+        let mut status = sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS;
+        let mut current = options;
+        while let Some(raw) = current.as_mut() {
+            if raw.key == 0 {
+                break;
+            }
+            status |= match OptionRequest::new(raw) {
+                Some(mut request) => OptionsErr::into(plugin.get(&mut request)),
+                None => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT,
+            };
+            current = current.add(1);
+        }
+        status
+    }
+
+    /// Handle a set request by the host.
+    ///
+    /// The plugin's [`set`](trait.OptionsInterface.html#tymethod.set) method is called for every option in the zero-terminated list and the returned statuses are combined.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_set(
+        instance: sys::LV2_Handle,
+        options: *const sys::LV2_Options_Option,
+    ) -> u32 {
+        let plugin: &mut P = if let Some(plugin) = (instance as *mut P).as_mut() {
+            plugin
+        } else {
+            return sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN;
+        };
+
+        let mut status = sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS;
+        let mut current = options;
+        while let Some(raw) = current.as_ref() {
+            if raw.key == 0 {
+                break;
+            }
+            status |= match OptionValue::from_raw(raw) {
+                Some(option) => OptionsErr::into(plugin.set(option)),
+                None => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE,
+            };
+            current = current.add(1);
+        }
+        status
+    }
+}
+
+impl<P: OptionsInterface> ExtensionDescriptor for OptionsDescriptor<P> {
+    type ExtensionInterface = sys::LV2_Options_Interface;
+
+    const INTERFACE: &'static sys::LV2_Options_Interface = &sys::LV2_Options_Interface {
+        get: Some(Self::extern_get),
+        set: Some(Self::extern_set),
+    };
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use atom::prelude::*;
+    use lv2_core::prelude::*;
+    use std::ffi::c_void;
+    use std::ptr::{null, null_mut};
+    use urid::*;
+
+    #[derive(URIDCollection)]
+    struct URIDs {
+        atom: AtomURIDCollection,
+        options: OptionsURIDCollection,
+    }
+
+    #[uri("urn:optionful")]
+    struct Optionful {
+        urids: URIDs,
+        sample_rate: f32,
+    }
+
+    impl Plugin for Optionful {
+        type InitFeatures = ();
+        type AudioFeatures = ();
+        type Ports = ();
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            None
+        }
+
+        fn run(&mut self, _: &mut (), _: &mut (), _: u32) {}
+    }
+
+    impl OptionsInterface for Optionful {
+        fn get<'a>(&'a self, request: &mut OptionRequest<'a>) -> Result<(), OptionsErr> {
+            if request.context() != OptionsContext::Instance {
+                return Err(OptionsErr::BadSubject);
+            }
+            if request.key() != self.urids.options.sample_rate {
+                return Err(OptionsErr::BadKey);
+            }
+            request.respond(self.urids.atom.float, &self.sample_rate);
+            Ok(())
+        }
+
+        fn set(&mut self, option: OptionValue) -> Result<(), OptionsErr> {
+            if option.key() != self.urids.options.sample_rate {
+                return Err(OptionsErr::BadKey);
+            }
+            self.sample_rate = option.read(self.urids.atom.float, ())?;
+            Ok(())
+        }
+    }
+
+    fn empty_option(context: sys::LV2_Options_Context, key: u32) -> sys::LV2_Options_Option {
+        sys::LV2_Options_Option {
+            context,
+            subject: 0,
+            key,
+            size: 0,
+            type_: 0,
+            value: null(),
+        }
+    }
+
+    #[test]
+    fn test_get_and_set() {
+        type Descriptor = OptionsDescriptor<Optionful>;
+
+        let map = HashURIDMapper::new();
+        let mut plugin = Optionful {
+            urids: map.populate_collection().unwrap(),
+            sample_rate: 44100.0,
+        };
+        let sample_rate_urid = plugin.urids.options.sample_rate.get();
+        let float_urid = plugin.urids.atom.float.get();
+        let int_urid = plugin.urids.atom.int.get();
+        let handle = &mut plugin as *mut Optionful as sys::LV2_Handle;
+
+        let mut requests = [
+            empty_option(
+                sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                sample_rate_urid,
+            ),
+            empty_option(sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE, 0),
+        ];
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS, unsafe {
+            Descriptor::extern_get(handle, requests.as_mut_ptr())
+        });
+        assert_eq!(float_urid, requests[0].type_);
+        assert_eq!(4, requests[0].size);
+        assert_eq!(44100.0, unsafe { *(requests[0].value as *const f32) });
+
+        let mut requests = [
+            empty_option(sys::LV2_Options_Context_LV2_OPTIONS_PORT, sample_rate_urid),
+            empty_option(sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE, int_urid),
+            empty_option(sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE, 0),
+        ];
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT
+                | sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY,
+            unsafe { Descriptor::extern_get(handle, requests.as_mut_ptr()) }
+        );
+
+        let new_sample_rate: f32 = 48000.0;
+        let options = [
+            sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                subject: 0,
+                key: sample_rate_urid,
+                size: 4,
+                type_: float_urid,
+                value: &new_sample_rate as *const f32 as *const c_void,
+            },
+            empty_option(sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE, 0),
+        ];
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS, unsafe {
+            Descriptor::extern_set(handle, options.as_ptr())
+        });
+        assert_eq!(48000.0, plugin.sample_rate);
+
+        let options = [
+            sys::LV2_Options_Option {
+                context: sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE,
+                subject: 0,
+                key: sample_rate_urid,
+                size: 4,
+                type_: int_urid,
+                value: &42i32 as *const i32 as *const c_void,
+            },
+            empty_option(sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE, 0),
+        ];
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE, unsafe {
+            Descriptor::extern_set(
+                &mut plugin as *mut Optionful as sys::LV2_Handle,
+                options.as_ptr(),
+            )
+        });
+        assert_eq!(48000.0, plugin.sample_rate);
+
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_get(null_mut(), requests.as_mut_ptr())
+        });
+        assert_eq!(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN, unsafe {
+            Descriptor::extern_set(null_mut(), options.as_ptr())
+        });
+    }
+}
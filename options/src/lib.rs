@@ -0,0 +1,290 @@
+//! LV2 specification for options, a mechanism for exchanging instance-specific parameters between a host and a plugin.
+//!
+//! Options are passed to the plugin in two ways: The host may provide the [`Options`](struct.Options.html) feature when instantiating a plugin, which contains a static list of options like the maximal block length or the sample rate, and a plugin may implement the [`OptionsInterface`](trait.OptionsInterface.html) extension to let the host get and set options dynamically.
+//!
+//! See the original [specification](https://lv2plug.in/ns/ext/options) for more information.
+//!
+//! # Example
+//!
+//! This example plugin reads the sample rate from the options feature and exposes it via the options interface.
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_core::prelude::*;
+//! use lv2_options::*;
+//! use lv2_urid::*;
+//! use urid::*;
+//!
+//! #[derive(FeatureCollection)]
+//! pub struct Features<'a> {
+//!     map: LV2Map<'a>,
+//!     options: Options<'a>,
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! pub struct URIDs {
+//!     atom: AtomURIDCollection,
+//!     options: OptionsURIDCollection,
+//! }
+//!
+//! #[uri("urn:my-plugin")]
+//! pub struct MyPlugin {
+//!     urids: URIDs,
+//!     sample_rate: f32,
+//! }
+//!
+//! impl Plugin for MyPlugin {
+//!     type Ports = ();
+//!     type InitFeatures = Features<'static>;
+//!     type AudioFeatures = ();
+//!
+//!     fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+//!         let urids: URIDs = features.map.populate_collection()?;
+//!         // Options are only valid during instantiation, so the value is copied.
+//!         let sample_rate = features
+//!             .options
+//!             .read(urids.options.sample_rate, urids.atom.float, ())?;
+//!         Some(Self { urids, sample_rate })
+//!     }
+//!
+//!     fn run(&mut self, _: &mut (), _: &mut (), _: u32) {}
+//!
+//!     fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+//!         match_extensions!(uri, OptionsDescriptor<Self>)
+//!     }
+//! }
+//!
+//! impl OptionsInterface for MyPlugin {
+//!     fn get<'a>(&'a self, request: &mut OptionRequest<'a>) -> Result<(), OptionsErr> {
+//!         if request.context() != OptionsContext::Instance {
+//!             return Err(OptionsErr::BadSubject);
+//!         }
+//!         if request.key() != self.urids.options.sample_rate {
+//!             return Err(OptionsErr::BadKey);
+//!         }
+//!         request.respond(self.urids.atom.float, &self.sample_rate);
+//!         Ok(())
+//!     }
+//!
+//!     fn set(&mut self, option: OptionValue) -> Result<(), OptionsErr> {
+//!         if option.key() != self.urids.options.sample_rate {
+//!             return Err(OptionsErr::BadKey);
+//!         }
+//!         self.sample_rate = option.read(self.urids.atom.float, ())?;
+//!         Ok(())
+//!     }
+//! }
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+
+mod feature;
+pub use feature::*;
+
+mod interface;
+pub use interface::*;
+
+use urid::*;
+
+/// All options URI bounds.
+pub mod options {
+    use urid::UriBound;
+
+    /// The class of options.
+    pub struct OptionClass;
+    unsafe impl UriBound for OptionClass {
+        const URI: &'static [u8] = sys::LV2_OPTIONS__Option;
+    }
+
+    /// Property to declare an option the plugin can't be instantiated without.
+    pub struct RequiredOption;
+    unsafe impl UriBound for RequiredOption {
+        const URI: &'static [u8] = sys::LV2_OPTIONS__requiredOption;
+    }
+
+    /// Property to declare an option the plugin understands.
+    pub struct SupportedOption;
+    unsafe impl UriBound for SupportedOption {
+        const URI: &'static [u8] = sys::LV2_OPTIONS__supportedOption;
+    }
+
+    /// The sample rate of the instance, a commonly passed option.
+    pub struct SampleRate;
+    unsafe impl UriBound for SampleRate {
+        const URI: &'static [u8] = sys::LV2_PARAMETERS__sampleRate;
+    }
+}
+
+use options::*;
+
+/// A URID cache containing all options properties.
+#[derive(URIDCollection)]
+pub struct OptionsURIDCollection {
+    pub option_class: URID<OptionClass>,
+    pub required_option: URID<RequiredOption>,
+    pub supported_option: URID<SupportedOption>,
+    pub sample_rate: URID<SampleRate>,
+}
+
+/// Kinds of errors that may occur in the crate.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionsErr {
+    /// The kind of the error is unknown or doesn't have a representation.
+    Unknown,
+    /// The subject of the option is invalid or unsupported.
+    BadSubject,
+    /// The key of the option is invalid or unsupported.
+    BadKey,
+    /// The value of the option is invalid or unsupported.
+    BadValue,
+}
+
+impl OptionsErr {
+    /// Convert a raw status flag to a result or possible error value.
+    pub fn from(value: sys::LV2_Options_Status) -> Result<(), OptionsErr> {
+        match value {
+            sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS => Ok(()),
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT => Err(OptionsErr::BadSubject),
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY => Err(OptionsErr::BadKey),
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE => Err(OptionsErr::BadValue),
+            _ => Err(OptionsErr::Unknown),
+        }
+    }
+
+    /// Convert a result to a raw status flag.
+    pub fn into(result: Result<(), OptionsErr>) -> sys::LV2_Options_Status {
+        match result {
+            Ok(()) => sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS,
+            Err(OptionsErr::BadSubject) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT,
+            Err(OptionsErr::BadKey) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY,
+            Err(OptionsErr::BadValue) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE,
+            Err(OptionsErr::Unknown) => sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN,
+        }
+    }
+}
+
+/// The subject an option applies to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OptionsContext {
+    /// The option applies to the plugin instance itself.
+    Instance,
+    /// The option applies to a named resource, identified by its URID.
+    Resource(URID),
+    /// The option applies to a blank node, identified by a local id.
+    Blank(u32),
+    /// The option applies to the port with the given index.
+    Port(u32),
+}
+
+impl OptionsContext {
+    /// Construct a context from the raw context and subject fields of an option.
+    ///
+    /// `None` is returned if the raw context is unknown or if a resource context has an invalid URID as it's subject.
+    pub fn from_raw(context: sys::LV2_Options_Context, subject: u32) -> Option<Self> {
+        match context {
+            sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE => Some(OptionsContext::Instance),
+            sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE => {
+                URID::new(subject).map(OptionsContext::Resource)
+            }
+            sys::LV2_Options_Context_LV2_OPTIONS_BLANK => Some(OptionsContext::Blank(subject)),
+            sys::LV2_Options_Context_LV2_OPTIONS_PORT => Some(OptionsContext::Port(subject)),
+            _ => None,
+        }
+    }
+
+    /// Convert the context to the raw context and subject fields of an option.
+    pub fn into_raw(self) -> (sys::LV2_Options_Context, u32) {
+        match self {
+            OptionsContext::Instance => (sys::LV2_Options_Context_LV2_OPTIONS_INSTANCE, 0),
+            OptionsContext::Resource(urid) => {
+                (sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE, urid.get())
+            }
+            OptionsContext::Blank(id) => (sys::LV2_Options_Context_LV2_OPTIONS_BLANK, id),
+            OptionsContext::Port(index) => (sys::LV2_Options_Context_LV2_OPTIONS_PORT, index),
+        }
+    }
+}
+
+/// Prelude of `lv2_options` for wildcard usage.
+pub mod prelude {
+    pub use crate::options::*;
+    pub use crate::{
+        OptionRequest, OptionValue, Options, OptionsContext, OptionsDescriptor, OptionsErr,
+        OptionsInterface, OptionsURIDCollection,
+    };
+}
+
+#[cfg(test)]
+mod test {
+    use crate::*;
+
+    #[test]
+    fn test_options_conversion() {
+        assert_eq!(
+            Ok(()),
+            OptionsErr::from(sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS)
+        );
+        assert_eq!(
+            Err(OptionsErr::BadSubject),
+            OptionsErr::from(sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT)
+        );
+        assert_eq!(
+            Err(OptionsErr::BadKey),
+            OptionsErr::from(sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY)
+        );
+        assert_eq!(
+            Err(OptionsErr::BadValue),
+            OptionsErr::from(sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE)
+        );
+        assert_eq!(
+            Err(OptionsErr::Unknown),
+            OptionsErr::from(sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN)
+        );
+        assert_eq!(Err(OptionsErr::Unknown), OptionsErr::from(u32::MAX));
+
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_SUCCESS,
+            OptionsErr::into(Ok(()))
+        );
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_SUBJECT,
+            OptionsErr::into(Err(OptionsErr::BadSubject))
+        );
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_KEY,
+            OptionsErr::into(Err(OptionsErr::BadKey))
+        );
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_BAD_VALUE,
+            OptionsErr::into(Err(OptionsErr::BadValue))
+        );
+        assert_eq!(
+            sys::LV2_Options_Status_LV2_OPTIONS_ERR_UNKNOWN,
+            OptionsErr::into(Err(OptionsErr::Unknown))
+        );
+    }
+
+    #[test]
+    fn test_context_conversion() {
+        let urid = unsafe { URID::new_unchecked(42) };
+        for context in [
+            OptionsContext::Instance,
+            OptionsContext::Resource(urid),
+            OptionsContext::Blank(3),
+            OptionsContext::Port(7),
+        ] {
+            let (raw_context, subject) = context.into_raw();
+            assert_eq!(
+                Some(context),
+                OptionsContext::from_raw(raw_context, subject)
+            );
+        }
+
+        assert_eq!(
+            None,
+            OptionsContext::from_raw(sys::LV2_Options_Context_LV2_OPTIONS_RESOURCE, 0)
+        );
+        assert_eq!(None, OptionsContext::from_raw(42, 0));
+    }
+}
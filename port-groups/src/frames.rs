@@ -0,0 +1,121 @@
+use std::slice::IterMut;
+
+/// Iterator over the frames of several input channels.
+///
+/// Every item contains the samples of all channels at the same time. The iterator stops at the end of the shortest channel.
+pub struct Frames<'a, const N: usize> {
+    channels: [&'a [f32]; N],
+    index: usize,
+    length: usize,
+}
+
+impl<'a, const N: usize> Frames<'a, N> {
+    /// Create an iterator over the frames of the channels.
+    ///
+    /// Input ports of audio or CV type can be passed directly, since they dereference to slices.
+    pub fn new(channels: [&'a [f32]; N]) -> Self {
+        let length = channels.iter().map(|channel| channel.len()).min();
+        Self {
+            channels,
+            index: 0,
+            length: length.unwrap_or(0),
+        }
+    }
+}
+
+impl<const N: usize> Iterator for Frames<'_, N> {
+    type Item = [f32; N];
+
+    fn next(&mut self) -> Option<[f32; N]> {
+        if self.index >= self.length {
+            return None;
+        }
+        let index = self.index;
+        self.index += 1;
+        Some(std::array::from_fn(|channel| self.channels[channel][index]))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.length - self.index;
+        (remaining, Some(remaining))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for Frames<'_, N> {}
+
+/// Iterator over the frames of several output channels.
+///
+/// Every item contains mutable references to the samples of all channels at the same time. The iterator stops at the end of the shortest channel.
+pub struct FramesMut<'a, const N: usize> {
+    channels: [IterMut<'a, f32>; N],
+}
+
+impl<'a, const N: usize> FramesMut<'a, N> {
+    /// Create an iterator over the frames of the channels.
+    ///
+    /// Output ports of audio or CV type can be passed directly, since they dereference to mutable slices.
+    pub fn new(channels: [&'a mut [f32]; N]) -> Self {
+        let length = channels.iter().map(|channel| channel.len()).min();
+        let length = length.unwrap_or(0);
+        Self {
+            channels: channels.map(|channel| channel[..length].iter_mut()),
+        }
+    }
+}
+
+impl<'a, const N: usize> Iterator for FramesMut<'a, N> {
+    type Item = [&'a mut f32; N];
+
+    fn next(&mut self) -> Option<[&'a mut f32; N]> {
+        if N == 0 {
+            return None;
+        }
+        // All channels have the same length, so either all of them or none of them have a next sample.
+        let frame: [Option<&'a mut f32>; N] =
+            std::array::from_fn(|channel| self.channels[channel].next());
+        if frame.iter().any(Option::is_none) {
+            return None;
+        }
+        Some(frame.map(Option::unwrap))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let remaining = self.channels.first().map_or(0, ExactSizeIterator::len);
+        (remaining, Some(remaining))
+    }
+}
+
+impl<const N: usize> ExactSizeIterator for FramesMut<'_, N> {}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+
+    #[test]
+    fn test_frames() {
+        let left = [1.0, 2.0, 3.0];
+        let right = [4.0, 5.0];
+        let frames = Frames::new([&left, &right]);
+        assert_eq!(2, frames.len());
+        assert_eq!(vec![[1.0, 4.0], [2.0, 5.0]], frames.collect::<Vec<_>>());
+
+        assert_eq!(0, Frames::<0>::new([]).count());
+    }
+
+    #[test]
+    fn test_frames_mut() {
+        let mut left = [0.0; 3];
+        let mut right = [0.0; 2];
+        let mut frames = FramesMut::new([&mut left, &mut right]);
+        assert_eq!(2, frames.len());
+        for (i, [left, right]) in frames.by_ref().enumerate() {
+            *left = i as f32;
+            *right = -(i as f32);
+        }
+        assert!(frames.next().is_none());
+        assert_eq!([0.0, 1.0, 0.0], left);
+        assert_eq!([-0.0, -1.0], right);
+
+        assert_eq!(0, FramesMut::<0>::new([]).count());
+    }
+}
@@ -0,0 +1,304 @@
+//! LV2 specification for port groups, like the channels of a stereo input.
+//!
+//! Groups are declared with the `lv2` attributes of a derived [`PortCollection`](../lv2_core/port/trait.PortCollection.html), using the group classes and channel designations of this crate. The metadata of the groups is written to the plugin description by the `turtle` module of `lv2-core`, so hosts know which ports belong together.
+//!
+//! In `run`, the channels of a group can be processed frame by frame with [`Frames`](struct.Frames.html) and [`FramesMut`](struct.FramesMut.html) instead of zipping the individual ports.
+//!
+//! See the original [specification](https://lv2plug.in/ns/ext/port-groups) for more information.
+//!
+//! # Example
+//!
+//! A stereo ducker, which lowers the volume of it's main input while there is a signal at it's sidechain:
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_port_groups::prelude::*;
+//! use urid::*;
+//!
+//! #[derive(PortCollection)]
+//! #[lv2(group(symbol = "main_in", name = "Input", class = StereoGroup, main))]
+//! #[lv2(group(symbol = "main_out", name = "Output", class = StereoGroup, main))]
+//! #[lv2(group(symbol = "sidechain", name = "Sidechain", class = StereoGroup, side_chain_of = "main_in"))]
+//! struct Ports {
+//!     #[lv2(group = "main_in", designation = Left)]
+//!     in_left: InputPort<Audio>,
+//!     #[lv2(group = "main_in", designation = Right)]
+//!     in_right: InputPort<Audio>,
+//!     #[lv2(group = "main_out", designation = Left)]
+//!     out_left: OutputPort<Audio>,
+//!     #[lv2(group = "main_out", designation = Right)]
+//!     out_right: OutputPort<Audio>,
+//!     #[lv2(group = "sidechain", designation = Left)]
+//!     sidechain_left: InputPort<Audio>,
+//!     #[lv2(group = "sidechain", designation = Right)]
+//!     sidechain_right: InputPort<Audio>,
+//! }
+//!
+//! fn run(ports: &mut Ports) {
+//!     let input = Frames::new([&ports.in_left, &ports.in_right]);
+//!     let sidechain = Frames::new([&ports.sidechain_left, &ports.sidechain_right]);
+//!     let output = FramesMut::new([&mut ports.out_left, &mut ports.out_right]);
+//!     for ((input, [sidechain_left, sidechain_right]), output) in input.zip(sidechain).zip(output) {
+//!         let gain = 1.0 - sidechain_left.abs().max(sidechain_right.abs()).min(1.0);
+//!         for (output, input) in output.into_iter().zip(input) {
+//!             *output = input * gain;
+//!         }
+//!     }
+//! }
+//! ```
+extern crate lv2_sys as sys;
+
+mod frames;
+
+pub use frames::*;
+
+/// All port groups URI bounds.
+///
+/// The group classes and designations can be used in the `lv2` attributes of a derived `PortCollection`. Where a class and a property share the same name, the class has a `Class` suffix.
+pub mod port_groups {
+    use urid::UriBound;
+
+    /// The base class of all groups, `pg:Group`.
+    pub struct GroupClass;
+
+    unsafe impl UriBound for GroupClass {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__Group;
+    }
+
+    /// A group that contains only inputs, `pg:InputGroup`.
+    pub struct InputGroup;
+
+    unsafe impl UriBound for InputGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__InputGroup;
+    }
+
+    /// A group that contains only outputs, `pg:OutputGroup`.
+    pub struct OutputGroup;
+
+    unsafe impl UriBound for OutputGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__OutputGroup;
+    }
+
+    /// A group of discrete channels without a spatial arrangement, `pg:DiscreteGroup`.
+    pub struct DiscreteGroup;
+
+    unsafe impl UriBound for DiscreteGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__DiscreteGroup;
+    }
+
+    /// A single channel, `pg:MonoGroup`.
+    pub struct MonoGroup;
+
+    unsafe impl UriBound for MonoGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__MonoGroup;
+    }
+
+    /// A left and a right channel, `pg:StereoGroup`.
+    pub struct StereoGroup;
+
+    unsafe impl UriBound for StereoGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__StereoGroup;
+    }
+
+    /// A center and a side channel, `pg:MidSideGroup`.
+    pub struct MidSideGroup;
+
+    unsafe impl UriBound for MidSideGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__MidSideGroup;
+    }
+
+    /// Left, right and rear center channels, `pg:ThreePointZeroGroup`.
+    pub struct ThreePointZeroGroup;
+
+    unsafe impl UriBound for ThreePointZeroGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__ThreePointZeroGroup;
+    }
+
+    /// Left, center, right and rear center channels, `pg:FourPointZeroGroup`.
+    pub struct FourPointZeroGroup;
+
+    unsafe impl UriBound for FourPointZeroGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__FourPointZeroGroup;
+    }
+
+    /// Left, center, right, rear left and rear right channels, `pg:FivePointZeroGroup`.
+    pub struct FivePointZeroGroup;
+
+    unsafe impl UriBound for FivePointZeroGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__FivePointZeroGroup;
+    }
+
+    /// The channels of a five-point-zero group with a low-frequency effects channel, `pg:FivePointOneGroup`.
+    pub struct FivePointOneGroup;
+
+    unsafe impl UriBound for FivePointOneGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__FivePointOneGroup;
+    }
+
+    /// The channels of a five-point-one group with a rear center channel, `pg:SixPointOneGroup`.
+    pub struct SixPointOneGroup;
+
+    unsafe impl UriBound for SixPointOneGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__SixPointOneGroup;
+    }
+
+    /// The channels of a five-point-one group with side left and side right channels, `pg:SevenPointOneGroup`.
+    pub struct SevenPointOneGroup;
+
+    unsafe impl UriBound for SevenPointOneGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__SevenPointOneGroup;
+    }
+
+    /// The channels of a five-point-one group with center left and center right channels, `pg:SevenPointOneWideGroup`.
+    pub struct SevenPointOneWideGroup;
+
+    unsafe impl UriBound for SevenPointOneWideGroup {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__SevenPointOneWideGroup;
+    }
+
+    /// The class of channel designations, `pg:Element`.
+    pub struct ElementClass;
+
+    unsafe impl UriBound for ElementClass {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__Element;
+    }
+
+    /// The left channel, `pg:left`.
+    pub struct Left;
+
+    unsafe impl UriBound for Left {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__left;
+    }
+
+    /// The right channel, `pg:right`.
+    pub struct Right;
+
+    unsafe impl UriBound for Right {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__right;
+    }
+
+    /// The center channel, which is also the mid channel of a mid-side group, `pg:center`.
+    pub struct Center;
+
+    unsafe impl UriBound for Center {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__center;
+    }
+
+    /// The side channel of a mid-side group, `pg:side`.
+    pub struct Side;
+
+    unsafe impl UriBound for Side {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__side;
+    }
+
+    /// The center left channel, `pg:centerLeft`.
+    pub struct CenterLeft;
+
+    unsafe impl UriBound for CenterLeft {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__centerLeft;
+    }
+
+    /// The center right channel, `pg:centerRight`.
+    pub struct CenterRight;
+
+    unsafe impl UriBound for CenterRight {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__centerRight;
+    }
+
+    /// The side left channel, `pg:sideLeft`.
+    pub struct SideLeft;
+
+    unsafe impl UriBound for SideLeft {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__sideLeft;
+    }
+
+    /// The side right channel, `pg:sideRight`.
+    pub struct SideRight;
+
+    unsafe impl UriBound for SideRight {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__sideRight;
+    }
+
+    /// The rear left channel, `pg:rearLeft`.
+    pub struct RearLeft;
+
+    unsafe impl UriBound for RearLeft {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__rearLeft;
+    }
+
+    /// The rear right channel, `pg:rearRight`.
+    pub struct RearRight;
+
+    unsafe impl UriBound for RearRight {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__rearRight;
+    }
+
+    /// The rear center channel, `pg:rearCenter`.
+    pub struct RearCenter;
+
+    unsafe impl UriBound for RearCenter {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__rearCenter;
+    }
+
+    /// The low-frequency effects channel, `pg:lowFrequencyEffects`.
+    pub struct LowFrequencyEffects;
+
+    unsafe impl UriBound for LowFrequencyEffects {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__lowFrequencyEffects;
+    }
+
+    /// The property that links a port to it's group, `pg:group`.
+    pub struct Group;
+
+    unsafe impl UriBound for Group {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__group;
+    }
+
+    /// The property that links a group to it's channel designations, `pg:element`.
+    pub struct Element;
+
+    unsafe impl UriBound for Element {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__element;
+    }
+
+    /// The property that links a plugin to it's main input group, `pg:mainInput`.
+    pub struct MainInput;
+
+    unsafe impl UriBound for MainInput {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__mainInput;
+    }
+
+    /// The property that links a plugin to it's main output group, `pg:mainOutput`.
+    pub struct MainOutput;
+
+    unsafe impl UriBound for MainOutput {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__mainOutput;
+    }
+
+    /// The property that links a side chain group to the group it controls, `pg:sideChainOf`.
+    pub struct SideChainOf;
+
+    unsafe impl UriBound for SideChainOf {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__sideChainOf;
+    }
+
+    /// The property that links a group to the group it is a part of, `pg:subGroupOf`.
+    pub struct SubGroupOf;
+
+    unsafe impl UriBound for SubGroupOf {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__subGroupOf;
+    }
+
+    /// The property that links an output group to the input group it is processed from, `pg:source`.
+    pub struct Source;
+
+    unsafe impl UriBound for Source {
+        const URI: &'static [u8] = sys::LV2_PORT_GROUPS__source;
+    }
+}
+
+/// Prelude of `lv2_port_groups` for wildcard usage.
+pub mod prelude {
+    pub use crate::frames::{Frames, FramesMut};
+    pub use crate::port_groups::*;
+}
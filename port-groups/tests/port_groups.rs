@@ -0,0 +1,79 @@
+use lv2_core::prelude::*;
+use lv2_core::turtle::plugin_description;
+use lv2_host_test::*;
+use lv2_port_groups::prelude::*;
+use urid::*;
+
+#[derive(PortCollection)]
+#[lv2(group(symbol = "main_in", name = "Input", class = MidSideGroup, main))]
+#[lv2(group(symbol = "main_out", name = "Output", class = StereoGroup, main))]
+struct Ports {
+    #[lv2(group = "main_in", designation = Center)]
+    mid: InputPort<Audio>,
+    #[lv2(group = "main_in", designation = Side)]
+    side: InputPort<Audio>,
+    #[lv2(group = "main_out", designation = Left)]
+    left: OutputPort<Audio>,
+    #[lv2(group = "main_out", designation = Right)]
+    right: OutputPort<Audio>,
+}
+
+/// Decodes a mid-side signal to left and right channels.
+#[uri("urn:rust-lv2:mid-side-decoder")]
+#[derive(PluginMetadata)]
+#[lv2(name = "Mid-Side Decoder", class = UtilityPlugin)]
+struct Decoder;
+
+impl Plugin for Decoder {
+    type Ports = Ports;
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self)
+    }
+
+    fn run(&mut self, ports: &mut Ports, _: &mut (), _: u32) {
+        let input = Frames::new([&ports.mid, &ports.side]);
+        let output = FramesMut::new([&mut ports.left, &mut ports.right]);
+        for ([mid, side], [left, right]) in input.zip(output) {
+            *left = mid + side;
+            *right = mid - side;
+        }
+    }
+}
+
+lv2_descriptors!(Decoder);
+
+#[test]
+fn test_run() {
+    let mut host = TestHost::new::<Decoder>(HostSettings::default()).unwrap();
+    host.connect_audio(0)[..3].copy_from_slice(&[1.0, 0.5, 0.0]);
+    host.connect_audio(1)[..3].copy_from_slice(&[0.0, 0.5, 1.0]);
+    host.connect_audio(2);
+    host.connect_audio(3);
+    host.activate();
+    host.run(3);
+
+    assert_eq!(&[1.0, 1.0, 1.0], &host.audio(2).unwrap()[..3]);
+    assert_eq!(&[1.0, 0.0, -1.0], &host.audio(3).unwrap()[..3]);
+}
+
+#[test]
+fn test_description() {
+    let description = plugin_description::<Decoder>();
+    assert!(description.contains(&format!(
+        "lv2:designation <{}>",
+        Center::URI
+            .strip_suffix(b"\0")
+            .map(String::from_utf8_lossy)
+            .unwrap()
+    )));
+    assert!(description.contains(
+        "<urn:rust-lv2:mid-side-decoder#main_in>
+    a <http://lv2plug.in/ns/ext/port-groups#Group> , <http://lv2plug.in/ns/ext/port-groups#MidSideGroup> , <http://lv2plug.in/ns/ext/port-groups#InputGroup> ;"
+    ));
+    assert!(description.contains(
+        "<http://lv2plug.in/ns/ext/port-groups#mainOutput> <urn:rust-lv2:mid-side-decoder#main_out>"
+    ));
+}
@@ -0,0 +1,173 @@
+//! LV2 specification for presets, named sets of control values and state properties of a plugin.
+//!
+//! A [`Preset`](struct.Preset.html) contains the values of the plugin's control inputs, identified by their symbol, and the state of the plugin, stored in a [`Storage`](../lv2_state/struct.Storage.html). The state is created and applied with the plugin's [`State`](../lv2_state/trait.State.html) implementation, so presets use the same properties as saved sessions.
+//!
+//! Plugins can provide factory presets by implementing [`FactoryPresets`](trait.FactoryPresets.html). These can be written to the bundle with [`write_presets`](fn.write_presets.html), and hosts and tests can enumerate and apply them.
+//!
+//! See the original [specification](https://lv2plug.in/ns/ext/presets) for more information.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_core::prelude::*;
+//! use lv2_presets::*;
+//! use lv2_state::*;
+//! use lv2_urid::*;
+//! use urid::*;
+//!
+//! #[derive(PortCollection)]
+//! struct Ports {
+//!     #[lv2(default = 0.0, min = -90.0, max = 24.0)]
+//!     gain: InputPort<Control>,
+//! }
+//!
+//! #[derive(FeatureCollection)]
+//! struct Features<'a> {
+//!     map: LV2Map<'a>,
+//! }
+//!
+//! struct Mode;
+//!
+//! unsafe impl UriBound for Mode {
+//!     const URI: &'static [u8] = b"urn:my-plugin#mode\0";
+//! }
+//!
+//! #[uri("urn:my-plugin")]
+//! struct MyPlugin {
+//!     mode: i32,
+//!     mode_urid: URID<Mode>,
+//!     int: URID<Int>,
+//! }
+//!
+//! impl Plugin for MyPlugin {
+//!     type Ports = Ports;
+//!     type InitFeatures = Features<'static>;
+//!     type AudioFeatures = ();
+//!
+//!     fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+//!         Some(Self {
+//!             mode: 0,
+//!             mode_urid: features.map.map_type()?,
+//!             int: features.map.map_type()?,
+//!         })
+//!     }
+//!
+//!     fn run(&mut self, _: &mut Ports, _: &mut (), _: u32) {}
+//! }
+//!
+//! impl State for MyPlugin {
+//!     type StateFeatures = ();
+//!
+//!     fn save(&self, mut store: StoreHandle, _: ()) -> Result<(), StateErr> {
+//!         store.draft(self.mode_urid).init(self.int, self.mode)?;
+//!         store.commit_all()
+//!     }
+//!
+//!     fn restore(&mut self, store: RetrieveHandle, _: ()) -> Result<(), StateErr> {
+//!         self.mode = store.retrieve(self.mode_urid)?.read(self.int, ())?;
+//!         Ok(())
+//!     }
+//! }
+//!
+//! impl FactoryPresets for MyPlugin {
+//!     fn factory_presets(map: &impl Map) -> Result<Vec<Preset>, StateErr> {
+//!         let mode = map.map_type::<Mode>().ok_or(StateErr::Unknown)?;
+//!         let int = map.map_type::<Int>().ok_or(StateErr::Unknown)?;
+//!
+//!         let mut loud = Preset::new("urn:my-plugin#loud", "Loud");
+//!         loud.set_port_value("gain", 12.0);
+//!         let mut store = loud.state_mut().store_handle();
+//!         store.draft(mode).init(int, 2)?;
+//!         store.commit_all()?;
+//!
+//!         Ok(vec![loud])
+//!     }
+//! }
+//!
+//! let mapper = HashURIDMapper::new();
+//! let presets = MyPlugin::factory_presets(&mapper).unwrap();
+//! let loud = Preset::find(&presets, "urn:my-plugin#loud").unwrap();
+//! assert_eq!(Some(12.0), loud.port_value("gain"));
+//! assert_eq!(vec![(0, 12.0)], loud.control_values(Ports::PORTS));
+//! ```
+extern crate lv2_core as core;
+extern crate lv2_state as state;
+extern crate lv2_sys as sys;
+
+mod preset;
+pub use preset::*;
+
+mod turtle;
+pub use turtle::*;
+
+use state::{State, StateErr};
+use urid::*;
+
+/// All presets URI bounds.
+pub mod presets {
+    use urid::UriBound;
+
+    /// The class of presets, `pset:Preset`.
+    pub struct PresetClass;
+
+    unsafe impl UriBound for PresetClass {
+        const URI: &'static [u8] = sys::LV2_PRESETS__Preset;
+    }
+
+    /// The class of preset banks, `pset:Bank`.
+    pub struct BankClass;
+
+    unsafe impl UriBound for BankClass {
+        const URI: &'static [u8] = sys::LV2_PRESETS__Bank;
+    }
+
+    /// The property that links a preset to it's bank, `pset:bank`.
+    pub struct Bank;
+
+    unsafe impl UriBound for Bank {
+        const URI: &'static [u8] = sys::LV2_PRESETS__bank;
+    }
+
+    /// The property that links a plugin instance to the preset currently applied to it, `pset:preset`.
+    pub struct CurrentPreset;
+
+    unsafe impl UriBound for CurrentPreset {
+        const URI: &'static [u8] = sys::LV2_PRESETS__preset;
+    }
+
+    /// The property that holds the value of a port in a preset, `pset:value`.
+    pub struct PresetValue;
+
+    unsafe impl UriBound for PresetValue {
+        const URI: &'static [u8] = sys::LV2_PRESETS__value;
+    }
+}
+
+use presets::*;
+
+/// A URID cache containing all presets properties.
+#[derive(URIDCollection)]
+pub struct PresetsURIDCollection {
+    pub preset_class: URID<PresetClass>,
+    pub bank_class: URID<BankClass>,
+    pub bank: URID<Bank>,
+    pub current_preset: URID<CurrentPreset>,
+    pub value: URID<PresetValue>,
+}
+
+/// A plugin that provides factory presets.
+///
+/// Factory presets are shipped with the plugin. They can be written to the plugin's bundle with [`write_presets`](fn.write_presets.html), so that hosts can offer them to the user.
+pub trait FactoryPresets: State {
+    /// Create the factory presets of the plugin.
+    ///
+    /// The state properties of the presets have to be stored with URIDs of the given map. The presets are returned in the order they should be presented to the user.
+    fn factory_presets(map: &impl Map) -> Result<Vec<Preset>, StateErr>;
+}
+
+/// Prelude of `lv2_presets` for wildcard usage.
+pub mod prelude {
+    pub use crate::presets::*;
+    pub use crate::{FactoryPresets, PortValue, Preset, PresetsURIDCollection};
+}
@@ -0,0 +1,262 @@
+use core::port::{PortDirection, PortMetadata};
+use state::{State, StateErr, Storage};
+
+/// The value of a control port in a preset.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PortValue {
+    /// The symbol of the port.
+    pub symbol: String,
+    /// The value of the port.
+    pub value: f32,
+}
+
+/// A named set of control port values and state properties.
+///
+/// The state properties are stored in a [`Storage`](../lv2_state/struct.Storage.html), which is filled and applied with the plugin's [`State`](../lv2_state/trait.State.html) implementation.
+#[derive(Clone, Default)]
+pub struct Preset {
+    uri: String,
+    label: String,
+    bank: Option<String>,
+    port_values: Vec<PortValue>,
+    state: Storage,
+}
+
+impl Preset {
+    /// Create an empty preset with the given URI and label.
+    pub fn new(uri: &str, label: &str) -> Self {
+        Self {
+            uri: uri.to_owned(),
+            label: label.to_owned(),
+            ..Default::default()
+        }
+    }
+
+    /// Create a preset from the current state of a plugin.
+    ///
+    /// The control port values have to be added separately, since the plugin doesn't know them outside of `run`.
+    pub fn from_plugin<P: State>(
+        uri: &str,
+        label: &str,
+        plugin: &P,
+        features: P::StateFeatures,
+    ) -> Result<Self, StateErr> {
+        let mut preset = Self::new(uri, label);
+        preset.save_state(plugin, features)?;
+        Ok(preset)
+    }
+
+    /// Find a preset by it's URI.
+    pub fn find<'a>(presets: &'a [Preset], uri: &str) -> Option<&'a Preset> {
+        presets.iter().find(|preset| preset.uri == uri)
+    }
+
+    /// Iterate over all presets in the given bank.
+    pub fn in_bank<'a>(presets: &'a [Preset], bank: &'a str) -> impl Iterator<Item = &'a Preset> {
+        presets
+            .iter()
+            .filter(move |preset| preset.bank.as_deref() == Some(bank))
+    }
+
+    /// Return the URI of the preset.
+    pub fn uri(&self) -> &str {
+        &self.uri
+    }
+
+    /// Return the human-readable label of the preset.
+    pub fn label(&self) -> &str {
+        &self.label
+    }
+
+    /// Return the URI of the bank the preset belongs to.
+    pub fn bank(&self) -> Option<&str> {
+        self.bank.as_deref()
+    }
+
+    /// Set the URI of the bank the preset belongs to.
+    pub fn set_bank(&mut self, bank: Option<&str>) {
+        self.bank = bank.map(str::to_owned);
+    }
+
+    /// Return the control port values of the preset, in the order they were set.
+    pub fn port_values(&self) -> &[PortValue] {
+        &self.port_values
+    }
+
+    /// Return the value of the control port with the given symbol.
+    pub fn port_value(&self, symbol: &str) -> Option<f32> {
+        self.port_values
+            .iter()
+            .find(|port_value| port_value.symbol == symbol)
+            .map(|port_value| port_value.value)
+    }
+
+    /// Set the value of a control port, replacing a previous value.
+    pub fn set_port_value(&mut self, symbol: &str, value: f32) {
+        match self
+            .port_values
+            .iter_mut()
+            .find(|port_value| port_value.symbol == symbol)
+        {
+            Some(port_value) => port_value.value = value,
+            None => self.port_values.push(PortValue {
+                symbol: symbol.to_owned(),
+                value,
+            }),
+        }
+    }
+
+    /// Set the values of all control inputs that have a default value to it.
+    ///
+    /// The ports are usually the [`PORTS`](../lv2_core/port/trait.PortCollection.html#associatedconstant.PORTS) of the plugin's port collection.
+    pub fn set_default_port_values(&mut self, ports: &[PortMetadata]) {
+        for port in ports.iter().filter(|port| is_control_input(port)) {
+            if let Some(default) = port.default {
+                self.set_port_value(port.symbol, default);
+            }
+        }
+    }
+
+    /// Map the control port values to port indices.
+    ///
+    /// Values of unknown ports are skipped and all other values are clamped to the range of their port. The returned pairs of index and value can be passed to the control ports of a plugin instance.
+    pub fn control_values(&self, ports: &[PortMetadata]) -> Vec<(u32, f32)> {
+        self.port_values
+            .iter()
+            .filter_map(|port_value| {
+                let port = PortMetadata::find(ports, &port_value.symbol)
+                    .filter(|port| is_control_input(port))?;
+                Some((port.index, port.clamp(port_value.value)))
+            })
+            .collect()
+    }
+
+    /// Return the state properties of the preset.
+    pub fn state(&self) -> &Storage {
+        &self.state
+    }
+
+    /// Return the mutable state properties of the preset, for example to store properties with a store handle.
+    pub fn state_mut(&mut self) -> &mut Storage {
+        &mut self.state
+    }
+
+    /// Replace the state properties of the preset with the current state of a plugin.
+    pub fn save_state<P: State>(
+        &mut self,
+        plugin: &P,
+        features: P::StateFeatures,
+    ) -> Result<(), StateErr> {
+        let mut state = Storage::default();
+        plugin.save(state.store_handle(), features)?;
+        self.state = state;
+        Ok(())
+    }
+
+    /// Restore the state of a plugin from the state properties of the preset.
+    pub fn apply_state<P: State>(
+        &mut self,
+        plugin: &mut P,
+        features: P::StateFeatures,
+    ) -> Result<(), StateErr> {
+        plugin.restore(self.state.retrieve_handle(), features)
+    }
+}
+
+fn is_control_input(port: &PortMetadata) -> bool {
+    port.direction == Some(PortDirection::Input) && port.class == Some(sys::LV2_CORE__ControlPort)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use core::port::{PortDirection, PortMetadata};
+    use urid::*;
+
+    fn control(index: u32, symbol: &'static str, default: Option<f32>) -> PortMetadata {
+        PortMetadata {
+            index,
+            symbol,
+            name: symbol,
+            direction: Some(PortDirection::Input),
+            class: Some(sys::LV2_CORE__ControlPort),
+            optional: false,
+            default,
+            minimum: Some(0.0),
+            maximum: Some(1.0),
+            unit: None,
+            buffer_type: None,
+            supports: &[],
+            properties: &[],
+            group: None,
+            designation: None,
+        }
+    }
+
+    #[test]
+    fn test_port_values() {
+        let ports = [
+            control(0, "gain", Some(0.5)),
+            control(1, "mix", None),
+            PortMetadata {
+                direction: Some(PortDirection::Output),
+                ..control(2, "level", Some(0.0))
+            },
+        ];
+
+        let mut preset = Preset::new("urn:preset", "Preset");
+        preset.set_default_port_values(&ports);
+        assert_eq!(
+            &[PortValue {
+                symbol: "gain".to_owned(),
+                value: 0.5
+            }],
+            preset.port_values()
+        );
+
+        preset.set_port_value("mix", 2.0);
+        preset.set_port_value("gain", 0.25);
+        preset.set_port_value("level", 1.0);
+        preset.set_port_value("unknown", 1.0);
+        assert_eq!(Some(0.25), preset.port_value("gain"));
+        assert_eq!(4, preset.port_values().len());
+        assert_eq!(vec![(0, 0.25), (1, 1.0)], preset.control_values(&ports));
+    }
+
+    #[test]
+    fn test_enumeration() {
+        let mut presets = vec![
+            Preset::new("urn:a", "A"),
+            Preset::new("urn:b", "B"),
+            Preset::new("urn:c", "C"),
+        ];
+        presets[0].set_bank(Some("urn:bank"));
+        presets[2].set_bank(Some("urn:bank"));
+
+        assert_eq!("B", Preset::find(&presets, "urn:b").unwrap().label());
+        assert!(Preset::find(&presets, "urn:d").is_none());
+        let bank: Vec<&str> = Preset::in_bank(&presets, "urn:bank")
+            .map(Preset::uri)
+            .collect();
+        assert_eq!(vec!["urn:a", "urn:c"], bank);
+    }
+
+    #[test]
+    fn test_state() {
+        let mapper = HashURIDMapper::new();
+        let key = mapper
+            .map_uri(Uri::from_bytes_with_nul(b"urn:key\0").unwrap())
+            .unwrap();
+        let int = mapper
+            .map_uri(Uri::from_bytes_with_nul(sys::LV2_ATOM__Int).unwrap())
+            .unwrap();
+
+        let mut preset = Preset::new("urn:preset", "Preset");
+        preset.state_mut().store(key, int, &42i32.to_ne_bytes());
+        let copy = preset.clone();
+        assert_eq!(
+            Some((int, &42i32.to_ne_bytes()[..])),
+            copy.state().retrieve(key)
+        );
+    }
+}
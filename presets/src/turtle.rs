@@ -0,0 +1,439 @@
+use crate::{FactoryPresets, Preset};
+use base64::engine::general_purpose::STANDARD;
+use base64::Engine;
+use core::turtle::{iri, literal, number, Manifest};
+use std::fmt::{self, Write};
+use std::path::Path;
+use urid::*;
+
+/// The prefixes used by the generated preset descriptions.
+const PREFIXES: &str = "@prefix lv2:   <http://lv2plug.in/ns/lv2core#> .
+@prefix pset:  <http://lv2plug.in/ns/ext/presets#> .
+@prefix rdfs:  <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix state: <http://lv2plug.in/ns/ext/state#> .
+@prefix xsd:   <http://www.w3.org/2001/XMLSchema#> .
+";
+
+/// Convert a floating-point number to the lexical form of `xsd:float` or `xsd:double`.
+fn float_lexical<T: fmt::Debug + Copy + Into<f64>>(value: T) -> String {
+    let double: f64 = value.into();
+    if double.is_nan() {
+        "NaN".to_owned()
+    } else if double.is_infinite() {
+        if double > 0.0 { "INF" } else { "-INF" }.to_owned()
+    } else {
+        format!("{:?}", value)
+    }
+}
+
+/// Convert the body of an atom to a Turtle object.
+///
+/// Numbers, booleans and strings are written as literals and URIDs as IRIs. All other atoms are written as literals with the URI of their atom type as datatype, like `"…"^^<http://lv2plug.in/ns/ext/atom#Vector>`. The literal is the string of URIs and paths and the base64-encoded body of all other atoms.
+fn atom_object(type_: &Uri, body: &[u8], unmap: &impl Unmap) -> String {
+    fn scalar<const N: usize>(body: &[u8]) -> Option<[u8; N]> {
+        body.get(..N)?.try_into().ok()
+    }
+    fn string(body: &[u8]) -> String {
+        let body = body.split(|byte| *byte == 0).next().unwrap_or(body);
+        String::from_utf8_lossy(body).into_owned()
+    }
+
+    let type_ = type_.to_bytes_with_nul();
+    let object = if type_ == sys::LV2_ATOM__Int {
+        scalar(body).map(|value| format!("\"{}\"^^xsd:int", i32::from_ne_bytes(value)))
+    } else if type_ == sys::LV2_ATOM__Long {
+        scalar(body).map(|value| format!("\"{}\"^^xsd:long", i64::from_ne_bytes(value)))
+    } else if type_ == sys::LV2_ATOM__Float {
+        scalar(body).map(|value| {
+            format!(
+                "\"{}\"^^xsd:float",
+                float_lexical(f32::from_ne_bytes(value))
+            )
+        })
+    } else if type_ == sys::LV2_ATOM__Double {
+        scalar(body).map(|value| {
+            format!(
+                "\"{}\"^^xsd:double",
+                float_lexical(f64::from_ne_bytes(value))
+            )
+        })
+    } else if type_ == sys::LV2_ATOM__Bool {
+        scalar(body).map(|value| (i32::from_ne_bytes(value) != 0).to_string())
+    } else if type_ == sys::LV2_ATOM__URID {
+        scalar(body)
+            .and_then(|value| URID::<()>::new(u32::from_ne_bytes(value)))
+            .and_then(|urid| unmap.unmap(urid))
+            .map(|uri| iri(uri.to_bytes_with_nul()))
+    } else if type_ == sys::LV2_ATOM__String {
+        Some(literal(&string(body)))
+    } else if type_ == sys::LV2_ATOM__URI || type_ == sys::LV2_ATOM__Path {
+        Some(format!("{}^^{}", literal(&string(body)), iri(type_)))
+    } else {
+        None
+    };
+    object.unwrap_or_else(|| format!("\"{}\"^^{}", STANDARD.encode(body), iri(type_)))
+}
+
+/// Read a Turtle string literal and return it's value and the rest of the input.
+fn parse_literal(input: &str) -> Option<(String, &str)> {
+    let mut chars = input.strip_prefix('"')?.char_indices();
+    let mut value = String::new();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '"' => return Some((value, &input[i + 2..])),
+            '\\' => value.push(match chars.next()?.1 {
+                'n' => '\n',
+                'r' => '\r',
+                't' => '\t',
+                c => c,
+            }),
+            c => value.push(c),
+        }
+    }
+    None
+}
+
+/// Convert a Turtle object of a state property back to the type and body of an atom.
+///
+/// This is the inverse of how [`presets_description`](fn.presets_description.html) writes the state properties of a preset, so hosts and tests can restore the properties from the description. The URIs of the atom types and URIDs are mapped with the given mapper. If the object can't be read, `None` is returned.
+///
+///     use lv2_presets::*;
+///     use urid::*;
+///
+///     let map = HashURIDMapper::new();
+///     let (type_, body) = parse_atom_object("\"-3\"^^xsd:int", &map).unwrap();
+///     assert_eq!(
+///         Uri::from_bytes_with_nul(b"http://lv2plug.in/ns/ext/atom#Int\0").ok(),
+///         map.unmap(type_)
+///     );
+///     assert_eq!(&(-3i32).to_ne_bytes(), body.as_slice());
+pub fn parse_atom_object(object: &str, map: &impl Map) -> Option<(URID, Vec<u8>)> {
+    fn map_uri(map: &impl Map, uri: &[u8]) -> Option<URID> {
+        let uri = uri.strip_suffix(b"\0").unwrap_or(uri);
+        map.map_uri(&UriBuf::new(uri).ok()?)
+    }
+    fn with_nul(value: &str) -> Vec<u8> {
+        let mut body = value.as_bytes().to_owned();
+        body.push(0);
+        body
+    }
+
+    let object = object.trim();
+    if object == "true" || object == "false" {
+        let value = i32::from(object == "true");
+        return Some((
+            map_uri(map, sys::LV2_ATOM__Bool)?,
+            value.to_ne_bytes().to_vec(),
+        ));
+    }
+    if let Some(uri) = object
+        .strip_prefix('<')
+        .and_then(|uri| uri.strip_suffix('>'))
+    {
+        let urid = map_uri(map, uri.as_bytes())?;
+        return Some((
+            map_uri(map, sys::LV2_ATOM__URID)?,
+            urid.get().to_ne_bytes().to_vec(),
+        ));
+    }
+
+    let (value, datatype) = parse_literal(object)?;
+    let (type_, body): (&[u8], Vec<u8>) = match datatype.strip_prefix("^^") {
+        None if datatype.is_empty() => (sys::LV2_ATOM__String, with_nul(&value)),
+        None => return None,
+        Some("xsd:int") => (
+            sys::LV2_ATOM__Int,
+            value.parse::<i32>().ok()?.to_ne_bytes().to_vec(),
+        ),
+        Some("xsd:long") => (
+            sys::LV2_ATOM__Long,
+            value.parse::<i64>().ok()?.to_ne_bytes().to_vec(),
+        ),
+        Some("xsd:float") => (
+            sys::LV2_ATOM__Float,
+            value.parse::<f32>().ok()?.to_ne_bytes().to_vec(),
+        ),
+        Some("xsd:double") => (
+            sys::LV2_ATOM__Double,
+            value.parse::<f64>().ok()?.to_ne_bytes().to_vec(),
+        ),
+        Some(datatype) => {
+            let type_ = datatype.strip_prefix('<')?.strip_suffix('>')?.as_bytes();
+            let type_urid = map_uri(map, type_)?;
+            let body = if map_uri(map, sys::LV2_ATOM__URI) == Some(type_urid)
+                || map_uri(map, sys::LV2_ATOM__Path) == Some(type_urid)
+            {
+                with_nul(&value)
+            } else {
+                STANDARD.decode(value).ok()?
+            };
+            return Some((type_urid, body));
+        }
+    };
+    Some((map_uri(map, type_)?, body))
+}
+
+/// Write the description of a preset.
+fn write_preset(out: &mut String, preset: &Preset, plugin: &[u8], unmap: &impl Unmap) {
+    let _ = write!(
+        out,
+        "\n{}\n    a pset:Preset ;\n    lv2:appliesTo {} ;\n    rdfs:label {}",
+        iri(preset.uri().as_bytes()),
+        iri(plugin),
+        literal(preset.label())
+    );
+    if let Some(bank) = preset.bank() {
+        let _ = write!(out, " ;\n    pset:bank {}", iri(bank.as_bytes()));
+    }
+    for (i, port_value) in preset.port_values().iter().enumerate() {
+        out.push_str(if i == 0 { " ;\n    lv2:port " } else { " , " });
+        let _ = write!(
+            out,
+            "[\n        lv2:symbol {} ;\n        pset:value {}\n    ]",
+            literal(&port_value.symbol),
+            number(port_value.value)
+        );
+    }
+
+    // Properties are sorted by their URI to get a stable output.
+    let mut properties: Vec<(String, String)> = preset
+        .state()
+        .iter()
+        .filter_map(|(key, (type_, body))| {
+            let key = unmap.unmap(*key)?;
+            let type_ = unmap.unmap(*type_)?;
+            Some((
+                iri(key.to_bytes_with_nul()),
+                atom_object(type_, body, unmap),
+            ))
+        })
+        .collect();
+    properties.sort();
+    for (i, (key, object)) in properties.iter().enumerate() {
+        out.push_str(if i == 0 {
+            " ;\n    state:state [\n"
+        } else {
+            " ;\n"
+        });
+        let _ = write!(out, "        {} {}", key, object);
+    }
+    if !properties.is_empty() {
+        out.push_str("\n    ]");
+    }
+    out.push_str(" .\n");
+}
+
+/// Generate the description of the presets of a plugin.
+///
+/// The keys and types of the state properties are unmapped with the given unmapper, which has to be the one the properties were stored with. Properties with unknown URIDs are skipped.
+pub fn presets_description<P: UriBound>(presets: &[Preset], unmap: &impl Unmap) -> String {
+    let mut out = String::from(PREFIXES);
+    for preset in presets {
+        write_preset(&mut out, preset, P::URI, unmap);
+    }
+    out
+}
+
+/// Write the factory presets of a plugin into a bundle directory.
+///
+/// The presets are written to the file with the given name and are added to the manifest, which still has to be written by the caller, for example together with the plugin itself:
+///
+///     # use lv2_core::prelude::*;
+///     # use lv2_presets::*;
+///     # use lv2_state::*;
+///     # use urid::*;
+///     # #[uri("urn:amp")]
+///     # struct Amp;
+///     # impl Plugin for Amp {
+///     #     type Ports = ();
+///     #     type InitFeatures = ();
+///     #     type AudioFeatures = ();
+///     #     fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> { Some(Self) }
+///     #     fn run(&mut self, _: &mut (), _: &mut (), _: u32) {}
+///     # }
+///     # impl State for Amp {
+///     #     type StateFeatures = ();
+///     #     fn save(&self, _: StoreHandle, _: ()) -> Result<(), StateErr> { Ok(()) }
+///     #     fn restore(&mut self, _: RetrieveHandle, _: ()) -> Result<(), StateErr> { Ok(()) }
+///     # }
+///     # impl FactoryPresets for Amp {
+///     #     fn factory_presets(_: &impl Map) -> Result<Vec<Preset>, StateErr> {
+///     #         Ok(vec![Preset::new("urn:amp#quiet", "Quiet")])
+///     #     }
+///     # }
+///     use lv2_core::turtle::Manifest;
+///
+///     let bundle = std::env::temp_dir().join("amp-presets.lv2");
+///     let mut manifest = Manifest::new();
+///     manifest.add::<Amp>("libamp.so", "amp.ttl");
+///     write_presets::<Amp>(&bundle, "presets.ttl", &mut manifest).unwrap();
+///     std::fs::write(bundle.join("manifest.ttl"), manifest.to_string()).unwrap();
+///     # std::fs::remove_dir_all(bundle).unwrap();
+pub fn write_presets<P: FactoryPresets>(
+    bundle: &Path,
+    description: &str,
+    manifest: &mut Manifest,
+) -> std::io::Result<()> {
+    let mapper = HashURIDMapper::new();
+    let presets = P::factory_presets(&mapper).map_err(|err| {
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("Can't create factory presets: {:?}", err),
+        )
+    })?;
+    for preset in presets.iter() {
+        manifest.add_preset::<P>(preset.uri(), description);
+    }
+    std::fs::create_dir_all(bundle)?;
+    std::fs::write(
+        bundle.join(description),
+        presets_description::<P>(&presets, &mapper),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lv2_atom::AtomURIDCollection;
+    use state::Storage;
+
+    #[uri("urn:rust-lv2:presets")]
+    struct Plugin;
+
+    fn map(mapper: &HashURIDMapper, uri: &[u8]) -> URID {
+        mapper
+            .map_uri(Uri::from_bytes_with_nul(uri).unwrap())
+            .unwrap()
+    }
+
+    /// Convert an atom to a Turtle object and back.
+    fn round_trip(
+        type_: URID,
+        body: &[u8],
+        mapper: &HashURIDMapper,
+    ) -> (String, Option<(URID, Vec<u8>)>) {
+        let object = atom_object(mapper.unmap(type_).unwrap(), body, mapper);
+        let parsed = parse_atom_object(&object, mapper);
+        (object, parsed)
+    }
+
+    #[test]
+    fn test_atom_object() {
+        let mapper = HashURIDMapper::new();
+        let urids: AtomURIDCollection = mapper.populate_collection().unwrap();
+        let urid = map(&mapper, b"urn:urid\0");
+        let objects: [(URID, Vec<u8>, &str); 12] = [
+            (
+                urids.int.into_general(),
+                (-3i32).to_ne_bytes().to_vec(),
+                "\"-3\"^^xsd:int",
+            ),
+            (
+                urids.long.into_general(),
+                (1i64 << 40).to_ne_bytes().to_vec(),
+                "\"1099511627776\"^^xsd:long",
+            ),
+            (
+                urids.float.into_general(),
+                1.5f32.to_ne_bytes().to_vec(),
+                "\"1.5\"^^xsd:float",
+            ),
+            (
+                urids.float.into_general(),
+                f32::NEG_INFINITY.to_ne_bytes().to_vec(),
+                "\"-INF\"^^xsd:float",
+            ),
+            (
+                urids.double.into_general(),
+                f64::INFINITY.to_ne_bytes().to_vec(),
+                "\"INF\"^^xsd:double",
+            ),
+            (
+                urids.double.into_general(),
+                1e-10f64.to_ne_bytes().to_vec(),
+                "\"1e-10\"^^xsd:double",
+            ),
+            (
+                urids.bool.into_general(),
+                1i32.to_ne_bytes().to_vec(),
+                "true",
+            ),
+            (
+                urids.urid.into_general(),
+                urid.get().to_ne_bytes().to_vec(),
+                "<urn:urid>",
+            ),
+            (
+                urids.string.into_general(),
+                b"say \"hi\"\n\0".to_vec(),
+                "\"say \\\"hi\\\"\\n\"",
+            ),
+            (
+                urids.uri.into_general(),
+                b"http://example.com/a b\0".to_vec(),
+                "\"http://example.com/a b\"^^<http://lv2plug.in/ns/ext/atom#URI>",
+            ),
+            (
+                urids.path.into_general(),
+                b"samples/\"kick\".wav\0".to_vec(),
+                "\"samples/\\\"kick\\\".wav\"^^<http://lv2plug.in/ns/ext/atom#Path>",
+            ),
+            (
+                urids.chunk.into_general(),
+                vec![1, 2],
+                "\"AQI=\"^^<http://lv2plug.in/ns/ext/atom#Chunk>",
+            ),
+        ];
+        for (type_, body, expected) in objects.iter() {
+            let (object, parsed) = round_trip(*type_, body, &mapper);
+            assert_eq!(*expected, object);
+            assert_eq!(Some((*type_, body.clone())), parsed);
+        }
+
+        // NaN has no numeric literal.
+        let (object, parsed) =
+            round_trip(urids.float.into_general(), &f32::NAN.to_ne_bytes(), &mapper);
+        assert_eq!("\"NaN\"^^xsd:float", object);
+        let (_, body) = parsed.unwrap();
+        assert!(f32::from_ne_bytes(body.as_slice().try_into().unwrap()).is_nan());
+
+        assert_eq!(None, parse_atom_object("\"x\"^^xsd:int", &mapper));
+        assert_eq!(None, parse_atom_object("\"unterminated", &mapper));
+    }
+
+    #[test]
+    fn test_restore_vector() {
+        let mapper = HashURIDMapper::new();
+        let urids: AtomURIDCollection = mapper.populate_collection().unwrap();
+        let key = map(&mapper, b"urn:rust-lv2:presets#steps\0");
+
+        let mut preset = Preset::new("urn:rust-lv2:presets#steps", "Steps");
+        let mut store = preset.state_mut().store_handle();
+        store
+            .draft(key)
+            .init(urids.vector(), urids.int)
+            .unwrap()
+            .append(&[1, 2, 3])
+            .unwrap();
+        store.commit_all().unwrap();
+
+        let description = presets_description::<Plugin>(&[preset], &mapper);
+        let object = description
+            .lines()
+            .find_map(|line| line.trim().strip_prefix("<urn:rust-lv2:presets#steps> "))
+            .unwrap();
+        assert!(object.ends_with("^^<http://lv2plug.in/ns/ext/atom#Vector>"));
+
+        // The property is restored with it's type and value.
+        let (type_, body) = parse_atom_object(object, &mapper).unwrap();
+        let mut state = Storage::default();
+        state.store(key, type_, &body);
+        let store = state.retrieve_handle();
+        let vector = store
+            .retrieve(key)
+            .unwrap()
+            .read(urids.vector(), urids.int)
+            .unwrap();
+        assert_eq!(&[1, 2, 3], vector);
+    }
+}
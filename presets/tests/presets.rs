@@ -0,0 +1,212 @@
+use lv2_atom::prelude::*;
+use lv2_core::prelude::*;
+use lv2_core::turtle::Manifest;
+use lv2_host_test::*;
+use lv2_presets::*;
+use lv2_state::*;
+use lv2_urid::*;
+use std::any::Any;
+use urid::*;
+
+#[derive(PortCollection)]
+#[allow(dead_code)]
+struct Ports {
+    #[lv2(default = 0.0, min = -90.0, max = 24.0)]
+    gain: InputPort<Control>,
+    #[lv2(default = 1.0, min = 0.0, max = 1.0)]
+    mix: InputPort<Control>,
+}
+
+#[derive(FeatureCollection)]
+struct Features<'a> {
+    map: LV2Map<'a>,
+}
+
+struct Mode;
+
+unsafe impl UriBound for Mode {
+    const URI: &'static [u8] = b"urn:rust-lv2:presets#mode\0";
+}
+
+struct Name;
+
+unsafe impl UriBound for Name {
+    const URI: &'static [u8] = b"urn:rust-lv2:presets#name\0";
+}
+
+#[derive(URIDCollection)]
+struct URIDs {
+    atom: AtomURIDCollection,
+    mode: URID<Mode>,
+    name: URID<Name>,
+}
+
+#[uri("urn:rust-lv2:presets")]
+struct Presetted {
+    urids: URIDs,
+    mode: i32,
+}
+
+impl Plugin for Presetted {
+    type Ports = Ports;
+    type InitFeatures = Features<'static>;
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, features: &mut Features<'static>) -> Option<Self> {
+        Some(Self {
+            urids: features.map.populate_collection()?,
+            mode: 0,
+        })
+    }
+
+    fn run(&mut self, _: &mut Ports, _: &mut (), _: u32) {}
+
+    fn extension_data(uri: &Uri) -> Option<&'static dyn Any> {
+        match_extensions![uri, StateDescriptor<Self>]
+    }
+}
+
+impl State for Presetted {
+    type StateFeatures = ();
+
+    fn save(&self, mut store: StoreHandle, _: ()) -> Result<(), StateErr> {
+        store
+            .draft(self.urids.mode)
+            .init(self.urids.atom.int, self.mode)?;
+        store.commit_all()
+    }
+
+    fn restore(&mut self, store: RetrieveHandle, _: ()) -> Result<(), StateErr> {
+        self.mode = store
+            .retrieve(self.urids.mode)?
+            .read(self.urids.atom.int, ())?;
+        Ok(())
+    }
+}
+
+impl FactoryPresets for Presetted {
+    fn factory_presets(map: &impl Map) -> Result<Vec<Preset>, StateErr> {
+        let urids: URIDs = map.populate_collection().ok_or(StateErr::Unknown)?;
+
+        let mut quiet = Preset::new("urn:rust-lv2:presets#quiet", "Quiet");
+        quiet.set_default_port_values(Ports::PORTS);
+        quiet.set_port_value("gain", -12.0);
+        let mut store = quiet.state_mut().store_handle();
+        store.draft(urids.mode).init(urids.atom.int, 1)?;
+        store.commit_all()?;
+
+        let mut loud = Preset::new("urn:rust-lv2:presets#loud", "Very \"loud\"");
+        loud.set_bank(Some("urn:rust-lv2:presets#bank"));
+        loud.set_port_value("gain", 48.0);
+        let mut store = loud.state_mut().store_handle();
+        store.draft(urids.mode).init(urids.atom.int, 2)?;
+        store
+            .draft(urids.name)
+            .init(urids.atom.string, ())?
+            .append("loud")
+            .unwrap();
+        store.commit_all()?;
+
+        Ok(vec![quiet, loud])
+    }
+}
+
+lv2_descriptors!(Presetted);
+
+#[test]
+fn test_round_trip() {
+    let mut host = TestHost::new::<Presetted>(HostSettings::default()).unwrap();
+    let mut presets = Presetted::factory_presets(host.mapper()).unwrap();
+    for (index, value) in Ports::PORTS.iter().map(|port| (port.index, 0.0)) {
+        host.set_control(index, value);
+    }
+
+    let loud = presets
+        .iter_mut()
+        .find(|preset| preset.uri() == "urn:rust-lv2:presets#loud")
+        .unwrap();
+    for (index, value) in loud.control_values(Ports::PORTS) {
+        host.set_control(index, value);
+    }
+    host.restore(loud.state_mut()).unwrap();
+    assert_eq!(Some(24.0), host.control(0));
+    assert_eq!(2, host.plugin::<Presetted>().unwrap().mode);
+
+    // Saving the applied preset returns the same property.
+    let mode = host.map::<Mode>();
+    let saved = host.save().unwrap();
+    assert_eq!(saved.retrieve(mode), loud.state().retrieve(mode));
+
+    // Presets can also be applied to and created from plugin instances directly.
+    let plugin = host.plugin_mut::<Presetted>().unwrap();
+    presets[0].apply_state(plugin, ()).unwrap();
+    assert_eq!(1, plugin.mode);
+    let preset =
+        Preset::from_plugin("urn:rust-lv2:presets#current", "Current", plugin, ()).unwrap();
+    assert_eq!(
+        presets[0].state().retrieve(mode),
+        preset.state().retrieve(mode)
+    );
+}
+
+#[test]
+fn test_presets_description() {
+    let mapper = HashURIDMapper::new();
+    let presets = Presetted::factory_presets(&mapper).unwrap();
+    assert_eq!(
+        "@prefix lv2:   <http://lv2plug.in/ns/lv2core#> .
+@prefix pset:  <http://lv2plug.in/ns/ext/presets#> .
+@prefix rdfs:  <http://www.w3.org/2000/01/rdf-schema#> .
+@prefix state: <http://lv2plug.in/ns/ext/state#> .
+@prefix xsd:   <http://www.w3.org/2001/XMLSchema#> .
+
+<urn:rust-lv2:presets#quiet>
+    a pset:Preset ;
+    lv2:appliesTo <urn:rust-lv2:presets> ;
+    rdfs:label \"Quiet\" ;
+    lv2:port [
+        lv2:symbol \"gain\" ;
+        pset:value -12.0
+    ] , [
+        lv2:symbol \"mix\" ;
+        pset:value 1.0
+    ] ;
+    state:state [
+        <urn:rust-lv2:presets#mode> \"1\"^^xsd:int
+    ] .
+
+<urn:rust-lv2:presets#loud>
+    a pset:Preset ;
+    lv2:appliesTo <urn:rust-lv2:presets> ;
+    rdfs:label \"Very \\\"loud\\\"\" ;
+    pset:bank <urn:rust-lv2:presets#bank> ;
+    lv2:port [
+        lv2:symbol \"gain\" ;
+        pset:value 48.0
+    ] ;
+    state:state [
+        <urn:rust-lv2:presets#mode> \"2\"^^xsd:int ;
+        <urn:rust-lv2:presets#name> \"loud\"
+    ] .
+",
+        presets_description::<Presetted>(&presets, &mapper)
+    );
+}
+
+#[test]
+fn test_write_presets() {
+    let bundle = std::env::temp_dir().join(format!("rust-lv2-presets-{}", std::process::id()));
+    let mut manifest = Manifest::new();
+    write_presets::<Presetted>(&bundle, "presets.ttl", &mut manifest).unwrap();
+
+    let manifest = manifest.to_string();
+    assert!(manifest.contains(
+        "<urn:rust-lv2:presets#loud>
+    a <http://lv2plug.in/ns/ext/presets#Preset> ;
+    lv2:appliesTo <urn:rust-lv2:presets> ;
+    rdfs:seeAlso <presets.ttl> ."
+    ));
+    let description = std::fs::read_to_string(bundle.join("presets.ttl")).unwrap();
+    assert!(description.contains("rdfs:label \"Quiet\""));
+    std::fs::remove_dir_all(bundle).unwrap();
+}
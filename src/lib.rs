@@ -106,11 +106,17 @@
 //! enabled via features. These are:
 //!
 //! * `lv2-atom`: General data IO.
+//! * `lv2-buf-size`: Specification to describe the block lengths a plugin is run with and the size of its port buffers.
 //! * `lv2-core`: Implementation of the core LV2 specification.
+//! * `lv2-log`: Library to print log messages through the host. The messages of the [`log`](https://crates.io/crates/log) and [`tracing`](https://crates.io/crates/tracing) crates can be routed to the host with the `log` and `tracing` features.
 //! * `lv2-midi`: MIDI message extension for `lv2-midi`. Support for the [`wmidi` crate](https://crates.io/crates/wmidi) can be enabled with the `wmidi` feature.
+//! * `lv2-options`: Specification to exchange instance-specific options like the block length or the sample rate between host and plugin.
+//! * `lv2-port-groups`: Specification for port groups and channel designations, like the channels of a stereo input.
+//! * `lv2-presets`: Specification for presets, named sets of control values and state properties of a plugin.
 //! * `lv2-state`: Extension for LV2 plugins to store their state.
 //! * `lv2-time`: Specification to describe position in time and passage of
 //!   time, in both real and musical terms.
+//! * `lv2-ui`: Library to create plugin user interfaces that are independent of any UI toolkit.
 //! * `lv2-units`: Measuring unit definitions.
 //! * `lv2-urid`: LV2 integration of the URID concept.
 //! * `lv2-worker`: Work scheduling library that allows real-time capable LV2
@@ -142,18 +148,28 @@ pub mod prelude {
     pub use ::control_port_state_update::*;
     #[cfg(feature = "lv2-atom")]
     pub use ::lv2_atom::prelude::*;
+    #[cfg(feature = "lv2-buf-size")]
+    pub use ::lv2_buf_size::prelude::*;
     #[cfg(feature = "lv2-core")]
     pub use ::lv2_core::prelude::*;
     #[cfg(feature = "lv2-log")]
     pub use ::lv2_log::*;
     #[cfg(feature = "lv2-midi")]
     pub use ::lv2_midi::prelude::*;
+    #[cfg(feature = "lv2-options")]
+    pub use ::lv2_options::prelude::*;
     #[cfg(feature = "lv2-patch")]
     pub use ::lv2_patch::prelude::*;
+    #[cfg(feature = "lv2-port-groups")]
+    pub use ::lv2_port_groups::prelude::*;
+    #[cfg(feature = "lv2-presets")]
+    pub use ::lv2_presets::prelude::*;
     #[cfg(feature = "lv2-state")]
     pub use ::lv2_state::*;
     #[cfg(feature = "lv2-time")]
     pub use ::lv2_time::prelude::*;
+    #[cfg(feature = "lv2-ui")]
+    pub use ::lv2_ui::prelude::*;
     #[cfg(feature = "lv2-units")]
     pub use ::lv2_units::prelude::*;
     #[cfg(feature = "lv2-urid")]
@@ -171,6 +187,9 @@ pub extern crate control_port_state_update;
 #[cfg(feature = "lv2-atom")]
 pub extern crate lv2_atom;
 
+#[cfg(feature = "lv2-buf-size")]
+pub extern crate lv2_buf_size;
+
 #[cfg(feature = "lv2-core")]
 pub extern crate lv2_core;
 
@@ -180,9 +199,18 @@ pub extern crate lv2_log;
 #[cfg(feature = "lv2-midi")]
 pub extern crate lv2_midi;
 
+#[cfg(feature = "lv2-options")]
+pub extern crate lv2_options;
+
 #[cfg(feature = "lv2-patch")]
 pub extern crate lv2_patch;
 
+#[cfg(feature = "lv2-port-groups")]
+pub extern crate lv2_port_groups;
+
+#[cfg(feature = "lv2-presets")]
+pub extern crate lv2_presets;
+
 #[cfg(feature = "lv2-state")]
 pub extern crate lv2_state;
 
@@ -192,6 +220,9 @@ pub extern crate lv2_sys;
 #[cfg(feature = "lv2-time")]
 pub extern crate lv2_time;
 
+#[cfg(feature = "lv2-ui")]
+pub extern crate lv2_ui;
+
 pub extern crate mod_license;
 
 #[cfg(feature = "urid")]
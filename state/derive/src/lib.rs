@@ -0,0 +1,12 @@
+//! Procedural macros for `lv2-state`.
+extern crate proc_macro;
+
+mod state_serialize_derive;
+
+use proc_macro::TokenStream;
+
+/// Implement the `StateSerialize` trait for a struct.
+#[proc_macro_derive(StateSerialize, attributes(lv2))]
+pub fn state_serialize_derive(input: TokenStream) -> TokenStream {
+    state_serialize_derive::state_serialize_derive_impl(input)
+}
@@ -0,0 +1,598 @@
+use proc_macro::TokenStream;
+use proc_macro2::{Literal, TokenStream as TokenStream2};
+use quote::{format_ident, quote};
+use syn::spanned::Spanned;
+use syn::{
+    parse_macro_input, Data, DeriveInput, Error, Expr, Fields, Generics, Ident, Index, LitInt,
+    Path, Result, Type,
+};
+
+/// Combine an optional error with another one.
+fn push_error(errors: &mut Option<Error>, error: Error) {
+    match errors {
+        Some(errors) => errors.combine(error),
+        None => *errors = Some(error),
+    }
+}
+
+/// Build the type of a collection from nested pairs, ending with `()`.
+fn nested_type(types: impl DoubleEndedIterator<Item = TokenStream2>) -> TokenStream2 {
+    types
+        .rev()
+        .fold(quote!(()), |rest, type_| quote!((#type_, #rest)))
+}
+
+/// Build the expression that accesses the element with the given index of a nested collection.
+fn nested_access(collection: TokenStream2, index: usize) -> TokenStream2 {
+    let second = Index::from(1);
+    let first = Index::from(0);
+    let mut access = collection;
+    for _ in 0..index {
+        access = quote!(#access.#second);
+    }
+    quote!(#access.#first)
+}
+
+/// The value of a field whose property is missing.
+enum FieldDefault {
+    /// The value returned by `Default::default()`.
+    Default,
+    /// The value of the given expression.
+    Expr(Expr),
+}
+
+/// A field of a struct that is stored as a property.
+struct StateField {
+    ident: Ident,
+    ty: Type,
+    key: Type,
+    /// Previous keys of the property, which are tried in order if the property with the key is missing.
+    aliases: Vec<Type>,
+    default: Option<FieldDefault>,
+}
+
+impl StateField {
+    /// Parse a field and it's `lv2` attributes.
+    ///
+    /// Returns `None` if the field is skipped.
+    fn from_field(field: &syn::Field) -> Result<Option<Self>> {
+        let mut key: Option<Type> = None;
+        let mut aliases: Vec<Type> = Vec::new();
+        let mut default: Option<FieldDefault> = None;
+        let mut skip = false;
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("lv2"))
+        {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("key") {
+                    key = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("alias") {
+                    aliases.push(meta.value()?.parse()?);
+                } else if meta.path.is_ident("default") {
+                    default = Some(if meta.input.peek(syn::Token![=]) {
+                        FieldDefault::Expr(meta.value()?.parse()?)
+                    } else {
+                        FieldDefault::Default
+                    });
+                } else if meta.path.is_ident("skip") {
+                    skip = true;
+                } else {
+                    return Err(meta.error("unsupported field attribute"));
+                }
+                Ok(())
+            })?;
+        }
+
+        let ident = field.ident.clone().unwrap();
+        if skip {
+            return if key.is_some() || !aliases.is_empty() || default.is_some() {
+                Err(Error::new(
+                    ident.span(),
+                    format!(
+                        "the skipped field `{}` can't have a key, aliases or a default",
+                        ident
+                    ),
+                ))
+            } else {
+                Ok(None)
+            };
+        }
+        match key {
+            Some(key) => Ok(Some(Self {
+                ident,
+                ty: field.ty.clone(),
+                key,
+                aliases,
+                default,
+            })),
+            None => Err(Error::new(
+                ident.span(),
+                format!("the field `{}` requires a key or has to be skipped", ident),
+            )),
+        }
+    }
+
+    /// The value of the field if none of it's properties exists.
+    fn fallback(&self) -> TokenStream2 {
+        let ty = &self.ty;
+        match &self.default {
+            Some(FieldDefault::Default) => quote!(Default::default()),
+            Some(FieldDefault::Expr(expr)) => quote!(#expr),
+            None => quote!(<#ty as AtomDeserialize>::absent().ok_or(StateErr::NoProperty)?),
+        }
+    }
+}
+
+/// The version of the stored state.
+struct Version {
+    version: i32,
+    key: Type,
+    /// The function that upgrades the state from an older version.
+    migrate: Option<Path>,
+}
+
+/// Representation of a struct we implement `StateSerialize` for.
+struct StateStruct<'a> {
+    ident: &'a Ident,
+    generics: &'a Generics,
+    version: Option<Version>,
+    fields: Vec<StateField>,
+}
+
+impl<'a> StateStruct<'a> {
+    /// Construct a `Self` instance from a `DeriveInput` and it's `lv2` attributes.
+    fn from_derive_input(input: &'a DeriveInput) -> Result<Self> {
+        let mut errors: Option<Error> = None;
+
+        let mut version: Option<(LitInt, i32)> = None;
+        let mut version_key: Option<Type> = None;
+        let mut migrate: Option<Path> = None;
+        for attr in input
+            .attrs
+            .iter()
+            .filter(|attr| attr.path().is_ident("lv2"))
+        {
+            let result = attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("version") {
+                    let literal: LitInt = meta.value()?.parse()?;
+                    let value = literal
+                        .base10_parse::<i32>()
+                        .ok()
+                        .filter(|value| *value >= 0);
+                    match value {
+                        Some(value) => version = Some((literal, value)),
+                        None => {
+                            return Err(Error::new(
+                                literal.span(),
+                                "the version has to be a non-negative 32-bit integer",
+                            ))
+                        }
+                    }
+                } else if meta.path.is_ident("version_key") {
+                    version_key = Some(meta.value()?.parse()?);
+                } else if meta.path.is_ident("migrate") {
+                    migrate = Some(meta.value()?.parse()?);
+                } else {
+                    return Err(meta.error("unsupported struct attribute"));
+                }
+                Ok(())
+            });
+            if let Err(error) = result {
+                push_error(&mut errors, error);
+            }
+        }
+
+        let version = match (version, version_key) {
+            (Some((_, version)), Some(key)) => Some(Version {
+                version,
+                key,
+                migrate,
+            }),
+            (Some((literal, _)), None) => {
+                push_error(
+                    &mut errors,
+                    Error::new(
+                        literal.span(),
+                        "a version requires a key, declared with `#[lv2(version_key = ...)]`",
+                    ),
+                );
+                None
+            }
+            (None, key) => {
+                if let Some(key) = key {
+                    push_error(
+                        &mut errors,
+                        Error::new_spanned(key, "a version key requires a version"),
+                    );
+                }
+                if let Some(migrate) = migrate {
+                    push_error(
+                        &mut errors,
+                        Error::new_spanned(migrate, "a migration requires a version"),
+                    );
+                }
+                None
+            }
+        };
+
+        let fields = match &input.data {
+            Data::Struct(data) => match &data.fields {
+                Fields::Named(fields) => fields.named.iter().collect(),
+                Fields::Unit => Vec::new(),
+                Fields::Unnamed(fields) => {
+                    push_error(
+                        &mut errors,
+                        Error::new(fields.span(), "only fields with names can be properties"),
+                    );
+                    Vec::new()
+                }
+            },
+            Data::Enum(data) => {
+                push_error(
+                    &mut errors,
+                    Error::new(data.enum_token.span(), "only structs can be saved as state"),
+                );
+                Vec::new()
+            }
+            Data::Union(data) => {
+                push_error(
+                    &mut errors,
+                    Error::new(
+                        data.union_token.span(),
+                        "only structs can be saved as state",
+                    ),
+                );
+                Vec::new()
+            }
+        };
+        let mut state_fields: Vec<StateField> = Vec::new();
+        for field in fields {
+            match StateField::from_field(field) {
+                Ok(Some(field)) => state_fields.push(field),
+                Ok(None) => (),
+                Err(error) => push_error(&mut errors, error),
+            }
+        }
+
+        // Compare the keys by their tokens, which catches the obvious duplicates.
+        let mut keys: Vec<String> = Vec::new();
+        let all_keys = version.iter().map(|version| &version.key).chain(
+            state_fields
+                .iter()
+                .flat_map(|field| std::iter::once(&field.key).chain(field.aliases.iter())),
+        );
+        for key in all_keys {
+            let name = quote!(#key).to_string();
+            if keys.contains(&name) {
+                push_error(
+                    &mut errors,
+                    Error::new_spanned(key, format!("the key `{}` is used more than once", name)),
+                );
+            } else {
+                keys.push(name);
+            }
+        }
+
+        match errors {
+            None => Ok(Self {
+                ident: &input.ident,
+                generics: &input.generics,
+                version,
+                fields: state_fields,
+            }),
+            Some(errors) => Err(errors),
+        }
+    }
+
+    /// The type of the URID collection.
+    ///
+    /// The collection contains the version key, or `()` if the state isn't versioned, and for every field the key, the URIDs of the value and the aliases.
+    fn urids_type(&self) -> TokenStream2 {
+        let version = match &self.version {
+            Some(version) => {
+                let key = &version.key;
+                quote!(URID<#key>)
+            }
+            None => quote!(()),
+        };
+        let fields = nested_type(self.fields.iter().map(|field| {
+            let key = &field.key;
+            let ty = &field.ty;
+            let aliases = nested_type(field.aliases.iter().map(|alias| quote!(URID<#alias>)));
+            quote!((URID<#key>, (<#ty as AtomSerialize>::URIDs, #aliases)))
+        }));
+        quote!((#version, #fields))
+    }
+
+    /// The statements that save the version and the fields.
+    fn save(&self) -> TokenStream2 {
+        let version = self.version.as_ref().map(|version| {
+            let value = Literal::i32_suffixed(version.version);
+            quote!(store.serialize_property(urids.0, &#value, &(), atom_urids)?;)
+        });
+        let fields = self.fields.iter().enumerate().map(|(index, field)| {
+            let ident = &field.ident;
+            let urids = nested_access(quote!(urids.1), index);
+            quote! {
+                store.serialize_property(#urids.0, &self.#ident, &#urids.1.0, atom_urids)?;
+            }
+        });
+        quote! {
+            #version
+            #(#fields)*
+            Ok(())
+        }
+    }
+
+    /// The statements that read the version and the fields, assign the fields and migrate the state.
+    fn restore(&self) -> TokenStream2 {
+        let read_version = self.version.as_ref().map(|version| {
+            let value = Literal::u32_unsuffixed(version.version as u32);
+            quote! {
+                let version: u32 = match store.deserialize_property::<_, i32>(urids.0, &(), atom_urids)? {
+                    Some(version) if version >= 0 => version as u32,
+                    Some(_) => return Err(StateErr::BadData),
+                    None => 0,
+                };
+                if version > #value {
+                    return Err(StateErr::BadData);
+                }
+            }
+        });
+
+        let bindings: Vec<Ident> = (0..self.fields.len())
+            .map(|index| format_ident!("field_{}", index))
+            .collect();
+        let reads =
+            self.fields
+                .iter()
+                .zip(bindings.iter())
+                .enumerate()
+                .map(|(index, (field, binding))| {
+                    let ty = &field.ty;
+                    let urids = nested_access(quote!(urids.1), index);
+                    let fallback = field.fallback();
+                    let aliases = (0..field.aliases.len()).map(|alias| {
+                    let alias = nested_access(quote!(#urids.1.1), alias);
+                    quote! {
+                        if #binding.is_none() {
+                            #binding = store.deserialize_property(#alias, &#urids.1.0, atom_urids)?;
+                        }
+                    }
+                });
+                    let mutability = if field.aliases.is_empty() {
+                        quote!()
+                    } else {
+                        quote!(mut)
+                    };
+                    quote! {
+                        let #mutability #binding: Option<#ty> =
+                            store.deserialize_property(#urids.0, &#urids.1.0, atom_urids)?;
+                        #(#aliases)*
+                        let #binding: #ty = match #binding {
+                            Some(value) => value,
+                            None => #fallback,
+                        };
+                    }
+                });
+        let assignments = self
+            .fields
+            .iter()
+            .zip(bindings.iter())
+            .map(|(field, binding)| {
+                let ident = &field.ident;
+                quote!(self.#ident = #binding;)
+            });
+
+        let migrate = self.version.as_ref().and_then(|version| {
+            let migrate = version.migrate.as_ref()?;
+            let value = Literal::u32_unsuffixed(version.version as u32);
+            Some(quote! {
+                if version < #value {
+                    #migrate(self, version, store, urids, atom_urids)?;
+                }
+            })
+        });
+
+        quote! {
+            #read_version
+            #(#reads)*
+            #(#assignments)*
+            #migrate
+            Ok(())
+        }
+    }
+
+    /// Implement `StateSerialize`.
+    fn make_state_serialize(&self) -> TokenStream {
+        let ident = self.ident;
+        let (impl_generics, type_generics, where_clause) = self.generics.split_for_impl();
+        let urids_type = self.urids_type();
+        let save = self.save();
+        let restore = self.restore();
+
+        // Without properties, the arguments aren't used.
+        let allow = if self.fields.is_empty() && self.version.is_none() {
+            quote!(#[allow(unused_variables)])
+        } else {
+            quote!()
+        };
+
+        (quote! {
+            #allow
+            impl #impl_generics StateSerialize for #ident #type_generics #where_clause {
+                type URIDs = #urids_type;
+
+                fn save(
+                    &self,
+                    store: &mut StoreHandle,
+                    urids: &Self::URIDs,
+                    atom_urids: &AtomURIDCollection,
+                ) -> Result<(), StateErr> {
+                    #save
+                }
+
+                fn restore(
+                    &mut self,
+                    store: &RetrieveHandle,
+                    urids: &Self::URIDs,
+                    atom_urids: &AtomURIDCollection,
+                ) -> Result<(), StateErr> {
+                    #restore
+                }
+            }
+        })
+        .into()
+    }
+}
+
+/// Implement `StateSerialize` for a struct.
+#[inline]
+pub fn state_serialize_derive_impl(input: TokenStream) -> TokenStream {
+    let input: DeriveInput = parse_macro_input!(input);
+    match StateStruct::from_derive_input(&input) {
+        Ok(state) => state.make_state_serialize(),
+        Err(error) => error.to_compile_error().into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    fn validate(input: DeriveInput) -> std::result::Result<(), String> {
+        StateStruct::from_derive_input(&input)
+            .map(|_| ())
+            .map_err(|error| error.to_string())
+    }
+
+    #[test]
+    fn test_valid_types() {
+        let input: DeriveInput = parse_quote! {
+            #[lv2(version = 2, version_key = Version, migrate = Self::migrate)]
+            struct Synth {
+                #[lv2(key = Gain, alias = Level, alias = Volume)]
+                gain: f32,
+                #[lv2(key = Name)]
+                name: Option<String>,
+                #[lv2(key = Cutoff, default = 1000.0)]
+                cutoff: f32,
+                #[lv2(key = Resonance, default)]
+                resonance: f32,
+                #[lv2(skip)]
+                buffer: Vec<f32>,
+            }
+        };
+        assert_eq!(Ok(()), validate(input));
+
+        let input: DeriveInput = parse_quote! {
+            struct Empty;
+        };
+        assert_eq!(Ok(()), validate(input));
+    }
+
+    #[test]
+    fn test_invalid_types() {
+        let input: DeriveInput = parse_quote! {
+            struct Synth {
+                gain: f32,
+            }
+        };
+        assert_eq!(
+            Err("the field `gain` requires a key or has to be skipped".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            struct Synth {
+                #[lv2(skip, default)]
+                gain: f32,
+            }
+        };
+        assert_eq!(
+            Err("the skipped field `gain` can't have a key, aliases or a default".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(version_key = Version)]
+            struct Synth {
+                #[lv2(key = Gain, alias = Version)]
+                gain: f32,
+            }
+        };
+        assert_eq!(
+            Err("a version key requires a version".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(version = 1)]
+            struct Synth {}
+        };
+        assert_eq!(
+            Err("a version requires a key, declared with `#[lv2(version_key = ...)]`".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(version = 3000000000, version_key = Version)]
+            struct Synth {}
+        };
+        assert_eq!(
+            Err("the version has to be a non-negative 32-bit integer".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(migrate = migrate)]
+            struct Synth {}
+        };
+        assert_eq!(
+            Err("a migration requires a version".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(version = 1, version_key = Version)]
+            struct Synth {
+                #[lv2(key = Gain, alias = Version)]
+                gain: f32,
+            }
+        };
+        assert_eq!(
+            Err("the key `Version` is used more than once".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            struct Synth(f32);
+        };
+        assert_eq!(
+            Err("only fields with names can be properties".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            enum Synth {
+                Mono,
+            }
+        };
+        assert_eq!(
+            Err("only structs can be saved as state".to_string()),
+            validate(input)
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[lv2(class = Synth)]
+            struct Synth {}
+        };
+        assert_eq!(
+            Err("unsupported struct attribute".to_string()),
+            validate(input)
+        );
+    }
+}
@@ -0,0 +1,222 @@
+use std::fmt;
+use std::ops::{BitAnd, BitOr, BitOrAssign, Not, Sub};
+
+/// Flags that describe properties and requests to save or restore a state.
+///
+/// The host passes flags to [`save`](trait.State.html#tymethod.save) and [`restore`](trait.State.html#tymethod.restore), which describe the purpose of the state and are available from [`StoreHandle::flags`](struct.StoreHandle.html#method.flags) and [`RetrieveHandle::flags`](struct.RetrieveHandle.html#method.flags). For example, a host saves a preset or a session with `POD | PORTABLE`, but may use `NATIVE` if the state is only used in the running process, for example to duplicate a plugin instance.
+///
+/// Every property has flags too. By default, properties are stored as `POD | PORTABLE`, which is true for all atoms that contain no absolute paths, pointers or handles. Values that aren't portable, like absolute paths, are stored as `POD`, and values that aren't plain old data either, like native pointers, are stored with `StateFlags::empty()`. These flags are set with [`StatePropertyWriter::set_flags`](struct.StatePropertyWriter.html#method.set_flags). `NATIVE` is only a flag of requests and never one of properties. A host may refuse to store properties that aren't `POD | PORTABLE`, and non-POD values should only be stored if the host asked for a `NATIVE` state.
+///
+/// ```
+/// use lv2_state::StateFlags;
+///
+/// let flags = StateFlags::POD | StateFlags::PORTABLE;
+/// assert!(flags.contains(StateFlags::POD));
+/// assert!(!flags.is_native());
+/// assert_eq!(StateFlags::POD, flags - StateFlags::PORTABLE);
+/// assert_eq!(3, u32::from(flags));
+/// ```
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct StateFlags(u32);
+
+impl StateFlags {
+    /// Plain old data.
+    ///
+    /// The value contains no pointers or references to other areas of memory and can be copied with `memcpy`. Note that plain old data isn't necessarily portable: Absolute paths, for example, are plain old data.
+    pub const POD: Self = Self(1);
+
+    /// Portable, architecture-independent data.
+    ///
+    /// The value can be restored on another machine, regardless of it's architecture. Portable values must not contain file names.
+    pub const PORTABLE: Self = Self(2);
+
+    /// Native data.
+    ///
+    /// The state is only going to be used in the currently running process, for example to duplicate a plugin instance, and may therefore contain non-portable and non-POD values.
+    pub const NATIVE: Self = Self(4);
+
+    /// Flags without any flag set.
+    pub const fn empty() -> Self {
+        Self(0)
+    }
+
+    /// Create flags from their raw representation.
+    ///
+    /// Unknown flags are kept.
+    pub const fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Return the raw representation of the flags.
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    /// Check whether all of the given flags are set.
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Check whether no flag is set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Check whether the `POD` flag is set.
+    pub const fn is_pod(self) -> bool {
+        self.contains(Self::POD)
+    }
+
+    /// Check whether the `PORTABLE` flag is set.
+    pub const fn is_portable(self) -> bool {
+        self.contains(Self::PORTABLE)
+    }
+
+    /// Check whether the `NATIVE` flag is set.
+    pub const fn is_native(self) -> bool {
+        self.contains(Self::NATIVE)
+    }
+}
+
+/// The default flags are `POD | PORTABLE`.
+impl Default for StateFlags {
+    fn default() -> Self {
+        Self::POD | Self::PORTABLE
+    }
+}
+
+impl From<u32> for StateFlags {
+    fn from(bits: u32) -> Self {
+        Self(bits)
+    }
+}
+
+impl From<StateFlags> for u32 {
+    fn from(flags: StateFlags) -> u32 {
+        flags.0
+    }
+}
+
+impl From<sys::LV2_State_Flags> for StateFlags {
+    fn from(flags: sys::LV2_State_Flags) -> Self {
+        Self(flags.into())
+    }
+}
+
+impl From<StateFlags> for sys::LV2_State_Flags {
+    fn from(flags: StateFlags) -> Self {
+        flags.0.into()
+    }
+}
+
+impl BitOr for StateFlags {
+    type Output = Self;
+
+    fn bitor(self, other: Self) -> Self {
+        Self(self.0 | other.0)
+    }
+}
+
+impl BitOrAssign for StateFlags {
+    fn bitor_assign(&mut self, other: Self) {
+        self.0 |= other.0;
+    }
+}
+
+impl BitAnd for StateFlags {
+    type Output = Self;
+
+    fn bitand(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+}
+
+/// Remove the flags on the right side from the flags on the left side.
+impl Sub for StateFlags {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        Self(self.0 & !other.0)
+    }
+}
+
+impl Not for StateFlags {
+    type Output = Self;
+
+    fn not(self) -> Self {
+        Self(!self.0)
+    }
+}
+
+impl fmt::Debug for StateFlags {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let names = [
+            (Self::POD, "POD"),
+            (Self::PORTABLE, "PORTABLE"),
+            (Self::NATIVE, "NATIVE"),
+        ];
+        let mut first = true;
+        for (flag, name) in names.iter() {
+            if self.contains(*flag) {
+                if !first {
+                    write!(f, " | ")?;
+                }
+                write!(f, "{}", name)?;
+                first = false;
+            }
+        }
+        let unknown = *self - Self::POD - Self::PORTABLE - Self::NATIVE;
+        if !unknown.is_empty() {
+            if !first {
+                write!(f, " | ")?;
+            }
+            write!(f, "{:#x}", unknown.0)?;
+            first = false;
+        }
+        if first {
+            write!(f, "(empty)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::StateFlags;
+
+    #[test]
+    fn test_flags() {
+        let flags = StateFlags::default();
+        assert!(flags.is_pod());
+        assert!(flags.is_portable());
+        assert!(!flags.is_native());
+        assert_eq!(
+            u32::from(
+                sys::LV2_State_Flags::LV2_STATE_IS_POD
+                    | sys::LV2_State_Flags::LV2_STATE_IS_PORTABLE
+            ),
+            flags.bits()
+        );
+        assert_eq!(
+            StateFlags::NATIVE,
+            StateFlags::from(sys::LV2_State_Flags::LV2_STATE_IS_NATIVE)
+        );
+
+        let mut flags = StateFlags::empty();
+        assert!(flags.is_empty());
+        flags |= StateFlags::NATIVE;
+        assert!(flags.is_native());
+        assert_eq!(StateFlags::empty(), flags & StateFlags::POD);
+        assert_eq!(
+            StateFlags::NATIVE,
+            !(StateFlags::POD | StateFlags::PORTABLE) & flags
+        );
+
+        assert_eq!("POD | PORTABLE", format!("{:?}", StateFlags::default()));
+        assert_eq!(
+            "NATIVE | 0x10",
+            format!("{:?}", StateFlags::from_bits(0x14))
+        );
+        assert_eq!("(empty)", format!("{:?}", StateFlags::empty()));
+    }
+}
@@ -1,7 +1,9 @@
 use crate::raw::*;
 use crate::StateErr;
 use core::extension::ExtensionDescriptor;
+use core::feature::Feature;
 use core::prelude::*;
+use std::ffi::c_void;
 use std::marker::PhantomData;
 use urid::*;
 
@@ -16,12 +18,12 @@ pub trait State: Plugin {
 
     /// Save the state of the plugin.
     ///
-    /// The storage is done with the store handle. You draft a property, write it using the property handle, and then commit it to the store.
+    /// The storage is done with the store handle. You draft a property, write it using the property handle, and then commit it to the store. The [flags](struct.StoreHandle.html#method.flags) of the handle tell what kind of state the host requested.
     fn save(&self, store: StoreHandle, features: Self::StateFeatures) -> Result<(), StateErr>;
 
     /// Restore the state of the plugin.
     ///
-    /// The properties you have previously written can be retrieved with the store handle.
+    /// The properties you have previously written can be retrieved with the store handle. The [flags](struct.RetrieveHandle.html#method.flags) of the handle are the ones the host passed to `restore`.
     fn restore(
         &mut self,
         store: RetrieveHandle,
@@ -29,6 +31,42 @@ pub trait State: Plugin {
     ) -> Result<(), StateErr>;
 }
 
+/// A variant of the [`State`](trait.State.html) extension for plugins that can be restored while they run.
+///
+/// The host may call [`restore`](#tymethod.restore) of a plugin that declares the [`ThreadSafeRestore`](struct.ThreadSafeRestore.html) feature concurrently with `run`. Therefore, it only receives a shared reference to the plugin: The restored state can't be written directly, but has to be passed to the audio threading class, for example by scheduling work whose response swaps it in. The extension is exported with the [`ThreadSafeStateDescriptor`](struct.ThreadSafeStateDescriptor.html).
+pub trait ThreadSafeState: Plugin {
+    /// The feature collection to populate for the [`save`](#tymethod.save) and [`restore`](#tymethod.restore) methods.
+    type StateFeatures: FeatureCollection<'static>;
+
+    /// Save the state of the plugin.
+    ///
+    /// This works just like [`State::save`](trait.State.html#tymethod.save).
+    fn save(&self, store: StoreHandle, features: Self::StateFeatures) -> Result<(), StateErr>;
+
+    /// Restore the state of the plugin, possibly while it's running.
+    ///
+    /// The properties you have previously written can be retrieved with the store handle. The [flags](struct.RetrieveHandle.html#method.flags) of the handle are the ones the host passed to `restore`.
+    fn restore(&self, store: RetrieveHandle, features: Self::StateFeatures)
+        -> Result<(), StateErr>;
+}
+
+/// Marker feature for plugins that can be restored while they run.
+///
+/// A plugin that declares this feature, for example with `optional_features(ThreadSafeRestore)` in it's [`PluginMetadata`](../lv2_core/plugin/trait.PluginMetadata.html), allows the host to call `restore` concurrently with `run`. Such a plugin has to implement [`ThreadSafeState`](trait.ThreadSafeState.html) and export the [`ThreadSafeStateDescriptor`](struct.ThreadSafeStateDescriptor.html) instead of the `StateDescriptor`, since [`State::restore`](trait.State.html#tymethod.restore) takes the plugin mutably and must not run at the same time as `run`. In return, the host should pass the `worker:schedule` feature to `restore`, so that the plugin can load it's state in the worker and swap it in the audio threading class. The `ScheduledRestore` helper of `lv2-worker` implements this pattern.
+///
+/// This feature has no data. A host may still pass it to tell the plugin that it supports thread-safe restoration.
+pub struct ThreadSafeRestore;
+
+unsafe impl UriBound for ThreadSafeRestore {
+    const URI: &'static [u8] = sys::LV2_STATE__threadSafeRestore;
+}
+
+unsafe impl Feature for ThreadSafeRestore {
+    unsafe fn from_feature_ptr(_: *const c_void, _: ThreadingClass) -> Option<Self> {
+        Some(Self)
+    }
+}
+
 /// Raw wrapper of the [`State`](trait.State.html) extension.
 ///
 /// This is a marker type that has the required external methods for the extension.
@@ -43,7 +81,7 @@ unsafe impl<P: State> UriBound for StateDescriptor<P> {
 impl<P: State> StateDescriptor<P> {
     /// Handle a save request by the host.
     ///
-    /// This involves creating the plugin reference, constructing the store handle and discovering the required host features.
+    /// This involves creating the plugin reference, constructing the store handle and discovering the required host features. The flags are passed to the plugin with the store handle.
     ///
     /// # Safety
     ///
@@ -55,19 +93,13 @@ impl<P: State> StateDescriptor<P> {
         flags: u32,
         features: *const *const sys::LV2_Feature,
     ) -> sys::LV2_State_Status {
-        let flags: u32 =
-            (sys::LV2_State_Flags::from(flags) & sys::LV2_State_Flags::LV2_STATE_IS_POD).into();
-        if flags == 0 {
-            return sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS;
-        }
-
         let plugin: &P = if let Some(plugin) = (instance as *const P).as_ref() {
             plugin
         } else {
             return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
         };
 
-        let store = StoreHandle::new(store, handle);
+        let store = StoreHandle::new(store, handle).with_flags(flags.into());
 
         let mut feature_container = core::feature::FeatureCache::from_raw(features);
         let features = if let Ok(features) =
@@ -83,7 +115,7 @@ impl<P: State> StateDescriptor<P> {
 
     /// Handle a restore request by the host.
     ///
-    /// This involves creating the plugin reference, constructing the retrieve handle and discovering the required host features.
+    /// This involves creating the plugin reference, constructing the retrieve handle and discovering the required host features. The flags are passed to the plugin with the retrieve handle.
     ///
     /// # Safety
     ///
@@ -95,19 +127,13 @@ impl<P: State> StateDescriptor<P> {
         flags: u32,
         features: *const *const sys::LV2_Feature,
     ) -> sys::LV2_State_Status {
-        let flags: u32 =
-            (sys::LV2_State_Flags::from(flags) & sys::LV2_State_Flags::LV2_STATE_IS_POD).into();
-        if flags == 0 {
-            return sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS;
-        }
-
         let plugin: &mut P = if let Some(plugin) = (instance as *mut P).as_mut() {
             plugin
         } else {
             return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
         };
 
-        let store = RetrieveHandle::new(retrieve, handle);
+        let store = RetrieveHandle::new(retrieve, handle).with_flags(flags.into());
 
         let mut feature_container = core::feature::FeatureCache::from_raw(features);
         let features = if let Ok(features) =
@@ -131,6 +157,96 @@ impl<P: State> ExtensionDescriptor for StateDescriptor<P> {
     };
 }
 
+/// Raw wrapper of the [`ThreadSafeState`](trait.ThreadSafeState.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension. It implements the same interface as the [`StateDescriptor`](struct.StateDescriptor.html), but never creates a mutable reference to the plugin.
+pub struct ThreadSafeStateDescriptor<P: ThreadSafeState> {
+    plugin: PhantomData<P>,
+}
+
+unsafe impl<P: ThreadSafeState> UriBound for ThreadSafeStateDescriptor<P> {
+    const URI: &'static [u8] = sys::LV2_STATE__interface;
+}
+
+impl<P: ThreadSafeState> ThreadSafeStateDescriptor<P> {
+    /// Handle a save request by the host.
+    ///
+    /// This involves creating the plugin reference, constructing the store handle and discovering the required host features. The flags are passed to the plugin with the store handle.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_save(
+        instance: sys::LV2_Handle,
+        store: sys::LV2_State_Store_Function,
+        handle: sys::LV2_State_Handle,
+        flags: u32,
+        features: *const *const sys::LV2_Feature,
+    ) -> sys::LV2_State_Status {
+        let plugin: &P = if let Some(plugin) = (instance as *const P).as_ref() {
+            plugin
+        } else {
+            return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
+        };
+
+        let store = StoreHandle::new(store, handle).with_flags(flags.into());
+
+        let mut feature_container = core::feature::FeatureCache::from_raw(features);
+        let features = if let Ok(features) =
+            P::StateFeatures::from_cache(&mut feature_container, ThreadingClass::Other)
+        {
+            features
+        } else {
+            return sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE;
+        };
+
+        StateErr::into(ThreadSafeState::save(plugin, store, features))
+    }
+
+    /// Handle a restore request by the host.
+    ///
+    /// This involves creating the plugin reference, constructing the retrieve handle and discovering the required host features. The flags are passed to the plugin with the retrieve handle. Since the host may call this method concurrently with `run`, the plugin is only borrowed immutably.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_restore(
+        instance: sys::LV2_Handle,
+        retrieve: sys::LV2_State_Retrieve_Function,
+        handle: sys::LV2_State_Handle,
+        flags: u32,
+        features: *const *const sys::LV2_Feature,
+    ) -> sys::LV2_State_Status {
+        let plugin: &P = if let Some(plugin) = (instance as *const P).as_ref() {
+            plugin
+        } else {
+            return sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN;
+        };
+
+        let store = RetrieveHandle::new(retrieve, handle).with_flags(flags.into());
+
+        let mut feature_container = core::feature::FeatureCache::from_raw(features);
+        let features = if let Ok(features) =
+            P::StateFeatures::from_cache(&mut feature_container, ThreadingClass::Other)
+        {
+            features
+        } else {
+            return sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE;
+        };
+
+        StateErr::into(ThreadSafeState::restore(plugin, store, features))
+    }
+}
+
+impl<P: ThreadSafeState> ExtensionDescriptor for ThreadSafeStateDescriptor<P> {
+    type ExtensionInterface = sys::LV2_State_Interface;
+
+    const INTERFACE: &'static sys::LV2_State_Interface = &sys::LV2_State_Interface {
+        save: Some(Self::extern_save),
+        restore: Some(Self::extern_restore),
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use crate::*;
@@ -179,17 +295,18 @@ mod tests {
         type Descriptor = StateDescriptor<Stateful>;
         let mut plugin = Stateful;
 
-        assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS, unsafe {
+        // All flags are accepted and passed to the plugin.
+        assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN, unsafe {
             Descriptor::extern_save(
                 std::ptr::null_mut(),
                 None,
                 std::ptr::null_mut(),
-                0,
+                sys::LV2_State_Flags::LV2_STATE_IS_NATIVE.into(),
                 std::ptr::null_mut(),
             )
         });
 
-        assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_BAD_FLAGS, unsafe {
+        assert_eq!(sys::LV2_State_Status_LV2_STATE_ERR_UNKNOWN, unsafe {
             Descriptor::extern_restore(
                 std::ptr::null_mut(),
                 None,
@@ -6,6 +6,10 @@
 //!
 //! This crate also includes features to create files in a unique namespace for the plugin instance. However, these files will not persist across `save`/`restore` calls and therefore need to be safed together with the state as well. For more information, see the documentation of the [`path` module](path/index.html).
 //!
+//! ## Saving structs
+//!
+//! Instead of storing every property by hand, a struct can be saved and restored as a whole by deriving [`StateSerialize`](serialize/trait.StateSerialize.html) for it. Properties can be optional, have defaults and be renamed, and the state can be versioned and migrated. For more information, see the documentation of the [`serialize` module](serialize/index.html).
+//!
 //! ## Example usage
 //!
 //! ```
@@ -92,8 +96,17 @@ pub use raw::*;
 mod storage;
 pub use storage::Storage;
 
+mod flags;
+pub use flags::StateFlags;
+
 pub mod path;
 
+pub mod serialize;
+pub use serialize::StateSerialize;
+
+#[cfg(feature = "lv2-state-derive")]
+pub use lv2_state_derive::*;
+
 /// Kinds of errors that may occur in the crate.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum StateErr {
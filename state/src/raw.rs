@@ -1,4 +1,4 @@
-use crate::StateErr;
+use crate::{StateErr, StateFlags};
 use atom::prelude::*;
 use atom::space::*;
 use std::collections::HashMap;
@@ -11,38 +11,58 @@ use urid::*;
 /// This handle can be used to store the properties of a plugin. It uses the atom system to encode the properties and is backed by a storage callback function.
 ///
 /// The written properties a buffered and flushed when requested. Create new properties by calling [`draft`](#method.draft) and write them like any other atom. Once you are done, you can commit your properties by calling [`commit_all`](#method.commit_all) or [`commit`](#method.commit). You have to commit manually: Uncommitted properties will be discarded when the handle is dropped.
+///
+/// The handle also contains the [flags](#method.flags) the host passed to `save`, which describe what kind of state the host requested.
 pub struct StoreHandle<'a> {
-    properties: HashMap<URID, SpaceElement>,
+    properties: HashMap<URID, (SpaceElement, StateFlags)>,
     store_fn: sys::LV2_State_Store_Function,
     handle: sys::LV2_State_Handle,
+    flags: StateFlags,
     lifetime: PhantomData<&'a mut c_void>,
 }
 
 impl<'a> StoreHandle<'a> {
     /// Create a new store handle.
+    ///
+    /// The flags of the handle are `POD | PORTABLE`, which can be changed with [`with_flags`](#method.with_flags).
     pub fn new(store_fn: sys::LV2_State_Store_Function, handle: sys::LV2_State_Handle) -> Self {
         StoreHandle {
             properties: HashMap::new(),
             store_fn,
             handle,
+            flags: StateFlags::default(),
             lifetime: PhantomData,
         }
     }
 
+    /// Set the flags the host passed to `save`.
+    pub fn with_flags(mut self, flags: StateFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Return the flags the host passed to `save`.
+    ///
+    /// If they contain [`StateFlags::NATIVE`](struct.StateFlags.html#associatedconstant.NATIVE), the state is only used in the running process and properties that aren't portable or plain old data may be stored.
+    pub fn flags(&self) -> StateFlags {
+        self.flags
+    }
+
     /// Draft a new property.
     ///
     /// This will return a new handle to create a property. Once the property is completely written, you can commit it by calling [`commit`](#method.commit) or [`commit_all`](#method.commit_all). Then, and only then, it will be saved by the host.
     ///
     /// If you began to write a property and don't want the written things to be stored, you can discard it with [`discard`](#method.discard) or [`discard_all`](#method.discard_all).
+    ///
+    /// The property is stored as `POD | PORTABLE`, unless other flags are set with [`StatePropertyWriter::set_flags`](struct.StatePropertyWriter.html#method.set_flags).
     pub fn draft<K: ?Sized>(&mut self, property_key: URID<K>) -> StatePropertyWriter<'_> {
         let property_key = property_key.into_general();
-        self.properties
-            .insert(property_key.into_general(), SpaceElement::default());
-        StatePropertyWriter::new(SpaceHead::new(
-            self.properties
-                .get_mut(&property_key.into_general())
-                .unwrap(),
-        ))
+        self.properties.insert(
+            property_key,
+            (SpaceElement::default(), StateFlags::default()),
+        );
+        let (space, flags) = self.properties.get_mut(&property_key).unwrap();
+        StatePropertyWriter::new(SpaceHead::new(space), flags)
     }
 
     /// Internal helper function to store a property.
@@ -51,6 +71,7 @@ impl<'a> StoreHandle<'a> {
         handle: sys::LV2_State_Handle,
         key: URID<K>,
         space: SpaceElement,
+        flags: StateFlags,
     ) -> Result<(), StateErr> {
         let store_fn = store_fn.ok_or(StateErr::BadCallback)?;
         let space: Vec<u8> = space.to_vec();
@@ -67,9 +88,7 @@ impl<'a> StoreHandle<'a> {
         let data_ptr = data as *const _ as *const c_void;
         let data_size = header.size as usize;
         let data_type = header.type_;
-        let flags: u32 = (sys::LV2_State_Flags::LV2_STATE_IS_POD
-            | sys::LV2_State_Flags::LV2_STATE_IS_PORTABLE)
-            .into();
+        let flags: u32 = flags.into();
         let result = unsafe { (store_fn)(handle, key, data_ptr, data_size, data_type, flags) };
         StateErr::from(result)
     }
@@ -78,8 +97,8 @@ impl<'a> StoreHandle<'a> {
     ///
     /// This will also clear the property buffer.
     pub fn commit_all(&mut self) -> Result<(), StateErr> {
-        for (key, space) in self.properties.drain() {
-            unsafe { Self::commit_pair(self.store_fn, self.handle, key, space)? };
+        for (key, (space, flags)) in self.properties.drain() {
+            unsafe { Self::commit_pair(self.store_fn, self.handle, key, space, flags)? };
         }
         Ok(())
     }
@@ -89,8 +108,8 @@ impl<'a> StoreHandle<'a> {
     /// This method returns `None` if the requested property was not marked for commit, `Some(Ok(()))` if the property was stored and `Some(Err(_))` if an error occured while storing the property.
     pub fn commit<K: ?Sized>(&mut self, key: URID<K>) -> Option<Result<(), StateErr>> {
         let key = key.into_general();
-        let space = self.properties.remove(&key)?;
-        Some(unsafe { Self::commit_pair(self.store_fn, self.handle, key, space) })
+        let (space, flags) = self.properties.remove(&key)?;
+        Some(unsafe { Self::commit_pair(self.store_fn, self.handle, key, space, flags) })
     }
 
     /// Discard all drafted properties.
@@ -109,18 +128,38 @@ impl<'a> StoreHandle<'a> {
 /// Writing handle for properties.
 pub struct StatePropertyWriter<'a> {
     head: SpaceHead<'a>,
+    flags: &'a mut StateFlags,
     initialized: bool,
 }
 
 impl<'a> StatePropertyWriter<'a> {
-    /// Create a new property writer that uses the given space head.
-    pub fn new(head: SpaceHead<'a>) -> Self {
+    /// Create a new property writer that uses the given space head and writes the flags of the property to `flags`.
+    pub fn new(head: SpaceHead<'a>, flags: &'a mut StateFlags) -> Self {
         Self {
             head,
+            flags,
             initialized: false,
         }
     }
 
+    /// Set the flags of the property.
+    ///
+    /// Properties are stored as `POD | PORTABLE` by default. Values that aren't portable, like absolute paths, are stored as `POD`, and values that are neither, like native pointers, are stored with `StateFlags::empty()`. Since the host may refuse to store such properties, you should check the [flags of the store](struct.StoreHandle.html#method.flags) before. The flags can be set before or after the property is written.
+    ///
+    /// Property flags may only combine `POD` and `PORTABLE`. `NATIVE` is a flag of the request the host passes to `save` and `Err(StateErr::BadFlags)` is returned if it's set.
+    pub fn set_flags(&mut self, flags: StateFlags) -> Result<(), StateErr> {
+        if flags.contains(StateFlags::NATIVE) {
+            return Err(StateErr::BadFlags);
+        }
+        *self.flags = flags;
+        Ok(())
+    }
+
+    /// Return the flags of the property.
+    pub fn flags(&self) -> StateFlags {
+        *self.flags
+    }
+
     /// Initialize the property.
     ///
     /// This works like any other atom writer: You have to provide the URID of the atom type you want to write, as well as the type-specific parameter. If the property hasn't been initialized before, it will be initialized and the writing handle is returned. Otherwise, `Err(StateErr::Unknown)` is returned.
@@ -138,17 +177,44 @@ impl<'a> StatePropertyWriter<'a> {
             Err(StateErr::Unknown)
         }
     }
+
+    /// Write a value as the property.
+    ///
+    /// Like [`init`](#method.init), this only works once and `Err(StateErr::Unknown)` is returned if the property has already been initialized. If there isn't enough space to write the value, `Err(StateErr::NoSpace)` is returned, and if it's a path that isn't valid UTF-8, `Err(StateErr::PathNotUTF8)` is returned.
+    pub fn serialize<T: AtomSerialize + ?Sized>(
+        &mut self,
+        value: &T,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), StateErr> {
+        if self.initialized {
+            return Err(StateErr::Unknown);
+        }
+        self.initialized = true;
+        value
+            .serialize(&mut self.head as &mut dyn MutSpace, urids, atom_urids)
+            .map_err(|error| match error {
+                SerializeError::NoSpace => StateErr::NoSpace,
+                SerializeError::Absent => StateErr::Unknown,
+                SerializeError::NotUtf8 => StateErr::PathNotUTF8,
+            })
+    }
 }
 
 /// Property retrieval handle.
+///
+/// The handle also contains the [flags](#method.flags) the host passed to `restore`.
 pub struct RetrieveHandle<'a> {
     retrieve_fn: sys::LV2_State_Retrieve_Function,
     handle: sys::LV2_State_Handle,
+    flags: StateFlags,
     lifetime: PhantomData<&'a mut c_void>,
 }
 
 impl<'a> RetrieveHandle<'a> {
     /// Create a new retrieval handle that uses the given callback function and handle.
+    ///
+    /// The flags of the handle are `POD | PORTABLE`, which can be changed with [`with_flags`](#method.with_flags).
     pub fn new(
         retrieve_fn: sys::LV2_State_Retrieve_Function,
         handle: sys::LV2_State_Handle,
@@ -156,10 +222,22 @@ impl<'a> RetrieveHandle<'a> {
         RetrieveHandle {
             retrieve_fn,
             handle,
+            flags: StateFlags::default(),
             lifetime: PhantomData,
         }
     }
 
+    /// Set the flags the host passed to `restore`.
+    pub fn with_flags(mut self, flags: StateFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Return the flags the host passed to `restore`.
+    pub fn flags(&self) -> StateFlags {
+        self.flags
+    }
+
     /// Try to retrieve a property from the host.
     ///
     /// This method calls the internal retrieve callback with the given URID. If there's no property with the given URID, `Err(StateErr::NoProperty)` is returned. Otherwise, a reading handle is returned that contains the type and the data of the property and can interpret it as an atom.
@@ -177,14 +255,14 @@ impl<'a> RetrieveHandle<'a> {
             )
         };
 
-        let type_ = URID::new(type_).ok_or(StateErr::Unknown)?;
         let space = if !property_ptr.is_null() {
             unsafe { std::slice::from_raw_parts(property_ptr as *const u8, size) }
         } else {
             return Err(StateErr::NoProperty);
         };
+        let type_ = URID::new(type_).ok_or(StateErr::Unknown)?;
 
-        Ok(StatePropertyReader::new(type_, Space::from_slice(space)))
+        Ok(StatePropertyReader::new(type_, Space::from_slice(space)).with_flags(flags.into()))
     }
 }
 
@@ -194,17 +272,34 @@ impl<'a> RetrieveHandle<'a> {
 pub struct StatePropertyReader<'a> {
     type_: URID,
     body: Space<'a>,
+    flags: StateFlags,
 }
 
 impl<'a> StatePropertyReader<'a> {
     /// Create a new reading handle with the given type and data.
+    ///
+    /// The flags of the property are `POD | PORTABLE`, which can be changed with [`with_flags`](#method.with_flags).
     pub fn new<T: ?Sized>(type_: URID<T>, body: Space<'a>) -> Self {
         Self {
             type_: type_.into_general(),
             body,
+            flags: StateFlags::default(),
         }
     }
 
+    /// Set the flags the property was stored with.
+    pub fn with_flags(mut self, flags: StateFlags) -> Self {
+        self.flags = flags;
+        self
+    }
+
+    /// Return the flags the property was stored with.
+    ///
+    /// A property that isn't plain old data should only be interpreted if you know how it was stored, for example because it contains a pointer that is only valid in the running process.
+    pub fn flags(&self) -> StateFlags {
+        self.flags
+    }
+
     /// Return the type of the property.
     pub fn type_(&self) -> URID {
         self.type_
@@ -231,12 +326,31 @@ impl<'a> StatePropertyReader<'a> {
             Err(StateErr::BadType)
         }
     }
+
+    /// Try to read the property as a value.
+    ///
+    /// If the property has another type than the value, `Err(StateErr::BadType)` is returned. If the property has the correct type, but can't be read otherwise, `Err(StateErr::BadData)` is returned.
+    pub fn deserialize<T: AtomDeserialize>(
+        &self,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<T, StateErr> {
+        let body = self.body.data().unwrap_or(&[]);
+        let atom = AtomBuf::from_body(self.type_, body).ok_or(StateErr::BadData)?;
+        atom.atom()
+            .deserialize(urids, atom_urids)
+            .map_err(|error| match error {
+                DeserializeError::WrongType { .. } => StateErr::BadType,
+                _ => StateErr::BadData,
+            })
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use crate::raw::*;
     use crate::storage::Storage;
+    use crate::StateFlags;
     use atom::space::Space;
 
     fn store(storage: &mut Storage, urids: &AtomURIDCollection) {
@@ -254,6 +368,13 @@ mod tests {
         store_handle.commit(URID::new(1).unwrap()).unwrap().unwrap();
 
         let mut vector_writer = store_handle.draft(URID::new(3).unwrap());
+        assert_eq!(StateFlags::default(), vector_writer.flags());
+        vector_writer.set_flags(StateFlags::POD).unwrap();
+        assert_eq!(
+            Err(StateErr::BadFlags),
+            vector_writer.set_flags(StateFlags::NATIVE)
+        );
+        assert_eq!(StateFlags::POD, vector_writer.flags());
         let mut vector_writer = vector_writer.init(urids.vector(), urids.int).unwrap();
         vector_writer.append(&[1, 2, 3, 4]).unwrap();
 
@@ -284,15 +405,19 @@ mod tests {
                 .read(urids.float, ())
                 .unwrap()
         );
+        let vector_reader = retrieve_handle.retrieve(URID::new(3).unwrap()).unwrap();
+        assert_eq!(StateFlags::POD, vector_reader.flags());
         assert_eq!(
             [1, 2, 3, 4],
+            vector_reader.read(urids.vector(), urids.int).unwrap()
+        );
+        assert_eq!(
+            StateErr::NoProperty,
             retrieve_handle
-                .retrieve(URID::new(3).unwrap())
-                .unwrap()
-                .read(urids.vector(), urids.int)
+                .retrieve(URID::new(4).unwrap())
+                .err()
                 .unwrap()
         );
-        assert!(retrieve_handle.retrieve(URID::new(4).unwrap()).is_err());
     }
 
     #[test]
@@ -307,6 +432,7 @@ mod tests {
         for (key, (type_, value)) in storage.iter() {
             match key.get() {
                 1 => {
+                    assert_eq!(Some(StateFlags::default()), storage.flags(*key));
                     assert_eq!(urids.int, *type_);
                     assert_eq!(17, unsafe { *(value.as_slice() as *const _ as *const i32) });
                 }
@@ -317,6 +443,7 @@ mod tests {
                     });
                 }
                 3 => {
+                    assert_eq!(Some(StateFlags::POD), storage.flags(*key));
                     assert_eq!(urids.vector::<Int>(), *type_);
                     let space = Space::from_slice(value.as_slice());
                     let data = Vector::read(space, urids.int).unwrap();
@@ -0,0 +1,218 @@
+//! Saving and restoring whole structs.
+//!
+//! Implementing [`State`](../trait.State.html) by hand means drafting, writing and committing every property and mirroring all of it when the state is restored. The [`StateSerialize`](trait.StateSerialize.html) trait instead describes how a struct is stored as a set of properties. It can be derived for structs with named fields, whose values implement `AtomSerialize` and `AtomDeserialize` from `lv2_atom`:
+//!
+//! * Every field needs the key of it's property, given with `#[lv2(key = MyProperty)]`, or has to be skipped with `#[lv2(skip)]`. Skipped fields are neither saved nor restored.
+//! * Fields of type `Option<T>` are optional: `None` isn't saved and a missing property is restored as `None`.
+//! * If a property is missing, the field is set to it's default value if it has `#[lv2(default)]` or to the given expression if it has `#[lv2(default = expr)]`. Otherwise, restoring fails with `StateErr::NoProperty`.
+//! * If the key of a field has changed, the old keys can be given with `#[lv2(alias = OldProperty)]`. The field is always saved with it's current key, but restored from the first of it's keys that exists.
+//!
+//! The struct itself can have a version, given with `#[lv2(version = 2, version_key = MyVersion)]`. The version is saved as an integer with the version key and states without a version are treated as version 0. Restoring a state with a newer version than the current one fails with `StateErr::BadData`. States with an older version can be upgraded by a migration function, given with `#[lv2(migrate = path)]`. It is called after the fields are restored, with the saved version, and has the signature
+//!
+//! ```ignore
+//! fn migrate(
+//!     &mut self,
+//!     version: u32,
+//!     store: &RetrieveHandle,
+//!     urids: &<Self as StateSerialize>::URIDs,
+//!     atom_urids: &AtomURIDCollection,
+//! ) -> Result<(), StateErr>;
+//! ```
+//!
+//! All fields are read before any of them is assigned, so if a property can't be read, the struct is left unchanged.
+//!
+//! # Example
+//!
+//! ```
+//! use lv2_atom::prelude::*;
+//! use lv2_state::*;
+//! use std::path::PathBuf;
+//! use urid::*;
+//!
+//! #[uri("urn:synth#version")]
+//! struct Version;
+//!
+//! #[uri("urn:synth#gain")]
+//! struct Gain;
+//!
+//! #[uri("urn:synth#level")]
+//! struct Level;
+//!
+//! #[uri("urn:synth#sample")]
+//! struct Sample;
+//!
+//! #[uri("urn:synth#cutoff")]
+//! struct Cutoff;
+//!
+//! #[derive(StateSerialize)]
+//! #[lv2(version = 2, version_key = Version, migrate = Self::migrate)]
+//! struct Synth {
+//!     /// The gain was called "level" in version 1 and was a factor instead of decibels.
+//!     #[lv2(key = Gain, alias = Level)]
+//!     gain: f32,
+//!     #[lv2(key = Sample)]
+//!     sample: Option<PathBuf>,
+//!     /// The cutoff was added in version 2.
+//!     #[lv2(key = Cutoff, default = 1000.0)]
+//!     cutoff: f32,
+//!     #[lv2(skip)]
+//!     buffer: Vec<f32>,
+//! }
+//!
+//! impl Synth {
+//!     fn migrate(
+//!         &mut self,
+//!         version: u32,
+//!         _: &RetrieveHandle,
+//!         _: &<Self as StateSerialize>::URIDs,
+//!         _: &AtomURIDCollection,
+//!     ) -> Result<(), StateErr> {
+//!         if version < 2 {
+//!             self.gain = 20.0 * self.gain.log10();
+//!         }
+//!         Ok(())
+//!     }
+//! }
+//!
+//! #[derive(URIDCollection)]
+//! struct URIDs {
+//!     atom: AtomURIDCollection,
+//!     synth: <Synth as StateSerialize>::URIDs,
+//!     level: URID<Level>,
+//! }
+//!
+//! # let map = HashURIDMapper::new();
+//! // URID cache creation is omitted.
+//! let urids: URIDs = map.populate_collection().unwrap();
+//!
+//! // A preset that was saved by version 1.
+//! let mut storage = Storage::default();
+//! storage.store(urids.level, urids.atom.float, &10.0f32.to_ne_bytes());
+//!
+//! let mut synth = Synth {
+//!     gain: 0.0,
+//!     sample: Some(PathBuf::from("kick.wav")),
+//!     cutoff: 440.0,
+//!     buffer: Vec::new(),
+//! };
+//! synth
+//!     .restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+//!     .unwrap();
+//! assert_eq!(20.0, synth.gain);
+//! assert_eq!(None, synth.sample);
+//! assert_eq!(1000.0, synth.cutoff);
+//!
+//! // Saving it again with the current version and keys.
+//! let mut storage = Storage::default();
+//! synth
+//!     .save(&mut storage.store_handle(), &urids.synth, &urids.atom)
+//!     .unwrap();
+//! assert!(storage.retrieve(urids.level).is_none());
+//! ```
+use crate::raw::{RetrieveHandle, StoreHandle};
+use crate::StateErr;
+use atom::prelude::*;
+use urid::*;
+
+/// A struct that can be saved as a set of properties.
+///
+/// [See also the module documentation.](index.html)
+pub trait StateSerialize {
+    /// The URIDs of the keys and the values.
+    type URIDs: URIDCollection;
+
+    /// Save the struct as properties and commit them.
+    fn save(
+        &self,
+        store: &mut StoreHandle,
+        urids: &Self::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), StateErr>;
+
+    /// Restore the struct from the properties.
+    fn restore(
+        &mut self,
+        store: &RetrieveHandle,
+        urids: &Self::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), StateErr>;
+}
+
+impl<'a> StoreHandle<'a> {
+    /// Write a value as a property and commit it.
+    ///
+    /// Absent values, like the `None` value of an `Option`, aren't written.
+    pub fn serialize_property<K: ?Sized, T: AtomSerialize + ?Sized>(
+        &mut self,
+        key: URID<K>,
+        value: &T,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), StateErr> {
+        if value.is_absent() {
+            return Ok(());
+        }
+        if let Err(error) = self.draft(key).serialize(value, urids, atom_urids) {
+            self.discard(key);
+            return Err(error);
+        }
+        self.commit(key).unwrap_or(Err(StateErr::Unknown))
+    }
+}
+
+impl<'a> RetrieveHandle<'a> {
+    /// Read the value of a property.
+    ///
+    /// If the property doesn't exist, `Ok(None)` is returned.
+    pub fn deserialize_property<K: ?Sized, T: AtomDeserialize>(
+        &self,
+        key: URID<K>,
+        urids: &T::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<Option<T>, StateErr> {
+        match self.retrieve(key) {
+            Ok(reader) => reader.deserialize(urids, atom_urids).map(Some),
+            Err(StateErr::NoProperty) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use atom::AtomURIDCollection;
+    use urid::*;
+
+    #[test]
+    fn test_properties() {
+        let map = HashURIDMapper::new();
+        let urids = AtomURIDCollection::from_map(&map).unwrap();
+        let key = URID::<()>::new(1000).unwrap();
+        let other_key = URID::<()>::new(1001).unwrap();
+
+        let mut storage = Storage::default();
+        {
+            let mut store = storage.store_handle();
+            store.serialize_property(key, "Hello", &(), &urids).unwrap();
+            store
+                .serialize_property(other_key, &None::<i32>, &(), &urids)
+                .unwrap();
+        }
+        assert_eq!(1, storage.len());
+
+        let store = storage.retrieve_handle();
+        assert_eq!(
+            Ok(Some("Hello".to_owned())),
+            store.deserialize_property(key, &(), &urids)
+        );
+        assert_eq!(
+            Ok(None),
+            store.deserialize_property::<_, String>(other_key, &(), &urids)
+        );
+        assert_eq!(
+            Err(StateErr::BadType),
+            store.deserialize_property::<_, i32>(key, &(), &urids)
+        );
+    }
+}
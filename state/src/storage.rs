@@ -1,4 +1,5 @@
 use crate::raw::{RetrieveHandle, StoreHandle};
+use crate::StateFlags;
 use std::collections::HashMap;
 use std::ffi::c_void;
 use std::ops::{Deref, DerefMut};
@@ -8,17 +9,62 @@ use urid::*;
 ///
 /// This is mostly used to test this crate, but can be used to store properties too. It contains a map from property URIDs to a tuple of a type URID and a vector of bytes. You can access this map by dereferencing the storage.
 ///
+/// The flags of the properties are kept separately and can be queried with [`flags`](#method.flags). Properties without recorded flags are treated as `POD | PORTABLE`. Modifying the map directly doesn't update the flags, so a property that is inserted into the map gets the flags of a previous property with the same key. Properties should therefore be stored with [`store`](#method.store) or [`store_with_flags`](#method.store_with_flags), and removed with [`remove`](#method.remove) or [`clear`](#method.clear).
+///
 /// You can also directly create [`StoreHandle`s](struct.StoreHandle.html) and [`RetrieveHandle`s](struct.RetrieveHandle.html) that access the storage.
-#[derive(Default)]
+#[derive(Clone, Default)]
 pub struct Storage {
     items: HashMap<URID, (URID, Vec<u8>)>,
+    flags: HashMap<URID, StateFlags>,
 }
 
 impl Storage {
     /// Store a property.
+    ///
+    /// The property is stored as `POD | PORTABLE`.
     pub fn store<K: ?Sized, T: ?Sized>(&mut self, key: URID<K>, type_: URID<T>, value: &[u8]) {
+        self.store_with_flags(key, type_, value, StateFlags::default());
+    }
+
+    /// Store a property with the given flags.
+    pub fn store_with_flags<K: ?Sized, T: ?Sized>(
+        &mut self,
+        key: URID<K>,
+        type_: URID<T>,
+        value: &[u8],
+        flags: StateFlags,
+    ) {
+        let key = key.into_general();
         self.items
-            .insert(key.into_general(), (type_.into_general(), value.to_owned()));
+            .insert(key, (type_.into_general(), value.to_owned()));
+        self.flags.insert(key, flags);
+    }
+
+    /// Return the flags of a property.
+    ///
+    /// If the property doesn't exist, `None` is returned.
+    pub fn flags<K: ?Sized>(&self, key: URID<K>) -> Option<StateFlags> {
+        let key = key.into_general();
+        if self.items.contains_key(&key) {
+            Some(self.flags.get(&key).copied().unwrap_or_default())
+        } else {
+            None
+        }
+    }
+
+    /// Remove a property and return it's type and value.
+    ///
+    /// If the property doesn't exist, `None` is returned.
+    pub fn remove<K: ?Sized>(&mut self, key: URID<K>) -> Option<(URID, Vec<u8>)> {
+        let key = key.into_general();
+        self.flags.remove(&key);
+        self.items.remove(&key)
+    }
+
+    /// Remove all properties.
+    pub fn clear(&mut self) {
+        self.items.clear();
+        self.flags.clear();
     }
 
     /// External version of [`store`](#method.store).
@@ -36,13 +82,13 @@ impl Storage {
         value: *const c_void,
         size: usize,
         type_: u32,
-        _: u32,
+        flags: u32,
     ) -> sys::LV2_State_Status {
         let handle = (handle as *mut Self).as_mut().unwrap();
         let key = URID::new(key).unwrap();
         let value = std::slice::from_raw_parts(value as *const u8, size);
         let type_ = URID::new(type_).unwrap();
-        handle.store(key, type_, value);
+        handle.store_with_flags(key, type_, value, flags.into());
         sys::LV2_State_Status_LV2_STATE_SUCCESS
     }
 
@@ -76,14 +122,11 @@ impl Storage {
         type_: *mut u32,
         flags: *mut u32,
     ) -> *const c_void {
-        if !flags.is_null() {
-            *flags = (sys::LV2_State_Flags::LV2_STATE_IS_POD
-                | sys::LV2_State_Flags::LV2_STATE_IS_PORTABLE)
-                .into();
-        }
-
         let handle = (handle as *mut Self).as_mut().unwrap();
         let key = URID::new(key).unwrap();
+        if !flags.is_null() {
+            *flags = handle.flags(key).unwrap_or_default().into();
+        }
         if let Some((type_urid, data)) = handle.retrieve(key) {
             *size = data.len();
             *type_ = type_urid.get();
@@ -115,3 +158,34 @@ impl DerefMut for Storage {
         &mut self.items
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use urid::*;
+
+    #[test]
+    fn test_flags() {
+        let key = URID::<()>::new(1).unwrap();
+        let type_ = URID::<()>::new(2).unwrap();
+        let mut storage = Storage::default();
+
+        storage.store_with_flags(key, type_, &[1], StateFlags::empty());
+        assert_eq!(Some(StateFlags::empty()), storage.flags(key));
+
+        // The flags are removed with the property and overwritten when it's stored again.
+        assert_eq!(Some((type_, vec![1])), storage.remove(key));
+        assert_eq!(None, storage.flags(key));
+        storage.store(key, type_, &[2]);
+        assert_eq!(Some(StateFlags::default()), storage.flags(key));
+
+        storage.clear();
+        assert!(storage.is_empty());
+        assert_eq!(None, storage.flags(key));
+
+        // Properties that are removed from the map directly have no flags.
+        storage.store_with_flags(key, type_, &[1], StateFlags::POD);
+        storage.retain(|_, _| false);
+        assert_eq!(None, storage.flags(key));
+    }
+}
@@ -58,6 +58,13 @@ impl State for Stateful {
             .init(self.urids.vector(), self.urids.float)?
             .append(self.audio.as_ref());
 
+        if store.flags().is_native() {
+            // The address of the buffer is only valid in this process.
+            let mut writer = store.draft(URID::new(1002).unwrap());
+            writer.set_flags(StateFlags::empty())?;
+            writer.init(self.urids.long, self.audio.as_ptr() as i64)?;
+        }
+
         store.commit_all()
     }
 
@@ -186,4 +193,40 @@ fn test_save_n_restore() {
 
     assert_eq!(17.0, second_plugin.internal);
     assert_eq!(32, second_plugin.audio.len());
+    assert!(storage.get(&URID::new(1002).unwrap()).is_none());
+}
+
+#[test]
+fn test_native_state() {
+    let mut mapper: Pin<Box<HostMap<HashURIDMapper>>> = Box::pin(HashURIDMapper::new().into());
+    let mut storage = lv2_state::Storage::default();
+    let mut plugin = create_plugin(mapper.as_mut());
+    plugin.run(&mut (), &mut (), 32);
+
+    let status = unsafe {
+        StateDescriptor::<Stateful>::extern_save(
+            &mut plugin as *mut Stateful as lv2_sys::LV2_Handle,
+            Some(lv2_state::Storage::extern_store),
+            &mut storage as *mut lv2_state::Storage as lv2_sys::LV2_State_Handle,
+            lv2_sys::LV2_State_Flags::LV2_STATE_IS_NATIVE.into(),
+            std::ptr::null_mut(),
+        )
+    };
+    assert_eq!(lv2_sys::LV2_State_Status_LV2_STATE_SUCCESS, status);
+
+    let key = URID::<()>::new(1002).unwrap();
+    assert_eq!(Some(StateFlags::empty()), storage.flags(key));
+    assert_eq!(
+        Some(StateFlags::POD | StateFlags::PORTABLE),
+        storage.flags(URID::<()>::new(1000).unwrap())
+    );
+
+    let retrieve_handle = storage.retrieve_handle().with_flags(StateFlags::NATIVE);
+    assert!(retrieve_handle.flags().is_native());
+    let reader = retrieve_handle.retrieve(key).unwrap();
+    assert!(!reader.flags().is_pod());
+    assert_eq!(
+        plugin.audio.as_ptr() as i64,
+        reader.read(plugin.urids.long, ()).unwrap()
+    );
 }
@@ -0,0 +1,278 @@
+use lv2_atom::prelude::*;
+use lv2_state::*;
+use std::path::PathBuf;
+use urid::*;
+
+#[uri("urn:rust-lv2:state-serialize#Voice")]
+struct VoiceClass;
+
+#[uri("urn:rust-lv2:state-serialize#version")]
+struct Version;
+
+#[uri("urn:rust-lv2:state-serialize#gain")]
+struct Gain;
+
+#[uri("urn:rust-lv2:state-serialize#volume")]
+struct Volume;
+
+#[uri("urn:rust-lv2:state-serialize#level")]
+struct Level;
+
+#[uri("urn:rust-lv2:state-serialize#sample")]
+struct SampleKey;
+
+#[uri("urn:rust-lv2:state-serialize#voice")]
+struct VoiceKey;
+
+#[uri("urn:rust-lv2:state-serialize#steps")]
+struct Steps;
+
+#[uri("urn:rust-lv2:state-serialize#cutoff")]
+struct Cutoff;
+
+#[uri("urn:rust-lv2:state-serialize#detune")]
+struct Detune;
+
+#[derive(AtomSerialize, AtomDeserialize, Debug, PartialEq)]
+#[lv2(class = VoiceClass)]
+struct Voice {
+    #[lv2(key = Detune)]
+    detune: f64,
+}
+
+#[derive(StateSerialize, Debug, PartialEq)]
+#[lv2(version = 3, version_key = Version, migrate = Self::migrate)]
+struct Synth {
+    #[lv2(key = Gain, alias = Volume, alias = Level)]
+    gain: f32,
+    #[lv2(key = SampleKey)]
+    sample: Option<PathBuf>,
+    #[lv2(key = VoiceKey)]
+    voice: Voice,
+    #[lv2(key = Steps, default)]
+    steps: Vec<i32>,
+    #[lv2(key = Cutoff, default = 1000.0)]
+    cutoff: f32,
+    #[lv2(skip)]
+    migrated_from: Option<u32>,
+}
+
+impl Synth {
+    fn migrate(
+        &mut self,
+        version: u32,
+        store: &RetrieveHandle,
+        urids: &<Self as StateSerialize>::URIDs,
+        atom_urids: &AtomURIDCollection,
+    ) -> Result<(), StateErr> {
+        self.migrated_from = Some(version);
+        // Before version 2, the gain was a percentage.
+        if version < 2 {
+            self.gain /= 100.0;
+        }
+        // Properties can still be read from the store, for example to merge them.
+        let gain_key = urids.1 .0 .0;
+        if store
+            .deserialize_property::<_, f32>(gain_key, &(), atom_urids)?
+            .is_none()
+        {
+            self.cutoff = 500.0;
+        }
+        Ok(())
+    }
+}
+
+impl Default for Synth {
+    fn default() -> Self {
+        Self {
+            gain: 1.0,
+            sample: Some(PathBuf::from("init.wav")),
+            voice: Voice { detune: 0.0 },
+            steps: vec![1, 2, 3],
+            cutoff: 440.0,
+            migrated_from: None,
+        }
+    }
+}
+
+#[derive(StateSerialize, Debug, Default, PartialEq)]
+struct Unversioned {
+    #[lv2(key = Gain)]
+    gain: f32,
+}
+
+#[derive(URIDCollection)]
+struct URIDs {
+    atom: AtomURIDCollection,
+    synth: <Synth as StateSerialize>::URIDs,
+    unversioned: <Unversioned as StateSerialize>::URIDs,
+    version: URID<Version>,
+    gain: URID<Gain>,
+    level: URID<Level>,
+    voice: URID<VoiceKey>,
+    voice_urids: <Voice as AtomSerialize>::URIDs,
+}
+
+fn create_urids() -> URIDs {
+    let map = HashURIDMapper::new();
+    map.populate_collection().unwrap()
+}
+
+fn store<K: ?Sized, T: AtomSerialize + ?Sized>(
+    storage: &mut Storage,
+    key: URID<K>,
+    value: &T,
+    urids: &T::URIDs,
+    atom_urids: &AtomURIDCollection,
+) {
+    storage
+        .store_handle()
+        .serialize_property(key, value, urids, atom_urids)
+        .unwrap();
+}
+
+#[test]
+fn test_round_trip() {
+    let urids = create_urids();
+    let synth = Synth {
+        gain: 0.5,
+        sample: None,
+        voice: Voice { detune: 0.1 },
+        steps: vec![4, 5],
+        cutoff: 2000.0,
+        migrated_from: None,
+    };
+
+    let mut storage = Storage::default();
+    synth
+        .save(&mut storage.store_handle(), &urids.synth, &urids.atom)
+        .unwrap();
+    // The version, the gain, the voice, the steps and the cutoff. The sample is absent.
+    assert_eq!(5, storage.len());
+    assert_eq!(
+        Some((urids.atom.int.into_general(), &3i32.to_ne_bytes()[..])),
+        storage.retrieve(urids.version)
+    );
+
+    let mut restored = Synth::default();
+    restored
+        .restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+        .unwrap();
+    assert_eq!(synth, restored);
+}
+
+#[test]
+fn test_defaults_and_aliases() {
+    let urids = create_urids();
+
+    // A state of version 2, with the gain saved under an old key.
+    let mut storage = Storage::default();
+    store(&mut storage, urids.version, &2, &(), &urids.atom);
+    store(&mut storage, urids.level, &0.25f32, &(), &urids.atom);
+    store(
+        &mut storage,
+        urids.voice,
+        &Voice { detune: 0.5 },
+        &urids.voice_urids,
+        &urids.atom,
+    );
+
+    let mut synth = Synth::default();
+    synth
+        .restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+        .unwrap();
+    assert_eq!(
+        Synth {
+            gain: 0.25,
+            sample: None,
+            voice: Voice { detune: 0.5 },
+            steps: Vec::new(),
+            cutoff: 500.0,
+            migrated_from: Some(2),
+        },
+        synth
+    );
+
+    // The current key has precedence over the aliases.
+    store(&mut storage, urids.gain, &0.75f32, &(), &urids.atom);
+    synth
+        .restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+        .unwrap();
+    assert_eq!(0.75, synth.gain);
+    assert_eq!(1000.0, synth.cutoff);
+}
+
+#[test]
+fn test_migration() {
+    let urids = create_urids();
+
+    // A state without a version is treated as version 0.
+    let mut storage = Storage::default();
+    store(&mut storage, urids.gain, &50.0f32, &(), &urids.atom);
+    store(
+        &mut storage,
+        urids.voice,
+        &Voice { detune: 0.0 },
+        &urids.voice_urids,
+        &urids.atom,
+    );
+
+    let mut synth = Synth::default();
+    synth
+        .restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+        .unwrap();
+    assert_eq!(0.5, synth.gain);
+    assert_eq!(Some(0), synth.migrated_from);
+
+    // The current version isn't migrated.
+    store(&mut storage, urids.version, &3, &(), &urids.atom);
+    let mut synth = Synth::default();
+    synth
+        .restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+        .unwrap();
+    assert_eq!(50.0, synth.gain);
+    assert_eq!(None, synth.migrated_from);
+
+    // Newer versions are rejected.
+    store(&mut storage, urids.version, &4, &(), &urids.atom);
+    assert_eq!(
+        Err(StateErr::BadData),
+        synth.restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+    );
+}
+
+#[test]
+fn test_errors() {
+    let urids = create_urids();
+    let mut storage = Storage::default();
+    store(&mut storage, urids.gain, &0.5f32, &(), &urids.atom);
+
+    // The voice is required and has no default.
+    let mut synth = Synth::default();
+    assert_eq!(
+        Err(StateErr::NoProperty),
+        synth.restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+    );
+    assert_eq!(Synth::default(), synth);
+
+    // The voice has the wrong type.
+    store(&mut storage, urids.voice, &1.0f32, &(), &urids.atom);
+    assert_eq!(
+        Err(StateErr::BadType),
+        synth.restore(&storage.retrieve_handle(), &urids.synth, &urids.atom)
+    );
+    assert_eq!(Synth::default(), synth);
+
+    // Without a version, the version key isn't read or written.
+    let mut unversioned = Unversioned::default();
+    unversioned
+        .restore(&storage.retrieve_handle(), &urids.unversioned, &urids.atom)
+        .unwrap();
+    assert_eq!(0.5, unversioned.gain);
+
+    let mut storage = Storage::default();
+    unversioned
+        .save(&mut storage.store_handle(), &urids.unversioned, &urids.atom)
+        .unwrap();
+    assert_eq!(1, storage.len());
+}
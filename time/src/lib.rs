@@ -6,6 +6,7 @@
 //! descriptions defined by the specification by binding them to marker types.
 extern crate lv2_sys as sys;
 
+use lv2_atom::prelude::*;
 use urid::*;
 
 /// All time URI bounds
@@ -127,7 +128,125 @@ pub struct TimeURIDCollection {
     pub speed: URID<Speed>,
 }
 
+/// Update a tempo map with the properties of a `time:Position` object.
+///
+/// Hosts send these objects to a plugin's atom input whenever the transport changes. The tempo, speed, frame, beat within the bar and beats per bar are copied to the tempo map if they are present, all other properties are ignored.
+///
+/// Returns `false` if the atom isn't a position object; The tempo map remains untouched in this case.
+///
+/// # Example
+///
+/// ```
+/// # use lv2_atom::prelude::*;
+/// # use lv2_atom::sequence::*;
+/// # use lv2_time::prelude::*;
+/// # use urid::*;
+/// fn run(events: SequenceIterator, tempo: &mut TempoMap, urids: &TimeURIDCollection, atom_urids: &AtomURIDCollection) {
+///     let mut events = events.frames(*tempo);
+///     while let Some((offset, atom)) = events.next() {
+///         if update_tempo_map(tempo, atom, urids, atom_urids) {
+///             events.set_tempo(*tempo);
+///             continue;
+///         }
+///         // Handle the event at `offset`.
+///     }
+/// }
+/// ```
+pub fn update_tempo_map(
+    tempo: &mut TempoMap,
+    atom: UnidentifiedAtom,
+    urids: &TimeURIDCollection,
+    atom_urids: &AtomURIDCollection,
+) -> bool {
+    let reader = match atom.read_object(atom_urids) {
+        Ok((header, reader)) if header.otype == urids.position_class => reader,
+        _ => return false,
+    };
+
+    for (header, value) in reader {
+        if header.key == urids.beats_per_minute {
+            if let Some(bpm) = Number::read(value, atom_urids).map(Number::as_f64) {
+                tempo.beats_per_minute = bpm;
+            }
+        } else if header.key == urids.speed {
+            if let Some(speed) = Number::read(value, atom_urids).map(Number::as_f64) {
+                tempo.speed = speed;
+            }
+        } else if header.key == urids.frame {
+            if let Some(frame) = Number::read(value, atom_urids).map(Number::as_f64) {
+                tempo.frame = frame as i64;
+            }
+        } else if header.key == urids.bar_beat {
+            if let Some(bar_beat) = Number::read(value, atom_urids).map(Number::as_f64) {
+                tempo.bar_beat = bar_beat;
+            }
+        } else if header.key == urids.beats_per_bar {
+            if let Some(beats_per_bar) = Number::read(value, atom_urids).map(Number::as_f64) {
+                tempo.beats_per_bar = Some(beats_per_bar);
+            }
+        }
+    }
+    true
+}
+
 /// Prelude of `lv2_time` for wildcard usage.
 pub mod prelude {
-    pub use crate::TimeURIDCollection;
+    pub use crate::{update_tempo_map, TimeURIDCollection};
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::prelude::*;
+    use lv2_atom::prelude::*;
+    use lv2_atom::space::*;
+    use urid::*;
+
+    #[test]
+    fn test_update_tempo_map() {
+        let map = HashURIDMapper::new();
+        let urids: TimeURIDCollection = map.populate_collection().unwrap();
+        let atom_urids: AtomURIDCollection = map.populate_collection().unwrap();
+
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            let mut writer = (&mut space as &mut dyn MutSpace)
+                .init(
+                    atom_urids.object,
+                    ObjectHeader {
+                        id: None,
+                        otype: urids.position_class.into_general(),
+                    },
+                )
+                .unwrap();
+            writer
+                .init(urids.beats_per_minute, atom_urids.float, 90.0)
+                .unwrap();
+            writer.init(urids.speed, atom_urids.float, 0.5).unwrap();
+            writer.init(urids.frame, atom_urids.long, 44100).unwrap();
+            writer.init(urids.bar_beat, atom_urids.double, 1.5).unwrap();
+            writer.init(urids.beats_per_bar, atom_urids.int, 3).unwrap();
+        }
+
+        let mut tempo = TempoMap::new(44100.0, 120.0);
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        assert!(update_tempo_map(&mut tempo, atom, &urids, &atom_urids));
+        assert_eq!(90.0, tempo.beats_per_minute);
+        assert_eq!(0.5, tempo.speed);
+        assert_eq!(44100, tempo.frame);
+        assert_eq!(1.5, tempo.bar_beat);
+        assert_eq!(Some(3.0), tempo.beats_per_bar);
+
+        // Other atoms are ignored.
+        let mut raw_space: Box<[u8]> = Box::new([0; 256]);
+        {
+            let mut space = RootMutSpace::new(raw_space.as_mut());
+            (&mut space as &mut dyn MutSpace)
+                .init(atom_urids.float, 1.0)
+                .unwrap();
+        }
+        let atom = UnidentifiedAtom::new(Space::from_slice(raw_space.as_ref()));
+        assert!(!update_tempo_map(&mut tempo, atom, &urids, &atom_urids));
+        assert_eq!(90.0, tempo.beats_per_minute);
+    }
 }
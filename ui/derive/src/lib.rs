@@ -0,0 +1,12 @@
+//! Procedural macros for `lv2-ui`.
+extern crate proc_macro;
+
+mod lv2ui_descriptors;
+
+use proc_macro::TokenStream;
+
+/// Generate external symbols for LV2 plugin UIs.
+#[proc_macro]
+pub fn lv2ui_descriptors(input: TokenStream) -> TokenStream {
+    lv2ui_descriptors::lv2ui_descriptors_impl(input)
+}
@@ -0,0 +1,390 @@
+//! Thin but safe wrappers for the host features of UIs.
+use crate::UIErr;
+use core::feature::Feature;
+use core::prelude::*;
+use std::ffi::{c_void, CStr};
+use urid::*;
+
+/// Host feature to look up the index of a port by it's symbol.
+#[repr(transparent)]
+pub struct PortMap<'a> {
+    internal: &'a sys::LV2UI_Port_Map,
+}
+
+unsafe impl<'a> UriBound for PortMap<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__portMap;
+}
+
+unsafe impl<'a> Feature for PortMap<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self> {
+        if class != ThreadingClass::Audio {
+            (feature as *const sys::LV2UI_Port_Map)
+                .as_ref()
+                .map(|internal| Self { internal })
+        } else {
+            panic!("The UI port map feature isn't allowed in the audio threading class");
+        }
+    }
+}
+
+impl<'a> PortMap<'a> {
+    /// Return the index of the port with the given symbol, or `None` if there is no such port.
+    pub fn port_index(&self, symbol: &CStr) -> Option<u32> {
+        let port_index = self.internal.port_index?;
+        match unsafe { (port_index)(self.internal.handle, symbol.as_ptr()) } {
+            u32::MAX => None,
+            index => Some(index),
+        }
+    }
+}
+
+/// Host feature to subscribe to the events of a port.
+///
+/// By default, the host only notifies the UI about changes of control input ports. With this feature, the UI can also request notifications for other ports, for example to display a level meter.
+#[repr(transparent)]
+pub struct PortSubscribe<'a> {
+    internal: &'a sys::LV2UI_Port_Subscribe,
+}
+
+unsafe impl<'a> UriBound for PortSubscribe<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__portSubscribe;
+}
+
+unsafe impl<'a> Feature for PortSubscribe<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self> {
+        if class != ThreadingClass::Audio {
+            (feature as *const sys::LV2UI_Port_Subscribe)
+                .as_ref()
+                .map(|internal| Self { internal })
+        } else {
+            panic!("The UI port subscribe feature isn't allowed in the audio threading class");
+        }
+    }
+}
+
+impl<'a> PortSubscribe<'a> {
+    /// Subscribe to the events of a port, delivered with the given protocol.
+    pub fn subscribe<P: ?Sized>(&self, port_index: u32, protocol: URID<P>) -> Result<(), UIErr> {
+        let subscribe = self.internal.subscribe.ok_or(UIErr::BadCallback)?;
+        match unsafe {
+            (subscribe)(
+                self.internal.handle,
+                port_index,
+                protocol.get(),
+                std::ptr::null(),
+            )
+        } {
+            0 => Ok(()),
+            _ => Err(UIErr::Unknown),
+        }
+    }
+
+    /// Cancel a subscription of a port.
+    pub fn unsubscribe<P: ?Sized>(&self, port_index: u32, protocol: URID<P>) -> Result<(), UIErr> {
+        let unsubscribe = self.internal.unsubscribe.ok_or(UIErr::BadCallback)?;
+        match unsafe {
+            (unsubscribe)(
+                self.internal.handle,
+                port_index,
+                protocol.get(),
+                std::ptr::null(),
+            )
+        } {
+            0 => Ok(()),
+            _ => Err(UIErr::Unknown),
+        }
+    }
+}
+
+/// Host feature to notify the host about user interaction with a control.
+///
+/// While the user is changing a control, for example by dragging a knob, the host should not automate the corresponding port. The easiest way to tell it is to start a [`TouchGesture`](struct.TouchGesture.html), which ends when it's dropped.
+#[repr(transparent)]
+pub struct Touch<'a> {
+    internal: &'a sys::LV2UI_Touch,
+}
+
+unsafe impl<'a> UriBound for Touch<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__touch;
+}
+
+unsafe impl<'a> Feature for Touch<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self> {
+        if class != ThreadingClass::Audio {
+            (feature as *const sys::LV2UI_Touch)
+                .as_ref()
+                .map(|internal| Self { internal })
+        } else {
+            panic!("The UI touch feature isn't allowed in the audio threading class");
+        }
+    }
+}
+
+impl<'a> Touch<'a> {
+    /// Tell the host that the control of a port has been grabbed or released by the user.
+    pub fn touch(&self, port_index: u32, grabbed: bool) {
+        if let Some(touch) = self.internal.touch {
+            unsafe { (touch)(self.internal.handle, port_index, grabbed) }
+        }
+    }
+
+    /// Start a gesture on the control of a port.
+    ///
+    /// The control is grabbed immediately and released when the returned gesture is dropped.
+    pub fn gesture(&self, port_index: u32) -> TouchGesture<'_, 'a> {
+        self.touch(port_index, true);
+        TouchGesture {
+            touch: self,
+            port_index,
+        }
+    }
+}
+
+/// A running gesture on the control of a port.
+///
+/// The control is released when the gesture is dropped.
+pub struct TouchGesture<'b, 'a> {
+    touch: &'b Touch<'a>,
+    port_index: u32,
+}
+
+impl<'b, 'a> TouchGesture<'b, 'a> {
+    /// Return the index of the touched port.
+    pub fn port_index(&self) -> u32 {
+        self.port_index
+    }
+}
+
+impl<'b, 'a> Drop for TouchGesture<'b, 'a> {
+    fn drop(&mut self) {
+        self.touch.touch(self.port_index, false);
+    }
+}
+
+/// Host feature to request a new parameter value from the user.
+///
+/// This can be used to let the host show a dialog, for example a file chooser for a path parameter.
+#[repr(transparent)]
+pub struct RequestValue<'a> {
+    internal: &'a sys::LV2UI_Request_Value,
+}
+
+unsafe impl<'a> UriBound for RequestValue<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__requestValue;
+}
+
+unsafe impl<'a> Feature for RequestValue<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self> {
+        if class != ThreadingClass::Audio {
+            (feature as *const sys::LV2UI_Request_Value)
+                .as_ref()
+                .map(|internal| Self { internal })
+        } else {
+            panic!("The UI value request feature isn't allowed in the audio threading class");
+        }
+    }
+}
+
+impl<'a> RequestValue<'a> {
+    /// Ask the host to request a new value of the given parameter from the user.
+    ///
+    /// The type is the type of the requested value, or `None` if the host should use the range of the parameter. The new value is sent to the plugin by the host.
+    pub fn request<K: ?Sized>(&self, key: URID<K>, type_: Option<URID>) -> Result<(), UIErr> {
+        let request = self.internal.request.ok_or(UIErr::BadCallback)?;
+        UIErr::from(unsafe {
+            (request)(
+                self.internal.handle,
+                key.get(),
+                type_.map_or(0, URID::get),
+                std::ptr::null(),
+            )
+        })
+    }
+}
+
+/// Host feature to resize the UI.
+#[repr(transparent)]
+pub struct Resize<'a> {
+    internal: &'a sys::LV2UI_Resize,
+}
+
+unsafe impl<'a> UriBound for Resize<'a> {
+    const URI: &'static [u8] = sys::LV2_UI__resize;
+}
+
+unsafe impl<'a> Feature for Resize<'a> {
+    unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self> {
+        if class != ThreadingClass::Audio {
+            (feature as *const sys::LV2UI_Resize)
+                .as_ref()
+                .map(|internal| Self { internal })
+        } else {
+            panic!("The UI resize feature isn't allowed in the audio threading class");
+        }
+    }
+}
+
+impl<'a> Resize<'a> {
+    /// Ask the host to resize the UI to the given size, in pixels.
+    pub fn resize(&self, width: i32, height: i32) -> Result<(), UIErr> {
+        let ui_resize = self.internal.ui_resize.ok_or(UIErr::BadCallback)?;
+        match unsafe { (ui_resize)(self.internal.handle, width, height) } {
+            0 => Ok(()),
+            _ => Err(UIErr::Unknown),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::feature::*;
+    use std::cell::RefCell;
+    use std::os::raw::{c_char, c_int};
+
+    thread_local! {
+        static CALLS: RefCell<Vec<(u32, u32)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    fn record(a: u32, b: u32) {
+        CALLS.with(|calls| calls.borrow_mut().push((a, b)));
+    }
+
+    fn take_calls() -> Vec<(u32, u32)> {
+        CALLS.with(|calls| calls.borrow_mut().drain(..).collect())
+    }
+
+    unsafe extern "C" fn port_index(_: sys::LV2UI_Feature_Handle, symbol: *const c_char) -> u32 {
+        match CStr::from_ptr(symbol).to_bytes() {
+            b"gain" => 2,
+            _ => u32::MAX,
+        }
+    }
+
+    unsafe extern "C" fn subscribe(
+        _: sys::LV2UI_Feature_Handle,
+        port_index: u32,
+        protocol: u32,
+        _: *const *const sys::LV2_Feature,
+    ) -> u32 {
+        record(port_index, protocol);
+        if port_index < 8 {
+            0
+        } else {
+            1
+        }
+    }
+
+    unsafe extern "C" fn touch(_: sys::LV2UI_Feature_Handle, port_index: u32, grabbed: bool) {
+        record(port_index, grabbed as u32);
+    }
+
+    unsafe extern "C" fn request(
+        _: sys::LV2UI_Feature_Handle,
+        key: u32,
+        type_: u32,
+        _: *const *const sys::LV2_Feature,
+    ) -> sys::LV2UI_Request_Value_Status {
+        record(key, type_);
+        sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_BUSY
+    }
+
+    unsafe extern "C" fn ui_resize(
+        _: sys::LV2UI_Feature_Handle,
+        width: c_int,
+        height: c_int,
+    ) -> c_int {
+        record(width as u32, height as u32);
+        0
+    }
+
+    #[test]
+    fn test_port_map() {
+        let raw = sys::LV2UI_Port_Map {
+            handle: std::ptr::null_mut(),
+            port_index: Some(port_index),
+        };
+        let port_map = unsafe {
+            PortMap::from_feature_ptr(&raw as *const _ as *const c_void, ThreadingClass::Other)
+        }
+        .unwrap();
+        assert_eq!(Some(2), port_map.port_index(c"gain"));
+        assert_eq!(None, port_map.port_index(c"volume"));
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_port_map_in_audio_class() {
+        let raw = sys::LV2UI_Port_Map {
+            handle: std::ptr::null_mut(),
+            port_index: Some(port_index),
+        };
+        let _ = unsafe {
+            PortMap::from_feature_ptr(&raw as *const _ as *const c_void, ThreadingClass::Audio)
+        };
+    }
+
+    #[test]
+    fn test_port_subscribe() {
+        let urid = unsafe { URID::<()>::new_unchecked(5) };
+        let raw = sys::LV2UI_Port_Subscribe {
+            handle: std::ptr::null_mut(),
+            subscribe: Some(subscribe),
+            unsubscribe: None,
+        };
+        let port_subscribe = unsafe {
+            PortSubscribe::from_feature_ptr(
+                &raw as *const _ as *const c_void,
+                ThreadingClass::Other,
+            )
+        }
+        .unwrap();
+        assert_eq!(Ok(()), port_subscribe.subscribe(3, urid));
+        assert_eq!(Err(UIErr::Unknown), port_subscribe.subscribe(9, urid));
+        assert_eq!(Err(UIErr::BadCallback), port_subscribe.unsubscribe(3, urid));
+        assert_eq!(vec![(3, 5), (9, 5)], take_calls());
+    }
+
+    #[test]
+    fn test_touch() {
+        let raw = sys::LV2UI_Touch {
+            handle: std::ptr::null_mut(),
+            touch: Some(touch),
+        };
+        let touch = unsafe {
+            Touch::from_feature_ptr(&raw as *const _ as *const c_void, ThreadingClass::Other)
+        }
+        .unwrap();
+        {
+            let gesture = touch.gesture(4);
+            assert_eq!(4, gesture.port_index());
+            assert_eq!(vec![(4, 1)], take_calls());
+        }
+        assert_eq!(vec![(4, 0)], take_calls());
+    }
+
+    #[test]
+    fn test_request_value_and_resize() {
+        let key = unsafe { URID::<()>::new_unchecked(7) };
+        let raw = sys::LV2UI_Request_Value {
+            handle: std::ptr::null_mut(),
+            request: Some(request),
+        };
+        let request_value = unsafe {
+            RequestValue::from_feature_ptr(&raw as *const _ as *const c_void, ThreadingClass::Other)
+        }
+        .unwrap();
+        assert_eq!(Err(UIErr::Busy), request_value.request(key, None));
+
+        let raw = sys::LV2UI_Resize {
+            handle: std::ptr::null_mut(),
+            ui_resize: Some(ui_resize),
+        };
+        let resize = unsafe {
+            Resize::from_feature_ptr(&raw as *const _ as *const c_void, ThreadingClass::Other)
+        }
+        .unwrap();
+        assert_eq!(Ok(()), resize.resize(640, 480));
+
+        assert_eq!(vec![(7, 0), (640, 480)], take_calls());
+    }
+}
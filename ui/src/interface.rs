@@ -0,0 +1,131 @@
+//! Extensions for UIs that aren't embedded into the host.
+use crate::plugin_ui::*;
+use core::extension::ExtensionDescriptor;
+use std::marker::PhantomData;
+use std::os::raw::c_int;
+use urid::*;
+
+/// A UI extension that lets the host drive the UI by calling it regularly.
+///
+/// UIs that don't provide a widget need to handle the events of their own window, which is done in the [`idle`](#tymethod.idle) method. It is called by the host regularly, usually at the UI update rate.
+pub trait IdleInterface: PluginUI {
+    /// Run a single iteration of the UI's event loop.
+    ///
+    /// Returns `false` if the UI has been closed by the user and `true` otherwise.
+    fn idle(&mut self, features: &mut Self::InitFeatures) -> bool;
+}
+
+/// Raw wrapper of the [`IdleInterface`](trait.IdleInterface.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct IdleDescriptor<U: IdleInterface> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: IdleInterface> UriBound for IdleDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__idleInterface;
+}
+
+impl<U: IdleInterface> IdleDescriptor<U> {
+    /// Handle an idle call by the host.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_idle(handle: sys::LV2UI_Handle) -> c_int {
+        match (handle as *mut PluginUIInstance<U>).as_mut() {
+            Some(instance) => {
+                let (ui, features) = instance.ui_handle();
+                if ui.idle(features) {
+                    0
+                } else {
+                    1
+                }
+            }
+            None => 1,
+        }
+    }
+}
+
+impl<U: IdleInterface> ExtensionDescriptor for IdleDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Idle_Interface;
+
+    const INTERFACE: &'static sys::LV2UI_Idle_Interface = &sys::LV2UI_Idle_Interface {
+        idle: Some(Self::extern_idle),
+    };
+}
+
+/// A UI extension that lets the host show and hide the UI's own window.
+///
+/// A UI that implements this extension also has to implement the [`IdleInterface`](trait.IdleInterface.html) since the host uses it to drive the UI.
+pub trait ShowInterface: IdleInterface {
+    /// Show the window of the UI, or bring it to the front if it's already visible.
+    ///
+    /// Returns `false` if the window couldn't be shown.
+    fn show(&mut self, features: &mut Self::InitFeatures) -> bool;
+
+    /// Hide the window of the UI.
+    ///
+    /// Returns `false` if the window couldn't be hidden.
+    fn hide(&mut self, features: &mut Self::InitFeatures) -> bool;
+}
+
+/// Raw wrapper of the [`ShowInterface`](trait.ShowInterface.html) extension.
+///
+/// This is a marker type that has the required external methods for the extension.
+pub struct ShowDescriptor<U: ShowInterface> {
+    ui: PhantomData<U>,
+}
+
+unsafe impl<U: ShowInterface> UriBound for ShowDescriptor<U> {
+    const URI: &'static [u8] = sys::LV2_UI__showInterface;
+}
+
+impl<U: ShowInterface> ShowDescriptor<U> {
+    /// Handle a show request by the host.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_show(handle: sys::LV2UI_Handle) -> c_int {
+        match (handle as *mut PluginUIInstance<U>).as_mut() {
+            Some(instance) => {
+                let (ui, features) = instance.ui_handle();
+                if ui.show(features) {
+                    0
+                } else {
+                    1
+                }
+            }
+            None => 1,
+        }
+    }
+
+    /// Handle a hide request by the host.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it is an interface for hosts written in C and since it dereferences raw pointers.
+    pub unsafe extern "C" fn extern_hide(handle: sys::LV2UI_Handle) -> c_int {
+        match (handle as *mut PluginUIInstance<U>).as_mut() {
+            Some(instance) => {
+                let (ui, features) = instance.ui_handle();
+                if ui.hide(features) {
+                    0
+                } else {
+                    1
+                }
+            }
+            None => 1,
+        }
+    }
+}
+
+impl<U: ShowInterface> ExtensionDescriptor for ShowDescriptor<U> {
+    type ExtensionInterface = sys::LV2UI_Show_Interface;
+
+    const INTERFACE: &'static sys::LV2UI_Show_Interface = &sys::LV2UI_Show_Interface {
+        show: Some(Self::extern_show),
+        hide: Some(Self::extern_hide),
+    };
+}
@@ -0,0 +1,227 @@
+//! LV2 specification for plugin user interfaces.
+//!
+//! A UI is a separate object that lives in the UI thread of the host and controls a plugin instance via it's ports: The host tells the UI about port changes and the UI sends new values to the plugin. This crate implements the plugin side of this specification without depending on any UI toolkit; The UI only has to provide a raw widget pointer or drive it's own window via the [`IdleInterface`](interface/trait.IdleInterface.html) and [`ShowInterface`](interface/trait.ShowInterface.html).
+//!
+//! See the original [specification](https://lv2plug.in/ns/extensions/ui) for more information.
+//!
+//! # Example
+//!
+//! This example UI shows how to receive and send the value of a gain control port. The actual drawing is left out.
+//!
+//! ```
+//! use lv2_core::prelude::*;
+//! use lv2_ui::prelude::*;
+//! use urid::*;
+//!
+//! const GAIN_PORT: u32 = 0;
+//!
+//! #[uri("urn:my-plugin#ui")]
+//! struct MyUI {
+//!     write_function: WriteFunction,
+//!     gain: f32,
+//!     open: bool,
+//! }
+//!
+//! impl PluginUI for MyUI {
+//!     type InitFeatures = ();
+//!
+//!     fn new(_: &PluginUIInfo, write_function: WriteFunction, _: &mut ()) -> Option<Self> {
+//!         Some(Self {
+//!             write_function,
+//!             gain: 0.0,
+//!             open: true,
+//!         })
+//!     }
+//!
+//!     fn port_event(&mut self, event: PortEvent, _: &mut ()) {
+//!         if event.port_index() == GAIN_PORT {
+//!             if let Some(gain) = event.control() {
+//!                 self.gain = gain;
+//!             }
+//!         }
+//!     }
+//!
+//!     fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+//!         match_extensions!(uri, IdleDescriptor<Self>, ShowDescriptor<Self>)
+//!     }
+//! }
+//!
+//! impl IdleInterface for MyUI {
+//!     fn idle(&mut self, _: &mut ()) -> bool {
+//!         // Process the events of the window and send new values to the plugin.
+//!         self.write_function.write_control(GAIN_PORT, self.gain);
+//!         self.open
+//!     }
+//! }
+//!
+//! impl ShowInterface for MyUI {
+//!     fn show(&mut self, _: &mut ()) -> bool {
+//!         self.open = true;
+//!         true
+//!     }
+//!
+//!     fn hide(&mut self, _: &mut ()) -> bool {
+//!         self.open = false;
+//!         true
+//!     }
+//! }
+//!
+//! lv2ui_descriptors!(MyUI);
+//! ```
+extern crate lv2_atom as atom;
+extern crate lv2_core as core;
+extern crate lv2_sys as sys;
+
+pub mod feature;
+pub mod interface;
+pub mod plugin_ui;
+pub mod port;
+
+use urid::*;
+
+/// All UI URI bounds.
+pub mod ui {
+    use urid::UriBound;
+
+    /// UI class of UIs that are Cocoa `NSView`s.
+    pub struct CocoaUI;
+    unsafe impl UriBound for CocoaUI {
+        const URI: &'static [u8] = sys::LV2_UI__CocoaUI;
+    }
+
+    /// UI class of UIs that are Gtk2 widgets.
+    pub struct GtkUI;
+    unsafe impl UriBound for GtkUI {
+        const URI: &'static [u8] = sys::LV2_UI__GtkUI;
+    }
+
+    /// UI class of UIs that are Gtk3 widgets.
+    pub struct Gtk3UI;
+    unsafe impl UriBound for Gtk3UI {
+        const URI: &'static [u8] = sys::LV2_UI__Gtk3UI;
+    }
+
+    /// UI class of UIs that are Qt5 widgets.
+    pub struct Qt5UI;
+    unsafe impl UriBound for Qt5UI {
+        const URI: &'static [u8] = sys::LV2_UI__Qt5UI;
+    }
+
+    /// UI class of UIs that are Windows `HWND` windows.
+    pub struct WindowsUI;
+    unsafe impl UriBound for WindowsUI {
+        const URI: &'static [u8] = sys::LV2_UI__WindowsUI;
+    }
+
+    /// UI class of UIs that are X11 windows.
+    pub struct X11UI;
+    unsafe impl UriBound for X11UI {
+        const URI: &'static [u8] = sys::LV2_UI__X11UI;
+    }
+
+    /// Protocol for single float values of control ports.
+    pub struct FloatProtocol;
+    unsafe impl UriBound for FloatProtocol {
+        const URI: &'static [u8] = sys::LV2_UI__floatProtocol;
+    }
+
+    /// Protocol for peak measurements of audio ports.
+    pub struct PeakProtocol;
+    unsafe impl UriBound for PeakProtocol {
+        const URI: &'static [u8] = sys::LV2_UI__peakProtocol;
+    }
+
+    /// Protocol to replace the content of an atom port with an atom.
+    pub struct AtomTransfer;
+    unsafe impl UriBound for AtomTransfer {
+        const URI: &'static [u8] = sys::LV2_ATOM__atomTransfer;
+    }
+
+    /// Protocol to append an atom to the sequence of an atom port.
+    pub struct EventTransfer;
+    unsafe impl UriBound for EventTransfer {
+        const URI: &'static [u8] = sys::LV2_ATOM__eventTransfer;
+    }
+}
+
+use ui::*;
+
+/// A URID cache containing all UI protocols.
+#[derive(URIDCollection)]
+pub struct UIURIDCollection {
+    pub float_protocol: URID<FloatProtocol>,
+    pub peak_protocol: URID<PeakProtocol>,
+    pub atom_transfer: URID<AtomTransfer>,
+    pub event_transfer: URID<EventTransfer>,
+}
+
+/// Errors that may occur when using the host features of a UI.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum UIErr {
+    /// The host reported an unknown error.
+    Unknown,
+    /// The host provided a null function pointer.
+    BadCallback,
+    /// The host is currently busy and can't handle the request.
+    Busy,
+    /// The host doesn't support the request.
+    Unsupported,
+}
+
+impl UIErr {
+    /// Convert a raw value request status to a result.
+    pub fn from(status: sys::LV2UI_Request_Value_Status) -> Result<(), UIErr> {
+        match status {
+            sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_SUCCESS => Ok(()),
+            sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_BUSY => Err(UIErr::Busy),
+            sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_ERR_UNSUPPORTED => {
+                Err(UIErr::Unsupported)
+            }
+            _ => Err(UIErr::Unknown),
+        }
+    }
+
+    /// Convert a result to a raw value request status.
+    pub fn into(result: Result<(), UIErr>) -> sys::LV2UI_Request_Value_Status {
+        match result {
+            Ok(()) => sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_SUCCESS,
+            Err(UIErr::Busy) => sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_BUSY,
+            Err(UIErr::Unsupported) => {
+                sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_ERR_UNSUPPORTED
+            }
+            Err(UIErr::Unknown) | Err(UIErr::BadCallback) => {
+                sys::LV2UI_Request_Value_Status_LV2UI_REQUEST_VALUE_ERR_UNKNOWN
+            }
+        }
+    }
+}
+
+/// Prelude of `lv2_ui` for wildcard usage.
+pub mod prelude {
+    pub use crate::feature::*;
+    pub use crate::interface::*;
+    pub use crate::plugin_ui::{
+        lv2ui_descriptors, PluginUI, PluginUIInfo, PluginUIInstance, PluginUIInstanceDescriptor,
+    };
+    pub use crate::port::*;
+    pub use crate::ui::*;
+    pub use crate::{UIErr, UIURIDCollection};
+    pub use sys::LV2UI_Descriptor;
+}
+
+#[cfg(test)]
+mod test {
+    use crate::UIErr;
+
+    #[test]
+    fn test_ui_err_conversion() {
+        assert_eq!(Ok(()), UIErr::from(UIErr::into(Ok(()))));
+        for err in [UIErr::Unknown, UIErr::Busy, UIErr::Unsupported] {
+            assert_eq!(Err(err), UIErr::from(UIErr::into(Err(err))));
+        }
+        assert_eq!(
+            Err(UIErr::Unknown),
+            UIErr::from(UIErr::into(Err(UIErr::BadCallback)))
+        );
+    }
+}
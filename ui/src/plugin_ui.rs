@@ -0,0 +1,274 @@
+//! Types to create plugin UIs.
+use crate::port::*;
+use core::feature::*;
+use std::any::Any;
+use std::ffi::c_void;
+use std::os::raw::c_char;
+use std::path::Path;
+use std::str::Utf8Error;
+use urid::*;
+
+pub use lv2_ui_derive::*;
+
+/// The central trait to describe LV2 plugin UIs.
+///
+/// A UI runs in the host's UI thread and communicates with the plugin instance exclusively via ports: The host notifies the UI about port changes by calling [`port_event`](#method.port_event) and the UI sends new values to the plugin with the [`WriteFunction`](../port/struct.WriteFunction.html) it received in [`new`](#tymethod.new).
+///
+/// This trait does not depend on any UI toolkit. A UI that embeds a native widget returns it with [`widget`](#method.widget), while a UI that opens it's own window implements the [`IdleInterface`](../interface/trait.IdleInterface.html) and the [`ShowInterface`](../interface/trait.ShowInterface.html) instead.
+///
+/// Like plugins, UIs don't talk to the host directly. Instead, the host talks to a [`PluginUIInstance`](struct.PluginUIInstance.html), which dereferences the raw pointers and calls the corresponding methods of the UI.
+pub trait PluginUI: UriBound + Sized + 'static {
+    /// The host features used by this UI.
+    ///
+    /// This collection will be created by the framework when the UI is instantiated and every method of the UI has access to it via a mutable reference. If a required feature is missing, the instantiation simply fails.
+    type InitFeatures: FeatureCollection<'static>;
+
+    /// Create a new UI instance.
+    ///
+    /// The write function is used to send data to the input ports of the plugin and should be stored by the UI.
+    fn new(
+        ui_info: &PluginUIInfo,
+        write_function: WriteFunction,
+        features: &mut Self::InitFeatures,
+    ) -> Option<Self>;
+
+    /// Return the main widget of the UI.
+    ///
+    /// The type of the widget is defined by the UI class of the UI, for example a `GtkWidget*` for a `ui:GtkUI` or a native window handle for a `ui:X11UI`. This method is called once, right after the UI has been created, and the default implementation returns a null pointer, which is correct for UIs that only use the show interface.
+    fn widget(&mut self) -> sys::LV2UI_Widget {
+        std::ptr::null_mut()
+    }
+
+    /// Handle a port event.
+    ///
+    /// This method is called by the host when the value of a control port changes and for all ports the UI has subscribed to. The data of the event is only valid during the call.
+    fn port_event(&mut self, _event: PortEvent, _features: &mut Self::InitFeatures) {}
+
+    /// Clean up the UI.
+    ///
+    /// This method is called right before the UI is dropped, while the host features are still available. The host doesn't access the widget after this call.
+    fn cleanup(&mut self, _features: &mut Self::InitFeatures) {}
+
+    /// Return additional, extension-specific data.
+    ///
+    /// This works exactly like [`Plugin::extension_data`](../../lv2_core/plugin/trait.Plugin.html#method.extension_data): In most cases, you can simply use the `match_extensions` macro to generate an appropiate method body.
+    fn extension_data(_uri: &Uri) -> Option<&'static dyn Any> {
+        None
+    }
+}
+
+/// Errors that may occur when the plugin UI info is created from raw information.
+#[derive(Debug)]
+pub enum PluginUIInfoError {
+    InvalidBundlePathUtf8(Utf8Error),
+}
+
+/// Holds various data that is passed from the host at UI instantiation time.
+pub struct PluginUIInfo<'a> {
+    ui_uri: &'a Uri,
+    plugin_uri: &'a Uri,
+    bundle_path: &'a Path,
+}
+
+impl<'a> PluginUIInfo<'a> {
+    /// Create a new UI info instance from raw information.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it dereferences raw pointers. It does not check the pointers for validity.
+    pub unsafe fn from_raw(
+        ui_descriptor: *const sys::LV2UI_Descriptor,
+        plugin_uri: *const c_char,
+        bundle_path: *const c_char,
+    ) -> Result<Self, PluginUIInfoError> {
+        let bundle_path = Path::new(
+            Uri::from_ptr(bundle_path)
+                .to_str()
+                .map_err(PluginUIInfoError::InvalidBundlePathUtf8)?,
+        );
+        Ok(Self::new(
+            Uri::from_ptr((*ui_descriptor).URI),
+            Uri::from_ptr(plugin_uri),
+            bundle_path,
+        ))
+    }
+
+    /// Create a new UI info instance.
+    pub fn new(ui_uri: &'a Uri, plugin_uri: &'a Uri, bundle_path: &'a Path) -> Self {
+        Self {
+            ui_uri,
+            plugin_uri,
+            bundle_path,
+        }
+    }
+
+    /// The URI of the UI that is being instantiated.
+    pub fn ui_uri(&self) -> &Uri {
+        self.ui_uri
+    }
+
+    /// The URI of the plugin this UI controls.
+    pub fn plugin_uri(&self) -> &Uri {
+        self.plugin_uri
+    }
+
+    /// The path to the LV2 bundle directory which contains this UI binary.
+    pub fn bundle_path(&self) -> &Path {
+        self.bundle_path
+    }
+}
+
+/// UI wrapper which translates between the host and the UI.
+///
+/// This struct is `repr(C)` and has the UI as it's first field. Therefore, a valid `*mut PluginUIInstance<T>` is also a valid `*mut T`.
+#[repr(C)]
+pub struct PluginUIInstance<T: PluginUI> {
+    /// The UI instance.
+    instance: T,
+    /// All features that may be used by the UI.
+    features: T::InitFeatures,
+}
+
+impl<T: PluginUI> PluginUIInstance<T> {
+    /// Instantiate the UI.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    #[allow(clippy::too_many_arguments)]
+    pub unsafe extern "C" fn instantiate(
+        descriptor: *const sys::LV2UI_Descriptor,
+        plugin_uri: *const c_char,
+        bundle_path: *const c_char,
+        write_function: sys::LV2UI_Write_Function,
+        controller: sys::LV2UI_Controller,
+        widget: *mut sys::LV2UI_Widget,
+        features: *const *const sys::LV2_Feature,
+    ) -> sys::LV2UI_Handle {
+        if descriptor.is_null() || plugin_uri.is_null() || bundle_path.is_null() {
+            eprintln!("Failed to initialize plugin UI: Null pointer passed by host");
+            return std::ptr::null_mut();
+        }
+
+        // Dereference the UI info.
+        let ui_info = match PluginUIInfo::from_raw(descriptor, plugin_uri, bundle_path) {
+            Ok(info) => info,
+            Err(e) => {
+                eprintln!(
+                    "Failed to initialize plugin UI: Illegal info from host: {:?}",
+                    e
+                );
+                return std::ptr::null_mut();
+            }
+        };
+
+        let write_function = match WriteFunction::new(write_function, Controller::new(controller)) {
+            Some(write_function) => write_function,
+            None => {
+                eprintln!("Failed to initialize plugin UI: Write function is null");
+                return std::ptr::null_mut();
+            }
+        };
+
+        // Collect the supported features.
+        let mut feature_cache = FeatureCache::from_raw(features);
+        let mut features =
+            match T::InitFeatures::from_cache(&mut feature_cache, ThreadingClass::Other) {
+                Ok(f) => f,
+                Err(e) => {
+                    eprintln!("{}", e);
+                    return std::ptr::null_mut();
+                }
+            };
+
+        // Instantiate the UI.
+        match T::new(&ui_info, write_function, &mut features) {
+            Some(instance) => {
+                let instance = Box::leak(Box::new(Self { instance, features }));
+                if let Some(widget) = widget.as_mut() {
+                    *widget = instance.instance.widget();
+                }
+                instance as *mut Self as sys::LV2UI_Handle
+            }
+            None => std::ptr::null_mut(),
+        }
+    }
+
+    /// Clean up and drop the UI.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn cleanup(instance: sys::LV2UI_Handle) {
+        if instance.is_null() {
+            return;
+        }
+        let mut instance = Box::from_raw(instance as *mut Self);
+        let (ui, features) = instance.ui_handle();
+        ui.cleanup(features);
+    }
+
+    /// Construct a port event and call the `port_event` method.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn port_event(
+        instance: sys::LV2UI_Handle,
+        port_index: u32,
+        buffer_size: u32,
+        format: u32,
+        buffer: *const c_void,
+    ) {
+        let instance = match (instance as *mut Self).as_mut() {
+            Some(instance) => instance,
+            None => return,
+        };
+        if buffer.is_null() {
+            return;
+        }
+
+        let data = std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize);
+        let event = PortEvent::new(port_index, URID::new(format), data);
+        instance.instance.port_event(event, &mut instance.features);
+    }
+
+    /// Dereference the URI, call the `extension_data` function and return the pointer.
+    ///
+    /// This method provides a required method for the C interface of a UI and is used by the `lv2ui_descriptors` macro.
+    ///
+    /// # Safety
+    ///
+    /// This method is unsafe since it derefences multiple raw pointers and is part of the C interface.
+    pub unsafe extern "C" fn extension_data(uri: *const c_char) -> *const c_void {
+        let uri = Uri::from_ptr(uri);
+        if let Some(data) = T::extension_data(uri) {
+            data as *const _ as *const c_void
+        } else {
+            std::ptr::null()
+        }
+    }
+
+    /// Retrieve the internal UI.
+    pub fn plugin_ui_handle(&mut self) -> &mut T {
+        &mut self.instance
+    }
+
+    /// Retrieve the required handles to execute a UI method.
+    ///
+    /// This method can be used by extensions to call an extension method and provide it the host features of the UI.
+    pub fn ui_handle(&mut self) -> (&mut T, &mut T::InitFeatures) {
+        (&mut self.instance, &mut self.features)
+    }
+}
+
+#[doc(hidden)]
+pub unsafe trait PluginUIInstanceDescriptor: PluginUI {
+    const DESCRIPTOR: sys::LV2UI_Descriptor;
+}
@@ -0,0 +1,230 @@
+//! Communication between the UI and the ports of the plugin.
+use atom::space::Space;
+use atom::UnidentifiedAtom;
+use std::ffi::c_void;
+use urid::*;
+
+/// Opaque handle of the host that identifies the plugin instance a UI controls.
+///
+/// The controller is passed to the UI when it's instantiated and has to be passed back to the host together with data for the plugin, which is done by the [`WriteFunction`](struct.WriteFunction.html).
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Controller {
+    internal: sys::LV2UI_Controller,
+}
+
+impl Controller {
+    /// Wrap the raw controller handle of the host.
+    pub fn new(internal: sys::LV2UI_Controller) -> Self {
+        Self { internal }
+    }
+
+    /// Return the raw controller handle.
+    pub fn as_ptr(&self) -> sys::LV2UI_Controller {
+        self.internal
+    }
+}
+
+/// Function to send data from the UI to the input ports of the plugin.
+///
+/// Every write targets a port, identified by it's index, and uses a protocol. Control ports accept single floats without a protocol, which is done with [`write_control`](#method.write_control), and atom ports accept atoms with the `atom:atomTransfer` or `atom:eventTransfer` protocol, which is done with [`write_atom`](#method.write_atom).
+#[derive(Clone, Copy)]
+pub struct WriteFunction {
+    function: unsafe extern "C" fn(sys::LV2UI_Controller, u32, u32, u32, *const c_void),
+    controller: Controller,
+}
+
+impl WriteFunction {
+    /// Create a write function from the raw function pointer and controller of the host.
+    ///
+    /// `None` is returned if the function pointer is null.
+    pub fn new(function: sys::LV2UI_Write_Function, controller: Controller) -> Option<Self> {
+        function.map(|function| Self {
+            function,
+            controller,
+        })
+    }
+
+    /// Return the controller this function writes to.
+    pub fn controller(&self) -> Controller {
+        self.controller
+    }
+
+    /// Set the value of a control port.
+    pub fn write_control(&self, port_index: u32, value: f32) {
+        unsafe {
+            (self.function)(
+                self.controller.as_ptr(),
+                port_index,
+                std::mem::size_of::<f32>() as u32,
+                0,
+                &value as *const f32 as *const c_void,
+            )
+        }
+    }
+
+    /// Send an atom to a port.
+    ///
+    /// The protocol is usually `atom:eventTransfer`, which appends the atom to the port's sequence, or `atom:atomTransfer`, which replaces the port's content. Returns `false` if the atom is malformed and therefore wasn't sent.
+    pub fn write_atom<P: ?Sized>(
+        &self,
+        port_index: u32,
+        protocol: URID<P>,
+        atom: UnidentifiedAtom,
+    ) -> bool {
+        match atom
+            .space()
+            .split_atom()
+            .and_then(|(atom_space, _)| atom_space.data())
+        {
+            Some(data) => {
+                self.write_raw(port_index, protocol, data);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Send raw data with the given protocol to a port.
+    pub fn write_raw<P: ?Sized>(&self, port_index: u32, protocol: URID<P>, data: &[u8]) {
+        unsafe {
+            (self.function)(
+                self.controller.as_ptr(),
+                port_index,
+                data.len() as u32,
+                protocol.get(),
+                data.as_ptr() as *const c_void,
+            )
+        }
+    }
+}
+
+/// Notification of the host that something happened at a port of the plugin.
+///
+/// The event contains the index of the port, the protocol of the data and the data itself. The data is only valid during the [`port_event`](../plugin_ui/trait.PluginUI.html#method.port_event) call.
+pub struct PortEvent<'a> {
+    port_index: u32,
+    protocol: Option<URID>,
+    data: &'a [u8],
+}
+
+impl<'a> PortEvent<'a> {
+    /// Create a new port event.
+    ///
+    /// A protocol of `None` marks the new value of a control port, which has to be a single float.
+    pub fn new(port_index: u32, protocol: Option<URID>, data: &'a [u8]) -> Self {
+        Self {
+            port_index,
+            protocol,
+            data,
+        }
+    }
+
+    /// Return the index of the port.
+    pub fn port_index(&self) -> u32 {
+        self.port_index
+    }
+
+    /// Return the protocol of the data, or `None` if it's the value of a control port.
+    pub fn protocol(&self) -> Option<URID> {
+        self.protocol
+    }
+
+    /// Return the raw data of the event.
+    pub fn data(&self) -> &'a [u8] {
+        self.data
+    }
+
+    /// Try to interpret the event as the new value of a control port.
+    pub fn control(&self) -> Option<f32> {
+        if self.protocol.is_some() {
+            return None;
+        }
+        self.data.try_into().ok().map(f32::from_ne_bytes)
+    }
+
+    /// Try to interpret the event as an atom.
+    ///
+    /// This is the case for events with the `atom:atomTransfer` or `atom:eventTransfer` protocol.
+    pub fn atom(&self) -> Option<UnidentifiedAtom<'a>> {
+        self.protocol?;
+        Space::from_slice(self.data)
+            .split_atom()
+            .map(|(space, _)| UnidentifiedAtom::new(space))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::port::*;
+    use crate::UIURIDCollection;
+    use atom::prelude::*;
+    use atom::space::*;
+    use std::cell::RefCell;
+
+    thread_local! {
+        static WRITTEN: RefCell<Vec<(u32, u32, Vec<u8>)>> = const { RefCell::new(Vec::new()) };
+    }
+
+    unsafe extern "C" fn write(
+        _: sys::LV2UI_Controller,
+        port_index: u32,
+        buffer_size: u32,
+        protocol: u32,
+        buffer: *const c_void,
+    ) {
+        let data = std::slice::from_raw_parts(buffer as *const u8, buffer_size as usize);
+        WRITTEN.with(|written| {
+            written
+                .borrow_mut()
+                .push((port_index, protocol, data.to_vec()))
+        });
+    }
+
+    #[test]
+    fn test_write_function() {
+        let map = HashURIDMapper::new();
+        let urids: AtomURIDCollection = map.populate_collection().unwrap();
+        let ui_urids: UIURIDCollection = map.populate_collection().unwrap();
+
+        assert!(WriteFunction::new(None, Controller::new(std::ptr::null_mut())).is_none());
+        let write_function =
+            WriteFunction::new(Some(write), Controller::new(std::ptr::null_mut())).unwrap();
+
+        write_function.write_control(3, 0.5);
+
+        let mut raw_space: Box<[u64]> = vec![0; 4].into_boxed_slice();
+        {
+            let mut space = RootMutSpace::new(unsafe {
+                std::slice::from_raw_parts_mut(raw_space.as_mut_ptr() as *mut u8, 32)
+            });
+            (&mut space as &mut dyn MutSpace)
+                .init(urids.int, 42)
+                .unwrap();
+        }
+        let atom_space = Space::from_slice(unsafe {
+            std::slice::from_raw_parts(raw_space.as_ptr() as *const u8, 32)
+        });
+        let atom = UnidentifiedAtom::new(atom_space);
+        assert!(write_function.write_atom(4, ui_urids.event_transfer, atom));
+
+        let written = WRITTEN.with(|written| written.borrow().clone());
+        assert_eq!(2, written.len());
+        assert_eq!((3, 0, 0.5f32.to_ne_bytes().to_vec()), written[0]);
+        assert_eq!(4, written[1].0);
+        assert_eq!(ui_urids.event_transfer, written[1].1);
+        assert_eq!(12, written[1].2.len());
+
+        let event = PortEvent::new(
+            4,
+            Some(ui_urids.event_transfer.into_general()),
+            &written[1].2,
+        );
+        assert_eq!(None, event.control());
+        assert_eq!(Some(42), event.atom().unwrap().read(urids.int, ()));
+
+        let event = PortEvent::new(3, None, &written[0].2);
+        assert_eq!(Some(0.5), event.control());
+        assert!(event.atom().is_none());
+    }
+}
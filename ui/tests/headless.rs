@@ -0,0 +1,180 @@
+use lv2_core::feature::IsLive;
+use lv2_core::prelude::*;
+use lv2_ui::prelude::*;
+use std::cell::RefCell;
+use std::ffi::c_void;
+use std::os::raw::{c_char, c_int};
+use urid::*;
+
+#[uri("http://lv2plug.in/plugins.rs/example_amp#ui")]
+struct AmpUI {
+    write_function: WriteFunction,
+    gain: f32,
+    visible: bool,
+    closed: bool,
+}
+
+#[derive(FeatureCollection)]
+struct Features {
+    is_live: Option<IsLive>,
+}
+
+impl PluginUI for AmpUI {
+    type InitFeatures = Features;
+
+    fn new(
+        ui_info: &PluginUIInfo,
+        write_function: WriteFunction,
+        features: &mut Features,
+    ) -> Option<Self> {
+        assert_eq!(
+            ui_info.ui_uri().to_str().unwrap(),
+            "http://lv2plug.in/plugins.rs/example_amp#ui"
+        );
+        assert_eq!(
+            ui_info.plugin_uri().to_str().unwrap(),
+            "http://lv2plug.in/plugins.rs/example_amp"
+        );
+        assert_eq!(
+            ui_info.bundle_path().to_str().unwrap(),
+            "/home/lv2/amp.lv2/"
+        );
+        assert!(features.is_live.is_none());
+
+        Some(Self {
+            write_function,
+            gain: 1.0,
+            visible: false,
+            closed: false,
+        })
+    }
+
+    fn port_event(&mut self, event: PortEvent, _: &mut Features) {
+        if let (0, Some(gain)) = (event.port_index(), event.control()) {
+            self.gain = gain;
+        }
+    }
+
+    fn cleanup(&mut self, _: &mut Features) {
+        self.closed = true;
+    }
+
+    fn extension_data(uri: &Uri) -> Option<&'static dyn std::any::Any> {
+        match_extensions!(uri, IdleDescriptor<Self>, ShowDescriptor<Self>)
+    }
+}
+
+impl IdleInterface for AmpUI {
+    fn idle(&mut self, _: &mut Features) -> bool {
+        if self.visible {
+            // Pretend the user doubled the gain.
+            self.write_function.write_control(0, self.gain * 2.0);
+        }
+        !self.closed
+    }
+}
+
+impl ShowInterface for AmpUI {
+    fn show(&mut self, _: &mut Features) -> bool {
+        self.visible = true;
+        true
+    }
+
+    fn hide(&mut self, _: &mut Features) -> bool {
+        self.visible = false;
+        true
+    }
+}
+
+lv2ui_descriptors!(AmpUI);
+
+thread_local! {
+    static WRITTEN: RefCell<Vec<(u32, f32)>> = const { RefCell::new(Vec::new()) };
+}
+
+unsafe extern "C" fn write(
+    controller: lv2_sys::LV2UI_Controller,
+    port_index: u32,
+    buffer_size: u32,
+    protocol: u32,
+    buffer: *const c_void,
+) {
+    assert_eq!(controller as usize, 0x1234);
+    assert_eq!(buffer_size, 4);
+    assert_eq!(protocol, 0);
+    WRITTEN.with(|written| {
+        written
+            .borrow_mut()
+            .push((port_index, *(buffer as *const f32)))
+    });
+}
+
+#[test]
+fn test_discovery() {
+    unsafe {
+        let descriptor: &LV2UI_Descriptor = lv2ui_descriptor(0).as_ref().unwrap();
+        assert_eq!(
+            Uri::from_ptr(descriptor.URI),
+            Uri::from_bytes_with_nul_unchecked(b"http://lv2plug.in/plugins.rs/example_amp#ui\0")
+        );
+        assert_eq!(lv2ui_descriptor(1), std::ptr::null());
+    }
+}
+
+#[test]
+fn test_ui() {
+    let features: &[*const lv2_sys::LV2_Feature] = &[std::ptr::null()];
+
+    unsafe {
+        // Retrieving the descriptor.
+        let descriptor: &LV2UI_Descriptor = lv2ui_descriptor(0).as_ref().unwrap();
+
+        // Constructing the UI.
+        let mut widget: lv2_sys::LV2UI_Widget = 0x1 as lv2_sys::LV2UI_Widget;
+        let ui = (descriptor.instantiate.unwrap())(
+            descriptor,
+            c"http://lv2plug.in/plugins.rs/example_amp".as_ptr(),
+            c"/home/lv2/amp.lv2/".as_ptr(),
+            Some(write),
+            0x1234 as lv2_sys::LV2UI_Controller,
+            &mut widget,
+            features.as_ptr(),
+        );
+        assert_ne!(ui, std::ptr::null_mut());
+        assert_eq!(widget, std::ptr::null_mut());
+
+        // Retrieving the extensions.
+        let extension_data = descriptor.extension_data.unwrap();
+        let idle_interface =
+            (extension_data(lv2_sys::LV2_UI__idleInterface.as_ptr() as *const c_char)
+                as *const lv2_sys::LV2UI_Idle_Interface)
+                .as_ref()
+                .unwrap();
+        let show_interface =
+            (extension_data(lv2_sys::LV2_UI__showInterface.as_ptr() as *const c_char)
+                as *const lv2_sys::LV2UI_Show_Interface)
+                .as_ref()
+                .unwrap();
+        assert!(extension_data(lv2_sys::LV2_UI__resize.as_ptr() as *const c_char).is_null());
+
+        // Notifying the UI about the gain.
+        let gain: f32 = 3.0;
+        (descriptor.port_event.unwrap())(ui, 0, 4, 0, &gain as *const f32 as *const c_void);
+
+        // Driving the hidden and the shown UI.
+        let idle: c_int = (idle_interface.idle.unwrap())(ui);
+        assert_eq!(idle, 0);
+        assert!(WRITTEN.with(|written| written.borrow().is_empty()));
+
+        assert_eq!((show_interface.show.unwrap())(ui), 0);
+        assert_eq!((idle_interface.idle.unwrap())(ui), 0);
+        assert_eq!((show_interface.hide.unwrap())(ui), 0);
+        assert_eq!(
+            vec![(0, 6.0)],
+            WRITTEN.with(|written| written.borrow().clone())
+        );
+
+        // Destroying the UI.
+        (descriptor.cleanup.unwrap())(ui);
+    }
+}
@@ -203,6 +203,21 @@ impl<T: UriBound + ?Sized> URIDCollection for URID<T> {
     }
 }
 
+impl URIDCollection for () {
+    fn from_map<M: Map + ?Sized>(_: &M) -> Option<Self> {
+        Some(())
+    }
+}
+
+/// Pairs of collections are collections too.
+///
+/// This is mostly used by generated code, which builds a collection of any size by nesting pairs.
+impl<A: URIDCollection, B: URIDCollection> URIDCollection for (A, B) {
+    fn from_map<M: Map + ?Sized>(map: &M) -> Option<Self> {
+        Some((A::from_map(map)?, B::from_map(map)?))
+    }
+}
+
 impl<T: ?Sized> fmt::Debug for URID<T> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         self.0.fmt(f)
@@ -0,0 +1,363 @@
+use crate::queue::{channel, Consumer, Producer};
+use crate::*;
+use std::io;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+
+/// Worker that runs in a background thread owned by the plugin.
+///
+/// This emulates the worker of the host for hosts that don't provide the [`Schedule`](struct.Schedule.html) feature. Work data is passed to the thread and responses are passed back through lock-free queues, so scheduling work and taking responses is real-time safe.
+///
+/// The thread calls [`Worker::work`](trait.Worker.html#tymethod.work) for every scheduled piece of work. Since there is no host to report to, errors returned by `work` are ignored. When the worker is dropped, the remaining work is done and the thread is joined.
+///
+/// Usually, this worker is created by a [`FallbackSchedule`](enum.FallbackSchedule.html).
+pub struct ThreadedWorker<P: Worker> {
+    work: Producer<P::WorkData>,
+    responses: Consumer<P::ResponseData>,
+    stop: Arc<AtomicBool>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<P: Worker> ThreadedWorker<P> {
+    /// The number of pending work and response messages a worker created by a `FallbackSchedule` can hold.
+    pub const DEFAULT_CAPACITY: usize = 64;
+
+    /// Start a worker thread.
+    ///
+    /// The queues for work and responses hold at least `capacity` messages each.
+    pub fn new(capacity: usize) -> io::Result<Self> {
+        let (work, pending_work) = channel::<P::WorkData>(capacity);
+        let (response_producer, responses) = channel::<P::ResponseData>(capacity);
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread = std::thread::Builder::new()
+            .name("lv2-worker".to_owned())
+            .spawn(move || {
+                let response_handler = ResponseHandler {
+                    response_function: None,
+                    respond_handle: ptr::null_mut(),
+                    fallback: &response_producer,
+                    phantom: PhantomData::<P>,
+                };
+                loop {
+                    while let Some(data) = pending_work.pop() {
+                        let _ = P::work(&response_handler, data);
+                    }
+                    if thread_stop.load(Ordering::Acquire) {
+                        break;
+                    }
+                    std::thread::park();
+                }
+            })?;
+        Ok(Self {
+            work,
+            responses,
+            stop,
+            thread: Some(thread),
+        })
+    }
+
+    /// Pass work to the worker thread.
+    ///
+    /// If the queue is full, the data is returned to the caller.
+    pub fn schedule_work(
+        &self,
+        worker_data: P::WorkData,
+    ) -> Result<(), ScheduleError<P::WorkData>> {
+        self.work
+            .push(worker_data)
+            .map_err(ScheduleError::NoSpace)?;
+        if let Some(thread) = self.thread.as_ref() {
+            thread.thread().unpark();
+        }
+        Ok(())
+    }
+
+    /// Take the oldest response of the worker thread.
+    pub fn take_response(&mut self) -> Option<P::ResponseData> {
+        self.responses.pop()
+    }
+}
+
+impl<P: Worker> Drop for ThreadedWorker<P> {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+        if let Some(thread) = self.thread.take() {
+            thread.thread().unpark();
+            let _ = thread.join();
+        }
+    }
+}
+
+/// The schedule feature of the host, or a threaded worker if the host doesn't provide it.
+///
+/// This type can be used instead of [`Schedule`](struct.Schedule.html) in the audio features of a plugin. If the host provides the schedule feature, work is passed to the host. Otherwise, a [`ThreadedWorker`](struct.ThreadedWorker.html) is started when the plugin is instantiated, so the plugin can be used in both kinds of hosts. The plugin should still export the [`WorkerDescriptor`](struct.WorkerDescriptor.html) for hosts with a worker.
+///
+/// The threaded worker can't call the plugin by itself, so the plugin has to call [`deliver_responses`](#method.deliver_responses) at the start of every `run`:
+///
+///     use lv2_core::prelude::*;
+///     use lv2_worker::*;
+///     use urid::*;
+///
+///     #[derive(FeatureCollection)]
+///     struct AudioFeatures<'a> {
+///         schedule: FallbackSchedule<'a, Loader>,
+///     }
+///
+///     #[uri("urn:loader")]
+///     struct Loader {
+///         loaded: u32,
+///     }
+///
+///     impl Plugin for Loader {
+///         type Ports = ();
+///         type InitFeatures = ();
+///         type AudioFeatures = AudioFeatures<'static>;
+///
+///         fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+///             Some(Self { loaded: 0 })
+///         }
+///
+///         fn run(&mut self, _: &mut (), features: &mut AudioFeatures<'static>, _: u32) {
+///             let _ = FallbackSchedule::deliver_responses(self, features, |features| {
+///                 &mut features.schedule
+///             });
+///             let _ = features.schedule.schedule_work(self.loaded + 1);
+///         }
+///     }
+///
+///     impl Worker for Loader {
+///         type WorkData = u32;
+///         type ResponseData = u32;
+///
+///         fn work(response_handler: &ResponseHandler<Self>, data: u32) -> Result<(), WorkerError> {
+///             response_handler.respond(data).map_err(|_| WorkerError::NoSpace)
+///         }
+///
+///         fn work_response(&mut self, data: u32, _: &mut AudioFeatures<'static>) -> Result<(), WorkerError> {
+///             self.loaded = data;
+///             Ok(())
+///         }
+///     }
+pub enum FallbackSchedule<'a, P: Worker> {
+    /// The schedule feature provided by the host.
+    Host(Schedule<'a, P>),
+    /// The worker thread owned by the plugin.
+    Threaded(ThreadedWorker<P>),
+}
+
+impl<'a, P: Worker> FallbackSchedule<'a, P> {
+    /// Check whether the work is done by the plugin's own worker thread.
+    pub fn is_threaded(&self) -> bool {
+        matches!(self, FallbackSchedule::Threaded(_))
+    }
+
+    /// Request the host or the worker thread to do some work.
+    ///
+    /// See [`Schedule::schedule_work`](struct.Schedule.html#method.schedule_work) for more information.
+    pub fn schedule_work(
+        &self,
+        worker_data: P::WorkData,
+    ) -> Result<(), ScheduleError<P::WorkData>> {
+        match self {
+            FallbackSchedule::Host(schedule) => schedule.schedule_work(worker_data),
+            FallbackSchedule::Threaded(worker) => worker.schedule_work(worker_data),
+        }
+    }
+
+    /// Request the host or the worker thread to do some work, encoding the data for the host.
+    ///
+    /// See [`Schedule::schedule_message`](struct.Schedule.html#method.schedule_message) for more information. The worker thread runs in the same process, so the data is passed to it as-is.
+    pub fn schedule_message(
+        &self,
+        worker_data: P::WorkData,
+    ) -> Result<(), ScheduleError<P::WorkData>>
+    where
+        P::WorkData: WorkerMessage,
+    {
+        match self {
+            FallbackSchedule::Host(schedule) => schedule.schedule_message(worker_data),
+            FallbackSchedule::Threaded(worker) => worker.schedule_work(worker_data),
+        }
+    }
+
+    /// Deliver the responses of the worker thread to the plugin.
+    ///
+    /// All pending responses are passed to [`Worker::work_response`](trait.Worker.html#method.work_response), followed by a call to [`Worker::end_run`](trait.Worker.html#method.end_run). The function to access the schedule in the audio features is needed since the features are passed to the plugin too. If the host provides the schedule feature, it delivers the responses itself and this method does nothing.
+    ///
+    /// If the plugin returns an error, the remaining responses are delivered in the next call.
+    pub fn deliver_responses(
+        plugin: &mut P,
+        features: &mut P::AudioFeatures,
+        schedule: impl Fn(&mut P::AudioFeatures) -> &mut Self,
+    ) -> Result<(), WorkerError> {
+        if !schedule(features).is_threaded() {
+            return Ok(());
+        }
+        while let Some(response) = schedule(features).take_response() {
+            plugin.work_response(response, features)?;
+        }
+        plugin.end_run(features)
+    }
+
+    fn take_response(&mut self) -> Option<P::ResponseData> {
+        match self {
+            FallbackSchedule::Host(_) => None,
+            FallbackSchedule::Threaded(worker) => worker.take_response(),
+        }
+    }
+}
+
+impl<'a, P: Worker> FromResolvedFeature<Schedule<'a, P>> for FallbackSchedule<'a, P> {
+    const REQUIRED: bool = false;
+
+    fn from_resolved_feature(
+        feature: Option<Schedule<'a, P>>,
+    ) -> Result<Self, MissingFeatureError> {
+        match feature {
+            Some(schedule) => Ok(FallbackSchedule::Host(schedule)),
+            None => ThreadedWorker::new(ThreadedWorker::<P>::DEFAULT_CAPACITY)
+                .map(FallbackSchedule::Threaded)
+                .map_err(|_| MissingFeatureError::new(Schedule::<P>::uri())),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::*;
+    use lv2_core::prelude::*;
+    use std::time::{Duration, Instant};
+
+    #[derive(FeatureCollection)]
+    struct AudioFeatures<'a> {
+        schedule: FallbackSchedule<'a, Counter>,
+    }
+
+    struct Counter {
+        responses: Vec<u32>,
+        end_runs: u32,
+    }
+
+    unsafe impl UriBound for Counter {
+        const URI: &'static [u8] = b"urn:rust-lv2:fallback-counter\0";
+    }
+
+    impl Plugin for Counter {
+        type Ports = ();
+        type InitFeatures = ();
+        type AudioFeatures = AudioFeatures<'static>;
+
+        fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+            Some(Self {
+                responses: Vec::new(),
+                end_runs: 0,
+            })
+        }
+
+        fn run(&mut self, _: &mut (), _: &mut AudioFeatures<'static>, _: u32) {}
+    }
+
+    impl Worker for Counter {
+        type WorkData = u32;
+        type ResponseData = u32;
+
+        fn work(response_handler: &ResponseHandler<Self>, data: u32) -> Result<(), WorkerError> {
+            response_handler
+                .respond(data * 2)
+                .map_err(|_| WorkerError::NoSpace)?;
+            response_handler
+                .respond_message(data * 2 + 1)
+                .map_err(|_| WorkerError::NoSpace)
+        }
+
+        fn work_response(
+            &mut self,
+            data: u32,
+            _: &mut AudioFeatures<'static>,
+        ) -> Result<(), WorkerError> {
+            self.responses.push(data);
+            Ok(())
+        }
+
+        fn end_run(&mut self, _: &mut AudioFeatures<'static>) -> Result<(), WorkerError> {
+            self.end_runs += 1;
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_fallback() {
+        // Without the schedule feature, the collection starts the threaded worker.
+        let features: [*const lv2_sys::LV2_Feature; 1] = [ptr::null()];
+        let mut cache = unsafe { FeatureCache::from_raw(features.as_ptr()) };
+        let mut features = AudioFeatures::from_cache(&mut cache, ThreadingClass::Audio).unwrap();
+        assert!(features.schedule.is_threaded());
+        assert_eq!(
+            &[FeatureRequirement::optional::<Schedule<Counter>>()],
+            AudioFeatures::FEATURES
+        );
+
+        let mut counter = Counter {
+            responses: Vec::new(),
+            end_runs: 0,
+        };
+        for data in 1..4 {
+            assert_eq!(Ok(()), features.schedule.schedule_work(data));
+        }
+
+        let start = Instant::now();
+        while counter.responses.len() < 6 {
+            assert!(start.elapsed() < Duration::from_secs(10));
+            std::thread::sleep(Duration::from_millis(1));
+            FallbackSchedule::deliver_responses(&mut counter, &mut features, |features| {
+                &mut features.schedule
+            })
+            .unwrap();
+        }
+        assert_eq!(vec![2, 3, 4, 5, 6, 7], counter.responses);
+        assert!(counter.end_runs > 0);
+    }
+
+    #[test]
+    fn test_host() {
+        extern "C" fn count_schedule(
+            handle: lv2_sys::LV2_Worker_Schedule_Handle,
+            _: u32,
+            _: *const c_void,
+        ) -> lv2_sys::LV2_Worker_Status {
+            unsafe { *(handle as *mut u32) += 1 };
+            lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS
+        }
+
+        let mut scheduled: u32 = 0;
+        // The features of the plugin are `'static`, so the host's struct is leaked.
+        let internal = Box::leak(Box::new(lv2_sys::LV2_Worker_Schedule {
+            handle: &mut scheduled as *mut u32 as *mut c_void,
+            schedule_work: Some(count_schedule),
+        }));
+        let schedule = Schedule {
+            internal,
+            phantom: PhantomData::<*const Counter>,
+        };
+        let mut features = AudioFeatures {
+            schedule: FallbackSchedule::from_resolved_feature(Some(schedule)).unwrap(),
+        };
+        assert!(!features.schedule.is_threaded());
+        assert_eq!(Ok(()), features.schedule.schedule_work(1));
+        assert_eq!(Ok(()), features.schedule.schedule_message(2));
+
+        // The host delivers the responses, so the plugin isn't called.
+        let mut counter = Counter {
+            responses: Vec::new(),
+            end_runs: 0,
+        };
+        FallbackSchedule::deliver_responses(&mut counter, &mut features, |features| {
+            &mut features.schedule
+        })
+        .unwrap();
+        assert_eq!(0, counter.end_runs);
+        assert_eq!(2, scheduled);
+    }
+}
@@ -111,10 +111,32 @@
 //!    }
 //!}
 //!```
+//!
+//! # Serialized messages
+//!
+//! By default, the work and response data are copied bit-for-bit, which assumes that the host delivers them to the same process. Hosts that save, replay or bridge worker messages need a real byte representation instead. Plugins can opt into it by using data types that implement [`WorkerMessage`](trait.WorkerMessage.html), scheduling with [`schedule_message`](struct.Schedule.html#method.schedule_message), responding with [`respond_message`](struct.ResponseHandler.html#method.respond_message) and exporting the [`SerializedWorkerDescriptor`](struct.SerializedWorkerDescriptor.html) instead of the `WorkerDescriptor`.
+//!
+//! # Hosts without a worker
+//!
+//! Some hosts don't provide the `worker:schedule` feature. Plugins that should still work in these hosts can use a [`FallbackSchedule`](enum.FallbackSchedule.html) instead of a `Schedule` in their audio features. If the feature is missing, it starts a worker thread owned by the plugin, which calls `work` like the host would. The responses of this thread have to be passed to the plugin with [`deliver_responses`](enum.FallbackSchedule.html#method.deliver_responses) at the start of every `run`.
+//!
+//! # Restoring the state in the worker
+//!
+//! Plugins that load large files while restoring their state can pass this work to the worker with a [`ScheduledRestore`](struct.ScheduledRestore.html) in the features of their [`State`](../lv2_state/trait.State.html) implementation. Plugins that declare the [`ThreadSafeRestore`](../lv2_state/struct.ThreadSafeRestore.html) feature may be restored while they are running; they implement [`ThreadSafeState`](../lv2_state/trait.ThreadSafeState.html) instead and only receive their state through the work response.
+
+mod fallback;
+mod message;
+mod queue;
+mod restore;
+
+pub use fallback::*;
+pub use message::*;
+pub use restore::*;
 
 use lv2_core::extension::ExtensionDescriptor;
 use lv2_core::feature::*;
 use lv2_core::plugin::{Plugin, PluginInstance};
+use queue::Producer;
 use std::fmt;
 use std::marker::PhantomData;
 use std::mem;
@@ -170,7 +192,8 @@ unsafe impl<'a, P> UriBound for Schedule<'a, P> {
 
 unsafe impl<'a, P> Feature for Schedule<'a, P> {
     unsafe fn from_feature_ptr(feature: *const c_void, class: ThreadingClass) -> Option<Self> {
-        if class == ThreadingClass::Audio {
+        // The schedule is also passed to `State::restore`, which is in the "Other" threading class.
+        if class == ThreadingClass::Audio || class == ThreadingClass::Other {
             (feature as *const lv2_sys::LV2_Worker_Schedule)
                 .as_ref()
                 .map(|internal| Self {
@@ -178,7 +201,7 @@ unsafe impl<'a, P> Feature for Schedule<'a, P> {
                     phantom: PhantomData::<*const P>,
                 })
         } else {
-            panic!("The Worker Schedule feature is only allowed in the audio threading class");
+            panic!("The Worker Schedule feature is only allowed in the audio and other threading classes");
         }
     }
 }
@@ -231,6 +254,39 @@ impl<'a, P: Worker> Schedule<'a, P> {
             ))),
         }
     }
+
+    /// Request the host to call the worker thread with an encoded message.
+    ///
+    /// This method works like [`schedule_work`](#method.schedule_work), but passes the encoded bytes of the data to the host instead of the data itself. The worker has to be exported with the [`SerializedWorkerDescriptor`](struct.SerializedWorkerDescriptor.html) to decode it.
+    ///
+    /// If this method fails, the data is returned to the caller.
+    pub fn schedule_message(
+        &self,
+        worker_data: P::WorkData,
+    ) -> Result<(), ScheduleError<P::WorkData>>
+    where
+        P::WorkData: WorkerMessage,
+    {
+        let schedule_work = if let Some(schedule_work) = self.internal.schedule_work {
+            schedule_work
+        } else {
+            return Err(ScheduleError::NoCallback(worker_data));
+        };
+        let status = worker_data.encode(|bytes| unsafe {
+            (schedule_work)(
+                self.internal.handle,
+                bytes.len() as u32,
+                bytes.as_ptr() as *const c_void,
+            )
+        });
+        match status {
+            lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS => Ok(()),
+            lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE => {
+                Err(ScheduleError::NoSpace(worker_data))
+            }
+            _ => Err(ScheduleError::Unknown(worker_data)),
+        }
+    }
 }
 
 /// Errors potentially generated by the
@@ -277,6 +333,8 @@ pub struct ResponseHandler<P: Worker> {
     /// Response handler provided by the host, must be passed to the host provided
     /// response_function.
     respond_handle: lv2_sys::LV2_Worker_Respond_Handle,
+    /// Response queue of the threaded fallback worker, or null if the host runs the worker.
+    fallback: *const Producer<P::ResponseData>,
     phantom: PhantomData<P>,
 }
 
@@ -294,6 +352,9 @@ impl<P: Worker> ResponseHandler<P> {
     where
         P::WorkData: 'static + Send,
     {
+        if let Some(fallback) = unsafe { self.fallback.as_ref() } {
+            return fallback.push(response_data).map_err(RespondError::NoSpace);
+        }
         let response_data = ManuallyDrop::new(response_data);
         let size = mem::size_of_val(&response_data) as u32;
         let ptr = &response_data as *const _ as *const c_void;
@@ -317,6 +378,43 @@ impl<P: Worker> ResponseHandler<P> {
             ))),
         }
     }
+
+    /// Send an encoded response to the `run` context.
+    ///
+    /// This method works like [`respond`](#method.respond), but passes the encoded bytes of the data to the host instead of the data itself. The worker has to be exported with the [`SerializedWorkerDescriptor`](struct.SerializedWorkerDescriptor.html) to decode it.
+    ///
+    /// If this method fails, the data is returned to the caller.
+    pub fn respond_message(
+        &self,
+        response_data: P::ResponseData,
+    ) -> Result<(), RespondError<P::ResponseData>>
+    where
+        P::ResponseData: WorkerMessage,
+    {
+        // The fallback worker runs in the same process, so the data doesn't need to be encoded.
+        if let Some(fallback) = unsafe { self.fallback.as_ref() } {
+            return fallback.push(response_data).map_err(RespondError::NoSpace);
+        }
+        let response_function = if let Some(response_function) = self.response_function {
+            response_function
+        } else {
+            return Err(RespondError::NoCallback(response_data));
+        };
+        let status = response_data.encode(|bytes| unsafe {
+            (response_function)(
+                self.respond_handle,
+                bytes.len() as u32,
+                bytes.as_ptr() as *const c_void,
+            )
+        });
+        match status {
+            lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS => Ok(()),
+            lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE => {
+                Err(RespondError::NoSpace(response_data))
+            }
+            _ => Err(RespondError::Unknown(response_data)),
+        }
+    }
 }
 
 /// Errors potentially generated by [`Worker`](trait.Worker.html) methods
@@ -400,6 +498,7 @@ impl<P: Worker> WorkerDescriptor<P> {
         let response_handler = ResponseHandler {
             response_function,
             respond_handle,
+            fallback: ptr::null(),
             phantom: PhantomData::<P>,
         };
         //build ref to worker data from raw pointer
@@ -471,6 +570,129 @@ impl<P: Worker> ExtensionDescriptor for WorkerDescriptor<P> {
     };
 }
 
+/// Convert the result of a worker method to a worker status.
+fn worker_status(result: Result<(), WorkerError>) -> lv2_sys::LV2_Worker_Status {
+    match result {
+        Ok(()) => lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS,
+        Err(WorkerError::Unknown) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+        Err(WorkerError::NoSpace) => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_NO_SPACE,
+    }
+}
+
+/// Create a slice from the message passed by the host.
+///
+/// Empty messages may be passed as a null pointer.
+unsafe fn message_bytes<'a>(size: u32, data: *const c_void) -> Option<&'a [u8]> {
+    if size == 0 {
+        Some(&[])
+    } else if data.is_null() {
+        None
+    } else {
+        Some(std::slice::from_raw_parts(data as *const u8, size as usize))
+    }
+}
+
+/// Raw wrapper of the [`Worker`](trait.Worker.html) extension for serialized messages.
+///
+/// This descriptor decodes the work and response data from their bytes with the [`WorkerMessage`](trait.WorkerMessage.html) implementation of the data types. Messages that can't be decoded are rejected with an error status. Export it instead of the `WorkerDescriptor` if the plugin schedules work with [`schedule_message`](struct.Schedule.html#method.schedule_message) and responds with [`respond_message`](struct.ResponseHandler.html#method.respond_message):
+///
+///     # use lv2_core::prelude::*;
+///     # use lv2_worker::*;
+///     # use std::any::Any;
+///     # use urid::*;
+///     # #[uri("urn:sampler")]
+///     # struct Sampler;
+///     # impl Plugin for Sampler {
+///     #     type Ports = ();
+///     #     type InitFeatures = ();
+///     #     type AudioFeatures = ();
+///     #     fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> { Some(Self) }
+///     #     fn run(&mut self, _: &mut (), _: &mut (), _: u32) {}
+///     #     fn extension_data(uri: &Uri) -> Option<&'static dyn Any> {
+///     #         match_extensions![uri, SerializedWorkerDescriptor<Self>]
+///     #     }
+///     # }
+///     impl Worker for Sampler {
+///         // The path of the sample to load.
+///         type WorkData = String;
+///         // The number of frames of the loaded sample.
+///         type ResponseData = u64;
+///
+///         fn work(response_handler: &ResponseHandler<Self>, path: String) -> Result<(), WorkerError> {
+///             let frames = path.len() as u64;
+///             response_handler
+///                 .respond_message(frames)
+///                 .map_err(|_| WorkerError::NoSpace)
+///         }
+///     }
+pub struct SerializedWorkerDescriptor<P: Worker> {
+    plugin: PhantomData<P>,
+}
+
+unsafe impl<P: Worker> UriBound for SerializedWorkerDescriptor<P> {
+    const URI: &'static [u8] = lv2_sys::LV2_WORKER__interface;
+}
+
+impl<P: Worker> SerializedWorkerDescriptor<P>
+where
+    P::WorkData: WorkerMessage,
+    P::ResponseData: WorkerMessage,
+{
+    /// Extern unsafe version of `work` method actually called by the host
+    unsafe extern "C" fn extern_work(
+        _handle: lv2_sys::LV2_Handle,
+        response_function: lv2_sys::LV2_Worker_Respond_Function,
+        respond_handle: lv2_sys::LV2_Worker_Respond_Handle,
+        size: u32,
+        data: *const c_void,
+    ) -> lv2_sys::LV2_Worker_Status {
+        let response_handler = ResponseHandler {
+            response_function,
+            respond_handle,
+            fallback: ptr::null(),
+            phantom: PhantomData::<P>,
+        };
+        match message_bytes(size, data).and_then(P::WorkData::decode) {
+            Some(worker_data) => worker_status(P::work(&response_handler, worker_data)),
+            None => lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+        }
+    }
+
+    /// Extern unsafe version of `work_response` method actually called by the host
+    unsafe extern "C" fn extern_work_response(
+        handle: lv2_sys::LV2_Handle,
+        size: u32,
+        body: *const c_void,
+    ) -> lv2_sys::LV2_Worker_Status {
+        let plugin_instance =
+            if let Some(plugin_instance) = (handle as *mut PluginInstance<P>).as_mut() {
+                plugin_instance
+            } else {
+                return lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN;
+            };
+        let response_data = match message_bytes(size, body).and_then(P::ResponseData::decode) {
+            Some(response_data) => response_data,
+            None => return lv2_sys::LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN,
+        };
+        let (instance, features) = plugin_instance.audio_class_handle();
+        worker_status(instance.work_response(response_data, features))
+    }
+}
+
+impl<P: Worker> ExtensionDescriptor for SerializedWorkerDescriptor<P>
+where
+    P::WorkData: WorkerMessage,
+    P::ResponseData: WorkerMessage,
+{
+    type ExtensionInterface = lv2_sys::LV2_Worker_Interface;
+
+    const INTERFACE: &'static lv2_sys::LV2_Worker_Interface = &lv2_sys::LV2_Worker_Interface {
+        work: Some(Self::extern_work),
+        work_response: Some(Self::extern_work_response),
+        end_run: Some(WorkerDescriptor::<P>::extern_end_run),
+    };
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -586,6 +808,120 @@ mod tests {
         LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN
     }
 
+    struct TestSerializedWorker;
+
+    unsafe impl UriBound for TestSerializedWorker {
+        const URI: &'static [u8] = b"not relevant\0";
+    }
+
+    impl Plugin for TestSerializedWorker {
+        type Ports = Ports;
+        type InitFeatures = ();
+        type AudioFeatures = ();
+
+        fn new(_plugin_info: &PluginInfo, _features: &mut Self::InitFeatures) -> Option<Self> {
+            Some(Self)
+        }
+
+        fn run(&mut self, _ports: &mut Ports, _features: &mut Self::InitFeatures, _: u32) {}
+    }
+
+    impl Worker for TestSerializedWorker {
+        type WorkData = u32;
+        type ResponseData = String;
+
+        fn work(response_handler: &ResponseHandler<Self>, data: u32) -> Result<(), WorkerError> {
+            response_handler
+                .respond_message(format!("work {}", data))
+                .map_err(|_| WorkerError::Unknown)
+        }
+    }
+
+    // Append the message to the `Vec<u8>` behind the handle, like a host that copies it into it's buffer.
+    unsafe extern "C" fn recording_schedule(
+        handle: LV2_Worker_Schedule_Handle,
+        size: u32,
+        data: *const c_void,
+    ) -> LV2_Worker_Status {
+        let received = &mut *(handle as *mut Vec<u8>);
+        received.extend_from_slice(std::slice::from_raw_parts(data as *const u8, size as usize));
+        LV2_Worker_Status_LV2_WORKER_SUCCESS
+    }
+
+    unsafe extern "C" fn recording_respond(
+        handle: LV2_Worker_Respond_Handle,
+        size: u32,
+        data: *const c_void,
+    ) -> LV2_Worker_Status {
+        recording_schedule(handle, size, data)
+    }
+
+    #[test]
+    fn schedule_message_passes_encoded_bytes() {
+        let mut received: Vec<u8> = Vec::new();
+        let internal = lv2_sys::LV2_Worker_Schedule {
+            handle: &mut received as *mut Vec<u8> as *mut c_void,
+            schedule_work: Some(recording_schedule),
+        };
+        let schedule = Schedule {
+            internal: &internal,
+            phantom: PhantomData::<*const TestSerializedWorker>,
+        };
+        assert_eq!(Ok(()), schedule.schedule_message(0x0102_0304));
+        assert_eq!(vec![4, 3, 2, 1], received);
+
+        let internal = lv2_sys::LV2_Worker_Schedule {
+            handle: ptr::null_mut(),
+            schedule_work: Some(faulty_schedule),
+        };
+        let schedule = Schedule {
+            internal: &internal,
+            phantom: PhantomData::<*const TestSerializedWorker>,
+        };
+        assert_eq!(Err(ScheduleError::Unknown(7)), schedule.schedule_message(7));
+    }
+
+    #[test]
+    fn serialized_extern_work_decodes_message() {
+        let mut received: Vec<u8> = Vec::new();
+        let respond_handle = &mut received as *mut Vec<u8> as *mut c_void;
+        let data = 3u32.to_le_bytes();
+        let status = unsafe {
+            SerializedWorkerDescriptor::<TestSerializedWorker>::extern_work(
+                ptr::null_mut(),
+                Some(recording_respond),
+                respond_handle,
+                data.len() as u32,
+                data.as_ptr() as *const c_void,
+            )
+        };
+        assert_eq!(LV2_Worker_Status_LV2_WORKER_SUCCESS, status);
+        assert_eq!(b"work 3", received.as_slice());
+
+        // Messages with the wrong size are rejected instead of being read out of bounds.
+        let status = unsafe {
+            SerializedWorkerDescriptor::<TestSerializedWorker>::extern_work(
+                ptr::null_mut(),
+                Some(recording_respond),
+                respond_handle,
+                2,
+                data.as_ptr() as *const c_void,
+            )
+        };
+        assert_eq!(LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, status);
+        let status = unsafe {
+            SerializedWorkerDescriptor::<TestSerializedWorker>::extern_work(
+                ptr::null_mut(),
+                Some(recording_respond),
+                respond_handle,
+                4,
+                ptr::null(),
+            )
+        };
+        assert_eq!(LV2_Worker_Status_LV2_WORKER_ERR_UNKNOWN, status);
+        assert_eq!(b"work 3", received.as_slice());
+    }
+
     #[test]
     fn schedule_must_not_drop() {
         let hd = HasDrop::new(0);
@@ -621,6 +957,7 @@ mod tests {
         let respond = ResponseHandler {
             response_function: Some(extern_respond),
             respond_handle: ptr::null_mut(),
+            fallback: ptr::null(),
             phantom: PhantomData::<TestDropWorker>,
         };
         let _ = respond.respond(hd);
@@ -633,6 +970,7 @@ mod tests {
         let respond = ResponseHandler {
             response_function: Some(faulty_respond),
             respond_handle: ptr::null_mut(),
+            fallback: ptr::null(),
             phantom: PhantomData::<TestDropWorker>,
         };
         let _ = respond.respond(hd);
@@ -0,0 +1,127 @@
+use std::convert::TryInto;
+
+/// Data that is passed between `run()` and the worker as encoded bytes.
+///
+/// By default, the worker data is copied bit-for-bit, which only works as long as the host delivers the message to the same process. Types implementing this trait define their own byte representation instead, which the host can save, replay or pass across process and architecture boundaries. Numbers are encoded in little-endian byte order.
+///
+/// Messages are used with [`Schedule::schedule_message`](struct.Schedule.html#method.schedule_message), [`ResponseHandler::respond_message`](struct.ResponseHandler.html#method.respond_message) and the [`SerializedWorkerDescriptor`](struct.SerializedWorkerDescriptor.html).
+///
+/// Encoding is done without allocations, but decoding types like `Vec<u8>` or `String` allocates. Since responses are decoded in the `run()` context, they should have a fixed size.
+pub trait WorkerMessage: Sized {
+    /// Pass the encoded bytes of the message to the given function and return it's result.
+    ///
+    /// The bytes are copied by the host, so they may be stored on the stack.
+    fn encode<R>(&self, write: impl FnOnce(&[u8]) -> R) -> R;
+
+    /// Decode a message from it's bytes.
+    ///
+    /// Returns `None` if the bytes are not a valid encoding of the message, for example because their length is wrong.
+    fn decode(bytes: &[u8]) -> Option<Self>;
+}
+
+macro_rules! impl_number_message {
+    ($($number:ty),*) => {
+        $(
+            impl WorkerMessage for $number {
+                fn encode<R>(&self, write: impl FnOnce(&[u8]) -> R) -> R {
+                    write(&self.to_le_bytes())
+                }
+
+                fn decode(bytes: &[u8]) -> Option<Self> {
+                    Some(Self::from_le_bytes(bytes.try_into().ok()?))
+                }
+            }
+        )*
+    };
+}
+
+impl_number_message!(u8, u16, u32, u64, i8, i16, i32, i64, f32, f64);
+
+impl WorkerMessage for () {
+    fn encode<R>(&self, write: impl FnOnce(&[u8]) -> R) -> R {
+        write(&[])
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        bytes.is_empty().then_some(())
+    }
+}
+
+impl WorkerMessage for bool {
+    fn encode<R>(&self, write: impl FnOnce(&[u8]) -> R) -> R {
+        write(&[u8::from(*self)])
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        match bytes {
+            [0] => Some(false),
+            [1] => Some(true),
+            _ => None,
+        }
+    }
+}
+
+impl<const N: usize> WorkerMessage for [u8; N] {
+    fn encode<R>(&self, write: impl FnOnce(&[u8]) -> R) -> R {
+        write(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        bytes.try_into().ok()
+    }
+}
+
+impl WorkerMessage for Vec<u8> {
+    fn encode<R>(&self, write: impl FnOnce(&[u8]) -> R) -> R {
+        write(self)
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        Some(bytes.to_vec())
+    }
+}
+
+impl WorkerMessage for String {
+    fn encode<R>(&self, write: impl FnOnce(&[u8]) -> R) -> R {
+        write(self.as_bytes())
+    }
+
+    fn decode(bytes: &[u8]) -> Option<Self> {
+        String::from_utf8(bytes.to_vec()).ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::WorkerMessage;
+
+    fn round_trip<T: WorkerMessage>(message: &T) -> Option<T> {
+        message.encode(T::decode)
+    }
+
+    #[test]
+    fn test_numbers() {
+        assert_eq!(Some(-42i32), round_trip(&-42i32));
+        assert_eq!(Some(u64::MAX), round_trip(&u64::MAX));
+        assert_eq!(Some(0.25f32), round_trip(&0.25f32));
+        assert_eq!([1, 0, 0, 0], 1u32.encode(<[u8; 4]>::decode).unwrap());
+        assert_eq!(None, u32::decode(&[1, 0, 0]));
+        assert_eq!(None, f64::decode(&[0; 4]));
+    }
+
+    #[test]
+    fn test_bytes() {
+        assert_eq!(Some(()), round_trip(&()));
+        assert_eq!(None, <()>::decode(&[0]));
+        assert_eq!(Some(true), round_trip(&true));
+        assert_eq!(None, bool::decode(&[2]));
+        assert_eq!(Some([1u8, 2, 3]), round_trip(&[1u8, 2, 3]));
+        assert_eq!(None, <[u8; 2]>::decode(&[1, 2, 3]));
+        assert_eq!(Some(vec![4u8, 5]), round_trip(&vec![4u8, 5]));
+        assert_eq!(
+            Some("sample.wav".to_owned()),
+            round_trip(&"sample.wav".to_owned())
+        );
+        assert_eq!(None, String::decode(&[0xff]));
+    }
+}
@@ -0,0 +1,168 @@
+//! A bounded, lock-free queue with a single producer and a single consumer.
+use std::cell::{Cell, UnsafeCell};
+use std::marker::PhantomData;
+use std::mem::MaybeUninit;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+/// The shared ring buffer of a queue.
+///
+/// The indices are only increased and wrap around at `usize::MAX`. Since the number of slots is a power of two, the slot of an index stays correct across the wrap-around.
+struct Queue<T> {
+    slots: Box<[UnsafeCell<MaybeUninit<T>>]>,
+    /// The index of the next slot to read, only written by the consumer.
+    head: AtomicUsize,
+    /// The index of the next slot to write, only written by the producer.
+    tail: AtomicUsize,
+}
+
+// The slots are only accessed by one producer and one consumer, and the indices make sure that they never access the same slot.
+unsafe impl<T: Send> Send for Queue<T> {}
+unsafe impl<T: Send> Sync for Queue<T> {}
+
+impl<T> Queue<T> {
+    fn slot(&self, index: usize) -> *mut MaybeUninit<T> {
+        self.slots[index & (self.slots.len() - 1)].get()
+    }
+}
+
+impl<T> Drop for Queue<T> {
+    fn drop(&mut self) {
+        let tail = *self.tail.get_mut();
+        let mut head = *self.head.get_mut();
+        while head != tail {
+            unsafe { (*self.slot(head)).assume_init_drop() };
+            head = head.wrapping_add(1);
+        }
+    }
+}
+
+/// The sending half of a queue.
+///
+/// The producer may be sent to another thread, but it can't be shared, since there may only be one thread that pushes.
+pub(crate) struct Producer<T> {
+    queue: Arc<Queue<T>>,
+    not_sync: PhantomData<Cell<()>>,
+}
+
+impl<T> Producer<T> {
+    /// Push a value to the queue without blocking or allocating.
+    ///
+    /// If the queue is full, the value is returned.
+    pub fn push(&self, value: T) -> Result<(), T> {
+        let queue = &*self.queue;
+        let tail = queue.tail.load(Ordering::Relaxed);
+        let head = queue.head.load(Ordering::Acquire);
+        if tail.wrapping_sub(head) == queue.slots.len() {
+            return Err(value);
+        }
+        unsafe { (*queue.slot(tail)).write(value) };
+        queue.tail.store(tail.wrapping_add(1), Ordering::Release);
+        Ok(())
+    }
+}
+
+/// The receiving half of a queue.
+///
+/// The consumer may be sent to another thread, but it can't be shared, since there may only be one thread that pops.
+pub(crate) struct Consumer<T> {
+    queue: Arc<Queue<T>>,
+    not_sync: PhantomData<Cell<()>>,
+}
+
+impl<T> Consumer<T> {
+    /// Pop the oldest value of the queue without blocking.
+    pub fn pop(&self) -> Option<T> {
+        let queue = &*self.queue;
+        let head = queue.head.load(Ordering::Relaxed);
+        let tail = queue.tail.load(Ordering::Acquire);
+        if head == tail {
+            return None;
+        }
+        let value = unsafe { (*queue.slot(head)).assume_init_read() };
+        queue.head.store(head.wrapping_add(1), Ordering::Release);
+        Some(value)
+    }
+}
+
+/// Create a queue that holds at least `capacity` values.
+///
+/// The capacity is rounded up to the next power of two.
+pub(crate) fn channel<T>(capacity: usize) -> (Producer<T>, Consumer<T>) {
+    let slots = (0..capacity.max(1).next_power_of_two())
+        .map(|_| UnsafeCell::new(MaybeUninit::uninit()))
+        .collect();
+    let queue = Arc::new(Queue {
+        slots,
+        head: AtomicUsize::new(0),
+        tail: AtomicUsize::new(0),
+    });
+    (
+        Producer {
+            queue: queue.clone(),
+            not_sync: PhantomData,
+        },
+        Consumer {
+            queue,
+            not_sync: PhantomData,
+        },
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::rc::Rc;
+
+    #[test]
+    fn test_push_pop() {
+        let (producer, consumer) = channel(3);
+        assert_eq!(None, consumer.pop());
+        for value in 0..4 {
+            assert_eq!(Ok(()), producer.push(value));
+        }
+        assert_eq!(Err(4), producer.push(4));
+
+        assert_eq!(Some(0), consumer.pop());
+        assert_eq!(Ok(()), producer.push(4));
+        let values: Vec<i32> = std::iter::from_fn(|| consumer.pop()).collect();
+        assert_eq!(vec![1, 2, 3, 4], values);
+    }
+
+    #[test]
+    fn test_threads() {
+        let (producer, consumer) = channel(4);
+        let thread = std::thread::spawn(move || {
+            for value in 0..1000 {
+                let mut value = value;
+                while let Err(rejected) = producer.push(value) {
+                    value = rejected;
+                    std::thread::yield_now();
+                }
+            }
+        });
+        let mut expected = 0;
+        while expected < 1000 {
+            match consumer.pop() {
+                Some(value) => {
+                    assert_eq!(expected, value);
+                    expected += 1;
+                }
+                None => std::thread::yield_now(),
+            }
+        }
+        thread.join().unwrap();
+    }
+
+    #[test]
+    fn test_drop() {
+        let value = Rc::new(());
+        {
+            let (producer, _consumer) = channel(2);
+            assert!(producer.push(value.clone()).is_ok());
+            assert!(producer.push(value.clone()).is_ok());
+            assert_eq!(3, Rc::strong_count(&value));
+        }
+        assert_eq!(1, Rc::strong_count(&value));
+    }
+}
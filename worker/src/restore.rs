@@ -0,0 +1,147 @@
+use crate::queue::channel;
+use crate::*;
+use lv2_state::StateErr;
+
+/// Restore the state of a plugin in the worker.
+///
+/// Loading a state may take a long time, for example when sample files have to be read. The [LV2 State specification](https://lv2plug.in/ns/ext/state) therefore allows the host to pass the `worker:schedule` feature to `restore`. The plugin reads the properties of the state, schedules the heavy work and swaps in the result in [`Worker::work_response`](trait.Worker.html#tymethod.work_response), where it doesn't interrupt `run`.
+///
+/// This type is used instead of a `Schedule` in the state features of the plugin and supports two cases:
+///
+/// * A plugin that declares the [`ThreadSafeRestore`](../lv2_state/struct.ThreadSafeRestore.html) feature may be restored while it's running. It implements [`ThreadSafeState`](../lv2_state/trait.ThreadSafeState.html), which only borrows the plugin immutably, and uses [`schedule`](#method.schedule). The restored state only reaches the plugin through `work_response`.
+/// * A plugin that implements [`State`](../lv2_state/trait.State.html) is never restored concurrently with `run`. It uses [`restore`](#method.restore), which does the work on the calling thread and applies the responses immediately if the host doesn't provide a worker.
+///
+/// A sampler that loads it's sample while running looks like this:
+///
+///     use lv2_core::prelude::*;
+///     use lv2_state::*;
+///     use lv2_worker::*;
+///     use urid::*;
+///
+///     #[derive(FeatureCollection)]
+///     struct RestoreFeatures<'a> {
+///         schedule: ScheduledRestore<'a, Sampler>,
+///     }
+///
+///     #[uri("urn:sampler")]
+///     #[derive(PluginMetadata)]
+///     #[lv2(
+///         name = "Sampler",
+///         optional_features(ThreadSafeRestore),
+///         extensions(ThreadSafeStateDescriptor<Sampler>, WorkerDescriptor<Sampler>),
+///     )]
+///     struct Sampler {
+///         sample: Vec<f32>,
+///     }
+///
+///     impl Plugin for Sampler {
+///         type Ports = ();
+///         type InitFeatures = ();
+///         type AudioFeatures = ();
+///
+///         fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+///             Some(Self { sample: Vec::new() })
+///         }
+///
+///         fn run(&mut self, _: &mut (), _: &mut (), _: u32) {}
+///     }
+///
+///     impl Worker for Sampler {
+///         type WorkData = usize;
+///         type ResponseData = Vec<f32>;
+///
+///         fn work(response_handler: &ResponseHandler<Self>, length: usize) -> Result<(), WorkerError> {
+///             // Load the sample, which may take a while.
+///             let sample = vec![0.0; length];
+///             response_handler.respond(sample).map_err(|_| WorkerError::NoSpace)
+///         }
+///
+///         fn work_response(&mut self, sample: Vec<f32>, _: &mut ()) -> Result<(), WorkerError> {
+///             self.sample = sample;
+///             Ok(())
+///         }
+///     }
+///
+///     impl ThreadSafeState for Sampler {
+///         type StateFeatures = RestoreFeatures<'static>;
+///
+///         fn save(&self, _: StoreHandle, _: RestoreFeatures<'static>) -> Result<(), StateErr> {
+///             Ok(())
+///         }
+///
+///         fn restore(&self, _: RetrieveHandle, features: RestoreFeatures<'static>) -> Result<(), StateErr> {
+///             // Only read the stored properties here and leave the rest to the worker.
+///             features.schedule.schedule(1024)
+///         }
+///     }
+pub struct ScheduledRestore<'a, P: Worker> {
+    schedule: Option<Schedule<'a, P>>,
+}
+
+impl<'a, P: Worker> ScheduledRestore<'a, P> {
+    /// The number of responses the work may send if it's done on the calling thread.
+    pub const CAPACITY: usize = 64;
+
+    /// Create a helper from the schedule feature of the host, if there is any.
+    pub fn new(schedule: Option<Schedule<'a, P>>) -> Self {
+        Self { schedule }
+    }
+
+    /// Check whether the work is passed to the worker of the host.
+    pub fn is_scheduled(&self) -> bool {
+        self.schedule.is_some()
+    }
+
+    /// Schedule the work of a thread-safe restore.
+    ///
+    /// The work data is scheduled and the responses are passed to [`Worker::work_response`](trait.Worker.html#tymethod.work_response) in the audio threading class. Since the plugin may be running, there is no fallback: If the host didn't pass the schedule feature, `StateErr::NoFeature` is returned.
+    pub fn schedule(&self, worker_data: P::WorkData) -> Result<(), StateErr> {
+        let schedule = self.schedule.as_ref().ok_or(StateErr::NoFeature)?;
+        schedule
+            .schedule_work(worker_data)
+            .map_err(|error| match error {
+                ScheduleError::Unknown(_) => StateErr::Unknown,
+                ScheduleError::NoSpace(_) => StateErr::NoSpace,
+                ScheduleError::NoCallback(_) => StateErr::BadCallback,
+            })
+    }
+
+    /// Restore the state with the worker.
+    ///
+    /// This method is meant for [`State::restore`](../lv2_state/trait.State.html#tymethod.restore), which the host never calls concurrently with `run`. If the host passed the schedule feature, the work data is scheduled like with [`schedule`](#method.schedule). Otherwise, [`Worker::work`](trait.Worker.html#tymethod.work) is called right away and it's responses are passed to `apply`, which may modify the plugin directly.
+    pub fn restore(
+        &self,
+        worker_data: P::WorkData,
+        mut apply: impl FnMut(P::ResponseData) -> Result<(), StateErr>,
+    ) -> Result<(), StateErr> {
+        if self.schedule.is_some() {
+            return self.schedule(worker_data);
+        }
+
+        let (producer, responses) = channel(Self::CAPACITY);
+        let response_handler = ResponseHandler {
+            response_function: None,
+            respond_handle: ptr::null_mut(),
+            fallback: &producer,
+            phantom: PhantomData::<P>,
+        };
+        let result = P::work(&response_handler, worker_data);
+        while let Some(response) = responses.pop() {
+            apply(response)?;
+        }
+        result.map_err(|error| match error {
+            WorkerError::Unknown => StateErr::Unknown,
+            WorkerError::NoSpace => StateErr::NoSpace,
+        })
+    }
+}
+
+impl<'a, P: Worker> FromResolvedFeature<Schedule<'a, P>> for ScheduledRestore<'a, P> {
+    const REQUIRED: bool = false;
+
+    fn from_resolved_feature(
+        feature: Option<Schedule<'a, P>>,
+    ) -> Result<Self, MissingFeatureError> {
+        Ok(Self::new(feature))
+    }
+}
@@ -0,0 +1,209 @@
+use lv2_atom::prelude::*;
+use lv2_core::prelude::*;
+use lv2_state::*;
+use lv2_worker::*;
+use std::ffi::c_void;
+use urid::*;
+
+const LENGTH_KEY: u32 = 1000;
+const INT_TYPE: u32 = 1;
+
+#[derive(FeatureCollection)]
+struct RestoreFeatures<'a> {
+    schedule: ScheduledRestore<'a, Sampler>,
+}
+
+#[uri("urn:rust-lv2:scheduled-restore")]
+struct Sampler {
+    sample: Vec<f32>,
+}
+
+impl Plugin for Sampler {
+    type Ports = ();
+    type InitFeatures = ();
+    type AudioFeatures = ();
+
+    fn new(_: &PluginInfo, _: &mut ()) -> Option<Self> {
+        Some(Self { sample: Vec::new() })
+    }
+
+    fn run(&mut self, _: &mut (), _: &mut (), _: u32) {}
+}
+
+impl Worker for Sampler {
+    type WorkData = i32;
+    type ResponseData = Vec<f32>;
+
+    fn work(response_handler: &ResponseHandler<Self>, length: i32) -> Result<(), WorkerError> {
+        response_handler
+            .respond(vec![0.5; length as usize])
+            .map_err(|_| WorkerError::NoSpace)
+    }
+
+    fn work_response(&mut self, sample: Vec<f32>, _: &mut ()) -> Result<(), WorkerError> {
+        self.sample = sample;
+        Ok(())
+    }
+}
+
+impl State for Sampler {
+    type StateFeatures = RestoreFeatures<'static>;
+
+    fn save(&self, _: StoreHandle, _: RestoreFeatures<'static>) -> Result<(), StateErr> {
+        Ok(())
+    }
+
+    fn restore(
+        &mut self,
+        store: RetrieveHandle,
+        features: RestoreFeatures<'static>,
+    ) -> Result<(), StateErr> {
+        // The test storage doesn't map URIs, so the type URID is fixed.
+        let int = unsafe { URID::<Int>::new_unchecked(INT_TYPE) };
+        let length = store
+            .retrieve(URID::new(LENGTH_KEY).unwrap())?
+            .read(int, ())?;
+        features.schedule.restore(length, |sample| {
+            self.sample = sample;
+            Ok(())
+        })
+    }
+}
+
+impl ThreadSafeState for Sampler {
+    type StateFeatures = RestoreFeatures<'static>;
+
+    fn save(&self, _: StoreHandle, _: RestoreFeatures<'static>) -> Result<(), StateErr> {
+        Ok(())
+    }
+
+    fn restore(
+        &self,
+        store: RetrieveHandle,
+        features: RestoreFeatures<'static>,
+    ) -> Result<(), StateErr> {
+        let int = unsafe { URID::<Int>::new_unchecked(INT_TYPE) };
+        let length = store
+            .retrieve(URID::new(LENGTH_KEY).unwrap())?
+            .read(int, ())?;
+        features.schedule.schedule(length)
+    }
+}
+
+fn storage() -> Storage {
+    let mut storage = Storage::default();
+    storage.store(
+        URID::<()>::new(LENGTH_KEY).unwrap(),
+        URID::<()>::new(INT_TYPE).unwrap(),
+        &4i32.to_ne_bytes(),
+    );
+    storage
+}
+
+unsafe fn restore(
+    plugin: &mut Sampler,
+    storage: &mut Storage,
+    features: &[*const lv2_sys::LV2_Feature],
+) -> lv2_sys::LV2_State_Status {
+    StateDescriptor::<Sampler>::extern_restore(
+        plugin as *mut Sampler as lv2_sys::LV2_Handle,
+        Some(Storage::extern_retrieve),
+        storage as *mut Storage as lv2_sys::LV2_State_Handle,
+        lv2_sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
+        features.as_ptr(),
+    )
+}
+
+unsafe fn restore_thread_safe(
+    plugin: &Sampler,
+    storage: &mut Storage,
+    features: &[*const lv2_sys::LV2_Feature],
+) -> lv2_sys::LV2_State_Status {
+    ThreadSafeStateDescriptor::<Sampler>::extern_restore(
+        plugin as *const Sampler as lv2_sys::LV2_Handle,
+        Some(Storage::extern_retrieve),
+        storage as *mut Storage as lv2_sys::LV2_State_Handle,
+        lv2_sys::LV2_State_Flags::LV2_STATE_IS_POD.into(),
+        features.as_ptr(),
+    )
+}
+
+extern "C" fn record_schedule(
+    handle: lv2_sys::LV2_Worker_Schedule_Handle,
+    size: u32,
+    data: *const c_void,
+) -> lv2_sys::LV2_Worker_Status {
+    let scheduled = unsafe { &mut *(handle as *mut Vec<u8>) };
+    let data = unsafe { std::slice::from_raw_parts(data as *const u8, size as usize) };
+    scheduled.extend_from_slice(data);
+    lv2_sys::LV2_Worker_Status_LV2_WORKER_SUCCESS
+}
+
+#[test]
+fn test_restore_without_schedule() {
+    let mut plugin = Sampler { sample: Vec::new() };
+    let mut storage = storage();
+
+    let features = [std::ptr::null()];
+    let status = unsafe { restore(&mut plugin, &mut storage, &features) };
+    assert_eq!(lv2_sys::LV2_State_Status_LV2_STATE_SUCCESS, status);
+    assert_eq!(vec![0.5; 4], plugin.sample);
+}
+
+#[test]
+fn test_restore_with_schedule() {
+    let mut plugin = Sampler { sample: Vec::new() };
+    let mut storage = storage();
+
+    let mut scheduled: Vec<u8> = Vec::new();
+    let schedule = lv2_sys::LV2_Worker_Schedule {
+        handle: &mut scheduled as *mut Vec<u8> as *mut c_void,
+        schedule_work: Some(record_schedule),
+    };
+    let schedule_feature = lv2_sys::LV2_Feature {
+        URI: lv2_sys::LV2_WORKER__schedule.as_ptr() as *const _,
+        data: &schedule as *const lv2_sys::LV2_Worker_Schedule as *mut c_void,
+    };
+    let features = [
+        &schedule_feature as *const lv2_sys::LV2_Feature,
+        std::ptr::null(),
+    ];
+
+    let status = unsafe { restore(&mut plugin, &mut storage, &features) };
+    assert_eq!(lv2_sys::LV2_State_Status_LV2_STATE_SUCCESS, status);
+
+    // The sample is loaded by the worker, not during the restore call.
+    assert!(plugin.sample.is_empty());
+    assert_eq!(4i32.to_ne_bytes().to_vec(), scheduled);
+}
+
+#[test]
+fn test_thread_safe_restore() {
+    let plugin = Sampler { sample: Vec::new() };
+    let mut storage = storage();
+
+    // Without a worker, a thread-safe restore can't touch the plugin and fails.
+    let features = [std::ptr::null()];
+    let status = unsafe { restore_thread_safe(&plugin, &mut storage, &features) };
+    assert_eq!(lv2_sys::LV2_State_Status_LV2_STATE_ERR_NO_FEATURE, status);
+    assert!(plugin.sample.is_empty());
+
+    let mut scheduled: Vec<u8> = Vec::new();
+    let schedule = lv2_sys::LV2_Worker_Schedule {
+        handle: &mut scheduled as *mut Vec<u8> as *mut c_void,
+        schedule_work: Some(record_schedule),
+    };
+    let schedule_feature = lv2_sys::LV2_Feature {
+        URI: lv2_sys::LV2_WORKER__schedule.as_ptr() as *const _,
+        data: &schedule as *const lv2_sys::LV2_Worker_Schedule as *mut c_void,
+    };
+    let features = [
+        &schedule_feature as *const lv2_sys::LV2_Feature,
+        std::ptr::null(),
+    ];
+
+    let status = unsafe { restore_thread_safe(&plugin, &mut storage, &features) };
+    assert_eq!(lv2_sys::LV2_State_Status_LV2_STATE_SUCCESS, status);
+    assert!(plugin.sample.is_empty());
+    assert_eq!(4i32.to_ne_bytes().to_vec(), scheduled);
+}